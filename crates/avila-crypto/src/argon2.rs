@@ -0,0 +1,554 @@
+// AvilaCrypto Argon2 - Argon2id memory-hard password hashing (RFC 9106)
+// Zero External Dependencies 🦀
+
+use crate::password::{PhcString, MIN_HASH_LEN};
+use crate::{constant_time_eq, random, CryptoError};
+
+/// Argon2 version 1.3
+pub const VERSION: u32 = 0x13;
+pub const DEFAULT_SALT_LEN: usize = 16;
+
+const BLOCK_WORDS: usize = 128;
+const SYNC_POINTS: u32 = 4;
+const ARGON2ID: u32 = 2;
+
+/// Largest costs accepted from a stored PHC string or vault header, so crafted
+/// parameters cannot exhaust memory or CPU (1 GiB, 16 passes, 16 lanes)
+const MAX_M_COST: u32 = 1024 * 1024;
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 16;
+
+/// Argon2id cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    /// Memory in KiB (number of 1 KiB blocks)
+    pub m_cost: u32,
+    /// Number of passes over memory
+    pub t_cost: u32,
+    /// Lanes (degree of parallelism)
+    pub p_cost: u32,
+    /// Tag length in bytes
+    pub output_len: usize,
+}
+
+impl Params {
+    pub fn new(m_cost: u32, t_cost: u32, p_cost: u32) -> Result<Self, CryptoError> {
+        let params = Self {
+            m_cost,
+            t_cost,
+            p_cost,
+            output_len: 32,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    fn validate(&self) -> Result<(), CryptoError> {
+        if self.p_cost == 0 || self.p_cost > 0x00ff_ffff {
+            return Err(CryptoError::InvalidParameter("Argon2 lanes must be 1..2^24-1".to_string()));
+        }
+        if self.t_cost == 0 {
+            return Err(CryptoError::InvalidParameter("Argon2 needs at least one pass".to_string()));
+        }
+        if self.m_cost < 8 * self.p_cost {
+            return Err(CryptoError::InvalidParameter(
+                "Argon2 memory must be at least 8 KiB per lane".to_string(),
+            ));
+        }
+        if self.output_len < 4 {
+            return Err(CryptoError::InvalidParameter("Argon2 tag must be at least 4 bytes".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Bounds for parameters stored next to a hash; hashes are only created
+    /// within them so that they can always be verified
+    pub(crate) fn check_limits(&self) -> Result<(), CryptoError> {
+        if self.m_cost > MAX_M_COST || self.t_cost > MAX_T_COST || self.p_cost > MAX_P_COST {
+            return Err(CryptoError::InvalidParameter("Argon2 cost above the supported limit".to_string()));
+        }
+        if self.output_len < MIN_HASH_LEN {
+            return Err(CryptoError::InvalidParameter("Argon2 hash must be at least 16 bytes".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Params {
+    /// 19 MiB, 2 passes, 1 lane (OWASP recommendation for Argon2id)
+    fn default() -> Self {
+        Self {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+            output_len: 32,
+        }
+    }
+}
+
+// BLAKE2b (RFC 7693), unkeyed with variable output length, as used by Argon2
+mod blake2b {
+    const IV: [u64; 8] = [
+        0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+        0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+    ];
+
+    const SIGMA: [[usize; 16]; 10] = [
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+        [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+        [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+        [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+        [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+        [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+        [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+        [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+        [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+        [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    ];
+
+    fn compress(h: &mut [u64; 8], block: &[u8; 128], counter: u128, last: bool) {
+        let mut m = [0u64; 16];
+        for (i, word) in block.chunks(8).enumerate() {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(word);
+            m[i] = u64::from_le_bytes(bytes);
+        }
+
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= counter as u64;
+        v[13] ^= (counter >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+            v[d] = (v[d] ^ v[a]).rotate_right(32);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(24);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+            v[d] = (v[d] ^ v[a]).rotate_right(16);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(63);
+        }
+
+        for round in 0..12 {
+            let s = &SIGMA[round % 10];
+            g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+
+    /// BLAKE2b digest of the concatenated `parts`, `out_len` bytes (1..=64)
+    pub fn hash(parts: &[&[u8]], out_len: usize) -> Vec<u8> {
+        let mut h = IV;
+        h[0] ^= 0x0101_0000 ^ out_len as u64;
+
+        let data = parts.concat();
+        let mut counter = 0u128;
+        let mut block = [0u8; 128];
+        let mut chunks = data.chunks(128).peekable();
+        if chunks.peek().is_none() {
+            compress(&mut h, &block, 0, true);
+        }
+        while let Some(chunk) = chunks.next() {
+            block = [0u8; 128];
+            block[..chunk.len()].copy_from_slice(chunk);
+            counter += chunk.len() as u128;
+            compress(&mut h, &block, counter, chunks.peek().is_none());
+        }
+
+        let mut out = Vec::with_capacity(64);
+        for word in h.iter() {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.truncate(out_len);
+        out
+    }
+}
+
+/// Variable-length hash H' (RFC 9106 section 3.3)
+fn hash_prime(parts: &[&[u8]], out_len: usize) -> Vec<u8> {
+    let len_prefix = (out_len as u32).to_le_bytes();
+    let mut input: Vec<&[u8]> = vec![&len_prefix];
+    input.extend_from_slice(parts);
+
+    if out_len <= 64 {
+        return blake2b::hash(&input, out_len);
+    }
+
+    let r = out_len.div_ceil(32) - 2;
+    let mut out = Vec::with_capacity(out_len);
+    let mut v = blake2b::hash(&input, 64);
+    out.extend_from_slice(&v[..32]);
+    for _ in 1..r {
+        v = blake2b::hash(&[&v], 64);
+        out.extend_from_slice(&v[..32]);
+    }
+    out.extend_from_slice(&blake2b::hash(&[&v], out_len - 32 * r));
+    out
+}
+
+type Block = [u64; BLOCK_WORDS];
+
+fn block_from_bytes(bytes: &[u8]) -> Block {
+    let mut block = [0u64; BLOCK_WORDS];
+    for (word, chunk) in block.iter_mut().zip(bytes.chunks(8)) {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(chunk);
+        *word = u64::from_le_bytes(buf);
+    }
+    block
+}
+
+/// BLAKE2b round function with the Argon2 multiplication hardening
+fn permute(v: &mut Block, idx: [usize; 16]) {
+    fn gb(v: &mut Block, a: usize, b: usize, c: usize, d: usize) {
+        fn fbla(x: u64, y: u64) -> u64 {
+            let lo = (x & 0xffff_ffff).wrapping_mul(y & 0xffff_ffff);
+            x.wrapping_add(y).wrapping_add(lo.wrapping_mul(2))
+        }
+        v[a] = fbla(v[a], v[b]);
+        v[d] = (v[d] ^ v[a]).rotate_right(32);
+        v[c] = fbla(v[c], v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(24);
+        v[a] = fbla(v[a], v[b]);
+        v[d] = (v[d] ^ v[a]).rotate_right(16);
+        v[c] = fbla(v[c], v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(63);
+    }
+
+    gb(v, idx[0], idx[4], idx[8], idx[12]);
+    gb(v, idx[1], idx[5], idx[9], idx[13]);
+    gb(v, idx[2], idx[6], idx[10], idx[14]);
+    gb(v, idx[3], idx[7], idx[11], idx[15]);
+    gb(v, idx[0], idx[5], idx[10], idx[15]);
+    gb(v, idx[1], idx[6], idx[11], idx[12]);
+    gb(v, idx[2], idx[7], idx[8], idx[13]);
+    gb(v, idx[3], idx[4], idx[9], idx[14]);
+}
+
+/// Compression function G; XORs into `next` instead of overwriting when `with_xor`
+fn fill_block(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+    let mut r = [0u64; BLOCK_WORDS];
+    for i in 0..BLOCK_WORDS {
+        r[i] = prev[i] ^ reference[i];
+    }
+    let mut tmp = r;
+    if with_xor {
+        for i in 0..BLOCK_WORDS {
+            tmp[i] ^= next[i];
+        }
+    }
+
+    // Rows: eight runs of 16 consecutive words
+    for row in 0..8 {
+        let base = row * 16;
+        let mut idx = [0usize; 16];
+        for (k, slot) in idx.iter_mut().enumerate() {
+            *slot = base + k;
+        }
+        permute(&mut r, idx);
+    }
+    // Columns: word pairs taken with a stride of 16
+    for col in 0..8 {
+        let mut idx = [0usize; 16];
+        for (k, slot) in idx.iter_mut().enumerate() {
+            *slot = 2 * col + (k / 2) * 16 + (k % 2);
+        }
+        permute(&mut r, idx);
+    }
+
+    for i in 0..BLOCK_WORDS {
+        next[i] = tmp[i] ^ r[i];
+    }
+}
+
+/// Advance the address generator for data-independent addressing
+fn next_addresses(input: &mut Block, address: &mut Block) {
+    let zero_block = [0u64; BLOCK_WORDS];
+    input[6] += 1;
+    let mut tmp = [0u64; BLOCK_WORDS];
+    fill_block(&zero_block, input, &mut tmp, false);
+    fill_block(&zero_block, &tmp, address, false);
+}
+
+/// Argon2id with optional secret key and associated data, writing the tag into `out`
+pub fn argon2id(
+    password: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    associated_data: &[u8],
+    params: &Params,
+    out: &mut [u8],
+) -> Result<(), CryptoError> {
+    params.validate()?;
+    if salt.len() < 8 {
+        return Err(CryptoError::InvalidParameter("Argon2 salt must be at least 8 bytes".to_string()));
+    }
+    if out.len() != params.output_len {
+        return Err(CryptoError::InvalidParameter("output length does not match params".to_string()));
+    }
+
+    let lanes = params.p_cost as usize;
+    let segment_length = (params.m_cost / (SYNC_POINTS * params.p_cost)) as usize;
+    let lane_length = segment_length * SYNC_POINTS as usize;
+    let memory_blocks = lane_length * lanes;
+
+    let le = |x: u32| x.to_le_bytes();
+    let h0 = blake2b::hash(
+        &[
+            &le(params.p_cost),
+            &le(out.len() as u32),
+            &le(params.m_cost),
+            &le(params.t_cost),
+            &le(VERSION),
+            &le(ARGON2ID),
+            &le(password.len() as u32),
+            password,
+            &le(salt.len() as u32),
+            salt,
+            &le(secret.len() as u32),
+            secret,
+            &le(associated_data.len() as u32),
+            associated_data,
+        ],
+        64,
+    );
+
+    let mut memory = vec![[0u64; BLOCK_WORDS]; memory_blocks];
+    for lane in 0..lanes {
+        for i in 0..2u32 {
+            let bytes = hash_prime(&[&h0, &le(i), &le(lane as u32)], 1024);
+            memory[lane * lane_length + i as usize] = block_from_bytes(&bytes);
+        }
+    }
+
+    for pass in 0..params.t_cost {
+        for slice in 0..SYNC_POINTS {
+            for lane in 0..lanes {
+                // Argon2id: data-independent addressing for the first half of the first pass
+                let independent = pass == 0 && slice < SYNC_POINTS / 2;
+                let mut input_block = [0u64; BLOCK_WORDS];
+                let mut address_block = [0u64; BLOCK_WORDS];
+                if independent {
+                    input_block[0] = pass as u64;
+                    input_block[1] = lane as u64;
+                    input_block[2] = slice as u64;
+                    input_block[3] = memory_blocks as u64;
+                    input_block[4] = params.t_cost as u64;
+                    input_block[5] = ARGON2ID as u64;
+                }
+
+                let mut starting_index = 0;
+                if pass == 0 && slice == 0 {
+                    starting_index = 2;
+                    if independent {
+                        next_addresses(&mut input_block, &mut address_block);
+                    }
+                }
+
+                let segment_start = lane * lane_length + slice as usize * segment_length;
+                for i in starting_index..segment_length {
+                    let curr_offset = segment_start + i;
+                    // The first block of a lane follows the lane's last block
+                    let prev_offset = if curr_offset.is_multiple_of(lane_length) {
+                        curr_offset + lane_length - 1
+                    } else {
+                        curr_offset - 1
+                    };
+
+                    let pseudo_rand = if independent {
+                        if i % BLOCK_WORDS == 0 {
+                            next_addresses(&mut input_block, &mut address_block);
+                        }
+                        address_block[i % BLOCK_WORDS]
+                    } else {
+                        memory[prev_offset][0]
+                    };
+
+                    let ref_lane = if pass == 0 && slice == 0 {
+                        lane
+                    } else {
+                        ((pseudo_rand >> 32) % lanes as u64) as usize
+                    };
+                    let same_lane = ref_lane == lane;
+
+                    // Size of the window the reference block is drawn from
+                    let area = (if pass == 0 {
+                        if slice == 0 {
+                            i - 1
+                        } else if same_lane {
+                            slice as usize * segment_length + i - 1
+                        } else {
+                            slice as usize * segment_length - (i == 0) as usize
+                        }
+                    } else if same_lane {
+                        lane_length - segment_length + i - 1
+                    } else {
+                        lane_length - segment_length - (i == 0) as usize
+                    }) as u64;
+
+                    let j1 = pseudo_rand & 0xffff_ffff;
+                    let x = (j1 * j1) >> 32;
+                    let relative = area - 1 - ((area * x) >> 32);
+                    let start = if pass == 0 || slice == SYNC_POINTS - 1 {
+                        0
+                    } else {
+                        (slice as usize + 1) * segment_length
+                    };
+                    let ref_index = (start + relative as usize) % lane_length;
+
+                    let prev = memory[prev_offset];
+                    let reference = memory[ref_lane * lane_length + ref_index];
+                    fill_block(&prev, &reference, &mut memory[curr_offset], pass > 0);
+                }
+            }
+        }
+    }
+
+    let mut last = memory[lane_length - 1];
+    for lane in 1..lanes {
+        let block = &memory[lane * lane_length + lane_length - 1];
+        for (acc, word) in last.iter_mut().zip(block.iter()) {
+            *acc ^= word;
+        }
+    }
+    let mut final_bytes = Vec::with_capacity(1024);
+    for word in last.iter() {
+        final_bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    out.copy_from_slice(&hash_prime(&[&final_bytes], out.len()));
+
+    // Memory may hold password-derived material
+    for block in memory.iter_mut() {
+        for word in block.iter_mut() {
+            // SAFETY: writing through a valid &mut u64
+            unsafe { std::ptr::write_volatile(word, 0) };
+        }
+    }
+    Ok(())
+}
+
+/// Hash a password with a fresh random salt, returning a PHC string
+/// such as `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`
+pub fn hash_password(password: &[u8], params: &Params) -> Result<String, CryptoError> {
+    params.check_limits()?;
+    let salt: [u8; DEFAULT_SALT_LEN] = random::secure_array()?;
+    let mut hash = vec![0u8; params.output_len];
+    argon2id(password, &salt, &[], &[], params, &mut hash)?;
+
+    Ok(PhcString {
+        algorithm: "argon2id".to_string(),
+        version: Some(VERSION),
+        params: vec![
+            ("m".to_string(), params.m_cost.to_string()),
+            ("t".to_string(), params.t_cost.to_string()),
+            ("p".to_string(), params.p_cost.to_string()),
+        ],
+        salt: salt.to_vec(),
+        hash,
+    }
+    .to_string())
+}
+
+/// Check a password against an Argon2id PHC string
+///
+/// Returns `Ok(false)` on mismatch and an error only if the string is malformed.
+pub fn verify_password(password: &[u8], phc: &str) -> Result<bool, CryptoError> {
+    verify_parsed(password, &PhcString::parse(phc)?)
+}
+
+/// [`verify_password`] for an already parsed PHC string
+pub(crate) fn verify_parsed(password: &[u8], parsed: &PhcString) -> Result<bool, CryptoError> {
+    if parsed.algorithm != "argon2id" {
+        return Err(CryptoError::UnsupportedAlgorithm(parsed.algorithm.clone()));
+    }
+    if parsed.version.unwrap_or(0x10) != VERSION {
+        return Err(CryptoError::InvalidHashString("unsupported Argon2 version".to_string()));
+    }
+
+    let params = Params {
+        m_cost: parsed.param_u32("m")?,
+        t_cost: parsed.param_u32("t")?,
+        p_cost: parsed.param_u32("p")?,
+        output_len: parsed.checked_hash()?.len(),
+    };
+    params.check_limits()?;
+    let mut computed = vec![0u8; params.output_len];
+    argon2id(password, &parsed.salt, &[], &[], &params, &mut computed)?;
+    Ok(constant_time_eq(&computed, &parsed.hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::unhex;
+
+    #[test]
+    fn test_blake2b() {
+        // RFC 7693 appendix A
+        assert_eq!(
+            blake2b::hash(&[b"abc"], 64),
+            unhex("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923")
+        );
+    }
+
+    #[test]
+    fn test_rfc9106_vector() {
+        // RFC 9106 section 5.3
+        let params = Params {
+            m_cost: 32,
+            t_cost: 3,
+            p_cost: 4,
+            output_len: 32,
+        };
+        let mut tag = [0u8; 32];
+        argon2id(&[0x01; 32], &[0x02; 16], &[0x03; 8], &[0x04; 12], &params, &mut tag).unwrap();
+        assert_eq!(
+            tag.to_vec(),
+            unhex("0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659")
+        );
+    }
+
+    #[test]
+    fn test_matches_openssl() {
+        // openssl kdf -keylen 32 -kdfopt pass:password -kdfopt salt:somesalt0000
+        //   -kdfopt iter:2 -kdfopt memcost:64 -kdfopt lanes:2 ARGON2ID
+        let params = Params {
+            m_cost: 64,
+            t_cost: 2,
+            p_cost: 2,
+            output_len: 32,
+        };
+        let mut tag = [0u8; 32];
+        argon2id(b"password", b"somesalt0000", &[], &[], &params, &mut tag).unwrap();
+        assert_eq!(
+            tag.to_vec(),
+            unhex("f0742f6ccf437ce59f471d74bdb3271e49097cdf6f318ea7e3b3b1299d9d9b66")
+        );
+    }
+
+    #[test]
+    fn test_phc_roundtrip() {
+        let params = Params::new(64, 1, 1).unwrap();
+        let phc = hash_password(b"correct horse", &params).unwrap();
+        assert!(phc.starts_with("$argon2id$v=19$m=64,t=1,p=1$"));
+        assert_eq!(verify_password(b"correct horse", &phc), Ok(true));
+        assert_eq!(verify_password(b"correct horsE", &phc), Ok(false));
+        assert!(Params::new(4, 1, 1).is_err());
+        assert!(verify_password(b"x", "$argon2id$v=19$m=4194304,t=1,p=1$c2FsdHNhbHQ$c2FsdHNhbHRzYWx0c2FsdA").is_err());
+    }
+}
@@ -0,0 +1,440 @@
+// AvilaCrypto BigNum - Arbitrary-precision unsigned integers for RSA
+// Zero External Dependencies 🦀
+
+use std::cmp::Ordering;
+
+/// Unsigned big integer, little-endian 64-bit limbs without trailing zero limbs
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { limbs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        Self::from_u64(1)
+    }
+
+    pub fn from_u64(value: u64) -> Self {
+        let mut n = Self { limbs: vec![value] };
+        n.normalize();
+        n
+    }
+
+    fn from_limbs(limbs: Vec<u64>) -> Self {
+        let mut n = Self { limbs };
+        n.normalize();
+        n
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    /// Parse a big-endian byte string
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        let mut limbs = Vec::with_capacity(bytes.len() / 8 + 1);
+        for chunk in bytes.rchunks(8) {
+            let mut buf = [0u8; 8];
+            buf[8 - chunk.len()..].copy_from_slice(chunk);
+            limbs.push(u64::from_be_bytes(buf));
+        }
+        Self::from_limbs(limbs)
+    }
+
+    /// Minimal big-endian encoding (empty for zero)
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.limbs.len() * 8);
+        for limb in self.limbs.iter().rev() {
+            out.extend_from_slice(&limb.to_be_bytes());
+        }
+        let start = out.iter().position(|&b| b != 0).unwrap_or(out.len());
+        out.split_off(start)
+    }
+
+    /// Big-endian encoding left-padded to `len` bytes; None if the value does not fit
+    pub fn to_bytes_be_padded(&self, len: usize) -> Option<Vec<u8>> {
+        let bytes = self.to_bytes_be();
+        if bytes.len() > len {
+            return None;
+        }
+        let mut out = vec![0u8; len - bytes.len()];
+        out.extend_from_slice(&bytes);
+        Some(out)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.limbs.first().is_some_and(|l| l & 1 == 1)
+    }
+
+    /// Number of significant bits
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * 64 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn bit(&self, i: usize) -> bool {
+        self.limbs
+            .get(i / 64)
+            .is_some_and(|limb| (limb >> (i % 64)) & 1 == 1)
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut out = Vec::with_capacity(len + 1);
+        let mut carry = 0u64;
+        for i in 0..len {
+            let a = self.limbs.get(i).copied().unwrap_or(0);
+            let b = other.limbs.get(i).copied().unwrap_or(0);
+            let (s1, c1) = a.overflowing_add(b);
+            let (s2, c2) = s1.overflowing_add(carry);
+            out.push(s2);
+            carry = (c1 | c2) as u64;
+        }
+        out.push(carry);
+        Self::from_limbs(out)
+    }
+
+    /// self - other; None if the result would be negative
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if *self < *other {
+            return None;
+        }
+        let mut out = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0u64;
+        for i in 0..self.limbs.len() {
+            let b = other.limbs.get(i).copied().unwrap_or(0);
+            let (d1, b1) = self.limbs[i].overflowing_sub(b);
+            let (d2, b2) = d1.overflowing_sub(borrow);
+            out.push(d2);
+            borrow = (b1 | b2) as u64;
+        }
+        Some(Self::from_limbs(out))
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Self::zero();
+        }
+        let mut out = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry: u128 = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let t = (a as u128) * (b as u128) + out[i + j] as u128 + carry;
+                out[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            out[i + other.limbs.len()] = carry as u64;
+        }
+        Self::from_limbs(out)
+    }
+
+    fn shl1_or(&mut self, bit: bool) {
+        let mut carry = bit as u64;
+        for limb in self.limbs.iter_mut() {
+            let next = *limb >> 63;
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+        if carry != 0 {
+            self.limbs.push(carry);
+        }
+    }
+
+    /// Quotient and remainder; panics on division by zero
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        assert!(!divisor.is_zero(), "division by zero");
+        if *self < *divisor {
+            return (Self::zero(), self.clone());
+        }
+
+        let bits = self.bits();
+        let mut quotient = vec![0u64; self.limbs.len()];
+        let mut rem = Self::zero();
+        for i in (0..bits).rev() {
+            rem.shl1_or(self.bit(i));
+            if rem >= *divisor {
+                rem = rem.checked_sub(divisor).unwrap_or_default();
+                quotient[i / 64] |= 1 << (i % 64);
+            }
+        }
+        (Self::from_limbs(quotient), rem)
+    }
+
+    pub fn rem(&self, modulus: &Self) -> Self {
+        self.div_rem(modulus).1
+    }
+
+    /// (self * other) mod modulus
+    pub fn mul_mod(&self, other: &Self, modulus: &Self) -> Self {
+        self.mul(other).rem(modulus)
+    }
+
+    /// (self - other) mod modulus, for inputs already reduced mod modulus
+    pub fn sub_mod(&self, other: &Self, modulus: &Self) -> Self {
+        match self.checked_sub(other) {
+            Some(diff) => diff,
+            None => self.add(modulus).checked_sub(other).unwrap_or_default(),
+        }
+    }
+
+    /// self^exponent mod modulus (modulus must be odd). Runs in time that
+    /// depends on the exponent's bits: only for public exponents.
+    pub fn mod_pow(&self, exponent: &Self, modulus: &Self) -> Self {
+        Montgomery::new(modulus).pow(self, exponent)
+    }
+
+    /// self^exponent mod modulus (modulus must be odd) for secret exponents:
+    /// the same operations run whatever the exponent's bits are
+    pub fn mod_pow_secret(&self, exponent: &Self, modulus: &Self) -> Self {
+        Montgomery::new(modulus).pow_fixed_window(self, exponent)
+    }
+
+    /// Overwrite the limbs before the memory is released
+    pub fn zeroize(&mut self) {
+        for limb in self.limbs.iter_mut() {
+            // SAFETY: writing through a valid &mut u64
+            unsafe { std::ptr::write_volatile(limb, 0) };
+        }
+        self.limbs.clear();
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+/// Montgomery multiplication context for an odd modulus
+struct Montgomery {
+    modulus: Vec<u64>,
+    n0_inv: u64,
+    r2: BigUint,
+}
+
+impl Montgomery {
+    fn new(modulus: &BigUint) -> Self {
+        assert!(modulus.is_odd(), "Montgomery modulus must be odd");
+        let n0 = modulus.limbs[0];
+
+        // Newton iteration for n0^-1 mod 2^64, then negate
+        let mut inv = 1u64;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(n0.wrapping_mul(inv)));
+        }
+
+        let k = modulus.limbs.len();
+        let mut r2_limbs = vec![0u64; 2 * k + 1];
+        r2_limbs[2 * k] = 1;
+        let r2 = BigUint::from_limbs(r2_limbs).rem(modulus);
+
+        Self {
+            modulus: modulus.limbs.clone(),
+            n0_inv: inv.wrapping_neg(),
+            r2,
+        }
+    }
+
+    fn limbs_of(&self, value: &BigUint) -> Vec<u64> {
+        let mut limbs = value.limbs.clone();
+        limbs.resize(self.modulus.len(), 0);
+        limbs
+    }
+
+    /// a * b * R^-1 mod n (CIOS)
+    fn mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let k = self.modulus.len();
+        let n = &self.modulus;
+        let mut t = vec![0u64; k + 2];
+
+        for &ai in a.iter().take(k) {
+            let mut carry: u128 = 0;
+            for j in 0..k {
+                let s = t[j] as u128 + (ai as u128) * (b[j] as u128) + carry;
+                t[j] = s as u64;
+                carry = s >> 64;
+            }
+            let s = t[k] as u128 + carry;
+            t[k] = s as u64;
+            t[k + 1] = (s >> 64) as u64;
+
+            let m = t[0].wrapping_mul(self.n0_inv);
+            let s = t[0] as u128 + (m as u128) * (n[0] as u128);
+            let mut carry = s >> 64;
+            for j in 1..k {
+                let s = t[j] as u128 + (m as u128) * (n[j] as u128) + carry;
+                t[j - 1] = s as u64;
+                carry = s >> 64;
+            }
+            let s = t[k] as u128 + carry;
+            t[k - 1] = s as u64;
+            t[k] = t[k + 1] + (s >> 64) as u64;
+            t[k + 1] = 0;
+        }
+
+        // Conditional final subtraction
+        let mut diff = vec![0u64; k];
+        let mut borrow = 0u64;
+        for j in 0..k {
+            let (d1, b1) = t[j].overflowing_sub(n[j]);
+            let (d2, b2) = d1.overflowing_sub(borrow);
+            diff[j] = d2;
+            borrow = (b1 | b2) as u64;
+        }
+        // Select without branching: all ones keeps t, all zeros keeps diff
+        let (_, under) = t[k].overflowing_sub(borrow);
+        let keep = (under as u64).wrapping_neg();
+        for j in 0..k {
+            diff[j] = (t[j] & keep) | (diff[j] & !keep);
+        }
+        diff
+    }
+
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        let modulus = BigUint::from_limbs(self.modulus.clone());
+        let base = self.limbs_of(&base.rem(&modulus));
+        let r2 = self.limbs_of(&self.r2);
+
+        let base_m = self.mul(&base, &r2);
+        let mut acc = self.mul(&self.limbs_of(&BigUint::one()), &r2);
+
+        for i in (0..exponent.bits()).rev() {
+            acc = self.mul(&acc, &acc);
+            if exponent.bit(i) {
+                acc = self.mul(&acc, &base_m);
+            }
+        }
+
+        let mut one = vec![0u64; self.modulus.len()];
+        one[0] = 1;
+        BigUint::from_limbs(self.mul(&acc, &one))
+    }
+
+    /// Fixed 4-bit windows over the full modulus width; table entries are
+    /// read with masks so the memory access pattern does not follow the
+    /// exponent either
+    fn pow_fixed_window(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        const WINDOW: usize = 4;
+        let modulus = BigUint::from_limbs(self.modulus.clone());
+        let base = self.limbs_of(&base.rem(&modulus));
+        let r2 = self.limbs_of(&self.r2);
+
+        // table[i] = base^i in Montgomery form
+        let base_m = self.mul(&base, &r2);
+        let mut table = vec![self.mul(&self.limbs_of(&BigUint::one()), &r2)];
+        for i in 1..1 << WINDOW {
+            let next = self.mul(&table[i - 1], &base_m);
+            table.push(next);
+        }
+
+        let windows = (self.modulus.len() * 64).max(exponent.bits()).div_ceil(WINDOW);
+        let mut acc = table[0].clone();
+        for w in (0..windows).rev() {
+            for _ in 0..WINDOW {
+                acc = self.mul(&acc, &acc);
+            }
+            let digit = (0..WINDOW).fold(0, |digit, b| digit | (exponent.bit(w * WINDOW + b) as usize) << b);
+            acc = self.mul(&acc, &select(&table, digit));
+        }
+
+        let mut one = vec![0u64; self.modulus.len()];
+        one[0] = 1;
+        BigUint::from_limbs(self.mul(&acc, &one))
+    }
+}
+
+/// `table[index]`, reading every entry
+fn select(table: &[Vec<u64>], index: usize) -> Vec<u64> {
+    let mut out = vec![0u64; table[0].len()];
+    for (i, entry) in table.iter().enumerate() {
+        let diff = (i ^ index) as u64;
+        // All ones when diff is zero, without a comparison
+        let mask = ((diff | diff.wrapping_neg()) >> 63).wrapping_sub(1);
+        for (o, limb) in out.iter_mut().zip(entry) {
+            *o |= limb & mask;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let bytes = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xfe, 0xdc];
+        let n = BigUint::from_bytes_be(&bytes);
+        assert_eq!(n.to_bytes_be(), bytes.to_vec());
+        assert_eq!(n.bits(), 73);
+        assert_eq!(BigUint::from_bytes_be(&[0, 0, 5]).to_bytes_be_padded(4), Some(vec![0, 0, 0, 5]));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let a = BigUint::from_bytes_be(&[0xff; 40]);
+        let b = BigUint::from_bytes_be(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x11]);
+        let (q, r) = a.div_rem(&b);
+        assert!(r < b);
+        assert_eq!(q.mul(&b).add(&r), a);
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        // Fermat: a^(p-1) = 1 mod p for p = 2^127 - 1
+        let p = BigUint::from_bytes_be(&{
+            let mut b = [0xffu8; 16];
+            b[0] = 0x7f;
+            b
+        });
+        let a = BigUint::from_u64(0x1234_5678_9abc_def0);
+        let exp = p.checked_sub(&BigUint::one()).unwrap();
+        assert_eq!(a.mod_pow(&exp, &p), BigUint::one());
+
+        assert_eq!(
+            BigUint::from_u64(4).mod_pow(&BigUint::from_u64(13), &BigUint::from_u64(497)),
+            BigUint::from_u64(445)
+        );
+    }
+
+    #[test]
+    fn test_mod_pow_secret_matches() {
+        let p = BigUint::from_bytes_be(&{
+            let mut b = [0xffu8; 16];
+            b[0] = 0x7f;
+            b
+        });
+        let a = BigUint::from_u64(0x1234_5678_9abc_def0);
+        for exp in [0u64, 1, 2, 15, 16, 65537, u64::MAX] {
+            let exp = BigUint::from_u64(exp);
+            assert_eq!(a.mod_pow_secret(&exp, &p), a.mod_pow(&exp, &p));
+        }
+        let big_exp = p.checked_sub(&BigUint::from_u64(2)).unwrap();
+        assert_eq!(a.mod_pow_secret(&big_exp, &p), a.mod_pow(&big_exp, &p));
+        assert_eq!(
+            BigUint::from_u64(4).mod_pow_secret(&BigUint::from_u64(13), &BigUint::from_u64(497)),
+            BigUint::from_u64(445)
+        );
+    }
+}
@@ -0,0 +1,289 @@
+// AvilaCrypto ChaCha20-Poly1305 - Authenticated encryption (RFC 8439)
+// Zero External Dependencies 🦀
+
+use crate::{constant_time_eq, random, CryptoError};
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+
+fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+/// One 64-byte ChaCha20 keystream block (RFC 8439 section 2.3)
+fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for i in 0..8 {
+        state[4 + i] = u32::from_le_bytes([key[i * 4], key[i * 4 + 1], key[i * 4 + 2], key[i * 4 + 3]]);
+    }
+    state[12] = counter;
+    for i in 0..3 {
+        state[13 + i] = u32::from_le_bytes([nonce[i * 4], nonce[i * 4 + 1], nonce[i * 4 + 2], nonce[i * 4 + 3]]);
+    }
+
+    let mut working = state;
+    for _ in 0..10 {
+        quarter_round(&mut working, 0, 4, 8, 12);
+        quarter_round(&mut working, 1, 5, 9, 13);
+        quarter_round(&mut working, 2, 6, 10, 14);
+        quarter_round(&mut working, 3, 7, 11, 15);
+        quarter_round(&mut working, 0, 5, 10, 15);
+        quarter_round(&mut working, 1, 6, 11, 12);
+        quarter_round(&mut working, 2, 7, 8, 13);
+        quarter_round(&mut working, 3, 4, 9, 14);
+    }
+
+    let mut out = [0u8; 64];
+    for i in 0..16 {
+        out[i * 4..(i + 1) * 4].copy_from_slice(&working[i].wrapping_add(state[i]).to_le_bytes());
+    }
+    out
+}
+
+/// XOR `data` with the ChaCha20 keystream starting at block `counter`
+pub fn chacha20_xor(key: &[u8; 32], counter: u32, nonce: &[u8; 12], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let block = chacha20_block(key, counter.wrapping_add(i as u32), nonce);
+        for (byte, k) in chunk.iter_mut().zip(block.iter()) {
+            *byte ^= k;
+        }
+    }
+}
+
+/// Poly1305 one-time authenticator (RFC 8439 section 2.5), 26-bit limbs
+fn poly1305(key: &[u8; 32], data: &[u8]) -> [u8; 16] {
+    let le32 = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+
+    // Clamped r
+    let r0 = le32(&key[0..4]) & 0x3ffffff;
+    let r1 = (le32(&key[3..7]) >> 2) & 0x3ffff03;
+    let r2 = (le32(&key[6..10]) >> 4) & 0x3ffc0ff;
+    let r3 = (le32(&key[9..13]) >> 6) & 0x3f03fff;
+    let r4 = (le32(&key[12..16]) >> 8) & 0x00fffff;
+    let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+
+    let mut h = [0u32; 5];
+    for chunk in data.chunks(16) {
+        let mut block = [0u8; 17];
+        block[..chunk.len()].copy_from_slice(chunk);
+        block[chunk.len()] = 1;
+
+        h[0] += le32(&block[0..4]) & 0x3ffffff;
+        h[1] += (le32(&block[3..7]) >> 2) & 0x3ffffff;
+        h[2] += (le32(&block[6..10]) >> 4) & 0x3ffffff;
+        h[3] += (le32(&block[9..13]) >> 6) & 0x3ffffff;
+        h[4] += (le32(&block[12..16]) >> 8) | ((block[16] as u32) << 24);
+
+        let m = |a: u32, b: u32| a as u64 * b as u64;
+        let d0 = m(h[0], r0) + m(h[1], s4) + m(h[2], s3) + m(h[3], s2) + m(h[4], s1);
+        let mut d1 = m(h[0], r1) + m(h[1], r0) + m(h[2], s4) + m(h[3], s3) + m(h[4], s2);
+        let mut d2 = m(h[0], r2) + m(h[1], r1) + m(h[2], r0) + m(h[3], s4) + m(h[4], s3);
+        let mut d3 = m(h[0], r3) + m(h[1], r2) + m(h[2], r1) + m(h[3], r0) + m(h[4], s4);
+        let mut d4 = m(h[0], r4) + m(h[1], r3) + m(h[2], r2) + m(h[3], r1) + m(h[4], r0);
+
+        // Partial carry propagation
+        let mut c;
+        c = d0 >> 26;
+        h[0] = d0 as u32 & 0x3ffffff;
+        d1 += c;
+        c = d1 >> 26;
+        h[1] = d1 as u32 & 0x3ffffff;
+        d2 += c;
+        c = d2 >> 26;
+        h[2] = d2 as u32 & 0x3ffffff;
+        d3 += c;
+        c = d3 >> 26;
+        h[3] = d3 as u32 & 0x3ffffff;
+        d4 += c;
+        c = d4 >> 26;
+        h[4] = d4 as u32 & 0x3ffffff;
+        h[0] += c as u32 * 5;
+        let c = h[0] >> 26;
+        h[0] &= 0x3ffffff;
+        h[1] += c;
+    }
+
+    // Full carry, then compute h - p and select it if non-negative
+    let mut c = h[1] >> 26;
+    h[1] &= 0x3ffffff;
+    h[2] += c;
+    c = h[2] >> 26;
+    h[2] &= 0x3ffffff;
+    h[3] += c;
+    c = h[3] >> 26;
+    h[3] &= 0x3ffffff;
+    h[4] += c;
+    c = h[4] >> 26;
+    h[4] &= 0x3ffffff;
+    h[0] += c * 5;
+    c = h[0] >> 26;
+    h[0] &= 0x3ffffff;
+    h[1] += c;
+
+    let mut g = [0u32; 5];
+    g[0] = h[0].wrapping_add(5);
+    c = g[0] >> 26;
+    g[0] &= 0x3ffffff;
+    for i in 1..4 {
+        g[i] = h[i].wrapping_add(c);
+        c = g[i] >> 26;
+        g[i] &= 0x3ffffff;
+    }
+    g[4] = h[4].wrapping_add(c).wrapping_sub(1 << 26);
+
+    // Top bit of g[4] set means h < p, keep h
+    let mask = (g[4] >> 31).wrapping_sub(1);
+    for i in 0..5 {
+        h[i] = (h[i] & !mask) | (g[i] & mask);
+    }
+
+    // Pack into 128 bits and add s
+    let h0 = h[0] | (h[1] << 26);
+    let h1 = (h[1] >> 6) | (h[2] << 20);
+    let h2 = (h[2] >> 12) | (h[3] << 14);
+    let h3 = (h[3] >> 18) | (h[4] << 8);
+
+    let mut tag = [0u8; 16];
+    let mut carry = 0u64;
+    for (i, word) in [h0, h1, h2, h3].iter().enumerate() {
+        let sum = *word as u64 + le32(&key[16 + i * 4..20 + i * 4]) as u64 + carry;
+        tag[i * 4..(i + 1) * 4].copy_from_slice(&(sum as u32).to_le_bytes());
+        carry = sum >> 32;
+    }
+    tag
+}
+
+/// ChaCha20-Poly1305 AEAD with a 256-bit key
+#[derive(Clone)]
+pub struct ChaCha20Poly1305 {
+    key: [u8; 32],
+}
+
+impl ChaCha20Poly1305 {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self { key }
+    }
+
+    fn tag(&self, nonce: &[u8; 12], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+        let block = chacha20_block(&self.key, 0, nonce);
+        let mut otk = [0u8; 32];
+        otk.copy_from_slice(&block[..32]);
+
+        let pad = |len: usize| vec![0u8; (16 - len % 16) % 16];
+        let mut mac_data = Vec::with_capacity(aad.len() + ciphertext.len() + 48);
+        mac_data.extend_from_slice(aad);
+        mac_data.extend_from_slice(&pad(aad.len()));
+        mac_data.extend_from_slice(ciphertext);
+        mac_data.extend_from_slice(&pad(ciphertext.len()));
+        mac_data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+        mac_data.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+        poly1305(&otk, &mac_data)
+    }
+
+    /// Encrypt and authenticate; returns ciphertext || tag
+    pub fn encrypt(&self, nonce: &[u8; 12], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let mut out = plaintext.to_vec();
+        chacha20_xor(&self.key, 1, nonce, &mut out);
+        let tag = self.tag(nonce, aad, &out);
+        out.extend_from_slice(&tag);
+        out
+    }
+
+    /// Verify the tag and decrypt ciphertext || tag
+    pub fn decrypt(&self, nonce: &[u8; 12], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if sealed.len() < TAG_LEN {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+        let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+        if !constant_time_eq(&self.tag(nonce, aad, ciphertext), tag) {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+        let mut out = ciphertext.to_vec();
+        chacha20_xor(&self.key, 1, nonce, &mut out);
+        Ok(out)
+    }
+
+    /// Encrypt under a fresh random nonce; returns nonce || ciphertext || tag
+    pub fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let nonce: [u8; NONCE_LEN] = random::secure_array()?;
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&self.encrypt(&nonce, aad, plaintext));
+        Ok(out)
+    }
+
+    /// Reverse of `seal`
+    pub fn open(&self, aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if sealed.len() < NONCE_LEN + TAG_LEN {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&sealed[..NONCE_LEN]);
+        self.decrypt(&nonce, aad, &sealed[NONCE_LEN..])
+    }
+}
+
+impl Drop for ChaCha20Poly1305 {
+    fn drop(&mut self) {
+        for b in self.key.iter_mut() {
+            // SAFETY: writing through a valid &mut u8
+            unsafe { std::ptr::write_volatile(b, 0) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::unhex;
+
+    #[test]
+    fn test_poly1305_rfc8439() {
+        // RFC 8439 section 2.5.2
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&unhex("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b"));
+        let tag = poly1305(&key, b"Cryptographic Forum Research Group");
+        assert_eq!(tag.to_vec(), unhex("a8061dc1305136c6c22b8baf0c0127a9"));
+    }
+
+    #[test]
+    fn test_aead_rfc8439() {
+        // RFC 8439 section 2.8.2
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&unhex("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f"));
+        let mut nonce = [0u8; 12];
+        nonce.copy_from_slice(&unhex("070000004041424344454647"));
+        let aad = unhex("50515253c0c1c2c3c4c5c6c7");
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+        let aead = ChaCha20Poly1305::new(key);
+        let sealed = aead.encrypt(&nonce, &aad, plaintext);
+        assert_eq!(
+            sealed[plaintext.len()..].to_vec(),
+            unhex("1ae10b594f09e26a7e902ecbd0600691")
+        );
+        assert_eq!(
+            sealed[..16].to_vec(),
+            unhex("d31a8d34648e60db7b86afbc53ef7ec2")
+        );
+        assert_eq!(aead.decrypt(&nonce, &aad, &sealed).unwrap(), plaintext.to_vec());
+    }
+
+    #[test]
+    fn test_tampering_rejected() {
+        let aead = ChaCha20Poly1305::new([7u8; 32]);
+        let mut sealed = aead.seal(b"header", b"MONGODB_ATLAS_API_KEY=secret").unwrap();
+        assert!(aead.open(b"header", &sealed).is_ok());
+        assert_eq!(aead.open(b"other", &sealed), Err(CryptoError::AuthenticationFailed));
+        sealed[20] ^= 1;
+        assert_eq!(aead.open(b"header", &sealed), Err(CryptoError::AuthenticationFailed));
+    }
+}
@@ -0,0 +1,543 @@
+// AvilaCrypto Curve25519 - Field, Edwards point and scalar arithmetic
+// Shared by x25519 (RFC 7748) and ed25519 (RFC 8032)
+//
+// All secret-dependent operations run in constant time: no branches or
+// table lookups on secret data, selection is done with bit masks.
+
+const MASK51: u64 = (1 << 51) - 1;
+
+/// Element of GF(2^255 - 19) as five 51-bit limbs
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FieldElement([u64; 5]);
+
+// d = -121665 / 121666
+const D_BYTES: [u8; 32] = [
+    0xa3, 0x78, 0x59, 0x13, 0xca, 0x4d, 0xeb, 0x75, 0xab, 0xd8, 0x41, 0x41, 0x4d, 0x0a, 0x70, 0x00,
+    0x98, 0xe8, 0x79, 0x77, 0x79, 0x40, 0xc7, 0x8c, 0x73, 0xfe, 0x6f, 0x2b, 0xee, 0x6c, 0x03, 0x52,
+];
+
+// 2 * d
+const D2_BYTES: [u8; 32] = [
+    0x59, 0xf1, 0xb2, 0x26, 0x94, 0x9b, 0xd6, 0xeb, 0x56, 0xb1, 0x83, 0x82, 0x9a, 0x14, 0xe0, 0x00,
+    0x30, 0xd1, 0xf3, 0xee, 0xf2, 0x80, 0x8e, 0x19, 0xe7, 0xfc, 0xdf, 0x56, 0xdc, 0xd9, 0x06, 0x24,
+];
+
+// sqrt(-1) = 2^((p - 1) / 4)
+const SQRT_M1_BYTES: [u8; 32] = [
+    0xb0, 0xa0, 0x0e, 0x4a, 0x27, 0x1b, 0xee, 0xc4, 0x78, 0xe4, 0x2f, 0xad, 0x06, 0x18, 0x43, 0x2f,
+    0xa7, 0xd7, 0xfb, 0x3d, 0x99, 0x00, 0x4d, 0x2b, 0x0b, 0xdf, 0xc1, 0x4f, 0x80, 0x24, 0x83, 0x2b,
+];
+
+// Ed25519 base point, compressed (y = 4/5, x even)
+const BASE_POINT_BYTES: [u8; 32] = [
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+];
+
+fn load8(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(buf)
+}
+
+#[inline(always)]
+fn m(a: u64, b: u64) -> u128 {
+    (a as u128) * (b as u128)
+}
+
+impl FieldElement {
+    pub(crate) const ZERO: Self = FieldElement([0, 0, 0, 0, 0]);
+    pub(crate) const ONE: Self = FieldElement([1, 0, 0, 0, 0]);
+
+    pub(crate) fn from_u64(value: u64) -> Self {
+        FieldElement([value & MASK51, value >> 51, 0, 0, 0])
+    }
+
+    /// Decode 32 little-endian bytes, ignoring the top bit
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Self {
+        FieldElement([
+            load8(&bytes[0..]) & MASK51,
+            (load8(&bytes[6..]) >> 3) & MASK51,
+            (load8(&bytes[12..]) >> 6) & MASK51,
+            (load8(&bytes[19..]) >> 1) & MASK51,
+            (load8(&bytes[24..]) >> 12) & MASK51,
+        ])
+    }
+
+    /// Canonical little-endian encoding (fully reduced mod p)
+    pub(crate) fn to_bytes(self) -> [u8; 32] {
+        let mut l = Self::carry(self.0).0;
+
+        // Compute q = 1 if l >= p, then subtract q * p by adding 19q and dropping bit 255
+        let mut q = (l[0] + 19) >> 51;
+        q = (l[1] + q) >> 51;
+        q = (l[2] + q) >> 51;
+        q = (l[3] + q) >> 51;
+        q = (l[4] + q) >> 51;
+
+        l[0] += 19 * q;
+        l[1] += l[0] >> 51;
+        l[0] &= MASK51;
+        l[2] += l[1] >> 51;
+        l[1] &= MASK51;
+        l[3] += l[2] >> 51;
+        l[2] &= MASK51;
+        l[4] += l[3] >> 51;
+        l[3] &= MASK51;
+        l[4] &= MASK51;
+
+        let mut out = [0u8; 32];
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+        let mut idx = 0;
+        for limb in l {
+            acc |= (limb as u128) << bits;
+            bits += 51;
+            while bits >= 8 {
+                out[idx] = acc as u8;
+                acc >>= 8;
+                bits -= 8;
+                idx += 1;
+            }
+        }
+        out[idx] = acc as u8;
+        out
+    }
+
+    fn carry(mut l: [u64; 5]) -> Self {
+        let c0 = l[0] >> 51;
+        let c1 = l[1] >> 51;
+        let c2 = l[2] >> 51;
+        let c3 = l[3] >> 51;
+        let c4 = l[4] >> 51;
+
+        l[0] &= MASK51;
+        l[1] &= MASK51;
+        l[2] &= MASK51;
+        l[3] &= MASK51;
+        l[4] &= MASK51;
+
+        l[0] += c4 * 19;
+        l[1] += c0;
+        l[2] += c1;
+        l[3] += c2;
+        l[4] += c3;
+
+        FieldElement(l)
+    }
+
+    pub(crate) fn add(&self, rhs: &Self) -> Self {
+        let a = self.0;
+        let b = rhs.0;
+        Self::carry([a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3], a[4] + b[4]])
+    }
+
+    pub(crate) fn sub(&self, rhs: &Self) -> Self {
+        // Add 16p before subtracting so no limb underflows
+        let a = self.0;
+        let b = rhs.0;
+        Self::carry([
+            (a[0] + 36028797018963664) - b[0],
+            (a[1] + 36028797018963952) - b[1],
+            (a[2] + 36028797018963952) - b[2],
+            (a[3] + 36028797018963952) - b[3],
+            (a[4] + 36028797018963952) - b[4],
+        ])
+    }
+
+    pub(crate) fn neg(&self) -> Self {
+        Self::ZERO.sub(self)
+    }
+
+    pub(crate) fn mul(&self, rhs: &Self) -> Self {
+        let a = self.0;
+        let b = rhs.0;
+
+        let b1_19 = b[1] * 19;
+        let b2_19 = b[2] * 19;
+        let b3_19 = b[3] * 19;
+        let b4_19 = b[4] * 19;
+
+        let c0 = m(a[0], b[0]) + m(a[4], b1_19) + m(a[3], b2_19) + m(a[2], b3_19) + m(a[1], b4_19);
+        let mut c1 = m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2_19) + m(a[3], b3_19) + m(a[2], b4_19);
+        let mut c2 = m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3_19) + m(a[3], b4_19);
+        let mut c3 = m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4_19);
+        let mut c4 = m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]);
+
+        c1 += c0 >> 51;
+        c2 += c1 >> 51;
+        c3 += c2 >> 51;
+        c4 += c3 >> 51;
+
+        let mut out = [
+            (c0 as u64) & MASK51,
+            (c1 as u64) & MASK51,
+            (c2 as u64) & MASK51,
+            (c3 as u64) & MASK51,
+            (c4 as u64) & MASK51,
+        ];
+
+        let carry = (c4 >> 51) as u64;
+        out[0] += carry * 19;
+        out[1] += out[0] >> 51;
+        out[0] &= MASK51;
+
+        FieldElement(out)
+    }
+
+    pub(crate) fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    /// self^(2^k)
+    fn pow2k(&self, k: u32) -> Self {
+        let mut out = *self;
+        for _ in 0..k {
+            out = out.square();
+        }
+        out
+    }
+
+    /// Returns (self^(2^250 - 1), self^11)
+    fn pow22501(&self) -> (Self, Self) {
+        let t0 = self.square(); // 2
+        let t1 = t0.pow2k(2); // 8
+        let t2 = self.mul(&t1); // 9
+        let t3 = t0.mul(&t2); // 11
+        let t4 = t3.square(); // 22
+        let t5 = t2.mul(&t4); // 2^5 - 1
+        let t6 = t5.pow2k(5);
+        let t7 = t6.mul(&t5); // 2^10 - 1
+        let t8 = t7.pow2k(10);
+        let t9 = t8.mul(&t7); // 2^20 - 1
+        let t10 = t9.pow2k(20);
+        let t11 = t10.mul(&t9); // 2^40 - 1
+        let t12 = t11.pow2k(10);
+        let t13 = t12.mul(&t7); // 2^50 - 1
+        let t14 = t13.pow2k(50);
+        let t15 = t14.mul(&t13); // 2^100 - 1
+        let t16 = t15.pow2k(100);
+        let t17 = t16.mul(&t15); // 2^200 - 1
+        let t18 = t17.pow2k(50);
+        let t19 = t18.mul(&t13); // 2^250 - 1
+        (t19, t3)
+    }
+
+    /// Multiplicative inverse via Fermat: self^(p - 2). Returns zero for zero.
+    pub(crate) fn invert(&self) -> Self {
+        let (t19, t3) = self.pow22501();
+        t19.pow2k(5).mul(&t3)
+    }
+
+    /// self^((p - 5) / 8), used for square roots
+    fn pow_p58(&self) -> Self {
+        let (t19, _) = self.pow22501();
+        t19.pow2k(2).mul(self)
+    }
+
+    pub(crate) fn is_negative(&self) -> bool {
+        self.to_bytes()[0] & 1 == 1
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.to_bytes().iter().fold(0u8, |acc, b| acc | b) == 0
+    }
+
+    pub(crate) fn ct_eq(&self, other: &Self) -> bool {
+        let a = self.to_bytes();
+        let b = other.to_bytes();
+        a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+
+    /// Swap `a` and `b` when `choice` is 1, without branching
+    pub(crate) fn conditional_swap(a: &mut Self, b: &mut Self, choice: u64) {
+        let mask = 0u64.wrapping_sub(choice);
+        for i in 0..5 {
+            let t = mask & (a.0[i] ^ b.0[i]);
+            a.0[i] ^= t;
+            b.0[i] ^= t;
+        }
+    }
+
+    /// Returns `b` when `choice` is 1, otherwise `a`
+    fn select(a: &Self, b: &Self, choice: u64) -> Self {
+        let mask = 0u64.wrapping_sub(choice);
+        let mut out = [0u64; 5];
+        for (i, limb) in out.iter_mut().enumerate() {
+            *limb = a.0[i] ^ (mask & (a.0[i] ^ b.0[i]));
+        }
+        FieldElement(out)
+    }
+}
+
+/// Point on the twisted Edwards curve -x^2 + y^2 = 1 + d x^2 y^2,
+/// in extended coordinates (X:Y:Z:T) with x = X/Z, y = Y/Z, xy = T/Z
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct EdwardsPoint {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+    t: FieldElement,
+}
+
+impl EdwardsPoint {
+    pub(crate) fn identity() -> Self {
+        EdwardsPoint {
+            x: FieldElement::ZERO,
+            y: FieldElement::ONE,
+            z: FieldElement::ONE,
+            t: FieldElement::ZERO,
+        }
+    }
+
+    pub(crate) fn base_point() -> Self {
+        // The constant is a valid encoding, decompression cannot fail
+        Self::decompress(&BASE_POINT_BYTES).unwrap_or_else(Self::identity)
+    }
+
+    /// Decode a compressed point (RFC 8032 section 5.1.3)
+    pub(crate) fn decompress(bytes: &[u8; 32]) -> Option<Self> {
+        let sign = (bytes[31] >> 7) as u64;
+        let y = FieldElement::from_bytes(bytes);
+
+        // Reject non-canonical y (y >= p)
+        let mut canonical = y.to_bytes();
+        canonical[31] |= bytes[31] & 0x80;
+        if canonical != *bytes {
+            return None;
+        }
+
+        let d = FieldElement::from_bytes(&D_BYTES);
+        let yy = y.square();
+        let u = yy.sub(&FieldElement::ONE);
+        let v = d.mul(&yy).add(&FieldElement::ONE);
+
+        // x = u v^3 (u v^7)^((p - 5) / 8)
+        let v3 = v.square().mul(&v);
+        let v7 = v3.square().mul(&v);
+        let mut x = u.mul(&v3).mul(&u.mul(&v7).pow_p58());
+
+        let vxx = v.mul(&x.square());
+        if !vxx.ct_eq(&u) {
+            if vxx.ct_eq(&u.neg()) {
+                x = x.mul(&FieldElement::from_bytes(&SQRT_M1_BYTES));
+            } else {
+                return None;
+            }
+        }
+
+        if x.is_zero() && sign == 1 {
+            return None;
+        }
+        if (x.is_negative() as u64) != sign {
+            x = x.neg();
+        }
+
+        Some(EdwardsPoint {
+            x,
+            y,
+            z: FieldElement::ONE,
+            t: x.mul(&y),
+        })
+    }
+
+    /// Encode as 32 bytes: y with the sign of x in the top bit
+    pub(crate) fn compress(&self) -> [u8; 32] {
+        let zinv = self.z.invert();
+        let x = self.x.mul(&zinv);
+        let y = self.y.mul(&zinv);
+        let mut out = y.to_bytes();
+        out[31] |= (x.is_negative() as u8) << 7;
+        out
+    }
+
+    /// Unified addition (RFC 8032 section 5.1.4), also valid for doubling
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        let d2 = FieldElement::from_bytes(&D2_BYTES);
+        let a = self.y.sub(&self.x).mul(&other.y.sub(&other.x));
+        let b = self.y.add(&self.x).mul(&other.y.add(&other.x));
+        let c = self.t.mul(&d2).mul(&other.t);
+        let d = self.z.add(&self.z).mul(&other.z);
+        let e = b.sub(&a);
+        let f = d.sub(&c);
+        let g = d.add(&c);
+        let h = b.add(&a);
+        EdwardsPoint {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            t: e.mul(&h),
+            z: f.mul(&g),
+        }
+    }
+
+    pub(crate) fn double(&self) -> Self {
+        let a = self.x.square();
+        let b = self.y.square();
+        let zz = self.z.square();
+        let c = zz.add(&zz);
+        let h = a.add(&b);
+        let e = h.sub(&self.x.add(&self.y).square());
+        let g = a.sub(&b);
+        let f = c.add(&g);
+        EdwardsPoint {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            t: e.mul(&h),
+            z: f.mul(&g),
+        }
+    }
+
+    pub(crate) fn neg(&self) -> Self {
+        EdwardsPoint {
+            x: self.x.neg(),
+            y: self.y,
+            z: self.z,
+            t: self.t.neg(),
+        }
+    }
+
+    fn select(a: &Self, b: &Self, choice: u64) -> Self {
+        EdwardsPoint {
+            x: FieldElement::select(&a.x, &b.x, choice),
+            y: FieldElement::select(&a.y, &b.y, choice),
+            z: FieldElement::select(&a.z, &b.z, choice),
+            t: FieldElement::select(&a.t, &b.t, choice),
+        }
+    }
+
+    /// Constant-time scalar multiplication by a 256-bit little-endian scalar
+    pub(crate) fn mul_scalar(&self, scalar: &[u8; 32]) -> Self {
+        let mut acc = Self::identity();
+        for i in (0..256).rev() {
+            let bit = ((scalar[i / 8] >> (i % 8)) & 1) as u64;
+            acc = acc.double();
+            let sum = acc.add(self);
+            acc = Self::select(&acc, &sum, bit);
+        }
+        acc
+    }
+}
+
+/// Group order L = 2^252 + 27742317777372353535851937790883648493
+const L: [u64; 4] = [0x5812631a5cf5d3ed, 0x14def9dea2f79cd6, 0x0000000000000000, 0x1000000000000000];
+
+/// Reduce a little-endian integer of any length modulo L (constant time in the value)
+fn reduce_bits(bytes: &[u8]) -> [u8; 32] {
+    let mut r = [0u64; 4];
+    for i in (0..bytes.len() * 8).rev() {
+        let bit = ((bytes[i / 8] >> (i % 8)) & 1) as u64;
+
+        // r = 2r + bit; r < 2L < 2^254 so nothing overflows the top limb
+        r[3] = (r[3] << 1) | (r[2] >> 63);
+        r[2] = (r[2] << 1) | (r[1] >> 63);
+        r[1] = (r[1] << 1) | (r[0] >> 63);
+        r[0] = (r[0] << 1) | bit;
+
+        // Subtract L when r >= L
+        let mut diff = [0u64; 4];
+        let mut borrow = 0u64;
+        for j in 0..4 {
+            let (d1, b1) = r[j].overflowing_sub(L[j]);
+            let (d2, b2) = d1.overflowing_sub(borrow);
+            diff[j] = d2;
+            borrow = (b1 | b2) as u64;
+        }
+        let keep = 0u64.wrapping_sub(borrow); // all ones if r < L
+        for j in 0..4 {
+            r[j] = (r[j] & keep) | (diff[j] & !keep);
+        }
+    }
+
+    let mut out = [0u8; 32];
+    for (j, limb) in r.iter().enumerate() {
+        out[j * 8..(j + 1) * 8].copy_from_slice(&limb.to_le_bytes());
+    }
+    out
+}
+
+/// Reduce a 64-byte hash output modulo L
+pub(crate) fn scalar_reduce(wide: &[u8; 64]) -> [u8; 32] {
+    reduce_bits(wide)
+}
+
+/// (a * b + c) mod L
+pub(crate) fn scalar_mul_add(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> [u8; 32] {
+    let limbs = |s: &[u8; 32]| -> [u64; 4] {
+        let mut out = [0u64; 4];
+        for (j, limb) in out.iter_mut().enumerate() {
+            *limb = load8(&s[j * 8..]);
+        }
+        out
+    };
+    let (a, b, c) = (limbs(a), limbs(b), limbs(c));
+
+    let mut product = [0u64; 9];
+    for i in 0..4 {
+        let mut carry: u128 = 0;
+        for j in 0..4 {
+            let t = m(a[i], b[j]) + product[i + j] as u128 + carry;
+            product[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        product[i + 4] = carry as u64;
+    }
+
+    let mut carry: u128 = 0;
+    for (i, limb) in product.iter_mut().enumerate() {
+        let addend = if i < 4 { c[i] } else { 0 };
+        let t = *limb as u128 + addend as u128 + carry;
+        *limb = t as u64;
+        carry = t >> 64;
+    }
+
+    let mut bytes = [0u8; 72];
+    for (i, limb) in product.iter().enumerate() {
+        bytes[i * 8..(i + 1) * 8].copy_from_slice(&limb.to_le_bytes());
+    }
+    reduce_bits(&bytes)
+}
+
+/// True if the little-endian scalar is already reduced (s < L)
+pub(crate) fn scalar_is_canonical(s: &[u8; 32]) -> bool {
+    for j in (0..4).rev() {
+        let limb = load8(&s[j * 8..]);
+        if limb < L[j] {
+            return true;
+        }
+        if limb > L[j] {
+            return false;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_inverse() {
+        let x = FieldElement::from_u64(121666);
+        let product = x.mul(&x.invert());
+        assert!(product.ct_eq(&FieldElement::ONE));
+    }
+
+    #[test]
+    fn test_base_point_roundtrip() {
+        let b = EdwardsPoint::base_point();
+        assert_eq!(b.compress(), BASE_POINT_BYTES);
+        assert_eq!(b.double().compress(), b.add(&b).compress());
+    }
+
+    #[test]
+    fn test_group_order() {
+        // [L]B is the identity
+        let mut l_bytes = [0u8; 32];
+        for (j, limb) in L.iter().enumerate() {
+            l_bytes[j * 8..(j + 1) * 8].copy_from_slice(&limb.to_le_bytes());
+        }
+        let p = EdwardsPoint::base_point().mul_scalar(&l_bytes);
+        assert_eq!(p.compress(), EdwardsPoint::identity().compress());
+        assert!(!scalar_is_canonical(&l_bytes));
+    }
+}
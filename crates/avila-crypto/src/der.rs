@@ -0,0 +1,277 @@
+// AvilaCrypto DER - Minimal ASN.1 DER reader/writer for key and signature formats
+// Zero External Dependencies 🦀
+
+use crate::CryptoError;
+
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_BIT_STRING: u8 = 0x03;
+pub const TAG_OCTET_STRING: u8 = 0x04;
+pub const TAG_NULL: u8 = 0x05;
+pub const TAG_OID: u8 = 0x06;
+pub const TAG_SEQUENCE: u8 = 0x30;
+
+/// Context-specific constructed tag `[n]`
+pub const fn context(n: u8) -> u8 {
+    0xa0 | n
+}
+
+/// Well-known object identifiers, DER content bytes
+pub mod oid {
+    /// 1.2.840.113549.1.1.1
+    pub const RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+    /// 1.2.840.113549.1.1.10
+    pub const RSASSA_PSS: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0a];
+    /// 1.2.840.10045.2.1
+    pub const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+    /// 1.2.840.10045.3.1.7 (prime256v1 / secp256r1)
+    pub const PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+}
+
+fn err(msg: &str) -> CryptoError {
+    CryptoError::Der(msg.to_string())
+}
+
+/// Sequential reader over DER-encoded TLV items
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    /// Read the next item, returning its tag and content
+    pub fn read_any(&mut self) -> Result<(u8, &'a [u8]), CryptoError> {
+        let tag = *self.data.get(self.pos).ok_or_else(|| err("unexpected end"))?;
+        let first = *self.data.get(self.pos + 1).ok_or_else(|| err("missing length"))?;
+        self.pos += 2;
+
+        let len = if first < 0x80 {
+            first as usize
+        } else {
+            let count = (first & 0x7f) as usize;
+            if count == 0 || count > 4 {
+                return Err(err("unsupported length encoding"));
+            }
+            let bytes = self
+                .data
+                .get(self.pos..self.pos + count)
+                .ok_or_else(|| err("truncated length"))?;
+            if bytes[0] == 0 {
+                return Err(err("non-minimal length"));
+            }
+            self.pos += count;
+            bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize)
+        };
+
+        let content = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| err("truncated content"))?;
+        self.pos += len;
+        Ok((tag, content))
+    }
+
+    /// Read the next item and check its tag
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8], CryptoError> {
+        let (found, content) = self.read_any()?;
+        if found != tag {
+            return Err(CryptoError::Der(format!(
+                "expected tag {:#04x}, found {:#04x}",
+                tag, found
+            )));
+        }
+        Ok(content)
+    }
+
+    /// Read an optional item with the given tag
+    pub fn read_optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>, CryptoError> {
+        if self.peek_tag() == Some(tag) {
+            self.read(tag).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn sequence(&mut self) -> Result<Reader<'a>, CryptoError> {
+        self.read(TAG_SEQUENCE).map(Reader::new)
+    }
+
+    /// Non-negative INTEGER as big-endian magnitude without leading zeros
+    pub fn unsigned_integer(&mut self) -> Result<&'a [u8], CryptoError> {
+        let content = self.read(TAG_INTEGER)?;
+        if content.is_empty() {
+            return Err(err("empty integer"));
+        }
+        if content[0] & 0x80 != 0 {
+            return Err(err("negative integer"));
+        }
+        let start = content.iter().position(|&b| b != 0).unwrap_or(content.len());
+        Ok(&content[start..])
+    }
+
+    pub fn small_integer(&mut self) -> Result<u64, CryptoError> {
+        let bytes = self.unsigned_integer()?;
+        if bytes.len() > 8 {
+            return Err(err("integer too large"));
+        }
+        Ok(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    pub fn oid(&mut self) -> Result<&'a [u8], CryptoError> {
+        self.read(TAG_OID)
+    }
+
+    pub fn octet_string(&mut self) -> Result<&'a [u8], CryptoError> {
+        self.read(TAG_OCTET_STRING)
+    }
+
+    /// BIT STRING content, which must not have unused bits
+    pub fn bit_string(&mut self) -> Result<&'a [u8], CryptoError> {
+        let content = self.read(TAG_BIT_STRING)?;
+        match content.split_first() {
+            Some((0, bits)) => Ok(bits),
+            _ => Err(err("bit string with unused bits")),
+        }
+    }
+
+    pub fn finish(&self) -> Result<(), CryptoError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(err("trailing data"))
+        }
+    }
+}
+
+/// Encode a single TLV item
+pub fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = (len as u32).to_be_bytes();
+        let skip = bytes.iter().position(|&b| b != 0).unwrap_or(3);
+        out.push(0x80 | (4 - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+pub fn sequence(items: &[Vec<u8>]) -> Vec<u8> {
+    tlv(TAG_SEQUENCE, &items.concat())
+}
+
+/// Non-negative INTEGER from a big-endian magnitude
+pub fn unsigned_integer(magnitude: &[u8]) -> Vec<u8> {
+    let start = magnitude.iter().position(|&b| b != 0).unwrap_or(magnitude.len());
+    let trimmed = &magnitude[start..];
+    let mut content = Vec::with_capacity(trimmed.len() + 1);
+    if trimmed.is_empty() || trimmed[0] & 0x80 != 0 {
+        content.push(0);
+    }
+    content.extend_from_slice(trimmed);
+    tlv(TAG_INTEGER, &content)
+}
+
+pub fn oid(bytes: &[u8]) -> Vec<u8> {
+    tlv(TAG_OID, bytes)
+}
+
+pub fn octet_string(bytes: &[u8]) -> Vec<u8> {
+    tlv(TAG_OCTET_STRING, bytes)
+}
+
+pub fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    let mut content = vec![0];
+    content.extend_from_slice(bytes);
+    tlv(TAG_BIT_STRING, &content)
+}
+
+pub fn null() -> Vec<u8> {
+    tlv(TAG_NULL, &[])
+}
+
+/// Algorithm OID, optional parameter OID and key bytes of a PKCS#8 or SPKI structure
+pub type KeyInfo<'a> = (&'a [u8], Option<&'a [u8]>, &'a [u8]);
+
+/// Split a PKCS#8 PrivateKeyInfo into (algorithm OID, parameters, private key bytes)
+pub fn parse_pkcs8(der: &[u8]) -> Result<KeyInfo<'_>, CryptoError> {
+    let mut outer = Reader::new(der);
+    let mut info = outer.sequence()?;
+    outer.finish()?;
+
+    let version = info.small_integer()?;
+    if version > 1 {
+        return Err(err("unsupported PKCS#8 version"));
+    }
+    let (algorithm, params) = parse_algorithm(&mut info)?;
+    let key = info.octet_string()?;
+    Ok((algorithm, params, key))
+}
+
+/// Split a SubjectPublicKeyInfo into (algorithm OID, parameters, public key bits)
+pub fn parse_spki(der: &[u8]) -> Result<KeyInfo<'_>, CryptoError> {
+    let mut outer = Reader::new(der);
+    let mut info = outer.sequence()?;
+    outer.finish()?;
+
+    let (algorithm, params) = parse_algorithm(&mut info)?;
+    let key = info.bit_string()?;
+    info.finish()?;
+    Ok((algorithm, params, key))
+}
+
+fn parse_algorithm<'a>(reader: &mut Reader<'a>) -> Result<(&'a [u8], Option<&'a [u8]>), CryptoError> {
+    let mut alg = reader.sequence()?;
+    let algorithm = alg.oid()?;
+    let params = match alg.peek_tag() {
+        Some(TAG_OID) => Some(alg.oid()?),
+        Some(_) => {
+            // NULL or structured parameters (e.g. RSASSA-PSS); not needed to load the key
+            alg.read_any()?;
+            None
+        }
+        None => None,
+    };
+    Ok((algorithm, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_roundtrip() {
+        let encoded = unsigned_integer(&[0x00, 0x80, 0x01]);
+        assert_eq!(encoded, vec![0x02, 0x03, 0x00, 0x80, 0x01]);
+        assert_eq!(Reader::new(&encoded).unsigned_integer().unwrap(), &[0x80, 0x01]);
+        assert_eq!(unsigned_integer(&[]), vec![0x02, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn test_long_length() {
+        let content = vec![0xab; 300];
+        let encoded = octet_string(&content);
+        assert_eq!(&encoded[..4], &[0x04, 0x82, 0x01, 0x2c]);
+        assert_eq!(Reader::new(&encoded).octet_string().unwrap(), &content[..]);
+    }
+
+    #[test]
+    fn test_truncated_input() {
+        assert!(Reader::new(&[0x30, 0x05, 0x02, 0x01]).sequence().is_err());
+        assert!(Reader::new(&[0x02]).unsigned_integer().is_err());
+    }
+}
@@ -0,0 +1,291 @@
+// AvilaCrypto Ed25519 - Edwards-curve digital signatures (RFC 8032)
+// Zero External Dependencies 🦀
+
+use crate::curve25519::{scalar_is_canonical, scalar_mul_add, scalar_reduce, EdwardsPoint};
+use crate::{pem, random, sha512, CryptoError};
+
+pub const SEED_LEN: usize = 32;
+pub const PUBLIC_KEY_LEN: usize = 32;
+pub const SIGNATURE_LEN: usize = 64;
+
+// DER prefixes for id-Ed25519 (OID 1.3.101.112) keys, RFC 8410
+const PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+const SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+
+fn hash_concat(parts: &[&[u8]]) -> [u8; 64] {
+    let mut data = Vec::new();
+    for part in parts {
+        data.extend_from_slice(part);
+    }
+    sha512::hash(&data)
+}
+
+/// Ed25519 signing key, derived from a 32-byte seed
+#[derive(Clone)]
+pub struct SigningKey {
+    seed: [u8; 32],
+    scalar: [u8; 32],
+    prefix: [u8; 32],
+    verifying_key: VerifyingKey,
+}
+
+impl SigningKey {
+    /// Generate a fresh key from the OS random generator
+    pub fn generate() -> Result<Self, CryptoError> {
+        Ok(Self::from_seed(random::secure_array()?))
+    }
+
+    /// Expand a seed into the signing scalar and nonce prefix (RFC 8032 section 5.1.5)
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let h = sha512::hash(&seed);
+
+        let mut scalar = [0u8; 32];
+        scalar.copy_from_slice(&h[..32]);
+        scalar[0] &= 248;
+        scalar[31] &= 127;
+        scalar[31] |= 64;
+
+        let mut prefix = [0u8; 32];
+        prefix.copy_from_slice(&h[32..]);
+
+        let public = EdwardsPoint::base_point().mul_scalar(&scalar).compress();
+
+        Self {
+            seed,
+            scalar,
+            prefix,
+            verifying_key: VerifyingKey(public),
+        }
+    }
+
+    pub fn to_seed(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.verifying_key
+    }
+
+    /// Sign a message (RFC 8032 section 5.1.6)
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        let r = scalar_reduce(&hash_concat(&[&self.prefix, message]));
+        let big_r = EdwardsPoint::base_point().mul_scalar(&r).compress();
+
+        let k = scalar_reduce(&hash_concat(&[&big_r, &self.verifying_key.0, message]));
+        let s = scalar_mul_add(&k, &self.scalar, &r);
+
+        let mut sig = [0u8; 64];
+        sig[..32].copy_from_slice(&big_r);
+        sig[32..].copy_from_slice(&s);
+        Signature(sig)
+    }
+
+    /// PKCS#8 DER encoding (RFC 8410)
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut der = PKCS8_PREFIX.to_vec();
+        der.extend_from_slice(&self.seed);
+        der
+    }
+
+    pub fn from_der(der: &[u8]) -> Result<Self, CryptoError> {
+        if der.len() != PKCS8_PREFIX.len() + SEED_LEN || der[..PKCS8_PREFIX.len()] != PKCS8_PREFIX {
+            return Err(CryptoError::InvalidKey);
+        }
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&der[PKCS8_PREFIX.len()..]);
+        Ok(Self::from_seed(seed))
+    }
+
+    pub fn to_pem(&self) -> String {
+        pem::encode("PRIVATE KEY", &self.to_der())
+    }
+
+    pub fn from_pem(text: &str) -> Result<Self, CryptoError> {
+        Self::from_der(&pem::decode_expect(text, "PRIVATE KEY")?)
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        for b in self.seed.iter_mut().chain(self.scalar.iter_mut()).chain(self.prefix.iter_mut()) {
+            // SAFETY: writing through a valid &mut u8
+            unsafe { std::ptr::write_volatile(b, 0) };
+        }
+    }
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "SigningKey({:?})", self.verifying_key)
+    }
+}
+
+/// Ed25519 public key (compressed Edwards point)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyingKey([u8; 32]);
+
+impl VerifyingKey {
+    /// Accepts only canonical encodings of points on the curve
+    pub fn from_bytes(bytes: [u8; 32]) -> Result<Self, CryptoError> {
+        EdwardsPoint::decompress(&bytes).ok_or(CryptoError::InvalidKey)?;
+        Ok(VerifyingKey(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Verify a signature (RFC 8032 section 5.1.7)
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> Result<(), CryptoError> {
+        let a = EdwardsPoint::decompress(&self.0).ok_or(CryptoError::InvalidKey)?;
+
+        let mut r_bytes = [0u8; 32];
+        r_bytes.copy_from_slice(&signature.0[..32]);
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&signature.0[32..]);
+
+        if !scalar_is_canonical(&s) {
+            return Err(CryptoError::InvalidSignature);
+        }
+        let r = EdwardsPoint::decompress(&r_bytes).ok_or(CryptoError::InvalidSignature)?;
+
+        let k = scalar_reduce(&hash_concat(&[&r_bytes, &self.0, message]));
+
+        // Check [8][S]B = [8]R + [8][k]A
+        let sb = EdwardsPoint::base_point().mul_scalar(&s);
+        let rka = r.add(&a.mul_scalar(&k));
+        let mut diff = sb.add(&rka.neg());
+        for _ in 0..3 {
+            diff = diff.double();
+        }
+
+        if diff.compress() == EdwardsPoint::identity().compress() {
+            Ok(())
+        } else {
+            Err(CryptoError::InvalidSignature)
+        }
+    }
+
+    /// SubjectPublicKeyInfo DER encoding (RFC 8410)
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut der = SPKI_PREFIX.to_vec();
+        der.extend_from_slice(&self.0);
+        der
+    }
+
+    pub fn from_der(der: &[u8]) -> Result<Self, CryptoError> {
+        if der.len() != SPKI_PREFIX.len() + PUBLIC_KEY_LEN || der[..SPKI_PREFIX.len()] != SPKI_PREFIX {
+            return Err(CryptoError::InvalidKey);
+        }
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&der[SPKI_PREFIX.len()..]);
+        Self::from_bytes(bytes)
+    }
+
+    pub fn to_pem(&self) -> String {
+        pem::encode("PUBLIC KEY", &self.to_der())
+    }
+
+    pub fn from_pem(text: &str) -> Result<Self, CryptoError> {
+        Self::from_der(&pem::decode_expect(text, "PUBLIC KEY")?)
+    }
+}
+
+/// Ed25519 signature: R (32 bytes) || S (32 bytes)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Signature([u8; 64]);
+
+impl Signature {
+    pub fn from_bytes(bytes: [u8; 64]) -> Self {
+        Signature(bytes)
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != SIGNATURE_LEN {
+            return Err(CryptoError::InvalidSignature);
+        }
+        let mut sig = [0u8; 64];
+        sig.copy_from_slice(bytes);
+        Ok(Signature(sig))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.0
+    }
+}
+
+impl std::fmt::Debug for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let hex: String = self.0.iter().map(|b| format!("{:02x}", b)).collect();
+        write!(f, "Signature({})", hex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::unhex;
+
+    fn check_vector(secret: &str, public: &str, message: &str, signature: &str) {
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&unhex(secret));
+        let key = SigningKey::from_seed(seed);
+        assert_eq!(key.verifying_key().as_bytes().to_vec(), unhex(public));
+
+        let message = unhex(message);
+        let sig = key.sign(&message);
+        assert_eq!(sig.to_bytes().to_vec(), unhex(signature));
+        assert!(key.verifying_key().verify(&message, &sig).is_ok());
+    }
+
+    #[test]
+    fn test_rfc8032_vectors() {
+        // RFC 8032 section 7.1, TEST 1-3
+        check_vector(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        );
+        check_vector(
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        );
+        check_vector(
+            "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+        );
+    }
+
+    #[test]
+    fn test_tampered_message_rejected() {
+        let key = SigningKey::generate().unwrap();
+        let sig = key.sign(b"relatorio mensal");
+        assert!(key.verifying_key().verify(b"relatorio mensal", &sig).is_ok());
+        assert_eq!(
+            key.verifying_key().verify(b"relatorio mensaL", &sig),
+            Err(CryptoError::InvalidSignature)
+        );
+
+        // S >= L must be rejected (malleability)
+        let mut bytes = sig.to_bytes();
+        bytes[63] |= 0xf0;
+        assert!(key.verifying_key().verify(b"relatorio mensal", &Signature::from_bytes(bytes)).is_err());
+    }
+
+    #[test]
+    fn test_pem_roundtrip() {
+        let key = SigningKey::generate().unwrap();
+        let restored = SigningKey::from_pem(&key.to_pem()).unwrap();
+        assert_eq!(restored.to_seed(), key.to_seed());
+
+        let public = VerifyingKey::from_pem(&key.verifying_key().to_pem()).unwrap();
+        assert_eq!(public, key.verifying_key());
+    }
+}
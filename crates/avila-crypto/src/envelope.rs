@@ -0,0 +1,306 @@
+// AvilaCrypto Envelope - Versioned AEAD envelopes with key IDs and rotation
+// Zero External Dependencies 🦀
+//
+// Binary layout (version 1):
+//   version | key_id_len | key_id | nonce[12] | tag[16] | ciphertext
+// The version byte and key ID are authenticated together with the caller's
+// associated data, so an envelope cannot be relabelled to another key.
+
+use crate::chacha20poly1305::{ChaCha20Poly1305, NONCE_LEN, TAG_LEN};
+use crate::vault::write_private_file;
+use crate::{base64, random, CryptoError};
+use std::path::Path;
+
+pub const VERSION: u8 = 1;
+
+/// Encrypted value plus everything needed to decrypt it except the key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub version: u8,
+    pub key_id: String,
+    pub nonce: [u8; NONCE_LEN],
+    pub ciphertext: Vec<u8>,
+    pub tag: [u8; TAG_LEN],
+}
+
+impl Envelope {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(2 + self.key_id.len() + NONCE_LEN + TAG_LEN + self.ciphertext.len());
+        out.push(self.version);
+        out.push(self.key_id.len() as u8);
+        out.extend_from_slice(self.key_id.as_bytes());
+        out.extend_from_slice(&self.nonce);
+        out.extend_from_slice(&self.tag);
+        out.extend_from_slice(&self.ciphertext);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, CryptoError> {
+        let malformed = || CryptoError::InvalidParameter("malformed envelope".to_string());
+        let (&version, rest) = data.split_first().ok_or_else(malformed)?;
+        if version != VERSION {
+            return Err(CryptoError::InvalidParameter(format!("unsupported envelope version {}", version)));
+        }
+        let (&id_len, rest) = rest.split_first().ok_or_else(malformed)?;
+        let id_len = id_len as usize;
+        if rest.len() < id_len + NONCE_LEN + TAG_LEN {
+            return Err(malformed());
+        }
+        let key_id = std::str::from_utf8(&rest[..id_len]).map_err(|_| malformed())?.to_string();
+        let rest = &rest[id_len..];
+
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&rest[..NONCE_LEN]);
+        let mut tag = [0u8; TAG_LEN];
+        tag.copy_from_slice(&rest[NONCE_LEN..NONCE_LEN + TAG_LEN]);
+
+        Ok(Self {
+            version,
+            key_id,
+            nonce,
+            ciphertext: rest[NONCE_LEN + TAG_LEN..].to_vec(),
+            tag,
+        })
+    }
+
+    /// Associated data actually fed to the AEAD
+    fn bound_aad(version: u8, key_id: &str, aad: &[u8]) -> Vec<u8> {
+        let mut bound = vec![version, key_id.len() as u8];
+        bound.extend_from_slice(key_id.as_bytes());
+        bound.extend_from_slice(aad);
+        bound
+    }
+}
+
+/// Set of named data keys, one of which encrypts new envelopes
+pub struct Keyring {
+    keys: Vec<(String, [u8; 32])>,
+    active: usize,
+}
+
+impl Keyring {
+    pub fn new(key_id: &str, key: [u8; 32]) -> Result<Self, CryptoError> {
+        check_key_id(key_id)?;
+        Ok(Self {
+            keys: vec![(key_id.to_string(), key)],
+            active: 0,
+        })
+    }
+
+    /// Keyring with a single fresh random key
+    pub fn generate(key_id: &str) -> Result<Self, CryptoError> {
+        Self::new(key_id, random::secure_array()?)
+    }
+
+    pub fn active_id(&self) -> &str {
+        &self.keys[self.active].0
+    }
+
+    pub fn key_ids(&self) -> Vec<&str> {
+        self.keys.iter().map(|(id, _)| id.as_str()).collect()
+    }
+
+    pub fn contains(&self, key_id: &str) -> bool {
+        self.keys.iter().any(|(id, _)| id == key_id)
+    }
+
+    /// Keep a key for decrypting old envelopes without making it active
+    pub fn add(&mut self, key_id: &str, key: [u8; 32]) -> Result<(), CryptoError> {
+        check_key_id(key_id)?;
+        if self.contains(key_id) {
+            return Err(CryptoError::InvalidParameter(format!("duplicate key id {}", key_id)));
+        }
+        self.keys.push((key_id.to_string(), key));
+        Ok(())
+    }
+
+    /// Add a fresh random key and make it the active one.
+    /// Older keys stay available until every envelope has been re-encrypted.
+    pub fn rotate(&mut self, new_key_id: &str) -> Result<(), CryptoError> {
+        self.add(new_key_id, random::secure_array()?)?;
+        self.active = self.keys.len() - 1;
+        Ok(())
+    }
+
+    /// Forget a retired key; the active key cannot be removed
+    pub fn remove(&mut self, key_id: &str) -> bool {
+        match self.keys.iter().position(|(id, _)| id == key_id) {
+            Some(idx) if idx != self.active => {
+                self.keys.remove(idx);
+                if idx < self.active {
+                    self.active -= 1;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn key(&self, key_id: &str) -> Result<&[u8; 32], CryptoError> {
+        self.keys
+            .iter()
+            .find(|(id, _)| id == key_id)
+            .map(|(_, key)| key)
+            .ok_or_else(|| CryptoError::UnknownKeyId(key_id.to_string()))
+    }
+
+    /// Encrypt under the active key; `aad` binds the envelope to its context (e.g. a record key)
+    pub fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Result<Envelope, CryptoError> {
+        let (key_id, key) = &self.keys[self.active];
+        let nonce: [u8; NONCE_LEN] = random::secure_array()?;
+        let bound = Envelope::bound_aad(VERSION, key_id, aad);
+
+        let mut sealed = ChaCha20Poly1305::new(*key).encrypt(&nonce, &bound, plaintext);
+        let mut tag = [0u8; TAG_LEN];
+        tag.copy_from_slice(&sealed[sealed.len() - TAG_LEN..]);
+        sealed.truncate(sealed.len() - TAG_LEN);
+
+        Ok(Envelope {
+            version: VERSION,
+            key_id: key_id.clone(),
+            nonce,
+            ciphertext: sealed,
+            tag,
+        })
+    }
+
+    /// Verify and decrypt with whichever key the envelope names
+    pub fn open(&self, envelope: &Envelope, aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let key = self.key(&envelope.key_id)?;
+        let bound = Envelope::bound_aad(envelope.version, &envelope.key_id, aad);
+
+        let mut sealed = envelope.ciphertext.clone();
+        sealed.extend_from_slice(&envelope.tag);
+        ChaCha20Poly1305::new(*key).decrypt(&envelope.nonce, &bound, &sealed)
+    }
+
+    /// Re-encrypt under the active key; `None` if the envelope already uses it
+    pub fn reencrypt(&self, envelope: &Envelope, aad: &[u8]) -> Result<Option<Envelope>, CryptoError> {
+        if envelope.key_id == self.active_id() {
+            return Ok(None);
+        }
+        let plaintext = self.open(envelope, aad)?;
+        self.seal(aad, &plaintext).map(Some)
+    }
+
+    /// Serialize as `id:base64key;id:base64key`, active key first
+    /// (the format of the `AVILA_STATS_KEYS` secret)
+    pub fn to_spec(&self) -> String {
+        let mut order: Vec<usize> = vec![self.active];
+        order.extend((0..self.keys.len()).filter(|&i| i != self.active));
+        order
+            .iter()
+            .map(|&i| format!("{}:{}", self.keys[i].0, base64::encode(&self.keys[i].1)))
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    pub fn from_spec(spec: &str) -> Result<Self, CryptoError> {
+        let invalid = |msg: &str| CryptoError::InvalidParameter(format!("keyring spec: {}", msg));
+        let mut keyring: Option<Keyring> = None;
+
+        for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, encoded) = entry.split_once(':').ok_or_else(|| invalid("expected id:key"))?;
+            let bytes = base64::decode(encoded.trim()).map_err(|_| invalid("key is not base64"))?;
+            let key: [u8; 32] = bytes.as_slice().try_into().map_err(|_| invalid("key must be 32 bytes"))?;
+            match keyring.as_mut() {
+                None => keyring = Some(Keyring::new(id.trim(), key)?),
+                Some(ring) => ring.add(id.trim(), key)?,
+            }
+        }
+        keyring.ok_or_else(|| invalid("no keys"))
+    }
+
+    /// Read a keyring file written by [`Keyring::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CryptoError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| CryptoError::Io(format!("{}: {}", path.display(), e)))?;
+        Self::from_spec(&text)
+    }
+
+    /// Write the keyring spec atomically with owner-only permissions
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CryptoError> {
+        let mut spec = self.to_spec();
+        spec.push('\n');
+        write_private_file(path.as_ref(), spec.as_bytes())
+    }
+}
+
+impl Drop for Keyring {
+    fn drop(&mut self) {
+        for (_, key) in self.keys.iter_mut() {
+            for b in key.iter_mut() {
+                // SAFETY: writing through a valid &mut u8
+                unsafe { std::ptr::write_volatile(b, 0) };
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Keyring(active: {}, keys: {:?})", self.active_id(), self.key_ids())
+    }
+}
+
+fn check_key_id(key_id: &str) -> Result<(), CryptoError> {
+    if key_id.is_empty() || key_id.len() > 255 || key_id.contains([':', ';']) {
+        return Err(CryptoError::InvalidParameter(format!("invalid key id {:?}", key_id)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let ring = Keyring::generate("k1").unwrap();
+        let envelope = ring.seal(b"stats:data", b"{\"total\": 1500}").unwrap();
+        assert_eq!(envelope.key_id, "k1");
+
+        let decoded = Envelope::from_bytes(&envelope.to_bytes()).unwrap();
+        assert_eq!(decoded, envelope);
+        assert_eq!(ring.open(&decoded, b"stats:data").unwrap(), b"{\"total\": 1500}".to_vec());
+
+        // Wrong context or a relabelled key id must fail
+        assert_eq!(ring.open(&decoded, b"stats:other"), Err(CryptoError::AuthenticationFailed));
+        let mut relabelled = decoded.clone();
+        relabelled.key_id = "k2".to_string();
+        assert_eq!(ring.open(&relabelled, b"stats:data"), Err(CryptoError::UnknownKeyId("k2".to_string())));
+    }
+
+    #[test]
+    fn test_rotation_reencrypts() {
+        let mut ring = Keyring::generate("k1").unwrap();
+        let old = ring.seal(b"ctx", b"secret").unwrap();
+
+        ring.rotate("k2").unwrap();
+        assert_eq!(ring.active_id(), "k2");
+        let new = ring.reencrypt(&old, b"ctx").unwrap().unwrap();
+        assert_eq!(new.key_id, "k2");
+        assert!(ring.reencrypt(&new, b"ctx").unwrap().is_none());
+
+        assert!(ring.remove("k1"));
+        assert!(!ring.remove("k2"));
+        assert_eq!(ring.open(&new, b"ctx").unwrap(), b"secret".to_vec());
+        assert!(ring.open(&old, b"ctx").is_err());
+    }
+
+    #[test]
+    fn test_spec_roundtrip() {
+        let mut ring = Keyring::generate("k1").unwrap();
+        ring.rotate("k2").unwrap();
+        let spec = ring.to_spec();
+        assert!(spec.starts_with("k2:"));
+
+        let restored = Keyring::from_spec(&spec).unwrap();
+        assert_eq!(restored.active_id(), "k2");
+        let envelope = ring.seal(b"", b"x").unwrap();
+        assert_eq!(restored.open(&envelope, b"").unwrap(), b"x".to_vec());
+
+        assert!(Keyring::from_spec("k1:not-base64!").is_err());
+        assert!(Keyring::from_spec("").is_err());
+    }
+}
@@ -0,0 +1,130 @@
+// AvilaCrypto KDF - HKDF (RFC 5869) and PBKDF2-HMAC (RFC 8018)
+// Zero External Dependencies 🦀
+
+use crate::hmac::{hmac, Hmac};
+use crate::{CryptoError, Hasher};
+
+/// HKDF-Extract: concentrate the input keying material into a pseudorandom key
+pub fn hkdf_extract<H: Hasher>(salt: &[u8], ikm: &[u8]) -> H::Output {
+    // An absent salt is a string of HashLen zeros
+    if salt.is_empty() {
+        hmac::<H>(&vec![0u8; H::OUTPUT_LEN], ikm)
+    } else {
+        hmac::<H>(salt, ikm)
+    }
+}
+
+/// HKDF-Expand: derive `len` bytes of output keying material bound to `info`
+pub fn hkdf_expand<H: Hasher>(prk: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, CryptoError> {
+    if len > 255 * H::OUTPUT_LEN {
+        return Err(CryptoError::InvalidParameter(format!(
+            "HKDF output limited to {} bytes",
+            255 * H::OUTPUT_LEN
+        )));
+    }
+
+    let mut okm = Vec::with_capacity(len + H::OUTPUT_LEN);
+    let mut previous: Option<H::Output> = None;
+    let mut counter = 1u8;
+    while okm.len() < len {
+        let mut mac = Hmac::<H>::new(prk);
+        if let Some(t) = &previous {
+            mac.update(t.as_ref());
+        }
+        mac.update(info);
+        mac.update(&[counter]);
+        let t = mac.finalize();
+        okm.extend_from_slice(t.as_ref());
+        previous = Some(t);
+        counter = counter.wrapping_add(1);
+    }
+    okm.truncate(len);
+    Ok(okm)
+}
+
+/// HKDF extract-then-expand in one call
+pub fn hkdf<H: Hasher>(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, CryptoError> {
+    let prk = hkdf_extract::<H>(salt, ikm);
+    hkdf_expand::<H>(prk.as_ref(), info, len)
+}
+
+/// PBKDF2 with HMAC as the PRF, filling `out` with the derived key
+pub fn pbkdf2_hmac<H: Hasher>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out: &mut [u8],
+) -> Result<(), CryptoError> {
+    if iterations == 0 {
+        return Err(CryptoError::InvalidParameter("PBKDF2 needs at least one iteration".to_string()));
+    }
+
+    // The keyed PRF state is computed once and cloned for every block
+    let prf = Hmac::<H>::new(password);
+
+    for (i, block) in out.chunks_mut(H::OUTPUT_LEN).enumerate() {
+        let mut mac = prf.clone();
+        mac.update(salt);
+        mac.update(&(i as u32 + 1).to_be_bytes());
+        let mut u = mac.finalize();
+
+        let mut t = u.as_ref().to_vec();
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(u.as_ref());
+            u = mac.finalize();
+            for (acc, b) in t.iter_mut().zip(u.as_ref()) {
+                *acc ^= b;
+            }
+        }
+        block.copy_from_slice(&t[..block.len()]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha1::Sha1;
+    use crate::sha256::Sha256;
+    use crate::test_util::unhex;
+
+    #[test]
+    fn test_hkdf_rfc5869() {
+        // RFC 5869 appendix A.1
+        let ikm = [0x0bu8; 22];
+        let salt = unhex("000102030405060708090a0b0c");
+        let info = unhex("f0f1f2f3f4f5f6f7f8f9");
+
+        let prk = hkdf_extract::<Sha256>(&salt, &ikm);
+        assert_eq!(
+            prk.to_vec(),
+            unhex("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5")
+        );
+        let okm = hkdf::<Sha256>(&salt, &ikm, &info, 42).unwrap();
+        assert_eq!(
+            okm,
+            unhex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865")
+        );
+
+        assert!(hkdf_expand::<Sha256>(&prk, &info, 255 * 32 + 1).is_err());
+    }
+
+    #[test]
+    fn test_pbkdf2_vectors() {
+        // RFC 6070, 2 iterations
+        let mut out = [0u8; 20];
+        pbkdf2_hmac::<Sha1>(b"password", b"salt", 2, &mut out).unwrap();
+        assert_eq!(out.to_vec(), unhex("ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957"));
+
+        // RFC 7914 section 11, output spanning two blocks
+        let mut out = [0u8; 64];
+        pbkdf2_hmac::<Sha256>(b"passwd", b"salt", 1, &mut out).unwrap();
+        assert_eq!(
+            out.to_vec(),
+            unhex("55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783")
+        );
+
+        assert!(pbkdf2_hmac::<Sha256>(b"p", b"s", 0, &mut out).is_err());
+    }
+}
@@ -1,8 +1,254 @@
 // AvilaCrypto - Native Cryptography Implementation
 // Zero External Dependencies 🦀
 
+pub mod argon2;
+pub mod bignum;
+pub mod chacha20poly1305;
+mod curve25519;
+pub mod der;
+pub mod ed25519;
+pub mod envelope;
+pub mod kdf;
+pub mod p256;
+pub mod password;
+pub mod rsa;
+pub mod scrypt;
+#[cfg(test)]
+mod test_util;
+pub mod vault;
+pub mod x25519;
+
+/// Errors produced by key handling, signatures and encodings
+#[derive(Debug, Clone, PartialEq)]
+pub enum CryptoError {
+    InvalidKey,
+    InvalidSignature,
+    InvalidPem(String),
+    Der(String),
+    UnsupportedAlgorithm(String),
+    MessageTooLong,
+    WeakPublicKey,
+    AuthenticationFailed,
+    InvalidParameter(String),
+    InvalidHashString(String),
+    Io(String),
+    Rng(String),
+    UnknownKeyId(String),
+}
+
+impl std::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CryptoError::InvalidKey => write!(f, "Invalid key"),
+            CryptoError::InvalidSignature => write!(f, "Invalid signature"),
+            CryptoError::InvalidPem(msg) => write!(f, "Invalid PEM: {}", msg),
+            CryptoError::Der(msg) => write!(f, "Invalid DER: {}", msg),
+            CryptoError::UnsupportedAlgorithm(alg) => write!(f, "Unsupported algorithm: {}", alg),
+            CryptoError::MessageTooLong => write!(f, "Message too long for key size"),
+            CryptoError::WeakPublicKey => write!(f, "Public key has low order"),
+            CryptoError::AuthenticationFailed => write!(f, "Decryption failed: data was modified or the key is wrong"),
+            CryptoError::InvalidParameter(msg) => write!(f, "Invalid parameter: {}", msg),
+            CryptoError::InvalidHashString(msg) => write!(f, "Invalid password hash: {}", msg),
+            CryptoError::Io(msg) => write!(f, "I/O error: {}", msg),
+            CryptoError::Rng(msg) => write!(f, "Random generator failed: {}", msg),
+            CryptoError::UnknownKeyId(id) => write!(f, "Unknown key id: {}", id),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+/// Compare secrets (MACs, password hashes) without leaking the position of the first difference
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    // black_box keeps the optimizer from turning the fold into an early exit
+    std::hint::black_box(diff) == 0
+}
+
+/// Incremental hash function: feed data with `update`, read the digest with `finalize`
+pub trait Hasher: Clone {
+    /// Digest type, a fixed-size byte array
+    type Output: AsRef<[u8]> + Copy;
+    /// Input block size in bytes (used by HMAC)
+    const BLOCK_LEN: usize;
+    const OUTPUT_LEN: usize;
+
+    fn new() -> Self;
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self) -> Self::Output;
+
+    /// One-shot digest of an in-memory slice
+    fn digest(data: &[u8]) -> Self::Output {
+        let mut hasher = Self::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+/// Hash everything readable from `reader` without loading it into memory
+pub fn hash_reader<H: Hasher, R: std::io::Read>(mut reader: R) -> std::io::Result<H::Output> {
+    let mut hasher = H::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(hasher.finalize()),
+            Ok(n) => hasher.update(&buf[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Shared Merkle-Damgard buffering: fill `buffer`, compressing each complete block
+fn buffered_update<const B: usize>(
+    buffer: &mut [u8; B],
+    buffered: &mut usize,
+    mut data: &[u8],
+    mut compress: impl FnMut(&[u8]),
+) {
+    if *buffered > 0 {
+        let take = (B - *buffered).min(data.len());
+        buffer[*buffered..*buffered + take].copy_from_slice(&data[..take]);
+        *buffered += take;
+        data = &data[take..];
+        if *buffered < B {
+            return;
+        }
+        compress(&buffer[..]);
+        *buffered = 0;
+    }
+
+    let mut blocks = data.chunks_exact(B);
+    for block in &mut blocks {
+        compress(block);
+    }
+    let rest = blocks.remainder();
+    buffer[..rest.len()].copy_from_slice(rest);
+    *buffered = rest.len();
+}
+
+/// Digest used by RSA and ECDSA signatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// Legacy only (TLS 1.0/1.1 signatures, TOTP); not collision resistant
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha1 => sha1::Sha1::digest(data).to_vec(),
+            HashAlgorithm::Sha256 => sha256::Sha256::digest(data).to_vec(),
+            HashAlgorithm::Sha384 => sha512::Sha384::digest(data).to_vec(),
+            HashAlgorithm::Sha512 => sha512::Sha512::digest(data).to_vec(),
+        }
+    }
+
+    pub fn output_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha1 => sha1::Sha1::OUTPUT_LEN,
+            HashAlgorithm::Sha256 => sha256::Sha256::OUTPUT_LEN,
+            HashAlgorithm::Sha384 => sha512::Sha384::OUTPUT_LEN,
+            HashAlgorithm::Sha512 => sha512::Sha512::OUTPUT_LEN,
+        }
+    }
+}
+
+// SHA-1 Implementation (legacy protocols only)
+pub mod sha1 {
+    use super::{buffered_update, Hasher};
+
+    #[derive(Clone)]
+    pub struct Sha1 {
+        state: [u32; 5],
+        buffer: [u8; 64],
+        buffered: usize,
+        length: u64,
+    }
+
+    fn compress(state: &mut [u32; 5], block: &[u8]) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = *state;
+        for (i, &wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | ((!b) & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+        state[4] = state[4].wrapping_add(e);
+    }
+
+    impl Hasher for Sha1 {
+        type Output = [u8; 20];
+        const BLOCK_LEN: usize = 64;
+        const OUTPUT_LEN: usize = 20;
+
+        fn new() -> Self {
+            Self {
+                state: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0],
+                buffer: [0u8; 64],
+                buffered: 0,
+                length: 0,
+            }
+        }
+
+        fn update(&mut self, data: &[u8]) {
+            self.length = self.length.wrapping_add(data.len() as u64);
+            let state = &mut self.state;
+            buffered_update(&mut self.buffer, &mut self.buffered, data, |block| compress(state, block));
+        }
+
+        fn finalize(mut self) -> [u8; 20] {
+            let bit_len = self.length.wrapping_mul(8);
+            let pad_len = if self.buffered < 56 { 56 - self.buffered } else { 120 - self.buffered };
+            let mut padding = [0u8; 72];
+            padding[0] = 0x80;
+            self.update(&padding[..pad_len]);
+            self.update(&bit_len.to_be_bytes());
+
+            let mut result = [0u8; 20];
+            for (i, &val) in self.state.iter().enumerate() {
+                result[i * 4..(i + 1) * 4].copy_from_slice(&val.to_be_bytes());
+            }
+            result
+        }
+    }
+
+    pub fn hash(data: &[u8]) -> [u8; 20] {
+        Sha1::digest(data)
+    }
+}
+
 // SHA-256 Implementation
 pub mod sha256 {
+    use super::{buffered_update, Hasher};
+
     const K: [u32; 64] = [
         0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
         0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
@@ -14,88 +260,116 @@ pub mod sha256 {
         0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
     ];
 
-    pub fn hash(data: &[u8]) -> [u8; 32] {
-        let mut h: [u32; 8] = [
-            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
-            0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
-        ];
-
-        let mut padded = data.to_vec();
-        let original_len = data.len() as u64;
-
-        // Padding
-        padded.push(0x80);
-        while (padded.len() % 64) != 56 {
-            padded.push(0x00);
-        }
-
-        // Append length in bits
-        padded.extend_from_slice(&(original_len * 8).to_be_bytes());
-
-        // Process in 512-bit chunks
-        for chunk in padded.chunks(64) {
-            let mut w = [0u32; 64];
-
-            // Break chunk into 16 32-bit words
-            for i in 0..16 {
-                w[i] = u32::from_be_bytes([
-                    chunk[i * 4],
-                    chunk[i * 4 + 1],
-                    chunk[i * 4 + 2],
-                    chunk[i * 4 + 3],
-                ]);
-            }
+    #[derive(Clone)]
+    pub struct Sha256 {
+        state: [u32; 8],
+        buffer: [u8; 64],
+        buffered: usize,
+        length: u64,
+    }
 
-            // Extend into 64 words
-            for i in 16..64 {
-                let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
-                let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
-                w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
-            }
+    /// Process one 512-bit chunk
+    fn compress(h: &mut [u32; 8], chunk: &[u8]) {
+        let mut w = [0u32; 64];
+
+        // Break chunk into 16 32-bit words
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes([
+                chunk[i * 4],
+                chunk[i * 4 + 1],
+                chunk[i * 4 + 2],
+                chunk[i * 4 + 3],
+            ]);
+        }
+
+        // Extend into 64 words
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let mut a = h[0];
+        let mut b = h[1];
+        let mut c = h[2];
+        let mut d = h[3];
+        let mut e = h[4];
+        let mut f = h[5];
+        let mut g = h[6];
+        let mut h_var = h[7];
+
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ ((!e) & g);
+            let temp1 = h_var.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h_var = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
 
-            let mut a = h[0];
-            let mut b = h[1];
-            let mut c = h[2];
-            let mut d = h[3];
-            let mut e = h[4];
-            let mut f = h[5];
-            let mut g = h[6];
-            let mut h_var = h[7];
-
-            for i in 0..64 {
-                let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
-                let ch = (e & f) ^ ((!e) & g);
-                let temp1 = h_var.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
-                let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
-                let maj = (a & b) ^ (a & c) ^ (b & c);
-                let temp2 = s0.wrapping_add(maj);
-
-                h_var = g;
-                g = f;
-                f = e;
-                e = d.wrapping_add(temp1);
-                d = c;
-                c = b;
-                b = a;
-                a = temp1.wrapping_add(temp2);
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+        h[5] = h[5].wrapping_add(f);
+        h[6] = h[6].wrapping_add(g);
+        h[7] = h[7].wrapping_add(h_var);
+    }
+
+    impl Hasher for Sha256 {
+        type Output = [u8; 32];
+        const BLOCK_LEN: usize = 64;
+        const OUTPUT_LEN: usize = 32;
+
+        fn new() -> Self {
+            Self {
+                state: [
+                    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+                    0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+                ],
+                buffer: [0u8; 64],
+                buffered: 0,
+                length: 0,
             }
+        }
 
-            h[0] = h[0].wrapping_add(a);
-            h[1] = h[1].wrapping_add(b);
-            h[2] = h[2].wrapping_add(c);
-            h[3] = h[3].wrapping_add(d);
-            h[4] = h[4].wrapping_add(e);
-            h[5] = h[5].wrapping_add(f);
-            h[6] = h[6].wrapping_add(g);
-            h[7] = h[7].wrapping_add(h_var);
+        fn update(&mut self, data: &[u8]) {
+            self.length = self.length.wrapping_add(data.len() as u64);
+            let state = &mut self.state;
+            buffered_update(&mut self.buffer, &mut self.buffered, data, |block| compress(state, block));
         }
 
-        let mut result = [0u8; 32];
-        for (i, &val) in h.iter().enumerate() {
-            let bytes = val.to_be_bytes();
-            result[i * 4..(i + 1) * 4].copy_from_slice(&bytes);
+        fn finalize(mut self) -> [u8; 32] {
+            let bit_len = self.length.wrapping_mul(8);
+
+            // Padding: 0x80, zeros up to 56 mod 64, then the length in bits
+            let pad_len = if self.buffered < 56 { 56 - self.buffered } else { 120 - self.buffered };
+            let mut padding = [0u8; 72];
+            padding[0] = 0x80;
+            self.update(&padding[..pad_len]);
+            self.update(&bit_len.to_be_bytes());
+
+            let mut result = [0u8; 32];
+            for (i, &val) in self.state.iter().enumerate() {
+                let bytes = val.to_be_bytes();
+                result[i * 4..(i + 1) * 4].copy_from_slice(&bytes);
+            }
+            result
         }
-        result
+    }
+
+    pub fn hash(data: &[u8]) -> [u8; 32] {
+        Sha256::digest(data)
     }
 
     pub fn hex(hash: &[u8; 32]) -> String {
@@ -103,6 +377,184 @@ pub mod sha256 {
     }
 }
 
+// SHA-512 and SHA-384 Implementation
+pub mod sha512 {
+    use super::{buffered_update, Hasher};
+
+    const K: [u64; 80] = [
+        0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+        0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+        0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+        0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+        0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+        0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+        0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+        0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+        0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+        0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+        0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+        0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+        0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+        0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+        0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+        0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+        0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+        0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+        0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+        0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+    ];
+
+    #[derive(Clone)]
+    pub struct Sha512 {
+        state: [u64; 8],
+        buffer: [u8; 128],
+        buffered: usize,
+        length: u128,
+    }
+
+    /// SHA-384: SHA-512 with its own initial state, truncated to 48 bytes
+    #[derive(Clone)]
+    pub struct Sha384(Sha512);
+
+    /// Process one 1024-bit chunk
+    fn compress(h: &mut [u64; 8], chunk: &[u8]) {
+        let mut w = [0u64; 80];
+
+        // Break chunk into 16 64-bit words
+        for (i, word) in chunk.chunks(8).enumerate() {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(word);
+            w[i] = u64::from_be_bytes(bytes);
+        }
+
+        // Extend into 80 words
+        for i in 16..80 {
+            let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+            let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let mut a = h[0];
+        let mut b = h[1];
+        let mut c = h[2];
+        let mut d = h[3];
+        let mut e = h[4];
+        let mut f = h[5];
+        let mut g = h[6];
+        let mut h_var = h[7];
+
+        for i in 0..80 {
+            let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+            let ch = (e & f) ^ ((!e) & g);
+            let temp1 = h_var.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h_var = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+        h[5] = h[5].wrapping_add(f);
+        h[6] = h[6].wrapping_add(g);
+        h[7] = h[7].wrapping_add(h_var);
+    }
+
+    impl Sha512 {
+        fn with_state(state: [u64; 8]) -> Self {
+            Self {
+                state,
+                buffer: [0u8; 128],
+                buffered: 0,
+                length: 0,
+            }
+        }
+
+        fn finalize_state(mut self) -> [u64; 8] {
+            let bit_len = self.length.wrapping_mul(8);
+
+            // Padding: 0x80, zeros up to 112 mod 128, then the 128-bit length in bits
+            let pad_len = if self.buffered < 112 { 112 - self.buffered } else { 240 - self.buffered };
+            let mut padding = [0u8; 144];
+            padding[0] = 0x80;
+            self.update(&padding[..pad_len]);
+            self.update(&bit_len.to_be_bytes());
+            self.state
+        }
+    }
+
+    impl Hasher for Sha512 {
+        type Output = [u8; 64];
+        const BLOCK_LEN: usize = 128;
+        const OUTPUT_LEN: usize = 64;
+
+        fn new() -> Self {
+            Self::with_state([
+                0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+                0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+            ])
+        }
+
+        fn update(&mut self, data: &[u8]) {
+            self.length = self.length.wrapping_add(data.len() as u128);
+            let state = &mut self.state;
+            buffered_update(&mut self.buffer, &mut self.buffered, data, |block| compress(state, block));
+        }
+
+        fn finalize(self) -> [u8; 64] {
+            let mut result = [0u8; 64];
+            for (i, &val) in self.finalize_state().iter().enumerate() {
+                result[i * 8..(i + 1) * 8].copy_from_slice(&val.to_be_bytes());
+            }
+            result
+        }
+    }
+
+    impl Hasher for Sha384 {
+        type Output = [u8; 48];
+        const BLOCK_LEN: usize = 128;
+        const OUTPUT_LEN: usize = 48;
+
+        fn new() -> Self {
+            Sha384(Sha512::with_state([
+                0xcbbb9d5dc1059ed8, 0x629a292a367cd507, 0x9159015a3070dd17, 0x152fecd8f70e5939,
+                0x67332667ffc00b31, 0x8eb44a8768581511, 0xdb0c2e0d64f98fa7, 0x47b5481dbefa4fa4,
+            ]))
+        }
+
+        fn update(&mut self, data: &[u8]) {
+            self.0.update(data);
+        }
+
+        fn finalize(self) -> [u8; 48] {
+            let mut result = [0u8; 48];
+            for (i, &val) in self.0.finalize_state().iter().take(6).enumerate() {
+                result[i * 8..(i + 1) * 8].copy_from_slice(&val.to_be_bytes());
+            }
+            result
+        }
+    }
+
+    pub fn hash(data: &[u8]) -> [u8; 64] {
+        Sha512::digest(data)
+    }
+
+    pub fn hash384(data: &[u8]) -> [u8; 48] {
+        Sha384::digest(data)
+    }
+}
+
 // AES-256 Implementation (Simplified for key derivation)
 pub mod aes {
     pub fn expand_key(key: &[u8; 32]) -> [u8; 240] {
@@ -154,6 +606,11 @@ pub mod base64 {
         result
     }
 
+    /// Encoding without `=` padding (PHC strings, JWT-style fields)
+    pub fn encode_unpadded(data: &[u8]) -> String {
+        encode(data).trim_end_matches('=').to_string()
+    }
+
     pub fn decode(encoded: &str) -> Result<Vec<u8>, &'static str> {
         let clean: String = encoded.chars().filter(|&c| c != '=' && !c.is_whitespace()).collect();
         let mut result = Vec::new();
@@ -184,39 +641,115 @@ pub mod base64 {
     }
 }
 
-// HMAC-SHA256
+// PEM armor (RFC 7468)
+pub mod pem {
+    use super::{base64, CryptoError};
+
+    /// Wrap DER bytes in a PEM block with the given label
+    pub fn encode(label: &str, der: &[u8]) -> String {
+        let body = base64::encode(der);
+        let mut pem = format!("-----BEGIN {}-----\n", label);
+        for line in body.as_bytes().chunks(64) {
+            pem.push_str(&String::from_utf8_lossy(line));
+            pem.push('\n');
+        }
+        pem.push_str(&format!("-----END {}-----\n", label));
+        pem
+    }
+
+    /// Decode the first PEM block, returning its label and DER bytes
+    pub fn decode(pem: &str) -> Result<(String, Vec<u8>), CryptoError> {
+        let begin = pem
+            .find("-----BEGIN ")
+            .ok_or_else(|| CryptoError::InvalidPem("missing BEGIN line".to_string()))?;
+        let rest = &pem[begin + 11..];
+        let label_end = rest
+            .find("-----")
+            .ok_or_else(|| CryptoError::InvalidPem("unterminated BEGIN line".to_string()))?;
+        let label = rest[..label_end].to_string();
+        let body = &rest[label_end + 5..];
+
+        let end_marker = format!("-----END {}-----", label);
+        let end = body
+            .find(&end_marker)
+            .ok_or_else(|| CryptoError::InvalidPem(format!("missing END {} line", label)))?;
+
+        let der = base64::decode(&body[..end])
+            .map_err(|e| CryptoError::InvalidPem(e.to_string()))?;
+        Ok((label, der))
+    }
+
+    /// Decode a PEM block and check that it carries the expected label
+    pub fn decode_expect(pem: &str, label: &str) -> Result<Vec<u8>, CryptoError> {
+        let (found, der) = decode(pem)?;
+        if found != label {
+            return Err(CryptoError::InvalidPem(format!(
+                "expected {}, found {}",
+                label, found
+            )));
+        }
+        Ok(der)
+    }
+}
+
+// HMAC (RFC 2104), generic over the hash function
 pub mod hmac {
-    use super::sha256;
+    use super::sha256::Sha256;
+    use super::Hasher;
+
+    /// Incremental HMAC computation
+    #[derive(Clone)]
+    pub struct Hmac<H: Hasher> {
+        inner: H,
+        outer: H,
+    }
 
-    pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
-        let mut k = [0u8; 64];
+    impl<H: Hasher> Hmac<H> {
+        pub fn new(key: &[u8]) -> Self {
+            let mut k = vec![0u8; H::BLOCK_LEN];
+
+            if key.len() > H::BLOCK_LEN {
+                let hash = H::digest(key);
+                k[..H::OUTPUT_LEN].copy_from_slice(hash.as_ref());
+            } else {
+                k[..key.len()].copy_from_slice(key);
+            }
 
-        if key.len() > 64 {
-            let hash = sha256::hash(key);
-            k[..32].copy_from_slice(&hash);
-        } else {
-            k[..key.len()].copy_from_slice(key);
+            let i_key_pad: Vec<u8> = k.iter().map(|b| b ^ 0x36).collect();
+            let o_key_pad: Vec<u8> = k.iter().map(|b| b ^ 0x5c).collect();
+
+            let mut inner = H::new();
+            inner.update(&i_key_pad);
+            let mut outer = H::new();
+            outer.update(&o_key_pad);
+            Self { inner, outer }
         }
 
-        let mut o_key_pad = [0x5c; 64];
-        let mut i_key_pad = [0x36; 64];
+        pub fn update(&mut self, data: &[u8]) {
+            self.inner.update(data);
+        }
 
-        for i in 0..64 {
-            o_key_pad[i] ^= k[i];
-            i_key_pad[i] ^= k[i];
+        pub fn finalize(self) -> H::Output {
+            let inner_hash = self.inner.finalize();
+            let mut outer = self.outer;
+            outer.update(inner_hash.as_ref());
+            outer.finalize()
         }
+    }
 
-        let mut inner = i_key_pad.to_vec();
-        inner.extend_from_slice(message);
-        let inner_hash = sha256::hash(&inner);
+    /// One-shot HMAC with any hash function
+    pub fn hmac<H: Hasher>(key: &[u8], message: &[u8]) -> H::Output {
+        let mut mac = Hmac::<H>::new(key);
+        mac.update(message);
+        mac.finalize()
+    }
 
-        let mut outer = o_key_pad.to_vec();
-        outer.extend_from_slice(&inner_hash);
-        sha256::hash(&outer)
+    pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+        hmac::<Sha256>(key, message)
     }
 }
 
-// Random number generation (simple PRNG - use OS random in production)
+// Random number generation (Rng is a simple PRNG - use fill_secure for key material)
 pub mod random {
     use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -253,11 +786,61 @@ pub mod random {
             Self::new()
         }
     }
+
+    /// Fill `buf` from the operating system CSPRNG (use this for keys and nonces)
+    #[cfg(unix)]
+    pub fn fill_secure(buf: &mut [u8]) -> Result<(), super::CryptoError> {
+        use std::io::Read;
+
+        std::fs::File::open("/dev/urandom")
+            .and_then(|mut f| f.read_exact(buf))
+            .map_err(|e| super::CryptoError::Rng(e.to_string()))
+    }
+
+    /// Fill `buf` from the operating system CSPRNG (use this for keys and nonces)
+    #[cfg(windows)]
+    pub fn fill_secure(buf: &mut [u8]) -> Result<(), super::CryptoError> {
+        const BCRYPT_USE_SYSTEM_PREFERRED_RNG: u32 = 0x00000002;
+
+        #[link(name = "bcrypt")]
+        extern "system" {
+            fn BCryptGenRandom(
+                algorithm: *mut std::ffi::c_void,
+                buffer: *mut u8,
+                len: u32,
+                flags: u32,
+            ) -> i32;
+        }
+
+        for chunk in buf.chunks_mut(u32::MAX as usize) {
+            // SAFETY: the pointer/length pair comes from a valid mutable slice
+            let status = unsafe {
+                BCryptGenRandom(
+                    std::ptr::null_mut(),
+                    chunk.as_mut_ptr(),
+                    chunk.len() as u32,
+                    BCRYPT_USE_SYSTEM_PREFERRED_RNG,
+                )
+            };
+            if status != 0 {
+                return Err(super::CryptoError::Rng(format!("BCryptGenRandom status {:#x}", status)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fixed-size array of OS random bytes
+    pub fn secure_array<const N: usize>() -> Result<[u8; N], super::CryptoError> {
+        let mut out = [0u8; N];
+        fill_secure(&mut out)?;
+        Ok(out)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::unhex;
 
     #[test]
     fn test_sha256() {
@@ -267,6 +850,46 @@ mod tests {
         assert_eq!(hex, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
     }
 
+    #[test]
+    fn test_sha_family() {
+        assert_eq!(
+            sha1::Sha1::digest(b"abc").to_vec(),
+            unhex("a9993e364706816aba3e25717850c26c9cd0d89d")
+        );
+        assert_eq!(
+            sha512::hash384(b"abc").to_vec(),
+            unhex("cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7")
+        );
+        assert_eq!(
+            sha512::hash(b"abc").to_vec(),
+            unhex("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f")
+        );
+    }
+
+    #[test]
+    fn test_incremental_matches_one_shot() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+
+        // Uneven chunk sizes cross the 64- and 128-byte block boundaries
+        let mut h1 = sha1::Sha1::new();
+        let mut h256 = sha256::Sha256::new();
+        let mut h384 = sha512::Sha384::new();
+        for chunk in data.chunks(37) {
+            h1.update(chunk);
+            h256.update(chunk);
+            h384.update(chunk);
+        }
+        assert_eq!(h1.finalize().to_vec(), unhex("c9c960a0b925474fab83942cc27d504fc24ac37b"));
+        assert_eq!(h256.finalize(), sha256::hash(&data));
+        assert_eq!(
+            h384.finalize().to_vec(),
+            unhex("7a2f8c7f12344964a13cb9260492b845e56615d6152b9eb9e54b580fc88405e64f31813bfda10de2a642fdf1676c61b4")
+        );
+
+        let streamed = hash_reader::<sha512::Sha512, _>(std::io::Cursor::new(&data)).unwrap();
+        assert_eq!(streamed, sha512::hash(&data));
+    }
+
     #[test]
     fn test_base64_encode() {
         let encoded = base64::encode(b"Hello, World!");
@@ -285,6 +908,18 @@ mod tests {
         let message = b"message";
         let mac = hmac::hmac_sha256(key, message);
         assert_eq!(mac.len(), 32);
+
+        // RFC 4231 test case 2
+        let mac = hmac::hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            mac.to_vec(),
+            unhex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+        );
+        let mac = hmac::hmac::<sha512::Sha512>(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            mac.to_vec(),
+            unhex("164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737")
+        );
     }
 
     #[test]
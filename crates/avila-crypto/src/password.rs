@@ -0,0 +1,174 @@
+// AvilaCrypto Password - PHC string format and algorithm-agnostic password hashing
+// Zero External Dependencies 🦀
+//
+// New hashes use Argon2id; stored scrypt hashes keep verifying so accounts
+// can be migrated on next login (see `needs_rehash`).
+
+use crate::{argon2, base64, scrypt, CryptoError};
+
+/// Stored hashes shorter than this are rejected: an empty or truncated hash
+/// would otherwise match every password
+pub(crate) const MIN_HASH_LEN: usize = 16;
+
+fn invalid(msg: &str) -> CryptoError {
+    CryptoError::InvalidHashString(msg.to_string())
+}
+
+/// Parsed `$id[$v=version][$param=value,...]$salt$hash` string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhcString {
+    pub algorithm: String,
+    pub version: Option<u32>,
+    pub params: Vec<(String, String)>,
+    pub salt: Vec<u8>,
+    pub hash: Vec<u8>,
+}
+
+impl PhcString {
+    pub fn parse(text: &str) -> Result<Self, CryptoError> {
+        let mut fields = text
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("must start with '$'"))?
+            .split('$')
+            .peekable();
+
+        let algorithm = fields.next().filter(|s| !s.is_empty()).ok_or_else(|| invalid("missing algorithm"))?;
+
+        let version = match fields.peek() {
+            Some(field) if field.starts_with("v=") => {
+                let v = field[2..].parse().map_err(|_| invalid("bad version"))?;
+                fields.next();
+                Some(v)
+            }
+            _ => None,
+        };
+
+        let mut params = Vec::new();
+        if let Some(field) = fields.peek() {
+            if field.contains('=') {
+                for pair in field.split(',') {
+                    let (name, value) = pair.split_once('=').ok_or_else(|| invalid("bad parameter"))?;
+                    params.push((name.to_string(), value.to_string()));
+                }
+                fields.next();
+            }
+        }
+
+        let salt = fields.next().ok_or_else(|| invalid("missing salt"))?;
+        let hash = fields.next().ok_or_else(|| invalid("missing hash"))?;
+        if fields.next().is_some() {
+            return Err(invalid("trailing fields"));
+        }
+
+        Ok(Self {
+            algorithm: algorithm.to_string(),
+            version,
+            params,
+            salt: base64::decode(salt).map_err(|_| invalid("salt is not base64"))?,
+            hash: base64::decode(hash).map_err(|_| invalid("hash is not base64"))?,
+        })
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// The stored hash, if it is long enough to be checked against
+    pub(crate) fn checked_hash(&self) -> Result<&[u8], CryptoError> {
+        if self.hash.len() < MIN_HASH_LEN {
+            return Err(invalid("hash too short"));
+        }
+        Ok(&self.hash)
+    }
+
+    pub fn param_u32(&self, name: &str) -> Result<u32, CryptoError> {
+        self.param(name)
+            .ok_or_else(|| CryptoError::InvalidHashString(format!("missing parameter {}", name)))?
+            .parse()
+            .map_err(|_| CryptoError::InvalidHashString(format!("parameter {} is not a number", name)))
+    }
+}
+
+impl std::fmt::Display for PhcString {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "${}", self.algorithm)?;
+        if let Some(v) = self.version {
+            write!(f, "$v={}", v)?;
+        }
+        if !self.params.is_empty() {
+            let params: Vec<String> = self.params.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+            write!(f, "${}", params.join(","))?;
+        }
+        write!(
+            f,
+            "${}${}",
+            base64::encode_unpadded(&self.salt),
+            base64::encode_unpadded(&self.hash)
+        )
+    }
+}
+
+/// Hash a password for storage with Argon2id and the default cost
+pub fn hash(password: &str) -> Result<String, CryptoError> {
+    argon2::hash_password(password.as_bytes(), &argon2::Params::default())
+}
+
+/// Verify a password against a stored Argon2id or scrypt PHC string
+pub fn verify(password: &str, phc: &str) -> Result<bool, CryptoError> {
+    let parsed = PhcString::parse(phc)?;
+    match parsed.algorithm.as_str() {
+        "argon2id" => argon2::verify_parsed(password.as_bytes(), &parsed),
+        "scrypt" => scrypt::verify_parsed(password.as_bytes(), &parsed),
+        _ => Err(CryptoError::UnsupportedAlgorithm(parsed.algorithm)),
+    }
+}
+
+/// True if a stored hash should be replaced on next successful login
+/// (older algorithm or weaker than the current default cost)
+pub fn needs_rehash(phc: &str) -> Result<bool, CryptoError> {
+    let parsed = PhcString::parse(phc)?;
+    if parsed.algorithm != "argon2id" {
+        return Ok(true);
+    }
+    let current = argon2::Params::default();
+    Ok(parsed.param_u32("m")? < current.m_cost || parsed.param_u32("t")? < current.t_cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phc_parse_and_format() {
+        let text = "$argon2id$v=19$m=65536,t=3,p=4$c29tZXNhbHQ$iWh06vD8Fy27wf9npn6FXWiCX4K6pW6Ue1Bnzz07Z8A";
+        let parsed = PhcString::parse(text).unwrap();
+        assert_eq!(parsed.algorithm, "argon2id");
+        assert_eq!(parsed.version, Some(19));
+        assert_eq!(parsed.param("t"), Some("3"));
+        assert_eq!(parsed.salt, b"somesalt".to_vec());
+        assert_eq!(parsed.hash.len(), 32);
+        assert_eq!(parsed.to_string(), text);
+
+        assert!(PhcString::parse("argon2id$v=19").is_err());
+        assert!(PhcString::parse("$argon2id$v=19$m=1$salt").is_err());
+    }
+
+    #[test]
+    fn test_verify_dispatch() {
+        let argon = argon2::hash_password(b"hunter2", &argon2::Params::new(32, 1, 1).unwrap()).unwrap();
+        let legacy = scrypt::hash_password(b"hunter2", &scrypt::Params::new(4, 8, 1).unwrap()).unwrap();
+
+        assert_eq!(verify("hunter2", &argon), Ok(true));
+        assert_eq!(verify("hunter2", &legacy), Ok(true));
+        assert_eq!(verify("hunter3", &legacy), Ok(false));
+        assert_eq!(needs_rehash(&legacy), Ok(true));
+        assert_eq!(needs_rehash(&argon), Ok(true));
+        // An empty hash must not match every password
+        assert!(verify("wrong", "$scrypt$ln=6,r=4,p=1$c2FsdHNhbHQ$").is_err());
+        assert!(verify("wrong", "$argon2id$v=19$m=64,t=1,p=1$c2FsdHNhbHQ$AAAA").is_err());
+        assert!(matches!(
+            verify("x", "$bcrypt$abc$def"),
+            Err(CryptoError::UnsupportedAlgorithm(_))
+        ));
+    }
+}
@@ -0,0 +1,270 @@
+// AvilaCrypto scrypt - Memory-hard key derivation (RFC 7914)
+// Zero External Dependencies 🦀
+
+use crate::kdf::pbkdf2_hmac;
+use crate::password::{PhcString, MIN_HASH_LEN};
+use crate::sha256::Sha256;
+use crate::{constant_time_eq, random, CryptoError};
+
+pub const DEFAULT_SALT_LEN: usize = 16;
+
+/// Largest costs accepted from a stored PHC string, so a crafted hash cannot
+/// exhaust memory or CPU (N = 2^20, r = 32, p = 16, 1 GiB of scratch memory)
+const MAX_LOG_N: u8 = 20;
+const MAX_R: u32 = 32;
+const MAX_P: u32 = 16;
+const MAX_MEMORY: u128 = 1 << 30;
+
+/// scrypt cost parameters: N = 2^log_n, block size r, parallelism p
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    /// Derived key length in bytes
+    pub output_len: usize,
+}
+
+impl Params {
+    pub fn new(log_n: u8, r: u32, p: u32) -> Result<Self, CryptoError> {
+        let params = Self {
+            log_n,
+            r,
+            p,
+            output_len: 32,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    fn validate(&self) -> Result<(), CryptoError> {
+        if self.log_n == 0 || self.log_n >= 64 {
+            return Err(CryptoError::InvalidParameter("scrypt log_n must be 1..63".to_string()));
+        }
+        if self.r == 0 || self.p == 0 {
+            return Err(CryptoError::InvalidParameter("scrypt r and p must be positive".to_string()));
+        }
+        if (self.r as u64) * (self.p as u64) >= 1 << 30 {
+            return Err(CryptoError::InvalidParameter("scrypt r * p must be below 2^30".to_string()));
+        }
+        // 128 * r * N bytes of scratch memory must be addressable
+        let memory = 128u128 * self.r as u128 * (1u128 << self.log_n);
+        if memory > usize::MAX as u128 / 2 {
+            return Err(CryptoError::InvalidParameter("scrypt memory cost too large".to_string()));
+        }
+        if self.output_len < MIN_HASH_LEN {
+            return Err(CryptoError::InvalidParameter("scrypt output must be at least 16 bytes".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Bounds for parameters stored next to a hash; hashes are only created
+    /// within them so that they can always be verified
+    fn check_limits(&self) -> Result<(), CryptoError> {
+        let memory = 128u128 * self.r as u128 * (1u128 << self.log_n.min(63));
+        if self.log_n > MAX_LOG_N || self.r > MAX_R || self.p > MAX_P || memory > MAX_MEMORY {
+            return Err(CryptoError::InvalidParameter("scrypt cost above the supported limit".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Params {
+    /// N = 2^17, r = 8, p = 1 (128 MiB)
+    fn default() -> Self {
+        Self {
+            log_n: 17,
+            r: 8,
+            p: 1,
+            output_len: 32,
+        }
+    }
+}
+
+fn salsa20_8(b: &mut [u32; 16]) {
+    let mut x = *b;
+    for _ in 0..4 {
+        // Column round
+        x[4] ^= x[0].wrapping_add(x[12]).rotate_left(7);
+        x[8] ^= x[4].wrapping_add(x[0]).rotate_left(9);
+        x[12] ^= x[8].wrapping_add(x[4]).rotate_left(13);
+        x[0] ^= x[12].wrapping_add(x[8]).rotate_left(18);
+        x[9] ^= x[5].wrapping_add(x[1]).rotate_left(7);
+        x[13] ^= x[9].wrapping_add(x[5]).rotate_left(9);
+        x[1] ^= x[13].wrapping_add(x[9]).rotate_left(13);
+        x[5] ^= x[1].wrapping_add(x[13]).rotate_left(18);
+        x[14] ^= x[10].wrapping_add(x[6]).rotate_left(7);
+        x[2] ^= x[14].wrapping_add(x[10]).rotate_left(9);
+        x[6] ^= x[2].wrapping_add(x[14]).rotate_left(13);
+        x[10] ^= x[6].wrapping_add(x[2]).rotate_left(18);
+        x[3] ^= x[15].wrapping_add(x[11]).rotate_left(7);
+        x[7] ^= x[3].wrapping_add(x[15]).rotate_left(9);
+        x[11] ^= x[7].wrapping_add(x[3]).rotate_left(13);
+        x[15] ^= x[11].wrapping_add(x[7]).rotate_left(18);
+        // Row round
+        x[1] ^= x[0].wrapping_add(x[3]).rotate_left(7);
+        x[2] ^= x[1].wrapping_add(x[0]).rotate_left(9);
+        x[3] ^= x[2].wrapping_add(x[1]).rotate_left(13);
+        x[0] ^= x[3].wrapping_add(x[2]).rotate_left(18);
+        x[6] ^= x[5].wrapping_add(x[4]).rotate_left(7);
+        x[7] ^= x[6].wrapping_add(x[5]).rotate_left(9);
+        x[4] ^= x[7].wrapping_add(x[6]).rotate_left(13);
+        x[5] ^= x[4].wrapping_add(x[7]).rotate_left(18);
+        x[11] ^= x[10].wrapping_add(x[9]).rotate_left(7);
+        x[8] ^= x[11].wrapping_add(x[10]).rotate_left(9);
+        x[9] ^= x[8].wrapping_add(x[11]).rotate_left(13);
+        x[10] ^= x[9].wrapping_add(x[8]).rotate_left(18);
+        x[12] ^= x[15].wrapping_add(x[14]).rotate_left(7);
+        x[13] ^= x[12].wrapping_add(x[15]).rotate_left(9);
+        x[14] ^= x[13].wrapping_add(x[12]).rotate_left(13);
+        x[15] ^= x[14].wrapping_add(x[13]).rotate_left(18);
+    }
+    for (out, x) in b.iter_mut().zip(x.iter()) {
+        *out = out.wrapping_add(*x);
+    }
+}
+
+/// scryptBlockMix over 2r 64-byte sub-blocks (as u32 words)
+fn block_mix(input: &[u32], output: &mut [u32], r: usize) {
+    let mut x = [0u32; 16];
+    x.copy_from_slice(&input[(2 * r - 1) * 16..]);
+
+    for i in 0..2 * r {
+        for (xw, bw) in x.iter_mut().zip(&input[i * 16..(i + 1) * 16]) {
+            *xw ^= bw;
+        }
+        salsa20_8(&mut x);
+        // Even sub-blocks go to the first half, odd ones to the second
+        let dest = (i / 2 + (i % 2) * r) * 16;
+        output[dest..dest + 16].copy_from_slice(&x);
+    }
+}
+
+/// scryptROMix on one 128r-byte block
+fn ro_mix(block: &mut [u8], r: usize, n: usize) {
+    let words = 32 * r;
+    let mut x: Vec<u32> = block
+        .chunks(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect();
+    let mut v = vec![0u32; words * n];
+    let mut tmp = vec![0u32; words];
+
+    for i in 0..n {
+        v[i * words..(i + 1) * words].copy_from_slice(&x);
+        block_mix(&x, &mut tmp, r);
+        std::mem::swap(&mut x, &mut tmp);
+    }
+    for _ in 0..n {
+        // Integerify: first word of the last 64-byte sub-block
+        let j = x[(2 * r - 1) * 16] as usize & (n - 1);
+        for (xw, vw) in x.iter_mut().zip(&v[j * words..(j + 1) * words]) {
+            *xw ^= vw;
+        }
+        block_mix(&x, &mut tmp, r);
+        std::mem::swap(&mut x, &mut tmp);
+    }
+
+    for (chunk, word) in block.chunks_mut(4).zip(x.iter()) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+}
+
+/// Derive `out.len()` bytes from a password and salt
+pub fn scrypt(password: &[u8], salt: &[u8], params: &Params, out: &mut [u8]) -> Result<(), CryptoError> {
+    params.validate()?;
+    let r = params.r as usize;
+    let n = 1usize << params.log_n;
+
+    let mut b = vec![0u8; params.p as usize * 128 * r];
+    pbkdf2_hmac::<Sha256>(password, salt, 1, &mut b)?;
+    for block in b.chunks_mut(128 * r) {
+        ro_mix(block, r, n);
+    }
+    pbkdf2_hmac::<Sha256>(password, &b, 1, out)
+}
+
+/// Hash a password with a fresh random salt, returning a PHC string
+/// such as `$scrypt$ln=17,r=8,p=1$<salt>$<hash>`
+pub fn hash_password(password: &[u8], params: &Params) -> Result<String, CryptoError> {
+    params.check_limits()?;
+    let salt: [u8; DEFAULT_SALT_LEN] = random::secure_array()?;
+    let mut hash = vec![0u8; params.output_len];
+    scrypt(password, &salt, params, &mut hash)?;
+
+    Ok(PhcString {
+        algorithm: "scrypt".to_string(),
+        version: None,
+        params: vec![
+            ("ln".to_string(), params.log_n.to_string()),
+            ("r".to_string(), params.r.to_string()),
+            ("p".to_string(), params.p.to_string()),
+        ],
+        salt: salt.to_vec(),
+        hash,
+    }
+    .to_string())
+}
+
+/// Check a password against an scrypt PHC string
+///
+/// Returns `Ok(false)` on mismatch and an error only if the string is malformed.
+pub fn verify_password(password: &[u8], phc: &str) -> Result<bool, CryptoError> {
+    verify_parsed(password, &PhcString::parse(phc)?)
+}
+
+/// [`verify_password`] for an already parsed PHC string
+pub(crate) fn verify_parsed(password: &[u8], parsed: &PhcString) -> Result<bool, CryptoError> {
+    if parsed.algorithm != "scrypt" {
+        return Err(CryptoError::UnsupportedAlgorithm(parsed.algorithm.clone()));
+    }
+
+    let log_n = parsed.param_u32("ln")?;
+    let params = Params {
+        log_n: u8::try_from(log_n).map_err(|_| CryptoError::InvalidHashString("ln out of range".to_string()))?,
+        r: parsed.param_u32("r")?,
+        p: parsed.param_u32("p")?,
+        output_len: parsed.checked_hash()?.len(),
+    };
+    params.check_limits()?;
+    let mut computed = vec![0u8; params.output_len];
+    scrypt(password, &parsed.salt, &params, &mut computed)?;
+    Ok(constant_time_eq(&computed, &parsed.hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::unhex;
+
+    #[test]
+    fn test_rfc7914_vector() {
+        // RFC 7914 section 12, N = 1024, r = 8, p = 16
+        let params = Params {
+            log_n: 10,
+            r: 8,
+            p: 16,
+            output_len: 64,
+        };
+        let mut out = [0u8; 64];
+        scrypt(b"password", b"NaCl", &params, &mut out).unwrap();
+        assert_eq!(
+            out.to_vec(),
+            unhex("fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640")
+        );
+    }
+
+    #[test]
+    fn test_phc_roundtrip() {
+        let params = Params::new(6, 4, 1).unwrap();
+        let phc = hash_password(b"vault passphrase", &params).unwrap();
+        assert!(phc.starts_with("$scrypt$ln=6,r=4,p=1$"));
+        assert_eq!(verify_password(b"vault passphrase", &phc), Ok(true));
+        assert_eq!(verify_password(b"vault passphrasE", &phc), Ok(false));
+        assert!(Params::new(0, 8, 1).is_err());
+
+        // Costs from a stored string are bounded before any memory is allocated
+        assert!(verify_password(b"x", "$scrypt$ln=40,r=8,p=1$c2FsdHNhbHQ$c2FsdHNhbHRzYWx0c2FsdA").is_err());
+        assert!(verify_password(b"x", "$scrypt$ln=6,r=4,p=1$c2FsdHNhbHQ$c2FsdA").is_err());
+    }
+}
@@ -0,0 +1,10 @@
+// AvilaCrypto Test Helpers
+// Zero External Dependencies 🦀
+
+/// Bytes of a hex string, for test vectors
+pub(crate) fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
@@ -0,0 +1,486 @@
+// AvilaCrypto Vault - Encrypted secrets file replacing plaintext .env files
+// Zero External Dependencies 🦀
+//
+// File layout (PEM armored, label "AVILA VAULT"):
+//   magic "AVLT" | version | kdf | m_cost | t_cost | p_cost | salt[16] | nonce[12] | ciphertext | tag[16]
+// Everything before the nonce is authenticated as associated data, so the
+// KDF parameters cannot be downgraded without failing decryption.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::chacha20poly1305::ChaCha20Poly1305;
+use crate::sha256::Sha256;
+use crate::{argon2, kdf, pem, random, CryptoError};
+
+pub const PEM_LABEL: &str = "AVILA VAULT";
+pub const DEFAULT_PATH: &str = "secrets.vault";
+
+const MAGIC: &[u8; 4] = b"AVLT";
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = 4 + 1 + 1 + 12 + SALT_LEN;
+const KEY_FILE_INFO: &[u8] = b"avila-vault v1 key file";
+
+/// Where the vault key comes from
+#[derive(Clone)]
+pub enum KeySource {
+    /// Passphrase stretched with Argon2id
+    Passphrase(String),
+    /// Contents of a key file (any length, ideally 32+ random bytes)
+    KeyFile(Vec<u8>),
+}
+
+impl KeySource {
+    pub fn key_file(path: impl AsRef<Path>) -> Result<Self, CryptoError> {
+        std::fs::read(path.as_ref())
+            .map(KeySource::KeyFile)
+            .map_err(|e| CryptoError::Io(format!("{}: {}", path.as_ref().display(), e)))
+    }
+
+    fn kdf_id(&self) -> u8 {
+        match self {
+            KeySource::Passphrase(_) => 1,
+            KeySource::KeyFile(_) => 2,
+        }
+    }
+
+    fn derive(&self, salt: &[u8], params: &argon2::Params) -> Result<[u8; 32], CryptoError> {
+        let mut key = [0u8; 32];
+        match self {
+            KeySource::Passphrase(passphrase) => {
+                // Vaults are only written with costs they can be opened with
+                params.check_limits()?;
+                argon2::argon2id(passphrase.as_bytes(), salt, &[], &[], params, &mut key)?;
+            }
+            KeySource::KeyFile(contents) => {
+                key.copy_from_slice(&kdf::hkdf::<Sha256>(salt, contents, KEY_FILE_INFO, 32)?);
+            }
+        }
+        Ok(key)
+    }
+}
+
+impl std::fmt::Debug for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeySource::Passphrase(_) => write!(f, "Passphrase(..)"),
+            KeySource::KeyFile(_) => write!(f, "KeyFile(..)"),
+        }
+    }
+}
+
+/// Decrypted vault contents plus the key needed to write them back
+pub struct Vault {
+    entries: BTreeMap<String, String>,
+    kdf_id: u8,
+    params: argon2::Params,
+    salt: [u8; SALT_LEN],
+    key: [u8; 32],
+}
+
+impl Vault {
+    /// Empty vault with the default Argon2id cost
+    pub fn create(source: &KeySource) -> Result<Self, CryptoError> {
+        Self::create_with_params(source, argon2::Params::default())
+    }
+
+    pub fn create_with_params(source: &KeySource, params: argon2::Params) -> Result<Self, CryptoError> {
+        let salt: [u8; SALT_LEN] = random::secure_array()?;
+        let key = source.derive(&salt, &params)?;
+        Ok(Self {
+            entries: BTreeMap::new(),
+            kdf_id: source.kdf_id(),
+            params,
+            salt,
+            key,
+        })
+    }
+
+    pub fn open(path: impl AsRef<Path>, source: &KeySource) -> Result<Self, CryptoError> {
+        let text = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| CryptoError::Io(format!("{}: {}", path.as_ref().display(), e)))?;
+        Self::from_pem(&text, source)
+    }
+
+    pub fn from_pem(text: &str, source: &KeySource) -> Result<Self, CryptoError> {
+        let data = pem::decode_expect(text, PEM_LABEL)?;
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return Err(CryptoError::InvalidParameter("not a vault file".to_string()));
+        }
+        if data[4] != VERSION {
+            return Err(CryptoError::InvalidParameter(format!("unsupported vault version {}", data[4])));
+        }
+        let kdf_id = data[5];
+        if kdf_id != source.kdf_id() {
+            return Err(CryptoError::InvalidKey);
+        }
+
+        let be32 = |i: usize| u32::from_be_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        let params = argon2::Params {
+            m_cost: be32(6),
+            t_cost: be32(10),
+            p_cost: be32(14),
+            output_len: 32,
+        };
+        // The header is only authenticated after key derivation, so bound the work it can request
+        params.check_limits()?;
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(&data[18..HEADER_LEN]);
+
+        let key = source.derive(&salt, &params)?;
+        let mut plaintext = ChaCha20Poly1305::new(key).open(&data[..HEADER_LEN], &data[HEADER_LEN..])?;
+        let entries = decode_entries(&plaintext);
+        wipe(&mut plaintext);
+
+        Ok(Self {
+            entries: entries?,
+            kdf_id,
+            params,
+            salt,
+            key,
+        })
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.push(self.kdf_id);
+        header.extend_from_slice(&self.params.m_cost.to_be_bytes());
+        header.extend_from_slice(&self.params.t_cost.to_be_bytes());
+        header.extend_from_slice(&self.params.p_cost.to_be_bytes());
+        header.extend_from_slice(&self.salt);
+        header
+    }
+
+    /// Encrypt under a fresh nonce and armor as PEM
+    pub fn to_pem(&self) -> Result<String, CryptoError> {
+        let header = self.header();
+        let mut plaintext = encode_entries(&self.entries);
+        let sealed = ChaCha20Poly1305::new(self.key).seal(&header, &plaintext);
+        wipe(&mut plaintext);
+
+        let mut data = header;
+        data.extend_from_slice(&sealed?);
+        Ok(pem::encode(PEM_LABEL, &data))
+    }
+
+    /// Write the vault, replacing the file atomically (owner-only permissions on unix)
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CryptoError> {
+        write_private_file(path.as_ref(), self.to_pem()?.as_bytes())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(|v| v.as_str())
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        if let Some(mut old) = self.entries.insert(name.into(), value.into()) {
+            wipe_string(&mut old);
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.entries.remove(name) {
+            Some(mut old) => {
+                wipe_string(&mut old);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Secret names in sorted order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|k| k.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Re-key the vault with a new passphrase or key file and a fresh salt.
+    /// Takes effect on the next `save`.
+    pub fn rotate(&mut self, source: &KeySource) -> Result<(), CryptoError> {
+        let salt: [u8; SALT_LEN] = random::secure_array()?;
+        self.key = source.derive(&salt, &self.params)?;
+        self.salt = salt;
+        self.kdf_id = source.kdf_id();
+        Ok(())
+    }
+
+    /// Change the Argon2id cost used by the next `rotate`
+    pub fn set_kdf_params(&mut self, params: argon2::Params) {
+        self.params = params;
+    }
+
+    /// Import `NAME=value` lines from a .env file, skipping comments and empty values.
+    /// Returns the number of secrets stored.
+    pub fn import_dotenv(&mut self, text: &str) -> usize {
+        let mut count = 0;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let name = name.trim();
+            let mut value = value.trim();
+            if value.len() >= 2
+                && ((value.starts_with('"') && value.ends_with('"'))
+                    || (value.starts_with('\'') && value.ends_with('\'')))
+            {
+                value = &value[1..value.len() - 1];
+            } else if let Some(idx) = value.find(" #") {
+                // Inline comment after an unquoted value
+                value = value[..idx].trim_end();
+            }
+            if name.is_empty() || value.is_empty() {
+                continue;
+            }
+            self.set(name, value);
+            count += 1;
+        }
+        count
+    }
+
+    /// Copy secrets into the process environment.
+    /// Variables already set are kept unless `overwrite`; returns how many were set.
+    pub fn export_to_env(&self, overwrite: bool) -> usize {
+        let mut count = 0;
+        for (name, value) in &self.entries {
+            if overwrite || std::env::var_os(name).is_none() {
+                std::env::set_var(name, value);
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+impl Drop for Vault {
+    fn drop(&mut self) {
+        wipe(&mut self.key);
+        for value in self.entries.values_mut() {
+            wipe_string(value);
+        }
+    }
+}
+
+impl std::fmt::Debug for Vault {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Vault")
+            .field("names", &self.entries.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Load the vault configured by the environment into the process environment.
+///
+/// Reads `AVILA_VAULT_PATH` (default `secrets.vault`) with the key from
+/// `AVILA_VAULT_KEY_FILE` or `AVILA_VAULT_PASSPHRASE`. Returns `Ok(0)` when
+/// no vault file exists, so binaries can call this unconditionally at startup.
+pub fn load_env() -> Result<usize, CryptoError> {
+    let path = std::env::var("AVILA_VAULT_PATH").unwrap_or_else(|_| DEFAULT_PATH.to_string());
+    if !Path::new(&path).exists() {
+        return Ok(0);
+    }
+    let source = source_from_env()?;
+    Ok(Vault::open(&path, &source)?.export_to_env(false))
+}
+
+/// Key source from `AVILA_VAULT_KEY_FILE` or `AVILA_VAULT_PASSPHRASE`
+pub fn source_from_env() -> Result<KeySource, CryptoError> {
+    if let Ok(path) = std::env::var("AVILA_VAULT_KEY_FILE") {
+        return KeySource::key_file(path);
+    }
+    if let Ok(passphrase) = std::env::var("AVILA_VAULT_PASSPHRASE") {
+        return Ok(KeySource::Passphrase(passphrase));
+    }
+    Err(CryptoError::InvalidParameter(
+        "set AVILA_VAULT_KEY_FILE or AVILA_VAULT_PASSPHRASE to unlock the vault".to_string(),
+    ))
+}
+
+fn encode_entries(entries: &BTreeMap<String, String>) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for (name, value) in entries {
+        for field in [name.as_bytes(), value.as_bytes()] {
+            out.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            out.extend_from_slice(field);
+        }
+    }
+    out
+}
+
+fn decode_entries(data: &[u8]) -> Result<BTreeMap<String, String>, CryptoError> {
+    let corrupt = || CryptoError::InvalidParameter("corrupt vault contents".to_string());
+    let mut pos = 0;
+    let read_u32 = |pos: &mut usize| -> Result<usize, CryptoError> {
+        let bytes = data.get(*pos..*pos + 4).ok_or_else(corrupt)?;
+        *pos += 4;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    };
+
+    let count = read_u32(&mut pos)?;
+    let mut entries = BTreeMap::new();
+    for _ in 0..count {
+        let mut fields = [String::new(), String::new()];
+        for field in fields.iter_mut() {
+            let len = read_u32(&mut pos)?;
+            let bytes = data.get(pos..pos + len).ok_or_else(corrupt)?;
+            *field = String::from_utf8(bytes.to_vec()).map_err(|_| corrupt())?;
+            pos += len;
+        }
+        let [name, value] = fields;
+        entries.insert(name, value);
+    }
+    if pos != data.len() {
+        return Err(corrupt());
+    }
+    Ok(entries)
+}
+
+fn wipe(bytes: &mut [u8]) {
+    for b in bytes.iter_mut() {
+        // SAFETY: writing through a valid &mut u8
+        unsafe { std::ptr::write_volatile(b, 0) };
+    }
+}
+
+fn wipe_string(s: &mut str) {
+    // SAFETY: zero bytes are valid UTF-8
+    wipe(unsafe { s.as_bytes_mut() });
+}
+
+/// Atomically replace `path` with `contents`, readable only by the owner on Unix
+pub(crate) fn write_private_file(path: &Path, contents: &[u8]) -> Result<(), CryptoError> {
+    let io_err = |e: std::io::Error| CryptoError::Io(format!("{}: {}", path.display(), e));
+    let tmp = path.with_extension("vault.tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    {
+        use std::io::Write;
+        let mut file = options.open(&tmp).map_err(io_err)?;
+        file.write_all(contents).map_err(io_err)?;
+        file.sync_all().map_err(io_err)?;
+    }
+    std::fs::rename(&tmp, path).map_err(io_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_params() -> argon2::Params {
+        argon2::Params::new(64, 1, 1).unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip_and_wrong_key() {
+        let source = KeySource::Passphrase("dubai 2025".to_string());
+        let mut vault = Vault::create_with_params(&source, fast_params()).unwrap();
+        vault.set("MONGODB_ATLAS_API_KEY", "atlas-secret");
+        vault.set("BAYUT_API_KEY", "bayut-secret");
+        let pem = vault.to_pem().unwrap();
+        assert!(pem.starts_with("-----BEGIN AVILA VAULT-----"));
+        assert!(!pem.contains("atlas-secret"));
+
+        let reopened = Vault::from_pem(&pem, &source).unwrap();
+        assert_eq!(reopened.get("MONGODB_ATLAS_API_KEY"), Some("atlas-secret"));
+        assert_eq!(reopened.names().collect::<Vec<_>>(), vec!["BAYUT_API_KEY", "MONGODB_ATLAS_API_KEY"]);
+
+        let wrong = KeySource::Passphrase("dubai 2026".to_string());
+        assert_eq!(Vault::from_pem(&pem, &wrong).unwrap_err(), CryptoError::AuthenticationFailed);
+        assert_eq!(
+            Vault::from_pem(&pem, &KeySource::KeyFile(vec![1; 32])).unwrap_err(),
+            CryptoError::InvalidKey
+        );
+    }
+
+    #[test]
+    fn test_header_costs_bounded() {
+        let source = KeySource::Passphrase("dubai 2025".to_string());
+        let pem = Vault::create_with_params(&source, fast_params()).unwrap().to_pem().unwrap();
+        let data = pem::decode_expect(&pem, PEM_LABEL).unwrap();
+        // m_cost, t_cost and p_cost are big-endian u32s at offsets 6, 10 and 14
+        for offset in [6, 10, 14] {
+            let mut crafted = data.clone();
+            crafted[offset..offset + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+            let crafted = pem::encode(PEM_LABEL, &crafted);
+            assert!(matches!(
+                Vault::from_pem(&crafted, &source),
+                Err(CryptoError::InvalidParameter(_))
+            ));
+        }
+        let too_slow = argon2::Params::new(64, 17, 1).unwrap();
+        assert!(Vault::create_with_params(&source, too_slow).is_err());
+    }
+
+    #[test]
+    fn test_rotate_to_key_file() {
+        let old = KeySource::Passphrase("old".to_string());
+        let new = KeySource::KeyFile(vec![0x42; 32]);
+        let mut vault = Vault::create_with_params(&old, fast_params()).unwrap();
+        vault.set("DLD_API_KEY", "dld");
+        vault.rotate(&new).unwrap();
+        let pem = vault.to_pem().unwrap();
+
+        assert!(Vault::from_pem(&pem, &old).is_err());
+        assert_eq!(Vault::from_pem(&pem, &new).unwrap().get("DLD_API_KEY"), Some("dld"));
+    }
+
+    #[test]
+    fn test_import_dotenv() {
+        let mut vault = Vault::create_with_params(&KeySource::KeyFile(vec![7; 32]), fast_params()).unwrap();
+        let count = vault.import_dotenv(
+            "# Banco de Dados\nMONGODB_ATLAS_APP_ID=\nMONGODB_ATLAS_CLUSTER=cluster0\n\
+             export SERVER_PORT=8080   # porta\nALERT_EMAIL=\"ops@example.com\"\n# BAYUT_API_KEY=x\n",
+        );
+        assert_eq!(count, 3);
+        assert_eq!(vault.get("MONGODB_ATLAS_CLUSTER"), Some("cluster0"));
+        assert_eq!(vault.get("SERVER_PORT"), Some("8080"));
+        assert_eq!(vault.get("ALERT_EMAIL"), Some("ops@example.com"));
+        assert_eq!(vault.get("MONGODB_ATLAS_APP_ID"), None);
+    }
+
+    #[test]
+    fn test_load_env() {
+        let path = std::env::temp_dir().join(format!("avila_vault_test_{}.vault", std::process::id()));
+        let source = KeySource::Passphrase("load env".to_string());
+        let mut vault = Vault::create_with_params(&source, fast_params()).unwrap();
+        vault.set("AVILA_VAULT_TEST_NEW", "from-vault");
+        vault.set("AVILA_VAULT_TEST_SET", "from-vault");
+        vault.save(&path).unwrap();
+
+        std::env::set_var("AVILA_VAULT_TEST_SET", "already-set");
+        std::env::set_var("AVILA_VAULT_PATH", &path);
+        std::env::set_var("AVILA_VAULT_PASSPHRASE", "load env");
+        let loaded = load_env();
+        std::fs::remove_file(&path).unwrap();
+
+        // Variables already in the environment win over the vault
+        assert_eq!(loaded, Ok(1));
+        assert_eq!(std::env::var("AVILA_VAULT_TEST_NEW").unwrap(), "from-vault");
+        assert_eq!(std::env::var("AVILA_VAULT_TEST_SET").unwrap(), "already-set");
+        assert_eq!(vault.export_to_env(true), 2);
+        assert_eq!(std::env::var("AVILA_VAULT_TEST_SET").unwrap(), "from-vault");
+
+        // Without a vault file there is nothing to load
+        assert_eq!(load_env(), Ok(0));
+    }
+}
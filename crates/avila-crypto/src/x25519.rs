@@ -0,0 +1,254 @@
+// AvilaCrypto X25519 - Elliptic-curve Diffie-Hellman (RFC 7748)
+// Zero External Dependencies 🦀
+
+use crate::curve25519::FieldElement;
+use crate::{pem, random, CryptoError};
+
+pub const KEY_LEN: usize = 32;
+
+/// The u-coordinate of the Curve25519 base point
+pub const BASEPOINT: [u8; 32] = [
+    9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+// DER prefixes for id-X25519 (OID 1.3.101.110) keys, RFC 8410
+const PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x04, 0x22, 0x04, 0x20,
+];
+const SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x03, 0x21, 0x00];
+
+fn clamp(scalar: &[u8; 32]) -> [u8; 32] {
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+    k
+}
+
+/// The X25519 function: scalar multiplication on the Montgomery u-line
+pub fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let k = clamp(scalar);
+    let x1 = FieldElement::from_bytes(u);
+    let a24 = FieldElement::from_u64(121665);
+
+    let mut x2 = FieldElement::ONE;
+    let mut z2 = FieldElement::ZERO;
+    let mut x3 = x1;
+    let mut z3 = FieldElement::ONE;
+    let mut swap = 0u64;
+
+    // Montgomery ladder, bit 254 down to 0
+    for t in (0..255).rev() {
+        let k_t = ((k[t / 8] >> (t % 8)) & 1) as u64;
+        swap ^= k_t;
+        FieldElement::conditional_swap(&mut x2, &mut x3, swap);
+        FieldElement::conditional_swap(&mut z2, &mut z3, swap);
+        swap = k_t;
+
+        let a = x2.add(&z2);
+        let aa = a.square();
+        let b = x2.sub(&z2);
+        let bb = b.square();
+        let e = aa.sub(&bb);
+        let c = x3.add(&z3);
+        let d = x3.sub(&z3);
+        let da = d.mul(&a);
+        let cb = c.mul(&b);
+
+        x3 = da.add(&cb).square();
+        z3 = x1.mul(&da.sub(&cb).square());
+        x2 = aa.mul(&bb);
+        z2 = e.mul(&aa.add(&a24.mul(&e)));
+    }
+
+    FieldElement::conditional_swap(&mut x2, &mut x3, swap);
+    FieldElement::conditional_swap(&mut z2, &mut z3, swap);
+
+    x2.mul(&z2.invert()).to_bytes()
+}
+
+/// X25519 private key (a 32-byte scalar, clamped on use)
+#[derive(Clone)]
+pub struct PrivateKey {
+    bytes: [u8; 32],
+}
+
+impl PrivateKey {
+    /// Generate a fresh key from the OS random generator
+    pub fn generate() -> Result<Self, CryptoError> {
+        Ok(Self {
+            bytes: random::secure_array()?,
+        })
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.bytes
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(x25519(&self.bytes, &BASEPOINT))
+    }
+
+    /// Compute the shared secret with a peer's public key.
+    ///
+    /// Fails with `WeakPublicKey` when the peer sent a low-order point,
+    /// which would make the result all zeros.
+    pub fn diffie_hellman(&self, peer: &PublicKey) -> Result<[u8; 32], CryptoError> {
+        let shared = x25519(&self.bytes, &peer.0);
+        if shared.iter().fold(0u8, |acc, b| acc | b) == 0 {
+            return Err(CryptoError::WeakPublicKey);
+        }
+        Ok(shared)
+    }
+
+    /// PKCS#8 DER encoding (RFC 8410)
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut der = PKCS8_PREFIX.to_vec();
+        der.extend_from_slice(&self.bytes);
+        der
+    }
+
+    pub fn from_der(der: &[u8]) -> Result<Self, CryptoError> {
+        if der.len() != PKCS8_PREFIX.len() + KEY_LEN || der[..PKCS8_PREFIX.len()] != PKCS8_PREFIX {
+            return Err(CryptoError::InvalidKey);
+        }
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&der[PKCS8_PREFIX.len()..]);
+        Ok(Self { bytes })
+    }
+
+    pub fn to_pem(&self) -> String {
+        pem::encode("PRIVATE KEY", &self.to_der())
+    }
+
+    pub fn from_pem(text: &str) -> Result<Self, CryptoError> {
+        Self::from_der(&pem::decode_expect(text, "PRIVATE KEY")?)
+    }
+}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        for b in self.bytes.iter_mut() {
+            // SAFETY: writing through a valid &mut u8
+            unsafe { std::ptr::write_volatile(b, 0) };
+        }
+    }
+}
+
+impl std::fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "PrivateKey(..)")
+    }
+}
+
+/// X25519 public key (u-coordinate)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey([u8; 32]);
+
+impl PublicKey {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        PublicKey(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// SubjectPublicKeyInfo DER encoding (RFC 8410)
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut der = SPKI_PREFIX.to_vec();
+        der.extend_from_slice(&self.0);
+        der
+    }
+
+    pub fn from_der(der: &[u8]) -> Result<Self, CryptoError> {
+        if der.len() != SPKI_PREFIX.len() + KEY_LEN || der[..SPKI_PREFIX.len()] != SPKI_PREFIX {
+            return Err(CryptoError::InvalidKey);
+        }
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&der[SPKI_PREFIX.len()..]);
+        Ok(PublicKey(bytes))
+    }
+
+    pub fn to_pem(&self) -> String {
+        pem::encode("PUBLIC KEY", &self.to_der())
+    }
+
+    pub fn from_pem(text: &str) -> Result<Self, CryptoError> {
+        Self::from_der(&pem::decode_expect(text, "PUBLIC KEY")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::unhex;
+
+    #[test]
+    fn test_rfc7748_vectors() {
+        // RFC 7748 section 5.2
+        let out = x25519(
+            &unhex("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4").try_into().unwrap(),
+            &unhex("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c").try_into().unwrap(),
+        );
+        assert_eq!(out.to_vec(), unhex("c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552"));
+
+        let out = x25519(
+            &unhex("4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d").try_into().unwrap(),
+            &unhex("e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493").try_into().unwrap(),
+        );
+        assert_eq!(out.to_vec(), unhex("95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957"));
+    }
+
+    #[test]
+    fn test_rfc7748_iterated() {
+        let mut k = BASEPOINT;
+        let mut u = BASEPOINT;
+        for i in 1..=1000 {
+            let out = x25519(&k, &u);
+            u = k;
+            k = out;
+            if i == 1 {
+                assert_eq!(k.to_vec(), unhex("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079"));
+            }
+        }
+        assert_eq!(k.to_vec(), unhex("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51"));
+    }
+
+    #[test]
+    fn test_rfc7748_diffie_hellman() {
+        // RFC 7748 section 6.1
+        let alice = PrivateKey::from_bytes(unhex("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a").try_into().unwrap());
+        let bob = PrivateKey::from_bytes(unhex("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb").try_into().unwrap());
+
+        assert_eq!(alice.public_key().as_bytes().to_vec(), unhex("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"));
+        assert_eq!(bob.public_key().as_bytes().to_vec(), unhex("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"));
+
+        let shared = unhex("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(alice.diffie_hellman(&bob.public_key()).unwrap().to_vec(), shared);
+        assert_eq!(bob.diffie_hellman(&alice.public_key()).unwrap().to_vec(), shared);
+    }
+
+    #[test]
+    fn test_low_order_point_rejected() {
+        let key = PrivateKey::generate().unwrap();
+        let zero = PublicKey::from_bytes([0u8; 32]);
+        assert_eq!(key.diffie_hellman(&zero), Err(CryptoError::WeakPublicKey));
+    }
+
+    #[test]
+    fn test_pem_roundtrip() {
+        let key = PrivateKey::generate().unwrap();
+        let restored = PrivateKey::from_pem(&key.to_pem()).unwrap();
+        assert_eq!(restored.to_bytes(), key.to_bytes());
+
+        let public = key.public_key();
+        let pem = public.to_pem();
+        assert!(pem.starts_with("-----BEGIN PUBLIC KEY-----"));
+        assert_eq!(PublicKey::from_pem(&pem).unwrap(), public);
+    }
+}
@@ -0,0 +1,507 @@
+// AvilaExtract Adapters - One implementation per property portal
+// Zero External Dependencies 🦀
+
+use crate::{Property, PropertyType, SearchCriteria};
+use avila_json::JsonValue;
+use avila_parser::{
+    parse_html, resolve_url, Element, ElementCapture, HtmlStream, Node, PageLinks, ParseError,
+    Selector, StructuredData,
+};
+
+const SQFT_TO_SQM: f64 = 0.092903;
+
+/// Start tags of the pagination links and blocks `PageLinks` looks for
+pub(crate) const PAGINATION_CAPTURE: &str = "[rel~=next i], [rel~=prev i], \
+    [class*=pagination i], [class*=pager i], [aria-label*=pagination i], [aria-label*=pager i], \
+    [data-testid*=pagination i], [data-testid*=pager i]";
+
+/// `<script type="application/ld+json">` blocks, for `StructuredData`
+pub(crate) const JSON_LD_CAPTURE: &str = "script[type*=ld+json i]";
+
+/// How to search one portal and read its pages
+///
+/// Adapters only interpret HTML; fetching is left to the caller, so each one
+/// can be tested against saved pages.
+pub trait SiteAdapter {
+    /// Registrable domain served by the adapter, e.g. `bayut.com`
+    fn domain(&self) -> &str;
+
+    /// URL of results page `page` (1-based) for the criteria
+    fn search_url(&self, criteria: &SearchCriteria, page: usize) -> String;
+
+    /// Listings on a search-results page
+    fn parse_cards(&self, document: &Element, page_url: &str) -> Vec<Property>;
+
+    /// The listing on a property detail page
+    fn parse_detail(&self, document: &Element, page_url: &str) -> Option<Property>;
+
+    /// The results page after `page_url`; `rel="next"` or a pagination block by default
+    fn next_page(&self, document: &Element, page_url: &str) -> Option<String> {
+        PageLinks::extract(document, page_url).pagination.next
+    }
+
+    /// Start tags of everything `parse_cards` and `next_page` read, as
+    /// selectors without combinators. Search pages are then built from just
+    /// those subtrees (see `search_document`); empty parses the whole page.
+    fn capture(&self) -> &[Selector] {
+        &[]
+    }
+
+    /// Whether `url` (or a bare host name) belongs to this portal
+    fn handles(&self, url: &str) -> bool {
+        let host = host_of(url);
+        let domain = self.domain();
+        host == domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.'))
+    }
+}
+
+/// A search-results page as `parse_cards` and `next_page` need it: with a
+/// `capture` selector, only the matching subtrees under an empty root, pulled
+/// from the token stream without building the page's DOM
+pub(crate) fn search_document(
+    adapter: &dyn SiteAdapter,
+    html: &str,
+) -> Result<Element, ParseError> {
+    let selectors = adapter.capture();
+    if selectors.is_empty() {
+        return parse_html(html);
+    }
+    let mut capture = ElementCapture::new(|name, attributes| {
+        selectors.iter().any(|s| s.matches_tag(name, attributes))
+    });
+    let mut stream = HtmlStream::new();
+    stream.feed(html.as_bytes());
+    stream.finish();
+    let children = stream
+        .events()
+        .filter_map(|token| capture.push(token))
+        .map(Node::Element)
+        .collect();
+    Ok(Element {
+        tag: "html".to_string(),
+        attributes: Vec::new(),
+        children,
+    })
+}
+
+/// Host of a URL, lowercased and without port; a bare host is returned as is
+pub(crate) fn host_of(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = host.split(':').next().unwrap_or_default();
+    host.to_ascii_lowercase()
+}
+
+/// CSS selectors for the fields of a listing card or detail page; an empty
+/// string means the portal does not show the field
+pub(crate) struct FieldSelectors<S = &'static str> {
+    pub link: S,
+    pub title: S,
+    pub price: S,
+    pub currency: S,
+    pub location: S,
+    pub bedrooms: S,
+    pub bathrooms: S,
+    pub area: S,
+    pub property_type: S,
+}
+
+impl FieldSelectors {
+    /// Compiled once and reused for every card
+    pub(crate) fn compile(&self) -> FieldSelectors<Option<Selector>> {
+        let compile = |selector: &str| Selector::parse(selector).ok();
+        FieldSelectors {
+            link: compile(self.link),
+            title: compile(self.title),
+            price: compile(self.price),
+            currency: compile(self.currency),
+            location: compile(self.location),
+            bedrooms: compile(self.bedrooms),
+            bathrooms: compile(self.bathrooms),
+            area: compile(self.area),
+            property_type: compile(self.property_type),
+        }
+    }
+}
+
+impl FieldSelectors<Option<Selector>> {
+    /// Reads a listing from `scope`. `None` without a price, which is what
+    /// separates listings from ads and placeholders.
+    pub(crate) fn read(&self, scope: &Element, page_url: &str) -> Option<Property> {
+        let first = |selector: &Option<Selector>| selector.as_ref()?.select_first(scope);
+        let text = |selector: &Option<Selector>| -> String {
+            first(selector)
+                .map(|found| found.text_content_normalized().replace('\n', " "))
+                .unwrap_or_default()
+        };
+
+        let price_text = text(&self.price);
+        let price = parse_price(&price_text)?;
+        let title = text(&self.title);
+        let url = first(&self.link)
+            .and_then(|link| link.get_attribute("href"))
+            .and_then(|href| resolve_url(page_url, href))
+            .unwrap_or_default();
+        let kind = text(&self.property_type);
+        let currency = match text(&self.currency) {
+            currency if !currency.is_empty() => currency,
+            _ => currency_of(&price_text),
+        };
+
+        Some(Property {
+            property_type: property_type_from(if kind.is_empty() { &title } else { &kind }),
+            title,
+            price,
+            currency,
+            location: text(&self.location),
+            coordinates: None,
+            bedrooms: parse_count(&text(&self.bedrooms)),
+            bathrooms: parse_count(&text(&self.bathrooms)),
+            area_sqm: parse_area_sqm(&text(&self.area)),
+            url,
+        })
+    }
+}
+
+/// An adapter's selectors, compiled once into a `OnceLock` and shared by
+/// every page it reads
+pub(crate) struct PageSelectors {
+    pub card: Option<Selector>,
+    pub card_fields: FieldSelectors<Option<Selector>>,
+    pub detail_fields: FieldSelectors<Option<Selector>>,
+    pub capture: Vec<Selector>,
+    /// The next-page link, for portals without `rel="next"`
+    pub next_link: Option<Selector>,
+}
+
+impl PageSelectors {
+    pub(crate) fn compile(
+        card: &str,
+        card_fields: &FieldSelectors,
+        detail_fields: &FieldSelectors,
+        capture: &[&str],
+        next_link: &str,
+    ) -> Self {
+        Self {
+            card: Selector::parse(card).ok(),
+            card_fields: card_fields.compile(),
+            detail_fields: detail_fields.compile(),
+            capture: capture
+                .iter()
+                .filter_map(|s| Selector::parse(s).ok())
+                .collect(),
+            next_link: Selector::parse(next_link).ok(),
+        }
+    }
+
+    /// Every card on the page read with `card_fields`
+    pub(crate) fn read_cards(&self, document: &Element, page_url: &str) -> Vec<Property> {
+        let Some(card) = &self.card else {
+            return vec![];
+        };
+        card.select(document)
+            .into_iter()
+            .filter_map(|card| self.card_fields.read(card, page_url))
+            .collect()
+    }
+
+    /// `next_link`'s target, resolved against the page
+    pub(crate) fn next_page(&self, document: &Element, page_url: &str) -> Option<String> {
+        self.next_link
+            .as_ref()?
+            .select_first(document)
+            .and_then(|link| link.get_attribute("href"))
+            .and_then(|href| resolve_url(page_url, href))
+    }
+}
+
+/// Residences described in JSON-LD (`Apartment`, `House`, `Residence`...) that
+/// carry an `Offer` with a price
+pub fn properties_from_structured_data(data: &StructuredData, page_url: &str) -> Vec<Property> {
+    const RESIDENCE_TYPES: [&str; 5] = [
+        "Residence",
+        "Apartment",
+        "House",
+        "SingleFamilyResidence",
+        "Accommodation",
+    ];
+
+    let mut seen = Vec::new();
+    let mut properties = Vec::new();
+    for residence_type in RESIDENCE_TYPES {
+        for residence in data.json_ld_of_type(residence_type) {
+            // An item with several @type values turns up in more than one search
+            if seen.iter().any(|r| std::ptr::eq(*r, residence)) {
+                continue;
+            }
+            seen.push(residence);
+
+            let offer = match residence.pointer("/offers") {
+                Some(JsonValue::Array(offers)) => offers.first(),
+                other => other,
+            };
+            let Some(price) = offer
+                .and_then(|o| o.pointer("/price"))
+                .and_then(json_amount)
+            else {
+                continue;
+            };
+
+            let text = |pointer: &str| {
+                residence
+                    .pointer(pointer)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            };
+            let count = |pointer: &str| {
+                residence
+                    .pointer(pointer)
+                    .and_then(json_amount)
+                    .map_or(0, |n| n as u32)
+            };
+
+            let coordinates = residence
+                .pointer("/geo/latitude")
+                .and_then(json_amount)
+                .zip(residence.pointer("/geo/longitude").and_then(json_amount));
+            let area_sqm = residence
+                .pointer("/floorSize/value")
+                .and_then(json_amount)
+                .map_or(0.0, |value| {
+                    match residence
+                        .pointer("/floorSize/unitCode")
+                        .and_then(|v| v.as_str())
+                    {
+                        Some("FTK") | Some("sqft") => value * SQFT_TO_SQM,
+                        _ => value,
+                    }
+                });
+            let declared_types = residence
+                .pointer("/@type")
+                .map(|t| t.to_string())
+                .unwrap_or_default();
+            let property_type = if declared_types.contains("House") {
+                PropertyType::Villa
+            } else {
+                property_type_from(&text("/name").unwrap_or_default())
+            };
+
+            properties.push(Property {
+                title: text("/name").unwrap_or_default(),
+                price,
+                currency: offer
+                    .and_then(|o| o.pointer("/priceCurrency"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("AED")
+                    .to_string(),
+                location: text("/address/addressLocality")
+                    .or_else(|| text("/address"))
+                    .unwrap_or_default(),
+                coordinates,
+                bedrooms: match count("/numberOfBedrooms") {
+                    0 => count("/numberOfRooms"),
+                    bedrooms => bedrooms,
+                },
+                bathrooms: count("/numberOfBathroomsTotal"),
+                area_sqm,
+                property_type,
+                url: text("/url")
+                    .and_then(|url| resolve_url(page_url, &url))
+                    .unwrap_or_default(),
+            });
+        }
+    }
+    properties
+}
+
+/// JSON number or text such as "1,850,000"
+fn json_amount(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::String(text) => text.replace(',', "").trim().parse().ok(),
+        other => other.as_f64(),
+    }
+}
+
+/// First amount in a text such as "AED 1,850,000" or "1,250,000 AED/yearly";
+/// Arabic-Indic digits (٠-٩) are accepted
+pub fn parse_price(text: &str) -> Option<f64> {
+    let text = ascii_digits(text);
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let amount: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || matches!(c, ',' | '.'))
+        .filter(|c| *c != ',')
+        .collect();
+    amount.trim_end_matches('.').parse().ok()
+}
+
+/// First whole number in a text such as "2 Beds"; "Studio" and missing values are 0
+pub fn parse_count(text: &str) -> u32 {
+    let text = ascii_digits(text);
+    text.split(|c: char| !c.is_ascii_digit())
+        .find(|part| !part.is_empty())
+        .and_then(|digits| digits.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Floor area in square metres from "1,200 sqft" or "111 sqm"; bare numbers
+/// are taken as square feet, which is what the portals default to
+pub fn parse_area_sqm(text: &str) -> f64 {
+    let text = text.to_lowercase();
+    let metric =
+        |part: &&str| part.contains("sqm") || part.contains("m²") || part.contains("sq. m");
+    // "812 sqft / 75 sqm" gives both; the metric figure is used as is
+    if let Some(value) = text.split('/').filter(metric).find_map(parse_price) {
+        return value;
+    }
+    parse_price(&text).map_or(0.0, |value| value * SQFT_TO_SQM)
+}
+
+/// Guess from a type label, a title or a URL path
+pub fn property_type_from(text: &str) -> PropertyType {
+    let text = text.to_lowercase();
+    if text.contains("penthouse") {
+        PropertyType::Penthouse
+    } else if text.contains("townhouse") {
+        PropertyType::Townhouse
+    } else if text.contains("villa") {
+        PropertyType::Villa
+    } else if text.contains("office") {
+        PropertyType::Office
+    } else {
+        PropertyType::Apartment
+    }
+}
+
+fn ascii_digits(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{660}'..='\u{669}' => char::from(b'0' + (c as u32 - 0x660) as u8),
+            _ => c,
+        })
+        .collect()
+}
+
+fn currency_of(price_text: &str) -> String {
+    let upper = price_text.to_ascii_uppercase();
+    ["USD", "EUR", "GBP"]
+        .into_iter()
+        .find(|code| upper.contains(code))
+        .unwrap_or("AED")
+        .to_string()
+}
+
+/// Lowercase words joined by dashes: "Dubai Marina" becomes "dubai-marina"
+pub(crate) fn slugify(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// `key=value` pairs joined into a query string, `?` included when not empty
+pub(crate) fn query_string(params: &[(&str, String)]) -> String {
+    let pairs: Vec<String> = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, percent_encode(value)))
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", pairs.join("&"))
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_parsing() {
+        assert_eq!(parse_price("AED 1,500,000"), Some(1500000.0));
+        assert_eq!(
+            parse_price("AED\u{a0}\u{661},\u{662}\u{665}\u{660},\u{660}\u{660}\u{660}"),
+            Some(1250000.0)
+        );
+        assert_eq!(parse_price("2,450,000.50 AED/yearly"), Some(2450000.5));
+        assert_eq!(parse_price("Price on request"), None);
+    }
+
+    #[test]
+    fn test_field_parsing() {
+        assert_eq!(parse_count("2 Beds"), 2);
+        assert_eq!(parse_count("Studio"), 0);
+        assert!((parse_area_sqm("1,200 sqft") - 111.48).abs() < 0.01);
+        assert_eq!(parse_area_sqm("95 sqm"), 95.0);
+        assert_eq!(parse_area_sqm("812 sqft / 75 sqm"), 75.0);
+        assert_eq!(
+            property_type_from("Luxury Penthouse | Palm"),
+            PropertyType::Penthouse
+        );
+        assert_eq!(property_type_from("villahouse"), PropertyType::Villa);
+        assert_eq!(
+            host_of("https://User@WWW.Bayut.com:443/x?y"),
+            "www.bayut.com"
+        );
+        assert_eq!(
+            query_string(&[("q", "Dubai Marina & JBR".to_string())]),
+            "?q=Dubai+Marina+%26+JBR"
+        );
+    }
+
+    #[test]
+    fn test_search_document_matches_full_parse() {
+        let pages: [(&dyn SiteAdapter, &str, &str); 3] = [
+            (
+                &crate::BayutAdapter,
+                include_str!("../fixtures/bayut_search.html"),
+                "https://www.bayut.com/for-sale/apartments/dubai/dubai-marina/",
+            ),
+            (
+                &crate::PropertyFinderAdapter,
+                include_str!("../fixtures/propertyfinder_search.html"),
+                "https://www.propertyfinder.ae/en/search?c=1&q=downtown-dubai",
+            ),
+            (
+                &crate::DubizzleAdapter,
+                include_str!("../fixtures/dubizzle_search.html"),
+                "https://dubai.dubizzle.com/property-for-sale/residential/",
+            ),
+        ];
+        for (adapter, html, url) in pages {
+            let full = parse_html(html).unwrap();
+            let captured = search_document(adapter, html).unwrap();
+            assert!(captured.find_by_tag("main").is_empty(), "{}", url);
+            assert_eq!(
+                format!("{:?}", adapter.parse_cards(&captured, url)),
+                format!("{:?}", adapter.parse_cards(&full, url)),
+            );
+            assert!(adapter.next_page(&captured, url).is_some());
+            assert_eq!(
+                adapter.next_page(&captured, url),
+                adapter.next_page(&full, url)
+            );
+        }
+    }
+}
@@ -0,0 +1,205 @@
+// AvilaExtract Bayut - bayut.com adapter
+// Zero External Dependencies 🦀
+
+use crate::adapter::{
+    properties_from_structured_data, query_string, slugify, FieldSelectors, PageSelectors,
+    SiteAdapter, JSON_LD_CAPTURE, PAGINATION_CAPTURE,
+};
+use crate::{Property, PropertyType, SearchCriteria};
+use avila_parser::{Element, Selector, StructuredData};
+use std::sync::OnceLock;
+
+/// Result cards; `article[class*=property]` is the markup of older pages
+const CARD: &str = "li[role=article][aria-label=Listing], article[class*=property]";
+
+/// Subtrees read from search pages: cards, JSON-LD and pagination
+const CAPTURE: [&str; 3] = [CARD, JSON_LD_CAPTURE, PAGINATION_CAPTURE];
+
+const CARD_FIELDS: FieldSelectors = FieldSelectors {
+    link: "a[aria-label='Listing link']",
+    title: "[aria-label=Title], h2",
+    price: "[aria-label=Price], .price, [data-testid=price]",
+    currency: "[aria-label=Currency]",
+    location: "[aria-label=Location], .location",
+    bedrooms: "[aria-label=Beds], .bedrooms",
+    bathrooms: "[aria-label=Baths], .bathrooms",
+    area: "[aria-label=Area]",
+    property_type: "[aria-label=Type]",
+};
+
+const DETAIL_FIELDS: FieldSelectors = FieldSelectors {
+    link: "link[rel=canonical]",
+    title: "h1",
+    price: "[aria-label=Price]",
+    currency: "[aria-label=Currency]",
+    location: "[aria-label='Property header']",
+    bedrooms: "[aria-label=Beds]",
+    bathrooms: "[aria-label=Baths]",
+    area: "[aria-label=Area]",
+    property_type: "[aria-label=Type]",
+};
+
+/// Search pages list cards under `li[role=article]`; detail pages carry JSON-LD
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BayutAdapter;
+
+impl BayutAdapter {
+    const BASE_URL: &'static str = "https://www.bayut.com";
+
+    /// No next-page selector: search pages have `<link rel="next">`
+    fn selectors() -> &'static PageSelectors {
+        static SELECTORS: OnceLock<PageSelectors> = OnceLock::new();
+        SELECTORS.get_or_init(|| {
+            PageSelectors::compile(CARD, &CARD_FIELDS, &DETAIL_FIELDS, &CAPTURE, "")
+        })
+    }
+}
+
+impl SiteAdapter for BayutAdapter {
+    fn domain(&self) -> &str {
+        "bayut.com"
+    }
+
+    fn search_url(&self, criteria: &SearchCriteria, page: usize) -> String {
+        let kind = match criteria.property_type {
+            None => "property",
+            Some(PropertyType::Apartment) => "apartments",
+            Some(PropertyType::Villa) => "villas",
+            Some(PropertyType::Townhouse) => "townhouses",
+            Some(PropertyType::Penthouse) => "penthouse",
+            Some(PropertyType::Office) => "offices",
+        };
+        let mut path = format!("{}/for-sale/{}/dubai/", Self::BASE_URL, kind);
+        if let Some(location) = &criteria.location {
+            path.push_str(&slugify(location));
+            path.push('/');
+        }
+
+        let mut params = Vec::new();
+        if let Some(bedrooms) = criteria.bedrooms {
+            params.push(("beds_in", bedrooms.to_string()));
+        }
+        if let Some(min) = criteria.min_price {
+            params.push(("price_min", format!("{:.0}", min)));
+        }
+        if let Some(max) = criteria.max_price {
+            params.push(("price_max", format!("{:.0}", max)));
+        }
+        if page > 1 {
+            params.push(("page", page.to_string()));
+        }
+        path + &query_string(&params)
+    }
+
+    fn capture(&self) -> &[Selector] {
+        &Self::selectors().capture
+    }
+
+    /// JSON-LD when the page has it, the result cards otherwise
+    fn parse_cards(&self, document: &Element, page_url: &str) -> Vec<Property> {
+        let structured =
+            properties_from_structured_data(&StructuredData::extract(document), page_url);
+        if !structured.is_empty() {
+            return structured;
+        }
+        let selectors = Self::selectors();
+        let Some(card) = &selectors.card else {
+            return vec![];
+        };
+        card.select(document)
+            .into_iter()
+            .filter_map(|card| {
+                let mut property = selectors.card_fields.read(card, page_url)?;
+                // Older cards have no link, only the listing id
+                if property.url.is_empty() {
+                    if let Some(id) = card.get_attribute("id") {
+                        property.url = format!("{}/property/{}", Self::BASE_URL, id);
+                    }
+                }
+                Some(property)
+            })
+            .collect()
+    }
+
+    fn parse_detail(&self, document: &Element, page_url: &str) -> Option<Property> {
+        let structured =
+            properties_from_structured_data(&StructuredData::extract(document), page_url);
+        let mut property = match structured.into_iter().next() {
+            Some(property) => property,
+            None => Self::selectors().detail_fields.read(document, page_url)?,
+        };
+        if property.url.is_empty() {
+            property.url = page_url.to_string();
+        }
+        Some(property)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use avila_parser::parse_html;
+
+    const SEARCH_URL: &str = "https://www.bayut.com/for-sale/apartments/dubai/dubai-marina/";
+
+    #[test]
+    fn test_search_url() {
+        let criteria = SearchCriteria::new()
+            .with_location("Dubai Marina".to_string())
+            .with_bedrooms(2)
+            .with_price_range(1_000_000.0, 2_500_000.0);
+        assert_eq!(
+            BayutAdapter.search_url(&criteria, 3),
+            "https://www.bayut.com/for-sale/property/dubai/dubai-marina/\
+             ?beds_in=2&price_min=1000000&price_max=2500000&page=3"
+        );
+        assert!(BayutAdapter.handles("https://www.bayut.com/property/details-1.html"));
+        assert!(!BayutAdapter.handles("https://notbayut.com/"));
+    }
+
+    #[test]
+    fn test_search_fixture() {
+        let document = parse_html(include_str!("../fixtures/bayut_search.html")).unwrap();
+        let properties = BayutAdapter.parse_cards(&document, SEARCH_URL);
+        assert_eq!(properties.len(), 3);
+
+        let first = &properties[0];
+        assert_eq!(
+            first.title,
+            "Marina Gate Tower 1 | High Floor | Full Sea View"
+        );
+        assert_eq!(first.price, 1_850_000.0);
+        assert_eq!(first.currency, "AED");
+        assert_eq!(first.location, "Marina Gate, Dubai Marina, Dubai");
+        assert_eq!((first.bedrooms, first.bathrooms), (2, 3));
+        assert!((first.area_sqm - 111.48).abs() < 0.01);
+        assert_eq!(first.property_type, PropertyType::Apartment);
+        assert_eq!(
+            first.url,
+            "https://www.bayut.com/property/details-8812345.html"
+        );
+
+        assert_eq!(properties[1].bedrooms, 0);
+        assert_eq!(properties[2].property_type, PropertyType::Penthouse);
+        assert_eq!(
+            BayutAdapter.next_page(&document, SEARCH_URL).as_deref(),
+            Some("https://www.bayut.com/for-sale/apartments/dubai/dubai-marina/?page=2")
+        );
+    }
+
+    #[test]
+    fn test_detail_fixture() {
+        let url = "https://www.bayut.com/property/details-8812345.html";
+        let document = parse_html(include_str!("../fixtures/bayut_detail.html")).unwrap();
+        let property = BayutAdapter.parse_detail(&document, url).unwrap();
+        assert_eq!(
+            property.title,
+            "Marina Gate Tower 1 | High Floor | Full Sea View"
+        );
+        assert_eq!(property.price, 1_850_000.0);
+        assert_eq!(property.location, "Dubai Marina");
+        assert_eq!((property.bedrooms, property.bathrooms), (2, 3));
+        assert_eq!(property.coordinates, Some((25.0867, 55.1474)));
+        assert_eq!(property.url, url);
+    }
+}
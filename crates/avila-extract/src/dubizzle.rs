@@ -0,0 +1,203 @@
+// AvilaExtract Dubizzle - dubizzle.com adapter
+// Zero External Dependencies 🦀
+
+use crate::adapter::{
+    property_type_from, query_string, FieldSelectors, PageSelectors, SiteAdapter,
+    PAGINATION_CAPTURE,
+};
+use crate::{Property, PropertyType, SearchCriteria};
+use avila_parser::{Element, Selector, StructuredData};
+use std::sync::OnceLock;
+
+const CARD: &str = "[data-testid^=listing-card]";
+
+/// Subtrees read from search pages: cards and pagination
+const CAPTURE: [&str; 2] = [CARD, PAGINATION_CAPTURE];
+
+const CARD_FIELDS: FieldSelectors = FieldSelectors {
+    link: "a[data-testid=listing-link]",
+    title: "[data-testid=listing-title]",
+    price: "[data-testid=listing-price]",
+    currency: "",
+    location: "[data-testid=listing-location]",
+    bedrooms: "[data-testid=listing-bedrooms]",
+    bathrooms: "[data-testid=listing-bathrooms]",
+    area: "[data-testid=listing-size]",
+    property_type: "",
+};
+
+const DETAIL_FIELDS: FieldSelectors = FieldSelectors {
+    link: "link[rel=canonical]",
+    title: "h1",
+    price: "[data-testid=listing-price]",
+    currency: "",
+    location: "[data-testid=location-information]",
+    bedrooms: "[data-testid=bed_space]",
+    bathrooms: "[data-testid=bath]",
+    area: "[data-testid=sqft]",
+    property_type: "",
+};
+
+/// Cards carry no type label; the category is the URL path segment after
+/// `residential/` (`apartmentflat`, `villahouse`, ...)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DubizzleAdapter;
+
+impl DubizzleAdapter {
+    const BASE_URL: &'static str = "https://dubai.dubizzle.com";
+
+    fn selectors() -> &'static PageSelectors {
+        static SELECTORS: OnceLock<PageSelectors> = OnceLock::new();
+        SELECTORS.get_or_init(|| {
+            PageSelectors::compile(
+                CARD,
+                &CARD_FIELDS,
+                &DETAIL_FIELDS,
+                &CAPTURE,
+                "a[data-testid=page-next]",
+            )
+        })
+    }
+
+    fn category(property: &mut Property) {
+        let category = property
+            .url
+            .split('/')
+            .skip_while(|segment| !matches!(*segment, "residential" | "commercial"))
+            .nth(1);
+        if let Some(category) = category.filter(|c| !c.is_empty()) {
+            property.property_type = property_type_from(category);
+        }
+    }
+}
+
+impl SiteAdapter for DubizzleAdapter {
+    fn domain(&self) -> &str {
+        "dubizzle.com"
+    }
+
+    fn search_url(&self, criteria: &SearchCriteria, page: usize) -> String {
+        let category = match criteria.property_type {
+            None => "residential/",
+            Some(PropertyType::Apartment) => "residential/apartmentflat/",
+            Some(PropertyType::Villa) => "residential/villahouse/",
+            Some(PropertyType::Townhouse) => "residential/townhouse/",
+            Some(PropertyType::Penthouse) => "residential/penthouse/",
+            Some(PropertyType::Office) => "commercial/office/",
+        };
+
+        let mut params = Vec::new();
+        if let Some(location) = &criteria.location {
+            params.push(("keywords", location.clone()));
+        }
+        if let Some(min) = criteria.min_price {
+            params.push(("price__gte", format!("{:.0}", min)));
+        }
+        if let Some(max) = criteria.max_price {
+            params.push(("price__lte", format!("{:.0}", max)));
+        }
+        if let Some(bedrooms) = criteria.bedrooms {
+            params.push(("bedrooms", bedrooms.to_string()));
+        }
+        if page > 1 {
+            params.push(("page", page.to_string()));
+        }
+        format!(
+            "{}/property-for-sale/{}{}",
+            Self::BASE_URL,
+            category,
+            query_string(&params)
+        )
+    }
+
+    fn capture(&self) -> &[Selector] {
+        &Self::selectors().capture
+    }
+
+    fn parse_cards(&self, document: &Element, page_url: &str) -> Vec<Property> {
+        let mut properties = Self::selectors().read_cards(document, page_url);
+        properties.iter_mut().for_each(Self::category);
+        properties
+    }
+
+    /// Detail pages have no JSON-LD; the title and URL come from OpenGraph
+    fn parse_detail(&self, document: &Element, page_url: &str) -> Option<Property> {
+        let data = StructuredData::extract(document);
+        let mut property = Self::selectors().detail_fields.read(document, page_url)?;
+        if property.title.is_empty() {
+            property.title = data.open_graph("og:title").unwrap_or_default().to_string();
+        }
+        if property.url.is_empty() {
+            property.url = data.open_graph("og:url").unwrap_or(page_url).to_string();
+        }
+        Self::category(&mut property);
+        Some(property)
+    }
+
+    fn next_page(&self, document: &Element, page_url: &str) -> Option<String> {
+        Self::selectors().next_page(document, page_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use avila_parser::parse_html;
+
+    const SEARCH_URL: &str =
+        "https://dubai.dubizzle.com/property-for-sale/residential/?keywords=Arabian+Ranches";
+
+    #[test]
+    fn test_search_url() {
+        let mut criteria = SearchCriteria::new()
+            .with_location("Arabian Ranches".to_string())
+            .with_price_range(1_500_000.0, 3_000_000.0);
+        criteria.property_type = Some(PropertyType::Villa);
+        assert_eq!(
+            DubizzleAdapter.search_url(&criteria, 1),
+            "https://dubai.dubizzle.com/property-for-sale/residential/villahouse/\
+             ?keywords=Arabian+Ranches&price__gte=1500000&price__lte=3000000"
+        );
+        assert!(DubizzleAdapter.handles("dubai.dubizzle.com"));
+    }
+
+    #[test]
+    fn test_search_fixture() {
+        let document = parse_html(include_str!("../fixtures/dubizzle_search.html")).unwrap();
+        let properties = DubizzleAdapter.parse_cards(&document, SEARCH_URL);
+        assert_eq!(properties.len(), 2);
+
+        let first = &properties[0];
+        assert_eq!(first.title, "Type 2E | Single Row | Vacant");
+        assert_eq!(first.price, 2_100_000.0);
+        assert_eq!(first.currency, "AED");
+        assert_eq!(
+            first.location,
+            "Alvorada 2, Alvorada, Arabian Ranches, Dubai"
+        );
+        assert_eq!((first.bedrooms, first.bathrooms), (3, 4));
+        assert_eq!(first.property_type, PropertyType::Villa);
+        assert!(first
+            .url
+            .starts_with("https://dubai.dubizzle.com/property-for-sale/residential/villahouse/"));
+        assert_eq!(properties[1].property_type, PropertyType::Townhouse);
+        assert_eq!(
+            DubizzleAdapter.next_page(&document, SEARCH_URL).as_deref(),
+            Some("https://dubai.dubizzle.com/property-for-sale/residential/?page=2")
+        );
+    }
+
+    #[test]
+    fn test_detail_fixture() {
+        let document = parse_html(include_str!("../fixtures/dubizzle_detail.html")).unwrap();
+        let property = DubizzleAdapter
+            .parse_detail(&document, "https://dubai.dubizzle.com/en/listing/4bf1c9e0")
+            .unwrap();
+        assert_eq!(property.title, "Type 2E | Single Row | Vacant");
+        assert_eq!(property.price, 2_100_000.0);
+        assert_eq!((property.bedrooms, property.bathrooms), (3, 4));
+        assert!((property.area_sqm - 263.93).abs() < 0.01);
+        assert_eq!(property.property_type, PropertyType::Villa);
+        assert!(property.url.ends_with("---4bf1c9e0d2a34b7c/"));
+    }
+}
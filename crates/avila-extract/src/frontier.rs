@@ -0,0 +1,358 @@
+// AvilaExtract Frontier - URLs waiting to be crawled
+// Zero External Dependencies 🦀
+
+use avila_parser::resolve_url;
+use std::collections::{HashSet, VecDeque};
+
+/// Query parameters that only track campaigns and never change the page
+const TRACKING_PARAMS: [&str; 4] = ["gclid", "fbclid", "msclkid", "yclid"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrawlRequest {
+    /// Normalized absolute URL
+    pub url: String,
+    /// Links followed from a seed to get here; seeds are at depth 0
+    pub depth: usize,
+}
+
+/// Breadth-first queue of URLs to visit
+///
+/// URLs are normalized before they are compared, so each page is queued at most
+/// once. Seeds are always accepted; discovered links must be within the depth
+/// and page limits, match an include pattern (when there are any) and match no
+/// exclude pattern. Patterns are matched against the normalized URL, and `*`
+/// stands for any run of characters. With a page parameter, `?page=1` is the
+/// same page as the URL without it.
+#[derive(Debug, Clone)]
+pub struct CrawlFrontier {
+    queue: VecDeque<CrawlRequest>,
+    seen: HashSet<String>,
+    max_depth: usize,
+    max_pages: usize,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    page_param: Option<String>,
+}
+
+impl CrawlFrontier {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            seen: HashSet::new(),
+            max_depth: 3,
+            max_pages: usize::MAX,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            page_param: None,
+        }
+    }
+
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Caps the number of distinct URLs ever queued, seeds included
+    pub fn with_max_pages(mut self, pages: usize) -> Self {
+        self.max_pages = pages;
+        self
+    }
+
+    pub fn with_include(mut self, pattern: &str) -> Self {
+        self.include.push(pattern.to_string());
+        self
+    }
+
+    pub fn with_exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(pattern.to_string());
+        self
+    }
+
+    /// Query parameter numbering a listing's pages; its first page is queued
+    /// without it
+    pub fn with_page_param(mut self, param: &str) -> Self {
+        self.page_param = Some(param.to_string());
+        self
+    }
+
+    /// Queues a starting URL; `false` if it is not http(s) or already known
+    pub fn add_seed(&mut self, url: &str) -> bool {
+        match self.canonical_url(url) {
+            Some(url) => self.enqueue(url, 0),
+            None => false,
+        }
+    }
+
+    /// Queues a link found on a page at `parent`'s depth
+    pub fn add_link(&mut self, parent: &CrawlRequest, url: &str) -> bool {
+        let depth = parent.depth + 1;
+        match self.canonical_url(url) {
+            Some(url) if depth <= self.max_depth && self.allows(&url) => self.enqueue(url, depth),
+            _ => false,
+        }
+    }
+
+    /// Whether a normalized URL passes the include and exclude patterns
+    pub fn allows(&self, url: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| glob_match(p, url)))
+            && !self.exclude.iter().any(|p| glob_match(p, url))
+    }
+
+    pub fn pop(&mut self) -> Option<CrawlRequest> {
+        self.queue.pop_front()
+    }
+
+    /// URLs still waiting
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Distinct URLs ever queued
+    pub fn seen_count(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// `normalize_url`, without the page parameter when it points at page 1
+    fn canonical_url(&self, url: &str) -> Option<String> {
+        let url = normalize_url(url)?;
+        let Some(param) = &self.page_param else {
+            return Some(url);
+        };
+        let Some((base, query)) = url.split_once('?') else {
+            return Some(url);
+        };
+        let first_page = format!("{}=1", param);
+        let params: Vec<&str> = query.split('&').filter(|p| *p != first_page).collect();
+        if params.is_empty() {
+            Some(base.to_string())
+        } else {
+            Some(format!("{}?{}", base, params.join("&")))
+        }
+    }
+
+    fn enqueue(&mut self, url: String, depth: usize) -> bool {
+        if self.seen.len() >= self.max_pages || !self.seen.insert(url.clone()) {
+            return false;
+        }
+        self.queue.push_back(CrawlRequest { url, depth });
+        true
+    }
+}
+
+impl Default for CrawlFrontier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Canonical form of an absolute http(s) URL, for deduplication: scheme and
+/// host lowercased, default port, fragment, tracking parameters (`utm_*`,
+/// `gclid`, ...) and dot segments removed, query parameters sorted by name, and
+/// percent-encoding of unreserved characters undone
+pub fn normalize_url(url: &str) -> Option<String> {
+    // Resolving a URL against itself removes dot segments and lowercases the scheme
+    let url = resolve_url(url, url)?;
+    let url = url.split_once('#').map_or(url.as_str(), |(url, _)| url);
+    let (scheme, rest) = url.split_once("://")?;
+    let default_port = match scheme {
+        "http" => ":80",
+        "https" => ":443",
+        _ => return None,
+    };
+
+    let (authority, rest) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (userinfo, host) = match authority.rsplit_once('@') {
+        Some((userinfo, host)) => (Some(userinfo), host),
+        None => (None, authority),
+    };
+    let host = host.to_ascii_lowercase();
+    let host = host
+        .strip_suffix(default_port)
+        .or_else(|| host.strip_suffix(':'))
+        .unwrap_or(&host);
+    if host.is_empty() {
+        return None;
+    }
+
+    let mut params: Vec<String> = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .filter(|param| {
+            let name = param
+                .split('=')
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            !name.starts_with("utm_") && !TRACKING_PARAMS.contains(&name.as_str())
+        })
+        .map(normalize_percent_encoding)
+        .collect();
+    params.sort_by(|a, b| a.split('=').next().cmp(&b.split('=').next()));
+
+    let mut normalized = format!("{}://", scheme);
+    if let Some(userinfo) = userinfo {
+        normalized.push_str(userinfo);
+        normalized.push('@');
+    }
+    normalized.push_str(host);
+    normalized.push_str(&normalize_percent_encoding(if path.is_empty() {
+        "/"
+    } else {
+        path
+    }));
+    if !params.is_empty() {
+        normalized.push('?');
+        normalized.push_str(&params.join("&"));
+    }
+    Some(normalized)
+}
+
+/// `%7e` becomes `~`, `%2f` becomes `%2F`
+fn normalize_percent_encoding(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|h| h.iter().all(u8::is_ascii_hexdigit));
+        match hex {
+            Some(hex) if bytes[i] == b'%' => {
+                let hex = std::str::from_utf8(hex).unwrap_or_default();
+                let value = u8::from_str_radix(hex, 16).unwrap_or_default();
+                if value.is_ascii_alphanumeric() || matches!(value, b'-' | b'.' | b'_' | b'~') {
+                    out.push(value as char);
+                } else {
+                    out.push('%');
+                    out.push_str(&hex.to_ascii_uppercase());
+                }
+                i += 3;
+            }
+            _ => {
+                let c = text[i..].chars().next().unwrap_or_default();
+                out.push(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    out
+}
+
+/// `*` matches any run of characters, everything else matches itself
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text) = (pattern.as_bytes(), text.as_bytes());
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*`, and where in the text it started matching
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            p += 1;
+            backtrack = Some((p, t));
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&b| b == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        let cases = [
+            (
+                "HTTPS://WWW.Bayut.com:443/for-sale/./property/../apartments/?page=2&utm_source=x#top",
+                "https://www.bayut.com/for-sale/apartments/?page=2",
+            ),
+            ("http://bayut.com", "http://bayut.com/"),
+            ("http://bayut.com:8080/a", "http://bayut.com:8080/a"),
+            (
+                "https://bayut.com/search?sort=price&beds=2&gclid=abc",
+                "https://bayut.com/search?beds=2&sort=price",
+            ),
+            ("https://bayut.com/%7euser/a%2fb", "https://bayut.com/~user/a%2Fb"),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(normalize_url(url).as_deref(), Some(expected), "{}", url);
+        }
+        assert_eq!(normalize_url("mailto:sales@bayut.com"), None);
+        assert_eq!(normalize_url("/relative"), None);
+    }
+
+    #[test]
+    fn test_frontier_dedupes_and_limits_depth() {
+        let mut frontier = CrawlFrontier::new().with_max_depth(1);
+        assert!(frontier.add_seed("https://www.bayut.com/for-sale/"));
+        assert!(!frontier.add_seed("https://www.bayut.com/for-sale/#results"));
+
+        let seed = frontier.pop().unwrap();
+        assert_eq!(seed.depth, 0);
+        assert!(frontier.add_link(&seed, "https://www.bayut.com/for-sale/?page=2"));
+        assert!(!frontier.add_link(
+            &seed,
+            "https://www.bayut.com/for-sale/?page=2&utm_medium=email"
+        ));
+        assert!(!frontier.add_link(&seed, "javascript:void(0)"));
+
+        let page_two = frontier.pop().unwrap();
+        assert_eq!(page_two.depth, 1);
+        assert!(!frontier.add_link(&page_two, "https://www.bayut.com/for-sale/?page=3"));
+        assert!(frontier.is_empty());
+        assert_eq!(frontier.seen_count(), 2);
+    }
+
+    #[test]
+    fn test_frontier_first_page() {
+        let mut frontier = CrawlFrontier::new().with_page_param("page");
+        assert!(frontier.add_seed("https://www.bayut.com/for-sale/?sort=price"));
+        let seed = frontier.pop().unwrap();
+        assert!(!frontier.add_link(&seed, "https://www.bayut.com/for-sale/?page=1&sort=price"));
+        assert!(frontier.add_link(&seed, "https://www.bayut.com/for-sale/?page=1"));
+        assert_eq!(
+            frontier.pop().unwrap().url,
+            "https://www.bayut.com/for-sale/"
+        );
+        assert!(frontier.add_link(&seed, "https://www.bayut.com/for-sale/?page=10"));
+
+        // Without a page parameter every query is its own page
+        let mut frontier = CrawlFrontier::new();
+        frontier.add_seed("https://www.bayut.com/for-sale/");
+        let seed = frontier.pop().unwrap();
+        assert!(frontier.add_link(&seed, "https://www.bayut.com/for-sale/?page=1"));
+    }
+
+    #[test]
+    fn test_frontier_patterns_and_page_limit() {
+        let mut frontier = CrawlFrontier::new()
+            .with_include("https://www.bayut.com/for-sale/*")
+            .with_exclude("*/commercial/*")
+            .with_max_pages(3);
+        frontier.add_seed("https://www.bayut.com/");
+        let seed = frontier.pop().unwrap();
+
+        assert!(!frontier.add_link(&seed, "https://www.bayut.com/to-rent/"));
+        assert!(!frontier.add_link(&seed, "https://www.bayut.com/for-sale/commercial/dubai/"));
+        assert!(!frontier.add_link(
+            &seed,
+            "https://evil.example/?u=https://www.bayut.com/for-sale/"
+        ));
+        assert!(frontier.add_link(&seed, "https://www.bayut.com/for-sale/villas/"));
+        assert!(frontier.add_link(&seed, "https://www.bayut.com/for-sale/apartments/"));
+        assert!(!frontier.add_link(&seed, "https://www.bayut.com/for-sale/townhouses/"));
+        assert_eq!(frontier.len(), 2);
+    }
+}
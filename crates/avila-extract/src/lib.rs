@@ -1,7 +1,23 @@
 // AvilaExtract - Native Web Scraping & Data Extraction
 // Zero External Dependencies 🦀
 
-use std::collections::HashMap;
+mod adapter;
+mod bayut;
+mod dubizzle;
+mod frontier;
+mod propertyfinder;
+
+pub use adapter::{
+    parse_area_sqm, parse_count, parse_price, properties_from_structured_data, property_type_from,
+    SiteAdapter,
+};
+pub use bayut::BayutAdapter;
+pub use dubizzle::DubizzleAdapter;
+pub use frontier::{normalize_url, CrawlFrontier, CrawlRequest};
+pub use propertyfinder::PropertyFinderAdapter;
+
+use adapter::search_document;
+use avila_parser::{Element, PageLinks};
 
 #[derive(Debug, Clone)]
 pub struct Property {
@@ -9,6 +25,8 @@ pub struct Property {
     pub price: f64,
     pub currency: String,
     pub location: String,
+    /// Latitude and longitude, when the page publishes them
+    pub coordinates: Option<(f64, f64)>,
     pub bedrooms: u32,
     pub bathrooms: u32,
     pub area_sqm: f64,
@@ -16,7 +34,7 @@ pub struct Property {
     pub url: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PropertyType {
     Apartment,
     Villa,
@@ -27,6 +45,7 @@ pub enum PropertyType {
 
 pub struct PropertyExtractor {
     sources: Vec<String>,
+    adapters: Vec<Box<dyn SiteAdapter>>,
 }
 
 impl PropertyExtractor {
@@ -37,6 +56,11 @@ impl PropertyExtractor {
                 "propertyfinder.ae".to_string(),
                 "bayut.com".to_string(),
             ],
+            adapters: vec![
+                Box::new(DubizzleAdapter),
+                Box::new(PropertyFinderAdapter),
+                Box::new(BayutAdapter),
+            ],
         }
     }
 
@@ -44,19 +68,177 @@ impl PropertyExtractor {
         self.sources.push(source);
     }
 
-    /// Extract properties from a source
-    /// TODO: Implement actual HTTP request and HTML parsing
+    /// Registers an adapter; it takes precedence over the built-in ones for its domain
+    pub fn add_adapter(&mut self, adapter: Box<dyn SiteAdapter>) {
+        if !self.sources.iter().any(|s| s == adapter.domain()) {
+            self.sources.push(adapter.domain().to_string());
+        }
+        self.adapters.insert(0, adapter);
+    }
+
+    /// Adapter for a URL or a domain such as "bayut.com"
+    pub fn adapter_for(&self, url: &str) -> Option<&dyn SiteAdapter> {
+        self.adapters
+            .iter()
+            .find(|adapter| adapter.handles(url))
+            .map(|adapter| adapter.as_ref())
+    }
+
+    /// Extract properties from a source: its first search-results page over
+    /// HTTP for a domain, or the page itself for a URL
     pub fn extract_properties(&self, source: &str) -> Vec<Property> {
-        // Placeholder - will implement actual scraping
         println!("📊 Extracting properties from: {}", source);
-        vec![]
+        let Some(adapter) = self.adapter_for(source) else {
+            println!("⚠️ No adapter for: {}", source);
+            return vec![];
+        };
+        let url = if source.contains("://") {
+            source.to_string()
+        } else {
+            adapter.search_url(&SearchCriteria::new(), 1)
+        };
+        match http_fetch(&url) {
+            Ok(html) => match search_document(adapter, &html) {
+                Ok(document) => adapter.parse_cards(&document, &url),
+                Err(err) => {
+                    println!("⚠️ Failed to parse {}: {:?}", url, err);
+                    vec![]
+                }
+            },
+            Err(err) => {
+                println!("⚠️ Failed to fetch {}: {}", url, err);
+                vec![]
+            }
+        }
+    }
+
+    /// First search-results page of a source for an area, e.g. "Dubai Marina"
+    pub fn area_search_url(&self, source: &str, area: &str) -> Option<String> {
+        let criteria = SearchCriteria::new().with_location(area.to_string());
+        Some(self.adapter_for(source)?.search_url(&criteria, 1))
+    }
+
+    /// Fetches up to `max_pages` search-results pages for an area over HTTP
+    pub fn search_area(&self, source: &str, area: &str, max_pages: usize) -> Vec<SearchPage> {
+        let Some(url) = self.area_search_url(source, area) else {
+            println!("⚠️ Unknown source: {}", source);
+            return vec![];
+        };
+        self.walk_search_pages(&url, max_pages, http_fetch)
+    }
+
+    /// Follows the pagination of a search-results listing from `start_url`,
+    /// visiting each page once. Pages that fail to load are skipped, and links
+    /// to other sites than `start_url`'s are not followed.
+    pub fn walk_search_pages<F>(
+        &self,
+        start_url: &str,
+        max_pages: usize,
+        fetch: F,
+    ) -> Vec<SearchPage>
+    where
+        F: FnMut(&str) -> Result<String, String>,
+    {
+        self.crawl_search_pages(start_url, max_pages, fetch, None)
     }
 
-    /// Search properties by criteria
+    /// `walk_search_pages`, reading each page through the adapter when there
+    /// is one: only its `capture` subtrees are built, and its `next_page` is
+    /// followed alongside the generic pagination links
+    fn crawl_search_pages<F>(
+        &self,
+        start_url: &str,
+        max_pages: usize,
+        mut fetch: F,
+        adapter: Option<&dyn SiteAdapter>,
+    ) -> Vec<SearchPage>
+    where
+        F: FnMut(&str) -> Result<String, String>,
+    {
+        let mut frontier = CrawlFrontier::new()
+            .with_max_depth(max_pages)
+            .with_max_pages(max_pages)
+            .with_page_param("page");
+        // A page can point its "next" link anywhere; stay on the start URL's origin
+        if let Some(start) = normalize_url(start_url) {
+            let scheme_end = start.find("://").map_or(0, |i| i + 3);
+            let origin_end = start[scheme_end..]
+                .find('/')
+                .map_or(start.len(), |i| scheme_end + i);
+            frontier = frontier.with_include(&format!("{}/*", &start[..origin_end]));
+        }
+        frontier.add_seed(start_url);
+
+        let mut pages = Vec::new();
+        while let Some(request) = frontier.pop() {
+            let html = match fetch(&request.url) {
+                Ok(html) => html,
+                Err(err) => {
+                    println!("⚠️ Failed to fetch {}: {}", request.url, err);
+                    continue;
+                }
+            };
+            let parsed = match adapter {
+                Some(adapter) => search_document(adapter, &html),
+                None => avila_parser::parse_html(&html),
+            };
+            let document = match parsed {
+                Ok(document) => document,
+                Err(err) => {
+                    println!("⚠️ Failed to parse {}: {:?}", request.url, err);
+                    continue;
+                }
+            };
+            let links = PageLinks::extract(&document, &request.url);
+            let pagination = &links.pagination;
+            let next = adapter.and_then(|adapter| adapter.next_page(&document, &request.url));
+            for url in next.iter().chain(&pagination.next).chain(&pagination.pages) {
+                frontier.add_link(&request, url);
+            }
+            println!("📄 Page {}: {}", pages.len() + 1, request.url);
+            pages.push(SearchPage {
+                url: request.url,
+                document,
+                links,
+            });
+        }
+        pages
+    }
+
+    /// Search properties by criteria on every source, one results page each
     pub fn search(&self, criteria: SearchCriteria) -> Vec<Property> {
         println!("🔍 Searching properties with criteria: {:?}", criteria);
-        // TODO: Implement actual search logic
-        vec![]
+        self.search_with(&criteria, 1, http_fetch)
+    }
+
+    /// Searches every source that has an adapter, following each one's
+    /// pagination for up to `max_pages` pages. Listings the portal returns
+    /// outside the criteria are filtered out.
+    pub fn search_with<F>(
+        &self,
+        criteria: &SearchCriteria,
+        max_pages: usize,
+        mut fetch: F,
+    ) -> Vec<Property>
+    where
+        F: FnMut(&str) -> Result<String, String>,
+    {
+        let mut properties = Vec::new();
+        for source in &self.sources {
+            let Some(adapter) = self.adapter_for(source) else {
+                continue;
+            };
+            let start_url = adapter.search_url(criteria, 1);
+            for page in self.crawl_search_pages(&start_url, max_pages, &mut fetch, Some(adapter)) {
+                properties.extend(
+                    adapter
+                        .parse_cards(&page.document, &page.url)
+                        .into_iter()
+                        .filter(|property| criteria.matches(property)),
+                );
+            }
+        }
+        properties
     }
 }
 
@@ -66,6 +248,24 @@ impl Default for PropertyExtractor {
     }
 }
 
+/// One fetched page of search results
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    pub url: String,
+    pub document: Element,
+    pub links: PageLinks,
+}
+
+fn http_fetch(url: &str) -> Result<String, String> {
+    let response = avila_http::HttpClient::new()
+        .get(url)
+        .map_err(|e| e.to_string())?;
+    if !response.is_success() {
+        return Err(format!("HTTP {}", response.status_code));
+    }
+    Ok(response.body)
+}
+
 #[derive(Debug, Clone)]
 pub struct SearchCriteria {
     pub min_price: Option<f64>,
@@ -101,6 +301,23 @@ impl SearchCriteria {
         self.location = Some(location);
         self
     }
+
+    /// Whether a listing satisfies every criterion that is set; the location
+    /// matches case-insensitively anywhere in the listing's location or title
+    pub fn matches(&self, property: &Property) -> bool {
+        let location = self.location.as_ref().map(|l| l.to_lowercase());
+        self.min_price.is_none_or(|min| property.price >= min)
+            && self.max_price.is_none_or(|max| property.price <= max)
+            && self.bedrooms.is_none_or(|b| property.bedrooms == b)
+            && self
+                .property_type
+                .as_ref()
+                .is_none_or(|t| property.property_type == *t)
+            && location.is_none_or(|l| {
+                property.location.to_lowercase().contains(&l)
+                    || property.title.to_lowercase().contains(&l)
+            })
+    }
 }
 
 impl Default for SearchCriteria {
@@ -129,4 +346,112 @@ mod tests {
         assert_eq!(criteria.min_price, Some(500000.0));
         assert_eq!(criteria.bedrooms, Some(3));
     }
+
+    #[test]
+    fn test_search_with_adapters() {
+        let extractor = PropertyExtractor::new();
+        assert_eq!(
+            extractor
+                .adapter_for("https://dubai.dubizzle.com/property-for-sale/")
+                .map(|adapter| adapter.domain()),
+            Some("dubizzle.com")
+        );
+        assert!(extractor.adapter_for("example.com").is_none());
+
+        let criteria = SearchCriteria::new()
+            .with_price_range(500_000.0, 2_000_000.0)
+            .with_location("Dubai Marina".to_string());
+        let mut fetched = Vec::new();
+        let properties = extractor.search_with(&criteria, 5, |url| {
+            fetched.push(url.to_string());
+            if url.starts_with("https://www.bayut.com/for-sale/property/") {
+                Ok(include_str!("../fixtures/bayut_search.html").to_string())
+            } else {
+                Err("offline".to_string())
+            }
+        });
+
+        // The fixture's `rel="next"` and numbered pagination links are all followed
+        assert_eq!(fetched.len(), 6);
+        assert_eq!(
+            fetched[2..],
+            [
+                "https://www.bayut.com/for-sale/property/dubai/dubai-marina/?price_max=2000000&price_min=500000",
+                "https://www.bayut.com/for-sale/apartments/dubai/dubai-marina/?page=2",
+                "https://www.bayut.com/for-sale/apartments/dubai/dubai-marina/",
+                "https://www.bayut.com/for-sale/apartments/dubai/dubai-marina/?page=3",
+            ]
+        );
+        // The 12.5M penthouse is outside the price range
+        assert_eq!(properties.len(), 2);
+        assert!(properties.iter().all(|p| criteria.matches(p)));
+    }
+
+    #[test]
+    fn test_walk_search_pages() {
+        let extractor = PropertyExtractor::new();
+        let start = extractor
+            .area_search_url("bayut.com", "Dubai Marina")
+            .unwrap();
+        assert_eq!(
+            start,
+            "https://www.bayut.com/for-sale/property/dubai/dubai-marina/"
+        );
+
+        let page = |n: usize| {
+            format!(
+                r#"<div class="results"><article>Listing {n}</article></div>
+                <nav aria-label="Pagination"><a href="?page=1">1</a><a href="?page=2">2</a>
+                <a href="?page=3">3</a><a href="?page={next}" rel="next">Next</a></nav>"#,
+                n = n,
+                next = n + 1
+            )
+        };
+        let mut fetched = Vec::new();
+        let pages = extractor.walk_search_pages(&start, 10, |url| {
+            fetched.push(url.to_string());
+            match url.rsplit_once("?page=") {
+                Some((_, "3")) => Err("timeout".to_string()),
+                Some((_, n)) => Ok(page(n.parse().unwrap())),
+                None => Ok(page(1)),
+            }
+        });
+
+        assert_eq!(
+            fetched,
+            vec![
+                "https://www.bayut.com/for-sale/property/dubai/dubai-marina/",
+                "https://www.bayut.com/for-sale/property/dubai/dubai-marina/?page=2",
+                "https://www.bayut.com/for-sale/property/dubai/dubai-marina/?page=3",
+            ]
+        );
+        assert_eq!(pages.len(), 2);
+        assert_eq!(
+            pages[1].document.find_by_tag("article")[0].text_content(),
+            "Listing 2"
+        );
+    }
+
+    #[test]
+    fn test_walk_search_pages_stays_on_site() {
+        let extractor = PropertyExtractor::new();
+        let start = "https://www.bayut.com/for-sale/property/dubai/";
+        let html = r#"<nav aria-label="Pagination">
+            <a href="https://evil.example/for-sale/?page=2" rel="next">Next</a>
+            <a href="http://www.bayut.com/for-sale/property/dubai/?page=3">3</a>
+            <a href="https://www.bayut.com.evil.example/?page=4">4</a>
+            <a href="/for-sale/property/dubai/?page=5">5</a></nav>"#;
+        let mut fetched = Vec::new();
+        extractor.walk_search_pages(start, 10, |url| {
+            fetched.push(url.to_string());
+            Ok(html.to_string())
+        });
+        assert_eq!(
+            fetched,
+            vec![
+                "https://www.bayut.com/for-sale/property/dubai/",
+                "https://www.bayut.com/for-sale/property/dubai/?page=5",
+            ]
+        );
+    }
 }
@@ -0,0 +1,191 @@
+// AvilaExtract PropertyFinder - propertyfinder.ae adapter
+// Zero External Dependencies 🦀
+
+use crate::adapter::{
+    properties_from_structured_data, query_string, FieldSelectors, PageSelectors, SiteAdapter,
+    PAGINATION_CAPTURE,
+};
+use crate::{Property, PropertyType, SearchCriteria};
+use avila_parser::{Element, Selector, StructuredData};
+use std::sync::OnceLock;
+
+const CARD: &str = "article[data-testid=property-card]";
+
+/// Subtrees read from search pages: cards and pagination
+const CAPTURE: [&str; 2] = [CARD, PAGINATION_CAPTURE];
+
+const CARD_FIELDS: FieldSelectors = FieldSelectors {
+    link: "a[data-testid=property-card-link]",
+    title: "[data-testid=property-card-title]",
+    price: "[data-testid=property-card-price]",
+    currency: "",
+    location: "[data-testid=property-card-location]",
+    bedrooms: "[data-testid=property-card-spec-bedroom]",
+    bathrooms: "[data-testid=property-card-spec-bathroom]",
+    area: "[data-testid=property-card-spec-area]",
+    property_type: "[data-testid=property-card-type]",
+};
+
+const DETAIL_FIELDS: FieldSelectors = FieldSelectors {
+    link: "link[rel=canonical]",
+    title: "[data-testid=property-title], h1",
+    price: "[data-testid=property-price-value]",
+    currency: "[data-testid=property-price-currency]",
+    location: "[data-testid=property-location]",
+    bedrooms: "[data-testid=property-bedrooms]",
+    bathrooms: "[data-testid=property-bathrooms]",
+    area: "[data-testid=property-size]",
+    property_type: "[data-testid=property-type]",
+};
+
+/// Markup is tagged with `data-testid` attributes, which outlive the
+/// generated class names
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PropertyFinderAdapter;
+
+impl PropertyFinderAdapter {
+    const BASE_URL: &'static str = "https://www.propertyfinder.ae";
+
+    fn selectors() -> &'static PageSelectors {
+        static SELECTORS: OnceLock<PageSelectors> = OnceLock::new();
+        SELECTORS.get_or_init(|| {
+            PageSelectors::compile(
+                CARD,
+                &CARD_FIELDS,
+                &DETAIL_FIELDS,
+                &CAPTURE,
+                "a[data-testid=pagination-page-next-link]",
+            )
+        })
+    }
+}
+
+impl SiteAdapter for PropertyFinderAdapter {
+    fn domain(&self) -> &str {
+        "propertyfinder.ae"
+    }
+
+    /// `c=1` is "buy"; `t` takes the portal's numeric property type ids
+    fn search_url(&self, criteria: &SearchCriteria, page: usize) -> String {
+        let mut params = vec![("c", "1".to_string())];
+        if let Some(kind) = &criteria.property_type {
+            let id = match kind {
+                PropertyType::Apartment => 1,
+                PropertyType::Villa => 35,
+                PropertyType::Townhouse => 22,
+                PropertyType::Penthouse => 20,
+                PropertyType::Office => 4,
+            };
+            params.push(("t", id.to_string()));
+        }
+        if let Some(bedrooms) = criteria.bedrooms {
+            params.push(("bdr", bedrooms.to_string()));
+        }
+        if let Some(min) = criteria.min_price {
+            params.push(("pf", format!("{:.0}", min)));
+        }
+        if let Some(max) = criteria.max_price {
+            params.push(("pt", format!("{:.0}", max)));
+        }
+        if let Some(location) = &criteria.location {
+            params.push(("q", location.clone()));
+        }
+        if page > 1 {
+            params.push(("page", page.to_string()));
+        }
+        format!("{}/en/search{}", Self::BASE_URL, query_string(&params))
+    }
+
+    fn capture(&self) -> &[Selector] {
+        &Self::selectors().capture
+    }
+
+    fn parse_cards(&self, document: &Element, page_url: &str) -> Vec<Property> {
+        Self::selectors().read_cards(document, page_url)
+    }
+
+    fn parse_detail(&self, document: &Element, page_url: &str) -> Option<Property> {
+        let data = StructuredData::extract(document);
+        let mut property = match properties_from_structured_data(&data, page_url)
+            .into_iter()
+            .next()
+        {
+            Some(property) => property,
+            None => Self::selectors().detail_fields.read(document, page_url)?,
+        };
+        if property.url.is_empty() {
+            property.url = data.open_graph("og:url").unwrap_or(page_url).to_string();
+        }
+        Some(property)
+    }
+
+    /// The next-page arrow is an icon with no text or `rel`
+    fn next_page(&self, document: &Element, page_url: &str) -> Option<String> {
+        Self::selectors().next_page(document, page_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use avila_parser::parse_html;
+
+    const SEARCH_URL: &str = "https://www.propertyfinder.ae/en/search?c=1&q=downtown-dubai";
+
+    #[test]
+    fn test_search_url() {
+        let mut criteria = SearchCriteria::new()
+            .with_location("Downtown Dubai".to_string())
+            .with_bedrooms(1);
+        criteria.property_type = Some(PropertyType::Villa);
+        assert_eq!(
+            PropertyFinderAdapter.search_url(&criteria, 2),
+            "https://www.propertyfinder.ae/en/search?c=1&t=35&bdr=1&q=Downtown+Dubai&page=2"
+        );
+    }
+
+    #[test]
+    fn test_search_fixture() {
+        let document = parse_html(include_str!("../fixtures/propertyfinder_search.html")).unwrap();
+        let properties = PropertyFinderAdapter.parse_cards(&document, SEARCH_URL);
+        assert_eq!(properties.len(), 2);
+
+        let first = &properties[0];
+        assert_eq!(first.title, "Brand New | Burj Khalifa View | Handover Soon");
+        assert_eq!(first.price, 1_250_000.0);
+        assert_eq!(first.currency, "AED");
+        assert_eq!((first.bedrooms, first.bathrooms), (1, 2));
+        assert_eq!(first.area_sqm, 75.0);
+        assert_eq!(
+            first.url,
+            "https://www.propertyfinder.ae/en/plp/buy/\
+             apartment-for-sale-dubai-downtown-dubai-burj-royale-11223344.html"
+        );
+        assert_eq!(properties[1].property_type, PropertyType::Townhouse);
+        assert_eq!(
+            PropertyFinderAdapter
+                .next_page(&document, SEARCH_URL)
+                .as_deref(),
+            Some("https://www.propertyfinder.ae/en/search?c=1&q=downtown-dubai&page=2")
+        );
+    }
+
+    #[test]
+    fn test_detail_fixture() {
+        let document = parse_html(include_str!("../fixtures/propertyfinder_detail.html")).unwrap();
+        let property = PropertyFinderAdapter
+            .parse_detail(
+                &document,
+                "https://www.propertyfinder.ae/en/plp/buy/11223344",
+            )
+            .unwrap();
+        assert_eq!(
+            property.title,
+            "Brand New | Burj Khalifa View | Handover Soon"
+        );
+        assert_eq!(property.price, 1_250_000.0);
+        assert_eq!(property.location, "Burj Royale, Downtown Dubai, Dubai");
+        assert_eq!((property.bedrooms, property.bathrooms), (1, 2));
+        assert!(property.url.ends_with("burj-royale-11223344.html"));
+    }
+}
@@ -0,0 +1,643 @@
+// AvilaJson Derive - #[derive(ToJson, FromJson)]
+// Zero External Dependencies 🦀
+//
+// Supports structs with named fields and enums with unit variants (as strings).
+//
+// Container attributes:
+//   #[json(rename_all = "snake_case")]   lowercase, UPPERCASE, snake_case,
+//                                        SCREAMING_SNAKE_CASE, kebab-case,
+//                                        camelCase, PascalCase
+// Field / variant attributes:
+//   #[json(rename = "name")]             key or string used in JSON
+//   #[json(alias = "other")]             extra name accepted when reading
+// Field attributes:
+//   #[json(default)]                     missing field -> Default::default()
+//   #[json(skip)]                        never written, Default::default() when read
+//   #[json(flatten)]                     the field's own keys are written into and
+//                                        read from this object
+//   #[json(with = "module")]             module::to_json(&T) -> JsonValue and
+//                                        module::from_json(&JsonValue) -> Result<T, FromJsonError>;
+//                                        a null result is not written, and a missing
+//                                        field is read as null
+//
+// `Option<T>` fields may be missing when reading and are left out when `None`.
+// An attribute written where it does not apply, or `flatten` together with any
+// other field attribute, is a compile error.
+//
+// The generated code refers to `::avila_json`, so the deriving crate must depend on avila-json.
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+/// ```
+/// use avila_json::ToJson;
+///
+/// #[derive(ToJson)]
+/// #[json(rename_all = "kebab-case")]
+/// enum Status {
+///     OffPlan,
+/// }
+///
+/// assert_eq!(Status::OffPlan.to_json().to_string(), r#""off-plan""#);
+/// ```
+///
+/// Rejected inputs are reported as compile errors:
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// #[json(rename_all = "Title Case")] // unknown rename_all style
+/// enum Status {
+///     OffPlan,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// struct Unit {
+///     #[json(renamed = "price")] // unknown attribute
+///     price: u64,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// struct Unit {
+///     #[json(rename = price)] // `rename` expects a string
+///     price: u64,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// struct Unit {
+///     #[json(rename_all = "camelCase")] // `rename_all` only applies to the type
+///     price_per_sqm: u64,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// #[json(default)] // `default` only applies to fields
+/// struct Unit {
+///     price: u64,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// enum Status {
+///     #[json(skip)] // `skip` only applies to fields
+///     OffPlan,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// struct Listing {
+///     #[json(flatten, rename = "location")] // a flattened field has no key
+///     location: Location,
+/// }
+/// # #[derive(avila_json::ToJson)]
+/// # struct Location { community: String }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// struct Wrapper<T> { // generic types are not supported
+///     value: T,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// struct Price(u64); // must have named fields
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::ToJson)]
+/// enum Status {
+///     Sold(u64), // must be a unit variant
+/// }
+/// ```
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    expand(input, generate_to_json)
+}
+
+/// ```
+/// use avila_json::{parse, FromJson};
+///
+/// #[derive(Debug, PartialEq, FromJson)]
+/// struct Unit {
+///     #[json(alias = "cost")]
+///     price: u64,
+/// }
+///
+/// let unit = Unit::from_json(&parse(r#"{"cost": 5}"#).unwrap()).unwrap();
+/// assert_eq!(unit, Unit { price: 5 });
+/// ```
+///
+/// The same inputs as [`derive_to_json`] are rejected:
+///
+/// ```compile_fail
+/// #[derive(avila_json::FromJson)]
+/// struct Unit {
+///     #[json(alias)] // `alias` expects a string
+///     price: u64,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::FromJson)]
+/// enum Status {
+///     Sold { price: u64 }, // must be a unit variant
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(avila_json::FromJson)]
+/// enum Status {
+///     #[json(default)] // `default` only applies to fields
+///     OffPlan,
+/// }
+/// ```
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    expand(input, generate_from_json)
+}
+
+fn expand(input: TokenStream, generate: fn(&Item) -> Result<String, String>) -> TokenStream {
+    let code = parse_item(input).and_then(|item| generate(&item));
+    match code {
+        Ok(code) => code.parse().expect("avila-json-derive generated invalid code"),
+        Err(message) => format!("compile_error!({:?});", message).parse().unwrap(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+struct Item {
+    name: String,
+    attrs: Attrs,
+    body: Body,
+}
+
+enum Body {
+    Struct(Vec<Field>),
+    Enum(Vec<Variant>),
+}
+
+struct Field {
+    ident: String,
+    ty: String,
+    attrs: Attrs,
+}
+
+struct Variant {
+    ident: String,
+    attrs: Attrs,
+}
+
+#[derive(Default)]
+struct Attrs {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    aliases: Vec<String>,
+    default: bool,
+    skip: bool,
+    flatten: bool,
+    with: Option<String>,
+}
+
+impl Attrs {
+    /// Names of the attributes that were given
+    fn given(&self) -> Vec<&'static str> {
+        let mut given = Vec::new();
+        if self.rename.is_some() {
+            given.push("rename");
+        }
+        if self.rename_all.is_some() {
+            given.push("rename_all");
+        }
+        if !self.aliases.is_empty() {
+            given.push("alias");
+        }
+        if self.default {
+            given.push("default");
+        }
+        if self.skip {
+            given.push("skip");
+        }
+        if self.flatten {
+            given.push("flatten");
+        }
+        if self.with.is_some() {
+            given.push("with");
+        }
+        given
+    }
+
+    /// Rejects attributes that mean nothing on `place`
+    fn check(&self, allowed: &[&str], place: &str) -> Result<(), String> {
+        match self.given().into_iter().find(|name| !allowed.contains(name)) {
+            Some(name) => Err(format!("avila-json-derive: `{}` cannot be used on {}", name, place)),
+            None => Ok(()),
+        }
+    }
+}
+
+const CONTAINER_ATTRS: [&str; 1] = ["rename_all"];
+const FIELD_ATTRS: [&str; 6] = ["rename", "alias", "default", "skip", "flatten", "with"];
+const VARIANT_ATTRS: [&str; 2] = ["rename", "alias"];
+
+fn is_punct(token: Option<&TokenTree>, ch: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(p)) if p.as_char() == ch)
+}
+
+fn is_ident(token: Option<&TokenTree>, name: &str) -> bool {
+    matches!(token, Some(TokenTree::Ident(i)) if i.to_string() == name)
+}
+
+fn take_ident(tokens: &[TokenTree], i: &mut usize, what: &str) -> Result<String, String> {
+    match tokens.get(*i) {
+        Some(TokenTree::Ident(ident)) => {
+            *i += 1;
+            Ok(ident.to_string())
+        }
+        _ => Err(format!("avila-json-derive: expected {}", what)),
+    }
+}
+
+/// Consume `#[...]` attributes, collecting the `json(...)` ones
+fn take_attrs(tokens: &[TokenTree], i: &mut usize) -> Result<Attrs, String> {
+    let mut attrs = Attrs::default();
+    while is_punct(tokens.get(*i), '#') {
+        if let Some(TokenTree::Group(group)) = tokens.get(*i + 1) {
+            parse_attr(group.stream(), &mut attrs)?;
+        }
+        *i += 2;
+    }
+    Ok(attrs)
+}
+
+fn parse_attr(stream: TokenStream, attrs: &mut Attrs) -> Result<(), String> {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    if !is_ident(tokens.first(), "json") {
+        return Ok(());
+    }
+    let args = match tokens.get(1) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => group.stream(),
+        _ => return Err("avila-json-derive: expected #[json(...)]".to_string()),
+    };
+
+    let args: Vec<TokenTree> = args.into_iter().collect();
+    let mut i = 0;
+    while i < args.len() {
+        let key = take_ident(&args, &mut i, "attribute name")?;
+        let value = if is_punct(args.get(i), '=') {
+            i += 1;
+            match args.get(i) {
+                Some(TokenTree::Literal(lit)) => {
+                    i += 1;
+                    Some(unquote(&lit.to_string())?)
+                }
+                _ => return Err(format!("avila-json-derive: `{}` expects a string", key)),
+            }
+        } else {
+            None
+        };
+
+        match (key.as_str(), value) {
+            ("rename", Some(v)) => attrs.rename = Some(v),
+            ("rename_all", Some(v)) => attrs.rename_all = Some(v),
+            ("alias", Some(v)) => attrs.aliases.push(v),
+            ("with", Some(v)) => attrs.with = Some(v),
+            ("default", None) => attrs.default = true,
+            ("skip", None) => attrs.skip = true,
+            ("flatten", None) => attrs.flatten = true,
+            (other, _) => return Err(format!("avila-json-derive: unknown or malformed attribute `{}`", other)),
+        }
+
+        if is_punct(args.get(i), ',') {
+            i += 1;
+        }
+    }
+    Ok(())
+}
+
+fn unquote(literal: &str) -> Result<String, String> {
+    literal
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .map(|s| s.replace("\\\"", "\"").replace("\\\\", "\\"))
+        .ok_or_else(|| format!("avila-json-derive: expected a string literal, found {}", literal))
+}
+
+/// Skip `pub`, `pub(crate)`, `pub(in path)`
+fn skip_visibility(tokens: &[TokenTree], i: &mut usize) {
+    if is_ident(tokens.get(*i), "pub") {
+        *i += 1;
+        if matches!(tokens.get(*i), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis) {
+            *i += 1;
+        }
+    }
+}
+
+fn parse_item(input: TokenStream) -> Result<Item, String> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut i = 0;
+    let attrs = take_attrs(&tokens, &mut i)?;
+    skip_visibility(&tokens, &mut i);
+
+    let kind = take_ident(&tokens, &mut i, "`struct` or `enum`")?;
+    let name = take_ident(&tokens, &mut i, "type name")?;
+    attrs.check(&CONTAINER_ATTRS, &format!("`{}` itself", name))?;
+    if is_punct(tokens.get(i), '<') {
+        return Err(format!("avila-json-derive: generic type `{}` is not supported", name));
+    }
+
+    let body = match (kind.as_str(), tokens.get(i)) {
+        ("struct", Some(TokenTree::Group(g))) if g.delimiter() == Delimiter::Brace => Body::Struct(parse_fields(g.stream())?),
+        ("enum", Some(TokenTree::Group(g))) if g.delimiter() == Delimiter::Brace => Body::Enum(parse_variants(g.stream())?),
+        ("struct", _) => return Err(format!("avila-json-derive: `{}` must have named fields", name)),
+        _ => return Err(format!("avila-json-derive: `{}` is not a struct or enum", name)),
+    };
+
+    Ok(Item { name, attrs, body })
+}
+
+fn parse_fields(stream: TokenStream) -> Result<Vec<Field>, String> {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    let mut fields = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let attrs = take_attrs(&tokens, &mut i)?;
+        skip_visibility(&tokens, &mut i);
+        let ident = take_ident(&tokens, &mut i, "field name")?;
+        let place = format!("field `{}`", ident);
+        attrs.check(&FIELD_ATTRS, &place)?;
+        if attrs.flatten {
+            // The field's keys are merged in, so it has no name of its own
+            attrs.check(&["flatten"], &format!("flattened {}", place))?;
+        }
+        if !is_punct(tokens.get(i), ':') {
+            return Err(format!("avila-json-derive: expected `:` after field `{}`", ident));
+        }
+        i += 1;
+
+        // The type runs to the next comma outside angle brackets
+        let start = i;
+        let mut depth = 0i32;
+        let mut prev = ' ';
+        while let Some(token) = tokens.get(i) {
+            if let TokenTree::Punct(p) = token {
+                match p.as_char() {
+                    ',' if depth == 0 => break,
+                    '<' => depth += 1,
+                    '>' if prev != '-' => depth -= 1,
+                    _ => {}
+                }
+                prev = p.as_char();
+            } else {
+                prev = ' ';
+            }
+            i += 1;
+        }
+        let ty: TokenStream = tokens[start..i].iter().cloned().collect();
+        i += 1; // comma
+
+        fields.push(Field {
+            ident,
+            ty: ty.to_string(),
+            attrs,
+        });
+    }
+    Ok(fields)
+}
+
+fn parse_variants(stream: TokenStream) -> Result<Vec<Variant>, String> {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    let mut variants = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let attrs = take_attrs(&tokens, &mut i)?;
+        let ident = take_ident(&tokens, &mut i, "variant name")?;
+        attrs.check(&VARIANT_ATTRS, &format!("variant `{}`", ident))?;
+        if matches!(tokens.get(i), Some(TokenTree::Group(_))) {
+            return Err(format!("avila-json-derive: variant `{}` must be a unit variant", ident));
+        }
+        // Skip an explicit discriminant
+        while i < tokens.len() && !is_punct(tokens.get(i), ',') {
+            i += 1;
+        }
+        i += 1;
+        variants.push(Variant { ident, attrs });
+    }
+    Ok(variants)
+}
+
+// ---------------------------------------------------------------------------
+// Naming
+// ---------------------------------------------------------------------------
+
+fn words(ident: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in ident.chars() {
+        if c == '_' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let lower = word.to_lowercase();
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn apply_rename_all(ident: &str, style: &str) -> Result<String, String> {
+    let words = words(ident);
+    let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    Ok(match style {
+        "lowercase" => ident.to_lowercase(),
+        "UPPERCASE" => ident.to_uppercase(),
+        "snake_case" => lower.join("_"),
+        "SCREAMING_SNAKE_CASE" => lower.join("_").to_uppercase(),
+        "kebab-case" => lower.join("-"),
+        "camelCase" => {
+            let mut out = lower.first().cloned().unwrap_or_default();
+            out.extend(words.iter().skip(1).map(|w| capitalize(w)));
+            out
+        }
+        "PascalCase" => words.iter().map(|w| capitalize(w)).collect(),
+        other => return Err(format!("avila-json-derive: unknown rename_all style `{}`", other)),
+    })
+}
+
+/// JSON name of a field or variant
+fn json_name(ident: &str, attrs: &Attrs, container: &Attrs) -> Result<String, String> {
+    let ident = ident.trim_start_matches("r#");
+    match (&attrs.rename, &container.rename_all) {
+        (Some(name), _) => Ok(name.clone()),
+        (None, Some(style)) => apply_rename_all(ident, style),
+        (None, None) => Ok(ident.to_string()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Code generation
+// ---------------------------------------------------------------------------
+
+fn generate_to_json(item: &Item) -> Result<String, String> {
+    let body = match &item.body {
+        Body::Struct(fields) => {
+            let mut inserts = String::new();
+            for field in fields.iter().filter(|f| !f.attrs.skip) {
+                if field.attrs.flatten {
+                    inserts += &format!(
+                        "if let ::avila_json::JsonValue::Object(inner) = ::avila_json::ToJson::to_json(&self.{}) {{ for (k, v) in inner {{ object.insert(k, v); }} }}\n",
+                        field.ident
+                    );
+                    continue;
+                }
+                let key = json_name(&field.ident, &field.attrs, &item.attrs)?;
+                inserts += &match &field.attrs.with {
+                    Some(with) => format!(
+                        "{{ let value = {with}::to_json(&self.{id}); if !value.is_null() {{ object.insert({key:?}, value); }} }}\n",
+                        with = with,
+                        id = field.ident,
+                        key = key
+                    ),
+                    None => format!(
+                        "if !::avila_json::ToJson::skip_serializing(&self.{id}) {{ object.insert({key:?}, ::avila_json::ToJson::to_json(&self.{id})); }}\n",
+                        id = field.ident,
+                        key = key
+                    ),
+                };
+            }
+            format!(
+                "let mut object = ::avila_json::JsonObject::new();\n{}::avila_json::JsonValue::Object(object)",
+                inserts
+            )
+        }
+        Body::Enum(variants) => {
+            let mut arms = String::new();
+            for variant in variants {
+                let name = json_name(&variant.ident, &variant.attrs, &item.attrs)?;
+                arms += &format!(
+                    "{}::{} => ::avila_json::JsonValue::String(::std::string::String::from({:?})),\n",
+                    item.name, variant.ident, name
+                );
+            }
+            format!("match *self {{\n{}}}", arms)
+        }
+    };
+
+    Ok(format!(
+        "impl ::avila_json::ToJson for {} {{\n    fn to_json(&self) -> ::avila_json::JsonValue {{\n{}\n    }}\n}}",
+        item.name, body
+    ))
+}
+
+fn generate_from_json(item: &Item) -> Result<String, String> {
+    let body = match &item.body {
+        Body::Struct(fields) => {
+            let mut inits = String::new();
+            for field in fields {
+                if field.attrs.skip {
+                    inits += &format!("{}: ::core::default::Default::default(),\n", field.ident);
+                    continue;
+                }
+                if field.attrs.flatten {
+                    inits += &format!("{}: <{} as ::avila_json::FromJson>::from_json(value)?,\n", field.ident, field.ty);
+                    continue;
+                }
+
+                let key = json_name(&field.ident, &field.attrs, &item.attrs)?;
+                let mut names = vec![format!("{:?}", key)];
+                names.extend(field.attrs.aliases.iter().map(|a| format!("{:?}", a)));
+
+                let (present, missing) = match &field.attrs.with {
+                    Some(with) => (
+                        format!("{}::from_json(v).map_err(|e| e.in_field(k))?", with),
+                        format!(
+                            "{}::from_json(&::avila_json::JsonValue::Null).map_err(|_| ::avila_json::FromJsonError::missing_field({:?}))?",
+                            with, key
+                        ),
+                    ),
+                    None => (
+                        format!("<{} as ::avila_json::FromJson>::from_json(v).map_err(|e| e.in_field(k))?", field.ty),
+                        format!(
+                            "<{} as ::avila_json::FromJson>::from_missing().ok_or_else(|| ::avila_json::FromJsonError::missing_field({:?}))?",
+                            field.ty, key
+                        ),
+                    ),
+                };
+                let missing = if field.attrs.default {
+                    "::core::default::Default::default()".to_string()
+                } else {
+                    missing
+                };
+
+                inits += &format!(
+                    "{id}: match [{names}].iter().find_map(|k| object.get(k).map(|v| (*k, v))) {{\n    Some((k, v)) => {present},\n    None => {missing},\n}},\n",
+                    id = field.ident,
+                    names = names.join(", "),
+                    present = present,
+                    missing = missing
+                );
+            }
+            format!(
+                "let object = match value {{\n    ::avila_json::JsonValue::Object(object) => object,\n    other => return ::core::result::Result::Err(::avila_json::FromJsonError::expected(\"object\", other)),\n}};\n::core::result::Result::Ok(Self {{\n{}}})",
+                inits
+            )
+        }
+        Body::Enum(variants) => {
+            let mut arms = String::new();
+            let mut expected = Vec::new();
+            for variant in variants {
+                let name = json_name(&variant.ident, &variant.attrs, &item.attrs)?;
+                let mut patterns = vec![format!("{:?}", name)];
+                patterns.extend(variant.attrs.aliases.iter().map(|a| format!("{:?}", a)));
+                arms += &format!(
+                    "{} => ::core::result::Result::Ok({}::{}),\n",
+                    patterns.join(" | "),
+                    item.name,
+                    variant.ident
+                );
+                expected.push(format!("`{}`", name));
+            }
+            format!(
+                "let text = match value {{\n    ::avila_json::JsonValue::String(text) => text.as_str(),\n    other => return ::core::result::Result::Err(::avila_json::FromJsonError::expected(\"string\", other)),\n}};\nmatch text {{\n{}other => ::core::result::Result::Err(::avila_json::FromJsonError::new(::std::format!(\"unknown variant `{{}}`, expected one of {{}}\", other, {:?}))),\n}}",
+                arms,
+                expected.join(", ")
+            )
+        }
+    };
+
+    Ok(format!(
+        "impl ::avila_json::FromJson for {} {{\n    fn from_json(value: &::avila_json::JsonValue) -> ::core::result::Result<Self, ::avila_json::FromJsonError> {{\n{}\n    }}\n}}",
+        item.name, body
+    ))
+}
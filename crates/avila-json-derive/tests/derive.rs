@@ -0,0 +1,189 @@
+// AvilaJson Derive - attribute behaviour through the generated impls
+// Zero External Dependencies 🦀
+
+use avila_json::{parse, FromJson, FromJsonError, JsonValue, ToJson};
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(rename_all = "lowercase")]
+enum Lower {
+    OffPlanUnit,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(rename_all = "UPPERCASE")]
+enum Upper {
+    OffPlanUnit,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(rename_all = "snake_case")]
+enum Snake {
+    OffPlanUnit,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(rename_all = "SCREAMING_SNAKE_CASE")]
+enum ScreamingSnake {
+    OffPlanUnit,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(rename_all = "kebab-case")]
+enum Kebab {
+    OffPlanUnit,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(rename_all = "camelCase")]
+enum Camel {
+    OffPlanUnit,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(rename_all = "PascalCase")]
+enum Pascal {
+    OffPlanUnit,
+}
+
+#[test]
+fn test_rename_all_styles() {
+    fn name<T: ToJson + FromJson + PartialEq + std::fmt::Debug>(value: T) -> String {
+        let json = value.to_json();
+        assert_eq!(T::from_json(&json).unwrap(), value);
+        json.as_str().unwrap().to_string()
+    }
+
+    assert_eq!(name(Lower::OffPlanUnit), "offplanunit");
+    assert_eq!(name(Upper::OffPlanUnit), "OFFPLANUNIT");
+    assert_eq!(name(Snake::OffPlanUnit), "off_plan_unit");
+    assert_eq!(name(ScreamingSnake::OffPlanUnit), "OFF_PLAN_UNIT");
+    assert_eq!(name(Kebab::OffPlanUnit), "off-plan-unit");
+    assert_eq!(name(Camel::OffPlanUnit), "offPlanUnit");
+    assert_eq!(name(Pascal::OffPlanUnit), "OffPlanUnit");
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+#[json(rename_all = "camelCase")]
+struct Unit {
+    price_per_sqm: f64,
+    #[json(rename = "service_charge")]
+    service_charge_aed: u32,
+    r#type: String,
+}
+
+#[test]
+fn test_rename_all_fields() {
+    let unit = Unit {
+        price_per_sqm: 15_400.5,
+        service_charge_aed: 18,
+        r#type: "studio".to_string(),
+    };
+    let json = unit.to_json();
+    assert_eq!(
+        json.to_string(),
+        r#"{"pricePerSqm":15400.5,"service_charge":18,"type":"studio"}"#
+    );
+    assert_eq!(Unit::from_json(&json).unwrap(), unit);
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Location {
+    community: String,
+    building: Option<String>,
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Listing {
+    id: String,
+    #[json(flatten)]
+    location: Location,
+    price: u64,
+}
+
+#[test]
+fn test_flatten() {
+    let listing = Listing {
+        id: "dp-7".to_string(),
+        location: Location {
+            community: "JVC".to_string(),
+            building: None,
+        },
+        price: 720_000,
+    };
+    let json = listing.to_json();
+    assert_eq!(
+        json.to_string(),
+        r#"{"id":"dp-7","community":"JVC","price":720000}"#
+    );
+    assert_eq!(Listing::from_json(&json).unwrap(), listing);
+
+    let missing = parse(r#"{"id": "dp-8", "price": 1}"#).unwrap();
+    assert_eq!(
+        Listing::from_json(&missing).unwrap_err().to_string(),
+        "$.community: missing field"
+    );
+}
+
+/// Square metres written as a rounded string
+mod sqm_text {
+    use super::*;
+
+    pub fn to_json(value: &Option<f64>) -> JsonValue {
+        match value {
+            Some(sqm) => JsonValue::String(format!("{:.0} sqm", sqm)),
+            None => JsonValue::Null,
+        }
+    }
+
+    pub fn from_json(value: &JsonValue) -> Result<Option<f64>, FromJsonError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            JsonValue::String(text) => text
+                .trim_end_matches(" sqm")
+                .parse()
+                .map(Some)
+                .map_err(|_| FromJsonError::new(format!("bad area `{}`", text))),
+            other => Err(FromJsonError::expected("string", other)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, ToJson, FromJson)]
+struct Sized {
+    #[json(with = "sqm_text")]
+    area: Option<f64>,
+}
+
+#[test]
+fn test_with_module() {
+    let sized = Sized { area: Some(75.0) };
+    let json = sized.to_json();
+    assert_eq!(json.to_string(), r#"{"area":"75 sqm"}"#);
+    assert_eq!(Sized::from_json(&json).unwrap(), sized);
+
+    // A null result is left out, and a missing key reads as null
+    let empty = Sized { area: None };
+    assert_eq!(empty.to_json().to_string(), "{}");
+    assert_eq!(Sized::from_json(&parse("{}").unwrap()).unwrap(), empty);
+
+    let bad = parse(r#"{"area": "big"}"#).unwrap();
+    assert_eq!(
+        Sized::from_json(&bad).unwrap_err().to_string(),
+        "$.area: bad area `big`"
+    );
+}
+
+#[derive(Debug, PartialEq, FromJson)]
+enum Layout {
+    #[json(rename = "{studio}")]
+    Studio,
+}
+
+#[test]
+fn test_unknown_variant_lists_names() {
+    let err = Layout::from_json(&JsonValue::String("loft".to_string())).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "$: unknown variant `loft`, expected one of `{studio}`"
+    );
+}
@@ -0,0 +1,457 @@
+// AvilaJson Convert - ToJson/FromJson traits for Rust types
+// Zero External Dependencies 🦀
+
+use crate::{Decimal, JsonNumber, JsonObject, JsonValue};
+use std::collections::{BTreeMap, HashMap};
+
+/// Convert a Rust value into a `JsonValue`
+///
+/// Usually derived with `#[derive(ToJson)]`; see the `avila-json-derive` crate
+/// for the supported `#[json(...)]` attributes.
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+
+    /// Whether a struct field holding this value is left out of the object
+    /// (true for `None`)
+    fn skip_serializing(&self) -> bool {
+        false
+    }
+}
+
+/// Build a Rust value from a `JsonValue`, reporting where a mismatch happened
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError>;
+
+    /// Value used when a struct field is absent; `Option` yields `None`
+    /// instead of a "missing field" error
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    Field(String),
+    Index(usize),
+}
+
+/// Mapping failure with the path of the offending value, e.g. `$.properties[3].price`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FromJsonError {
+    /// Innermost segment first; errors are wrapped on the way out
+    path: Vec<PathSegment>,
+    message: String,
+}
+
+impl FromJsonError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            path: Vec::new(),
+            message: message.into(),
+        }
+    }
+
+    /// `expected <what>, found <type of value>`
+    pub fn expected(what: &str, found: &JsonValue) -> Self {
+        Self::new(format!("expected {}, found {}", what, found.type_name()))
+    }
+
+    pub fn missing_field(name: &str) -> Self {
+        Self::new("missing field").in_field(name)
+    }
+
+    /// Prefix the path with an object key
+    pub fn in_field(mut self, name: &str) -> Self {
+        self.path.push(PathSegment::Field(name.to_string()));
+        self
+    }
+
+    /// Prefix the path with an array index
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.path.push(PathSegment::Index(index));
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// JSONPath-style location: `$`, `$.a[3].b`, `$["odd key"]`
+    pub fn path(&self) -> String {
+        let mut out = String::from("$");
+        for segment in self.path.iter().rev() {
+            match segment {
+                PathSegment::Index(i) => out.push_str(&format!("[{}]", i)),
+                PathSegment::Field(name) if is_plain_key(name) => {
+                    out.push('.');
+                    out.push_str(name);
+                }
+                PathSegment::Field(name) => {
+                    out.push('[');
+                    crate::ser::write_string(&mut out, name, false);
+                    out.push(']');
+                }
+            }
+        }
+        out
+    }
+}
+
+fn is_plain_key(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+impl std::fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path(), self.message)
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+// ---------------------------------------------------------------------------
+// Implementations for std types
+// ---------------------------------------------------------------------------
+
+impl ToJson for JsonValue {
+    fn to_json(&self) -> JsonValue {
+        self.clone()
+    }
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for JsonObject {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Object(self.clone())
+    }
+}
+
+impl FromJson for JsonObject {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value.as_object().cloned().ok_or_else(|| FromJsonError::expected("object", value))
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Bool(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value.as_bool().ok_or_else(|| FromJsonError::expected("boolean", value))
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.to_string())
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.clone())
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value.as_str().map(str::to_string).ok_or_else(|| FromJsonError::expected("string", value))
+    }
+}
+
+impl ToJson for f64 {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::from(*self)
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value.as_f64().ok_or_else(|| FromJsonError::expected("number", value))
+    }
+}
+
+impl ToJson for f32 {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::from(*self)
+    }
+}
+
+impl FromJson for f32 {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        f64::from_json(value).map(|n| n as f32)
+    }
+}
+
+impl ToJson for JsonNumber {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Number(self.clone())
+    }
+}
+
+impl FromJson for JsonNumber {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value.as_number().cloned().ok_or_else(|| FromJsonError::expected("number", value))
+    }
+}
+
+impl ToJson for Decimal {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Number(JsonNumber::from(*self))
+    }
+}
+
+impl FromJson for Decimal {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let n = value.as_number().ok_or_else(|| FromJsonError::expected("number", value))?;
+        n.as_decimal()
+            .ok_or_else(|| FromJsonError::new(format!("expected decimal, found {}", n)))
+    }
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {$(
+        impl ToJson for $t {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::from(*self)
+            }
+        }
+
+        impl FromJson for $t {
+            fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                let n = value.as_number().ok_or_else(|| FromJsonError::expected("number", value))?;
+                let exact = if <$t>::MIN == 0 {
+                    n.as_u64().and_then(|n| <$t>::try_from(n).ok())
+                } else {
+                    n.as_i64().and_then(|n| <$t>::try_from(n).ok())
+                };
+                exact.ok_or_else(|| FromJsonError::new(format!("expected {}, found {}", stringify!($t), n)))
+            }
+        }
+    )*};
+}
+
+impl_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        T::from_json(value).map(Box::new)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_ref().map_or(JsonValue::Null, ToJson::to_json)
+    }
+
+    fn skip_serializing(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            other => T::from_json(other).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let items = value.as_array().ok_or_else(|| FromJsonError::expected("array", value))?;
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| T::from_json(item).map_err(|e| e.at_index(i)))
+            .collect()
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Object(self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let object = value.as_object().ok_or_else(|| FromJsonError::expected("object", value))?;
+        object
+            .iter()
+            .map(|(k, v)| T::from_json(v).map(|v| (k.clone(), v)).map_err(|e| e.in_field(k)))
+            .collect()
+    }
+}
+
+impl<T: ToJson> ToJson for HashMap<String, T> {
+    /// Keys are sorted so the output is deterministic
+    fn to_json(&self) -> JsonValue {
+        let mut object: JsonObject = self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect();
+        object.sort_keys();
+        JsonValue::Object(object)
+    }
+}
+
+impl<T: FromJson> FromJson for HashMap<String, T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let object = value.as_object().ok_or_else(|| FromJsonError::expected("object", value))?;
+        object
+            .iter()
+            .map(|(k, v)| T::from_json(v).map(|v| (k.clone(), v)).map_err(|e| e.in_field(k)))
+            .collect()
+    }
+}
+
+macro_rules! impl_tuple {
+    ($len:expr => $($name:ident $idx:tt),+) => {
+        /// Tuples map to fixed-length arrays
+        impl<$($name: ToJson),+> ToJson for ($($name,)+) {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::Array(vec![$(self.$idx.to_json()),+])
+            }
+        }
+
+        impl<$($name: FromJson),+> FromJson for ($($name,)+) {
+            fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                match value.as_array() {
+                    Some(items) if items.len() == $len => {
+                        Ok(($($name::from_json(&items[$idx]).map_err(|e| e.at_index($idx))?,)+))
+                    }
+                    _ => Err(FromJsonError::expected(concat!("array of ", $len), value)),
+                }
+            }
+        }
+    };
+}
+
+impl_tuple!(2 => A 0, B 1);
+impl_tuple!(3 => A 0, B 1, C 2);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[derive(Debug, PartialEq, crate::ToJson, crate::FromJson)]
+    #[json(rename_all = "lowercase")]
+    enum Kind {
+        Apartment,
+        #[json(alias = "Penthouse")]
+        Penthouse,
+        #[json(rename = "town-house")]
+        Townhouse,
+    }
+
+    #[derive(Debug, PartialEq, crate::ToJson, crate::FromJson)]
+    struct Listing {
+        title: String,
+        #[json(rename = "price_aed")]
+        price: u64,
+        kind: Kind,
+        #[json(default)]
+        features: Vec<String>,
+        distance_km: Option<f64>,
+        #[json(skip)]
+        cached: bool,
+    }
+
+    #[derive(Debug, PartialEq, crate::ToJson, crate::FromJson)]
+    struct Record {
+        id: String,
+        #[json(flatten)]
+        listing: Listing,
+    }
+
+    #[test]
+    fn test_derive_roundtrip() {
+        let listing = Listing {
+            title: "Marina 2BR".to_string(),
+            price: 1_850_000,
+            kind: Kind::Townhouse,
+            features: vec!["Sea view".to_string()],
+            distance_km: None,
+            cached: true,
+        };
+        let json = listing.to_json();
+        assert_eq!(
+            json.to_string(),
+            r#"{"title":"Marina 2BR","price_aed":1850000,"kind":"town-house","features":["Sea view"]}"#
+        );
+
+        let back = Listing::from_json(&json).unwrap();
+        assert_eq!(back, Listing { cached: false, ..listing });
+
+        let record = Record { id: "dp-001".to_string(), listing: back };
+        let json = record.to_json();
+        assert_eq!(json.as_object().unwrap().keys().take(3).collect::<Vec<_>>(), ["id", "title", "price_aed"]);
+        assert_eq!(Record::from_json(&json).unwrap(), record);
+    }
+
+    #[test]
+    fn test_defaults_and_aliases() {
+        let value = parse(r#"{"title": "Villa", "price_aed": 8500000, "kind": "Penthouse", "distance_km": null}"#).unwrap();
+        let listing = Listing::from_json(&value).unwrap();
+        assert_eq!(listing.kind, Kind::Penthouse);
+        assert!(listing.features.is_empty());
+        assert_eq!(listing.distance_km, None);
+    }
+
+    #[test]
+    fn test_error_paths() {
+        let value = parse(r#"[{"title": "A", "price_aed": 1, "kind": "apartment"}, {"title": "B", "price_aed": 2.5, "kind": "villa"}]"#).unwrap();
+        let err = Vec::<Listing>::from_json(&value).unwrap_err();
+        assert_eq!(err.to_string(), "$[1].price_aed: expected u64, found 2.5");
+
+        let value = parse(r#"{"price_aed": 1, "kind": "villa"}"#).unwrap();
+        assert_eq!(Listing::from_json(&value).unwrap_err().to_string(), "$.title: missing field");
+
+        let value = parse(r#"{"title": "C", "price_aed": 1, "kind": "villa"}"#).unwrap();
+        assert_eq!(
+            Listing::from_json(&value).unwrap_err().to_string(),
+            "$.kind: unknown variant `villa`, expected one of `apartment`, `penthouse`, `town-house`"
+        );
+
+        let err = HashMap::<String, bool>::from_json(&parse(r#"{"odd key": 1}"#).unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), r#"$["odd key"]: expected boolean, found number"#);
+    }
+}
@@ -1,19 +1,60 @@
 // AvilaJson - Native JSON Implementation
 // Zero External Dependencies 🦀
 
-use std::collections::HashMap;
+// Lets the derive output's `::avila_json::` paths resolve inside this crate too
+extern crate self as avila_json;
+
+mod convert;
+mod number;
+mod object;
+mod patch;
+mod path;
+mod pointer;
+mod schema;
+mod ser;
+mod stream;
+
+pub use avila_json_derive::{FromJson, ToJson};
+pub use convert::{FromJson, FromJsonError, ToJson};
+pub use number::{Decimal, JsonNumber};
+pub use object::JsonObject;
+pub use patch::{diff, merge_diff, merge_patch, Change, JsonPatch, PatchError, PatchOperation};
+pub use path::{JsonPath, JsonPathError};
+pub use pointer::{JsonPointer, PointerError};
+pub use schema::{JsonSchema, SchemaError, ValidationError};
+pub use ser::SerializeOptions;
+pub use stream::{from_reader, JsonEvent, JsonReader, NdjsonReader, NdjsonWriter, StreamError};
+
+/// Default limit on nested arrays and objects; both parsers recurse once per level
+pub(crate) const MAX_DEPTH: usize = 128;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum JsonValue {
     Null,
     Bool(bool),
-    Number(f64),
+    Number(JsonNumber),
     String(String),
     Array(Vec<JsonValue>),
-    Object(HashMap<String, JsonValue>),
+    Object(JsonObject),
 }
 
 impl JsonValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    /// JSON type name, as used in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        }
+    }
+
     pub fn as_str(&self) -> Option<&str> {
         match self {
             JsonValue::String(s) => Some(s),
@@ -21,13 +62,31 @@ impl JsonValue {
         }
     }
 
-    pub fn as_f64(&self) -> Option<f64> {
+    pub fn as_number(&self) -> Option<&JsonNumber> {
         match self {
-            JsonValue::Number(n) => Some(*n),
+            JsonValue::Number(n) => Some(n),
             _ => None,
         }
     }
 
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(JsonNumber::as_f64)
+    }
+
+    /// Exact integer value; `None` for fractions and out-of-range numbers
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number()?.as_i64()
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number()?.as_u64()
+    }
+
+    /// Exact decimal value, e.g. for money amounts
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        self.as_number()?.as_decimal()
+    }
+
     pub fn as_bool(&self) -> Option<bool> {
         match self {
             JsonValue::Bool(b) => Some(*b),
@@ -42,85 +101,178 @@ impl JsonValue {
         }
     }
 
-    pub fn as_object(&self) -> Option<&HashMap<String, JsonValue>> {
+    pub fn as_object(&self) -> Option<&JsonObject> {
         match self {
             JsonValue::Object(obj) => Some(obj),
             _ => None,
         }
     }
 
-    /// Serialize to JSON string
-    pub fn to_string(&self) -> String {
+    pub fn as_object_mut(&mut self) -> Option<&mut JsonObject> {
         match self {
-            JsonValue::Null => "null".to_string(),
-            JsonValue::Bool(b) => b.to_string(),
-            JsonValue::Number(n) => n.to_string(),
-            JsonValue::String(s) => format!("\"{}\"", escape_string(s)),
-            JsonValue::Array(arr) => {
-                let items: Vec<String> = arr.iter().map(|v| v.to_string()).collect();
-                format!("[{}]", items.join(","))
-            }
-            JsonValue::Object(obj) => {
-                let items: Vec<String> = obj
-                    .iter()
-                    .map(|(k, v)| format!("\"{}\":{}", escape_string(k), v.to_string()))
-                    .collect();
-                format!("{{{}}}", items.join(","))
-            }
+            JsonValue::Object(obj) => Some(obj),
+            _ => None,
         }
     }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// Value at an RFC 6901 JSON Pointer such as `/properties/0/price`
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        JsonPointer::parse(pointer).ok()?.get(self)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        JsonPointer::parse(pointer).ok()?.get_mut(self)
+    }
+
+    /// Replace or add the value at a JSON Pointer (`-` appends to an array)
+    pub fn set_pointer(&mut self, pointer: &str, value: JsonValue) -> Result<Option<JsonValue>, PointerError> {
+        JsonPointer::parse(pointer)?.set(self, value)
+    }
+
+    pub fn remove_pointer(&mut self, pointer: &str) -> Option<JsonValue> {
+        JsonPointer::parse(pointer).ok()?.remove(self)
+    }
+
+    /// Values selected by a JSONPath expression, e.g. `$.properties[?(@.bedrooms>=2)].price`
+    pub fn query(&self, path: &str) -> Result<Vec<&JsonValue>, JsonPathError> {
+        Ok(JsonPath::parse(path)?.query(self))
+    }
+
+    /// Mutable references to the values selected by a JSONPath expression
+    pub fn query_mut(&mut self, path: &str) -> Result<Vec<&mut JsonValue>, JsonPathError> {
+        JsonPath::parse(path)?.query_mut(self)
+    }
+
+    /// Serialize with explicit layout options
+    pub fn to_string_with(&self, options: &SerializeOptions) -> String {
+        let mut out = String::new();
+        ser::write_value(&mut out, self, options, 0);
+        out
+    }
+
+    /// Serialize with two-space indentation
+    pub fn to_pretty_string(&self) -> String {
+        self.to_string_with(&SerializeOptions::pretty())
+    }
+}
+
+impl From<JsonNumber> for JsonValue {
+    fn from(n: JsonNumber) -> Self {
+        JsonValue::Number(n)
+    }
+}
+
+/// NaN and infinities have no JSON form and become `null`
+impl From<f64> for JsonValue {
+    fn from(n: f64) -> Self {
+        JsonNumber::from_f64(n).map_or(JsonValue::Null, JsonValue::Number)
+    }
+}
+
+impl From<f32> for JsonValue {
+    fn from(n: f32) -> Self {
+        // Through the shortest f32 text, so 0.1f32 stays 0.1
+        n.to_string().parse::<f64>().map_or(JsonValue::Null, JsonValue::from)
+    }
 }
 
-fn escape_string(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
-        .replace('\t', "\\t")
+macro_rules! impl_value_from_integer {
+    ($($t:ty),*) => {$(
+        impl From<$t> for JsonValue {
+            fn from(n: $t) -> Self {
+                JsonValue::Number(JsonNumber::from(n))
+            }
+        }
+    )*};
 }
 
-pub struct JsonParser {
-    input: Vec<char>,
+impl_value_from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Compact JSON; `{:#}` pretty-prints
+impl std::fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let options = if f.alternate() {
+            SerializeOptions::pretty()
+        } else {
+            SerializeOptions::compact()
+        };
+        f.write_str(&self.to_string_with(&options))
+    }
+}
+
+/// Parser over a complete in-memory document; works on the UTF-8 bytes
+/// directly without copying the input. See `JsonReader` for input that does
+/// not fit in memory. Nesting deeper than 128 levels is rejected with
+/// `NestingTooDeep`.
+pub struct JsonParser<'a> {
+    text: &'a str,
+    input: &'a [u8],
     pos: usize,
+    depth: usize,
 }
 
-impl JsonParser {
-    pub fn new(input: &str) -> Self {
+impl<'a> JsonParser<'a> {
+    pub fn new(input: &'a str) -> Self {
         Self {
-            input: input.chars().collect(),
+            text: input,
+            input: input.as_bytes(),
             pos: 0,
+            depth: 0,
         }
     }
 
     pub fn parse(&mut self) -> Result<JsonValue, JsonError> {
         self.skip_whitespace();
-        self.parse_value()
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+        if self.pos < self.input.len() {
+            return Err(self.error(JsonErrorKind::TrailingCharacters));
+        }
+        Ok(value)
     }
 
     fn parse_value(&mut self) -> Result<JsonValue, JsonError> {
         self.skip_whitespace();
 
         if self.pos >= self.input.len() {
-            return Err(JsonError::UnexpectedEnd);
+            return Err(self.error(JsonErrorKind::UnexpectedEnd));
         }
 
-        match self.current_char() {
-            '{' => self.parse_object(),
-            '[' => self.parse_array(),
-            '"' => self.parse_string(),
-            't' | 'f' => self.parse_bool(),
-            'n' => self.parse_null(),
-            c if c.is_numeric() || c == '-' => self.parse_number(),
-            _ => Err(JsonError::InvalidValue),
+        match self.current_byte() {
+            b'{' | b'[' => {
+                if self.depth >= MAX_DEPTH {
+                    return Err(self.error(JsonErrorKind::NestingTooDeep));
+                }
+                self.depth += 1;
+                let value = if self.current_byte() == b'{' {
+                    self.parse_object()
+                } else {
+                    self.parse_array()
+                };
+                self.depth -= 1;
+                value
+            }
+            b'"' => self.parse_string(),
+            b't' | b'f' => self.parse_bool(),
+            b'n' => self.parse_null(),
+            b'0'..=b'9' | b'-' => self.parse_number(),
+            _ => Err(self.error(JsonErrorKind::InvalidValue)),
         }
     }
 
     fn parse_object(&mut self) -> Result<JsonValue, JsonError> {
-        let mut obj = HashMap::new();
+        let mut obj = JsonObject::new();
         self.pos += 1; // skip '{'
         self.skip_whitespace();
 
-        if self.current_char() == '}' {
+        if self.current_byte() == b'}' {
             self.pos += 1;
             return Ok(JsonValue::Object(obj));
         }
@@ -129,14 +281,17 @@ impl JsonParser {
             self.skip_whitespace();
 
             // Parse key
+            if self.pos >= self.input.len() || self.current_byte() != b'"' {
+                return Err(self.error(JsonErrorKind::InvalidKey));
+            }
             let key = match self.parse_string()? {
                 JsonValue::String(s) => s,
-                _ => return Err(JsonError::InvalidKey),
+                _ => return Err(self.error(JsonErrorKind::InvalidKey)),
             };
 
             self.skip_whitespace();
-            if self.current_char() != ':' {
-                return Err(JsonError::ExpectedColon);
+            if self.current_byte() != b':' {
+                return Err(self.error(JsonErrorKind::ExpectedColon));
             }
             self.pos += 1;
 
@@ -145,16 +300,16 @@ impl JsonParser {
             obj.insert(key, value);
 
             self.skip_whitespace();
-            match self.current_char() {
-                ',' => {
+            match self.current_byte() {
+                b',' => {
                     self.pos += 1;
                     continue;
                 }
-                '}' => {
+                b'}' => {
                     self.pos += 1;
                     break;
                 }
-                _ => return Err(JsonError::InvalidObject),
+                _ => return Err(self.error(JsonErrorKind::InvalidObject)),
             }
         }
 
@@ -166,7 +321,7 @@ impl JsonParser {
         self.pos += 1; // skip '['
         self.skip_whitespace();
 
-        if self.current_char() == ']' {
+        if self.current_byte() == b']' {
             self.pos += 1;
             return Ok(JsonValue::Array(arr));
         }
@@ -175,16 +330,16 @@ impl JsonParser {
             arr.push(self.parse_value()?);
             self.skip_whitespace();
 
-            match self.current_char() {
-                ',' => {
+            match self.current_byte() {
+                b',' => {
                     self.pos += 1;
                     continue;
                 }
-                ']' => {
+                b']' => {
                     self.pos += 1;
                     break;
                 }
-                _ => return Err(JsonError::InvalidArray),
+                _ => return Err(self.error(JsonErrorKind::InvalidArray)),
             }
         }
 
@@ -196,45 +351,82 @@ impl JsonParser {
         let mut result = String::new();
 
         while self.pos < self.input.len() {
-            match self.current_char() {
-                '"' => {
+            match self.current_byte() {
+                b'"' => {
                     self.pos += 1;
                     return Ok(JsonValue::String(result));
                 }
-                '\\' => {
+                b'\\' => {
                     self.pos += 1;
                     if self.pos >= self.input.len() {
-                        return Err(JsonError::UnexpectedEnd);
+                        return Err(self.error(JsonErrorKind::UnexpectedEnd));
                     }
-                    result.push(self.current_char());
+                    let escaped = match self.current_byte() {
+                        b'u' => self.parse_unicode_escape()?,
+                        b => unescape(b).ok_or_else(|| self.error(JsonErrorKind::InvalidEscape))?,
+                    };
+                    result.push(escaped);
                     self.pos += 1;
                 }
-                c => {
-                    result.push(c);
-                    self.pos += 1;
+                b if b < 0x20 => return Err(self.error(JsonErrorKind::ControlCharacter)),
+                _ => {
+                    // Copy the run of plain characters up to the next quote, escape or control byte
+                    let start = self.pos;
+                    while self.pos < self.input.len() && !matches!(self.input[self.pos], b'"' | b'\\' | 0..=0x1F) {
+                        self.pos += 1;
+                    }
+                    // The run starts and ends on ASCII bytes, so these are char boundaries
+                    result.push_str(&self.text[start..self.pos]);
                 }
             }
         }
 
-        Err(JsonError::UnexpectedEnd)
+        Err(self.error(JsonErrorKind::UnexpectedEnd))
+    }
+
+    /// Decode `uXXXX` (pos on the `u`), joining a UTF-16 surrogate pair when a
+    /// second escape follows. Leaves pos on the last hex digit consumed.
+    fn parse_unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.read_hex4()?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                if !self.input[self.pos + 1..].starts_with(b"\\u") {
+                    return Err(self.error(JsonErrorKind::InvalidUnicodeEscape));
+                }
+                self.pos += 2;
+                let low = self.read_hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error(JsonErrorKind::InvalidUnicodeEscape));
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            0xDC00..=0xDFFF => return Err(self.error(JsonErrorKind::InvalidUnicodeEscape)),
+            code => code,
+        };
+        char::from_u32(code).ok_or_else(|| self.error(JsonErrorKind::InvalidUnicodeEscape))
+    }
+
+    fn read_hex4(&mut self) -> Result<u32, JsonError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            self.pos += 1;
+            let b = *self.input.get(self.pos).ok_or_else(|| self.error(JsonErrorKind::UnexpectedEnd))?;
+            let digit = (b as char).to_digit(16).ok_or_else(|| self.error(JsonErrorKind::InvalidUnicodeEscape))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
     }
 
     fn parse_number(&mut self) -> Result<JsonValue, JsonError> {
         let start = self.pos;
 
-        while self.pos < self.input.len() {
-            let c = self.current_char();
-            if !c.is_numeric() && c != '.' && c != '-' && c != 'e' && c != 'E' && c != '+' {
-                break;
-            }
+        while self.pos < self.input.len() && is_number_byte(self.current_byte()) {
             self.pos += 1;
         }
 
-        let num_str: String = self.input[start..self.pos].iter().collect();
-        num_str
-            .parse::<f64>()
+        JsonNumber::from_literal(&self.text[start..self.pos])
             .map(JsonValue::Number)
-            .map_err(|_| JsonError::InvalidNumber)
+            .ok_or_else(|| self.error_at(start, JsonErrorKind::InvalidNumber))
     }
 
     fn parse_bool(&mut self) -> Result<JsonValue, JsonError> {
@@ -245,7 +437,7 @@ impl JsonParser {
             self.pos += 5;
             Ok(JsonValue::Bool(false))
         } else {
-            Err(JsonError::InvalidValue)
+            Err(self.error(JsonErrorKind::InvalidValue))
         }
     }
 
@@ -254,31 +446,83 @@ impl JsonParser {
             self.pos += 4;
             Ok(JsonValue::Null)
         } else {
-            Err(JsonError::InvalidValue)
+            Err(self.error(JsonErrorKind::InvalidValue))
         }
     }
 
-    fn current_char(&self) -> char {
-        self.input[self.pos]
+    fn error(&self, kind: JsonErrorKind) -> JsonError {
+        self.error_at(self.pos, kind)
+    }
+
+    /// Build an error located at byte offset `pos`
+    fn error_at(&self, pos: usize, kind: JsonErrorKind) -> JsonError {
+        let pos = pos.min(self.input.len());
+        // Whatever was expected, running out of input is the real problem
+        let kind = if pos == self.input.len() && kind != JsonErrorKind::TrailingCharacters {
+            JsonErrorKind::UnexpectedEnd
+        } else {
+            kind
+        };
+        let text = self.text;
+        let pos = (0..=pos).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+        let line_start = text[..pos].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[pos..].find('\n').map_or(text.len(), |i| pos + i);
+
+        // Up to 40 characters of the offending line on each side of the error
+        let before: String = text[line_start..pos].chars().rev().take(40).collect::<Vec<_>>().into_iter().rev().collect();
+        let after: String = text[pos..line_end].chars().take(40).collect();
+
+        JsonError {
+            kind,
+            offset: pos,
+            line: text[..pos].matches('\n').count() + 1,
+            column: text[line_start..pos].chars().count() + 1,
+            context: format!("{}{}", before, after).trim().to_string(),
+        }
+    }
+
+    /// Byte at pos, or NUL past the end so truncated input reports an error instead of panicking
+    fn current_byte(&self) -> u8 {
+        self.input.get(self.pos).copied().unwrap_or(0)
     }
 
     fn skip_whitespace(&mut self) {
-        while self.pos < self.input.len() && self.current_char().is_whitespace() {
+        while self.pos < self.input.len() && is_whitespace_byte(self.current_byte()) {
             self.pos += 1;
         }
     }
 
     fn matches_str(&self, s: &str) -> bool {
-        let chars: Vec<char> = s.chars().collect();
-        if self.pos + chars.len() > self.input.len() {
-            return false;
-        }
-        &self.input[self.pos..self.pos + chars.len()] == &chars[..]
+        self.input[self.pos..].starts_with(s.as_bytes())
     }
 }
 
-#[derive(Debug)]
-pub enum JsonError {
+/// JSON insignificant whitespace (RFC 8259 section 2)
+pub(crate) fn is_whitespace_byte(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+pub(crate) fn is_number_byte(b: u8) -> bool {
+    matches!(b, b'0'..=b'9' | b'.' | b'-' | b'+' | b'e' | b'E')
+}
+
+/// Character for a single-letter escape such as `\n` (everything but `\u`)
+pub(crate) fn unescape(b: u8) -> Option<char> {
+    Some(match b {
+        b'"' => '"',
+        b'\\' => '\\',
+        b'/' => '/',
+        b'b' => '\u{08}',
+        b'f' => '\u{0C}',
+        b'n' => '\n',
+        b'r' => '\r',
+        b't' => '\t',
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonErrorKind {
     UnexpectedEnd,
     InvalidValue,
     InvalidKey,
@@ -286,8 +530,61 @@ pub enum JsonError {
     InvalidArray,
     InvalidNumber,
     ExpectedColon,
+    InvalidEscape,
+    InvalidUnicodeEscape,
+    ControlCharacter,
+    InvalidUtf8,
+    TrailingCharacters,
+    NestingTooDeep,
+}
+
+impl std::fmt::Display for JsonErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let message = match self {
+            JsonErrorKind::UnexpectedEnd => "unexpected end of input",
+            JsonErrorKind::InvalidValue => "expected a JSON value",
+            JsonErrorKind::InvalidKey => "expected a string key",
+            JsonErrorKind::InvalidObject => "expected ',' or '}'",
+            JsonErrorKind::InvalidArray => "expected ',' or ']'",
+            JsonErrorKind::InvalidNumber => "invalid number",
+            JsonErrorKind::ExpectedColon => "expected ':'",
+            JsonErrorKind::InvalidEscape => "invalid escape sequence",
+            JsonErrorKind::InvalidUnicodeEscape => "invalid \\u escape",
+            JsonErrorKind::ControlCharacter => "unescaped control character in string",
+            JsonErrorKind::InvalidUtf8 => "invalid UTF-8",
+            JsonErrorKind::TrailingCharacters => "trailing characters after JSON value",
+            JsonErrorKind::NestingTooDeep => "arrays and objects nested too deeply",
+        };
+        f.write_str(message)
+    }
+}
+
+/// Parse failure with its location in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    pub kind: JsonErrorKind,
+    /// Byte offset into the input
+    pub offset: usize,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+    /// The offending line, trimmed to the neighbourhood of the error
+    pub context: String,
+}
+
+impl std::fmt::Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}, col {}: {}", self.line, self.column, self.kind)?;
+        if !self.context.is_empty() {
+            write!(f, " near `{}`", self.context)?;
+        }
+        Ok(())
+    }
 }
 
+impl std::error::Error for JsonError {}
+
 pub fn parse(input: &str) -> Result<JsonValue, JsonError> {
     JsonParser::new(input).parse()
 }
@@ -307,7 +604,12 @@ mod tests {
     fn test_parse_number() {
         let json = "42.5";
         let value = parse(json).unwrap();
-        assert_eq!(value, JsonValue::Number(42.5));
+        assert_eq!(value, JsonValue::from(42.5));
+        assert_eq!(parse("9007199254740993").unwrap().as_i64(), Some(9_007_199_254_740_993));
+        assert_eq!(parse("-0.5e1").unwrap().as_i64(), Some(-5));
+        for invalid in ["01", "1.", "-", "1e", "2.5.1"] {
+            assert_eq!(parse(invalid).unwrap_err().kind, JsonErrorKind::InvalidNumber, "{}", invalid);
+        }
     }
 
     #[test]
@@ -338,11 +640,119 @@ mod tests {
     #[test]
     fn test_serialize() {
         let value = JsonValue::Object({
-            let mut map = HashMap::new();
-            map.insert("test".to_string(), JsonValue::Number(123.0));
+            let mut map = JsonObject::new();
+            map.insert("test".to_string(), JsonValue::from(123));
             map
         });
         let json = value.to_string();
         assert!(json.contains("test"));
     }
+
+    fn first_string(json: &str) -> Result<String, JsonError> {
+        match parse(json)? {
+            JsonValue::Array(items) => Ok(items[0].as_str().unwrap().to_string()),
+            other => panic!("expected array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(first_string(r#"["line\nbreak\ttab"]"#).unwrap(), "line\nbreak\ttab");
+        assert_eq!(first_string(r#"["\u0041\u00e7\u00e3o"]"#).unwrap(), "Ação");
+        // Arabic listing title sent fully escaped
+        assert_eq!(first_string(r#"["\u0634\u0642\u0629 \u0641\u064a \u062f\u0628\u064a"]"#).unwrap(), "شقة في دبي");
+        assert_eq!(first_string(r#"["\ud83c\udfe0"]"#).unwrap(), "🏠");
+    }
+
+    #[test]
+    fn test_escape_roundtrip() {
+        let original = "quote \" backslash \\ slash / \u{08}\u{0C}\n\r\t \u{01}\u{1f} €𝄞 دبي";
+        let encoded = JsonValue::String(original.to_string()).to_string();
+        assert!(encoded.contains("\\u0001") && encoded.contains("\\u001f") && encoded.contains("\\b"));
+        assert!(!encoded.chars().any(|c| (c as u32) < 0x20));
+        assert_eq!(parse(&encoded).unwrap(), JsonValue::String(original.to_string()));
+    }
+
+    /// String cases from the JSONTestSuite corpus (y_ must parse, n_/i_ are rejected)
+    #[test]
+    fn test_json_test_suite_strings() {
+        let accepted = [
+            (r#"["\u0060\u012a\u12AB"]"#, "`\u{12a}\u{12ab}"),
+            (r#"["\uD801\udc37"]"#, "\u{10437}"),
+            (r#"["\ud83d\ude39\ud83d\udc8d"]"#, "😹💍"),
+            (r#"["\"\\\/\b\f\n\r\t"]"#, "\"\\/\u{08}\u{0C}\n\r\t"),
+            (r#"["\\u0000"]"#, "\\u0000"),
+            (r#"["\""]"#, "\""),
+            (r#"["\u0012"]"#, "\u{12}"),
+            (r#"["\uFFFF"]"#, "\u{FFFF}"),
+            (r#"["\u0000"]"#, "\u{0}"),
+            (r#"["\u0061\u30af\u30EA\u30b9"]"#, "aクリス"),
+            (r#"["\uDBFF\uDFFE"]"#, "\u{10FFFE}"),
+            (r#"["\u0022"]"#, "\""),
+            ("[\"€𝄞\"]", "€𝄞"),
+            ("[\"a\u{7f}a\"]", "a\u{7f}a"),
+        ];
+        for (json, expected) in accepted {
+            assert_eq!(first_string(json).unwrap(), expected, "{}", json);
+        }
+
+        let rejected = [
+            r#"["\uD800\"]"#,
+            r#"["\uD800\u"]"#,
+            r#"["\uD800\u1"]"#,
+            "[\"\\\u{0}\"]",
+            r#"["\x00"]"#,
+            r#"["\\\"]"#,
+            "[\"\\\t\"]",
+            "[\"\\🌀\"]",
+            r#"["\"]"#,
+            r#"["\u00A"]"#,
+            r#"["\uD834\uDd"]"#,
+            r#"["\a"]"#,
+            r#"["\uqqqq"]"#,
+            "[\"a\u{0}a\"]",
+            "[\"new\nline\"]",
+            "[\"\t\"]",
+            "\"",
+            r#"["\"#,
+            // Lone or inverted surrogates (implementation-defined, rejected here)
+            r#"["\uDADA"]"#,
+            r#"["\uDFAA"]"#,
+            r#"["\uDd1ea"]"#,
+            r#"["\uDd1e\uD834"]"#,
+        ];
+        for json in rejected {
+            assert!(parse(json).is_err(), "should reject {:?}", json);
+        }
+    }
+
+    #[test]
+    fn test_error_position() {
+        let json = "{\n  \"name\": \"Marina\",\n  \"price\": 100 \"area\": 80\n}";
+        let err = parse(json).unwrap_err();
+        assert_eq!(err.kind, JsonErrorKind::InvalidObject);
+        assert_eq!((err.line, err.column), (3, 16));
+        assert_eq!(err.offset, json.find("\"area").unwrap());
+        assert_eq!(err.to_string(), "line 3, col 16: expected ',' or '}' near `\"price\": 100 \"area\": 80`");
+
+        // Columns count characters, offsets count bytes
+        let err = parse("[\"دبي\", tru]").unwrap_err();
+        assert_eq!((err.kind, err.line, err.column), (JsonErrorKind::InvalidValue, 1, 9));
+        assert_eq!(err.offset, 11);
+
+        let err = parse("{\"a\": 1} x").unwrap_err();
+        assert_eq!((err.kind, err.column), (JsonErrorKind::TrailingCharacters, 10));
+        assert_eq!(parse("[1, 2").unwrap_err().kind, JsonErrorKind::UnexpectedEnd);
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let err = parse(&"[".repeat(200_000)).unwrap_err();
+        assert_eq!((err.kind, err.column), (JsonErrorKind::NestingTooDeep, 129));
+        let err = parse(&"{\"a\":".repeat(200_000)).unwrap_err();
+        assert_eq!(err.kind, JsonErrorKind::NestingTooDeep);
+
+        let limit = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&limit).is_ok());
+    }
 }
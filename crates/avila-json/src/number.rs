@@ -0,0 +1,369 @@
+// AvilaJson Number - Lossless JSON numbers
+// Zero External Dependencies 🦀
+
+use std::cmp::Ordering;
+
+/// JSON number kept in its lexical form
+///
+/// Parsed numbers are written back exactly as they appeared (`2.60` stays
+/// `2.60`, 19-digit IDs keep every digit). Numbers built from `f64` use the
+/// shortest text that reads back as the same float. Equality and ordering
+/// are numeric: `1.5e6 == 1500000`.
+#[derive(Debug, Clone)]
+pub struct JsonNumber {
+    repr: String,
+}
+
+impl JsonNumber {
+    /// Validate a number literal against the RFC 8259 grammar
+    pub fn from_literal(text: &str) -> Option<Self> {
+        let b = text.as_bytes();
+        let digits = |mut i: usize| {
+            while b.get(i).is_some_and(u8::is_ascii_digit) {
+                i += 1;
+            }
+            i
+        };
+
+        let mut i = usize::from(b.first() == Some(&b'-'));
+        i = match b.get(i) {
+            Some(b'0') => i + 1,
+            Some(b'1'..=b'9') => digits(i),
+            _ => return None,
+        };
+        if b.get(i) == Some(&b'.') {
+            let end = digits(i + 1);
+            if end == i + 1 {
+                return None;
+            }
+            i = end;
+        }
+        if matches!(b.get(i), Some(b'e' | b'E')) {
+            i += 1;
+            if matches!(b.get(i), Some(b'+' | b'-')) {
+                i += 1;
+            }
+            let end = digits(i);
+            if end == i {
+                return None;
+            }
+            i = end;
+        }
+
+        (i == b.len()).then(|| Self { repr: text.to_string() })
+    }
+
+    /// `None` for NaN and infinities, which JSON cannot represent
+    pub fn from_f64(n: f64) -> Option<Self> {
+        if !n.is_finite() {
+            return None;
+        }
+        let magnitude = n.abs();
+        let repr = if magnitude != 0.0 && !(1e-5..1e16).contains(&magnitude) {
+            format!("{:e}", n)
+        } else {
+            n.to_string()
+        };
+        Some(Self { repr })
+    }
+
+    /// The number exactly as it will be serialized
+    pub fn as_str(&self) -> &str {
+        &self.repr
+    }
+
+    /// Written without a fraction or exponent
+    pub fn is_integer(&self) -> bool {
+        !self.repr.contains(['.', 'e', 'E'])
+    }
+
+    /// Nearest `f64`; precision beyond 17 significant digits is lost
+    pub fn as_f64(&self) -> f64 {
+        self.repr.parse().unwrap_or(f64::NAN)
+    }
+
+    /// Integral value in any notation (`7.0` and `1e3` count)
+    pub(crate) fn has_integer_value(&self) -> bool {
+        match self.as_decimal() {
+            Some(d) => d.to_integer().is_some(),
+            None => self.as_f64().fract() == 0.0,
+        }
+    }
+
+    /// Exact integer value, if the number is integral and fits (`1e3` and `7.0` qualify)
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_decimal()?.to_integer().and_then(|n| i64::try_from(n).ok())
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_decimal()?.to_integer().and_then(|n| u64::try_from(n).ok())
+    }
+
+    /// Exact decimal value; `None` beyond 38 significant digits
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        let (negative, unsigned) = match self.repr.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, self.repr.as_str()),
+        };
+        let (significand, exponent) = match unsigned.find(['e', 'E']) {
+            Some(i) => (&unsigned[..i], unsigned[i + 1..].parse::<i64>().ok()?),
+            None => (unsigned, 0),
+        };
+        let (int, frac) = significand.split_once('.').unwrap_or((significand, ""));
+
+        let mut mantissa: i128 = 0;
+        for digit in int.bytes().chain(frac.bytes()) {
+            mantissa = mantissa.checked_mul(10)?.checked_add(i128::from(digit - b'0'))?;
+        }
+        let mut scale = (frac.len() as i64).checked_sub(exponent)?;
+        if scale < 0 {
+            mantissa = mantissa.checked_mul(10i128.checked_pow(u32::try_from(-scale).ok()?)?)?;
+            scale = 0;
+        }
+        let mantissa = if negative { -mantissa } else { mantissa };
+        Some(Decimal::new(mantissa, u32::try_from(scale).ok()?))
+    }
+
+    /// Sign, significant digits and the power of ten of the first digit:
+    /// `-0.0250` is `(true, "25", -2)`. Zero has no digits. `None` only for
+    /// exponents beyond `i128`.
+    fn scientific(&self) -> Option<(bool, String, i128)> {
+        let (negative, unsigned) = match self.repr.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, self.repr.as_str()),
+        };
+        let (significand, exponent) = match unsigned.find(['e', 'E']) {
+            Some(i) => (&unsigned[..i], unsigned[i + 1..].parse::<i128>().ok()?),
+            None => (unsigned, 0),
+        };
+        let (int, frac) = significand.split_once('.').unwrap_or((significand, ""));
+
+        let all: String = int.chars().chain(frac.chars()).collect();
+        let leading = all.len() - all.trim_start_matches('0').len();
+        let digits = all[leading..].trim_end_matches('0').to_string();
+        let position = int.len() as i128 - 1 - leading as i128;
+        Some((negative, digits, exponent.checked_add(position)?))
+    }
+}
+
+/// Numeric order of two numbers written in any notation, without rounding
+fn compare_scientific(a: (bool, String, i128), b: (bool, String, i128)) -> Ordering {
+    let sign = |(negative, digits, _): &(bool, String, i128)| match (digits.is_empty(), negative) {
+        (true, _) => 0,
+        (false, true) => -1,
+        (false, false) => 1,
+    };
+    let (sign_a, sign_b) = (sign(&a), sign(&b));
+    if sign_a != sign_b || sign_a == 0 {
+        return sign_a.cmp(&sign_b);
+    }
+    // Both start with a non-zero digit, so the exponent decides first and
+    // then the digits, compared as text
+    let magnitude = a.2.cmp(&b.2).then_with(|| a.1.cmp(&b.1));
+    if sign_a < 0 {
+        magnitude.reverse()
+    } else {
+        magnitude
+    }
+}
+
+impl std::fmt::Display for JsonNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.repr)
+    }
+}
+
+impl PartialEq for JsonNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for JsonNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.repr == other.repr {
+            return Some(Ordering::Equal);
+        }
+        match (self.as_decimal(), other.as_decimal()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            // Beyond 38 digits or i64 exponents: compare the notation, as
+            // f64 would make `1e400 == 2e400`
+            _ => match (self.scientific(), other.scientific()) {
+                (Some(a), Some(b)) => Some(compare_scientific(a, b)),
+                _ => self.as_f64().partial_cmp(&other.as_f64()),
+            },
+        }
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {$(
+        impl From<$t> for JsonNumber {
+            fn from(n: $t) -> Self {
+                Self { repr: n.to_string() }
+            }
+        }
+    )*};
+}
+
+impl_from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl From<Decimal> for JsonNumber {
+    fn from(d: Decimal) -> Self {
+        Self { repr: d.to_string() }
+    }
+}
+
+/// Exact decimal: `mantissa × 10^-scale`, e.g. `2.60` is `(260, 2)`
+///
+/// The scale written in the input is kept, so amounts print with the same
+/// number of decimal places. Comparison is numeric (`2.6 == 2.60`).
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        // Going through the text gives the correctly rounded float
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// Mantissa at a larger scale, if it fits
+    fn rescaled(&self, scale: u32) -> Option<i128> {
+        self.mantissa.checked_mul(10i128.checked_pow(scale.checked_sub(self.scale)?)?)
+    }
+
+    /// Exact for decimals like `0.01`, where `f64` remainders are not
+    pub(crate) fn is_multiple_of(&self, divisor: &Decimal) -> Option<bool> {
+        let scale = self.scale.max(divisor.scale);
+        let (a, b) = (self.rescaled(scale)?, divisor.rescaled(scale)?);
+        (b != 0).then(|| a % b == 0)
+    }
+
+    fn to_integer(self) -> Option<i128> {
+        if self.scale == 0 {
+            return Some(self.mantissa);
+        }
+        match 10i128.checked_pow(self.scale) {
+            Some(divisor) => (self.mantissa % divisor == 0).then(|| self.mantissa / divisor),
+            // No i128 mantissa is a multiple of a larger power of ten, except zero
+            None => (self.mantissa == 0).then_some(0),
+        }
+    }
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let padded = format!("{:0>width$}", digits, width = scale + 1);
+        let (int, frac) = padded.split_at(padded.len() - scale);
+        write!(f, "{}{}.{}", sign, int, frac)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        match (self.rescaled(scale), other.rescaled(scale)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            // Too many digits to line up exactly
+            _ => self.to_f64().total_cmp(&other.to_f64()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grammar() {
+        for valid in ["0", "-0", "42", "2.60", "1.5e6", "1E+2", "-1e-7", "9223372036854775808"] {
+            assert_eq!(JsonNumber::from_literal(valid).map(|n| n.to_string()).as_deref(), Some(valid));
+        }
+        for invalid in ["", "-", "01", "1.", ".5", "+1", "1e", "1e+", "0x10", "1.2.3", "Infinity"] {
+            assert!(JsonNumber::from_literal(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_exact_accessors() {
+        let n = |s: &str| JsonNumber::from_literal(s).unwrap();
+
+        assert_eq!(n("9007199254740993").as_i64(), Some(9_007_199_254_740_993));
+        assert_eq!(n("18446744073709551615").as_u64(), Some(u64::MAX));
+        assert_eq!(n("18446744073709551615").as_i64(), None);
+        assert_eq!(n("-3").as_u64(), None);
+        assert_eq!(n("1e3").as_i64(), Some(1000));
+        assert_eq!(n("7.0").as_i64(), Some(7));
+        assert_eq!(n("2.5").as_i64(), None);
+        assert_eq!(n("1e400").as_i64(), None);
+
+        let taxes = n("2.60").as_decimal().unwrap();
+        assert_eq!((taxes.mantissa(), taxes.scale()), (260, 2));
+        assert_eq!(taxes.to_string(), "2.60");
+        assert_eq!(taxes, Decimal::new(26, 1));
+        assert_eq!(n("-0.005").as_decimal().unwrap().to_string(), "-0.005");
+        assert_eq!(n("1.5e-3").as_decimal().unwrap(), Decimal::new(15, 4));
+        assert!(n("0.1").as_decimal().unwrap() < n("0.10000000000000001").as_decimal().unwrap());
+
+        assert_eq!(n("1.5e6"), JsonNumber::from(1_500_000));
+        assert!(n("100") > n("99.99"));
+
+        // Out of range for Decimal: compared exactly, and without overflow
+        assert_eq!(n("1e-9223372036854775808").as_decimal(), None);
+        assert!(n("1e-9223372036854775808") > n("0"));
+        assert!(n("-1e-9223372036854775808") < n("0.0"));
+        assert_ne!(n("1e400"), n("2e400"));
+        assert!(n("1e400") < n("2e400"));
+        assert!(n("-1e400") > n("-2e400"));
+        assert_eq!(n("1e400"), n("10.0e399"));
+        assert_eq!(n("0.00e999"), n("-0"));
+        assert!(n("123456789012345678901234567890123456789012") > n("123456789012345678901234567890123456789011"));
+    }
+
+    #[test]
+    fn test_from_f64() {
+        let text = |x: f64| JsonNumber::from_f64(x).unwrap().to_string();
+        assert_eq!(text(1_850_000.0), "1850000");
+        assert_eq!(text(15416.67), "15416.67");
+        assert_eq!(text(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(text(1e300), "1e300");
+        assert_eq!(text(-2.5e-7), "-2.5e-7");
+        assert!(JsonNumber::from_f64(f64::NAN).is_none());
+        assert!(JsonNumber::from_f64(f64::INFINITY).is_none());
+    }
+}
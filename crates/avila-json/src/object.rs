@@ -0,0 +1,177 @@
+// AvilaJson Object - Insertion-ordered JSON object map
+// Zero External Dependencies 🦀
+
+use crate::JsonValue;
+use std::collections::HashMap;
+
+/// JSON object that remembers the order keys were inserted in
+///
+/// Lookups go through a key index; iteration and serialization follow insertion
+/// order, so parsed documents are written back with their original key layout.
+/// Equality ignores order, as JSON object semantics do.
+#[derive(Debug, Clone, Default)]
+pub struct JsonObject {
+    entries: Vec<(String, JsonValue)>,
+    index: HashMap<String, usize>,
+}
+
+impl JsonObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Insert or replace a value; a replaced key keeps its original position
+    pub fn insert(&mut self, key: impl Into<String>, value: JsonValue) -> Option<JsonValue> {
+        let key = key.into();
+        match self.index.get(&key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        match self.index.get(key) {
+            Some(&i) => Some(&mut self.entries[i].1),
+            None => None,
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Remove a key, shifting later entries down to keep their relative order
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        let i = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for (k, _) in &self.entries[i..] {
+            if let Some(pos) = self.index.get_mut(k) {
+                *pos -= 1;
+            }
+        }
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &JsonValue)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut JsonValue)> {
+        self.entries.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    /// Reorder entries by key (byte order), e.g. for canonical output
+    pub fn sort_keys(&mut self) {
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+        for (i, (k, _)) in self.entries.iter().enumerate() {
+            self.index.insert(k.clone(), i);
+        }
+    }
+}
+
+impl PartialEq for JsonObject {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl FromIterator<(String, JsonValue)> for JsonObject {
+    fn from_iter<I: IntoIterator<Item = (String, JsonValue)>>(iter: I) -> Self {
+        let mut object = JsonObject::new();
+        for (k, v) in iter {
+            object.insert(k, v);
+        }
+        object
+    }
+}
+
+impl IntoIterator for JsonObject {
+    type Item = (String, JsonValue);
+    type IntoIter = std::vec::IntoIter<(String, JsonValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonObject {
+    type Item = (&'a String, &'a JsonValue);
+    type IntoIter = std::iter::Map<std::slice::Iter<'a, (String, JsonValue)>, fn(&'a (String, JsonValue)) -> (&'a String, &'a JsonValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+}
+
+impl From<HashMap<String, JsonValue>> for JsonObject {
+    /// Keys of a `HashMap` have no meaningful order, so they are sorted
+    fn from(map: HashMap<String, JsonValue>) -> Self {
+        let mut object: JsonObject = map.into_iter().collect();
+        object.sort_keys();
+        object
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insertion_order() {
+        let mut obj = JsonObject::new();
+        obj.insert("zeta", JsonValue::from(1));
+        obj.insert("alpha", JsonValue::from(2));
+        obj.insert("mid", JsonValue::from(3));
+        assert_eq!(obj.keys().collect::<Vec<_>>(), ["zeta", "alpha", "mid"]);
+
+        // Replacing keeps the position, removing shifts the rest
+        assert_eq!(obj.insert("zeta", JsonValue::Null), Some(JsonValue::from(1)));
+        assert_eq!(obj.remove("alpha"), Some(JsonValue::from(2)));
+        assert_eq!(obj.keys().collect::<Vec<_>>(), ["zeta", "mid"]);
+        assert_eq!(obj.get("mid"), Some(&JsonValue::from(3)));
+    }
+
+    #[test]
+    fn test_equality_ignores_order() {
+        let a: JsonObject = vec![("x".to_string(), JsonValue::Bool(true)), ("y".to_string(), JsonValue::Null)]
+            .into_iter()
+            .collect();
+        let mut b = JsonObject::new();
+        b.insert("y", JsonValue::Null);
+        b.insert("x", JsonValue::Bool(true));
+        assert_eq!(a, b);
+        b.insert("x", JsonValue::Bool(false));
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,528 @@
+// AvilaJson Patch - Merge Patch (RFC 7386), JSON Patch (RFC 6902) and diff
+// Zero External Dependencies 🦀
+
+use crate::pointer::array_index;
+use crate::{FromJson, FromJsonError, JsonObject, JsonPointer, JsonValue, PointerError, ToJson};
+
+/// Apply an RFC 7386 merge patch
+///
+/// Objects merge key by key, `null` deletes a key and any other value
+/// replaces the target outright (arrays included).
+pub fn merge_patch(target: &mut JsonValue, patch: &JsonValue) {
+    let JsonValue::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !matches!(target, JsonValue::Object(_)) {
+        *target = JsonValue::Object(JsonObject::new());
+    }
+    let JsonValue::Object(target) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+            continue;
+        }
+        match target.get_mut(key) {
+            Some(existing) => merge_patch(existing, value),
+            None => {
+                // Goes through merge_patch so nested nulls are dropped, as the RFC requires
+                let mut added = JsonValue::Null;
+                merge_patch(&mut added, value);
+                target.insert(key.clone(), added);
+            }
+        }
+    }
+}
+
+/// Merge patch turning `from` into `to`, or `None` when they are equal
+///
+/// Merge patches cannot set a member to `null` (that means "delete"), so
+/// nulls inside `to` objects do not survive the round trip; use `JsonPatch`
+/// when that matters.
+pub fn merge_diff(from: &JsonValue, to: &JsonValue) -> Option<JsonValue> {
+    let (JsonValue::Object(old), JsonValue::Object(new)) = (from, to) else {
+        return (from != to).then(|| to.clone());
+    };
+    let mut patch = JsonObject::new();
+    for key in old.keys().filter(|key| !new.contains_key(key)) {
+        patch.insert(key.clone(), JsonValue::Null);
+    }
+    for (key, value) in new {
+        let changed = match old.get(key) {
+            Some(previous) => merge_diff(previous, value),
+            None => Some(value.clone()),
+        };
+        if let Some(changed) = changed {
+            patch.insert(key.clone(), changed);
+        }
+    }
+    (!patch.is_empty()).then_some(JsonValue::Object(patch))
+}
+
+/// One difference between two values
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added { path: JsonPointer, value: JsonValue },
+    Removed { path: JsonPointer, value: JsonValue },
+    Changed { path: JsonPointer, from: JsonValue, to: JsonValue },
+}
+
+impl Change {
+    pub fn path(&self) -> &JsonPointer {
+        match self {
+            Change::Added { path, .. } | Change::Removed { path, .. } | Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// One line per change: `~ #/price: 1850000 → 1800000`
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Change::Added { path, value } => write!(f, "+ #{}: {}", path, value),
+            Change::Removed { path, value } => write!(f, "- #{}: {}", path, value),
+            Change::Changed { path, from, to } => write!(f, "~ #{}: {} → {}", path, from, to),
+        }
+    }
+}
+
+/// Structural differences from `from` to `to`
+///
+/// Objects are compared key by key. Arrays are compared after skipping their
+/// common prefix and suffix, so inserting or deleting one element reports one
+/// change. Changes are ordered so that applying them in sequence (as
+/// `JsonPatch::diff` does) reproduces `to`; array removals come last-first.
+pub fn diff(from: &JsonValue, to: &JsonValue) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_into(from, to, &JsonPointer::root(), &mut changes);
+    changes
+}
+
+fn diff_into(from: &JsonValue, to: &JsonValue, path: &JsonPointer, changes: &mut Vec<Change>) {
+    match (from, to) {
+        (JsonValue::Object(old), JsonValue::Object(new)) => {
+            for (key, value) in old {
+                let at = child(path, key);
+                match new.get(key) {
+                    Some(updated) => diff_into(value, updated, &at, changes),
+                    None => changes.push(Change::Removed { path: at, value: value.clone() }),
+                }
+            }
+            for (key, value) in new.iter().filter(|(key, _)| !old.contains_key(key)) {
+                changes.push(Change::Added {
+                    path: child(path, key),
+                    value: value.clone(),
+                });
+            }
+        }
+        (JsonValue::Array(old), JsonValue::Array(new)) => {
+            let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+            let suffix = old[prefix..]
+                .iter()
+                .rev()
+                .zip(new[prefix..].iter().rev())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let old_middle = &old[prefix..old.len() - suffix];
+            let new_middle = &new[prefix..new.len() - suffix];
+            let common = old_middle.len().min(new_middle.len());
+
+            for i in 0..common {
+                diff_into(&old_middle[i], &new_middle[i], &child(path, prefix + i), changes);
+            }
+            for (i, value) in new_middle.iter().enumerate().skip(common) {
+                changes.push(Change::Added {
+                    path: child(path, prefix + i),
+                    value: value.clone(),
+                });
+            }
+            for (i, value) in old_middle.iter().enumerate().skip(common).rev() {
+                changes.push(Change::Removed {
+                    path: child(path, prefix + i),
+                    value: value.clone(),
+                });
+            }
+        }
+        _ if from != to => changes.push(Change::Changed {
+            path: path.clone(),
+            from: from.clone(),
+            to: to.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn child(path: &JsonPointer, token: impl ToString) -> JsonPointer {
+    let mut child = path.clone();
+    child.push(token.to_string());
+    child
+}
+
+/// RFC 6902 operation
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOperation {
+    /// Insert into an array (shifting later elements) or set an object member
+    Add { path: JsonPointer, value: JsonValue },
+    Remove { path: JsonPointer },
+    /// Overwrite a value that must already exist
+    Replace { path: JsonPointer, value: JsonValue },
+    Move { from: JsonPointer, path: JsonPointer },
+    Copy { from: JsonPointer, path: JsonPointer },
+    /// Abort the patch unless the value at `path` equals `value`
+    Test { path: JsonPointer, value: JsonValue },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// Operation `index` addressed something that is not there
+    Pointer { index: usize, error: PointerError },
+    /// A `test` operation did not match
+    TestFailed { index: usize, path: JsonPointer },
+    /// `move` into a child of the moved value
+    MoveIntoChild { index: usize, from: JsonPointer },
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PatchError::Pointer { index, error } => write!(f, "Patch operation {} failed: {}", index, error),
+            PatchError::TestFailed { index, path } => write!(f, "Patch operation {}: test failed at \"{}\"", index, path),
+            PatchError::MoveIntoChild { index, from } => {
+                write!(f, "Patch operation {}: cannot move \"{}\" into itself", index, from)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// RFC 6902 JSON Patch document
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct JsonPatch {
+    operations: Vec<PatchOperation>,
+}
+
+impl JsonPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Patch that turns `from` into `to` (see `diff` for how arrays are compared)
+    pub fn diff(from: &JsonValue, to: &JsonValue) -> Self {
+        diff(from, to).into_iter().collect()
+    }
+
+    pub fn operations(&self) -> &[PatchOperation] {
+        &self.operations
+    }
+
+    pub fn push(&mut self, operation: PatchOperation) {
+        self.operations.push(operation);
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Apply every operation, or none of them if one fails
+    pub fn apply(&self, document: &mut JsonValue) -> Result<(), PatchError> {
+        let mut patched = document.clone();
+        for (index, operation) in self.operations.iter().enumerate() {
+            apply_operation(&mut patched, operation).map_err(|e| match e {
+                Failure::Pointer(error) => PatchError::Pointer { index, error },
+                Failure::Test(path) => PatchError::TestFailed { index, path },
+                Failure::MoveIntoChild(from) => PatchError::MoveIntoChild { index, from },
+            })?;
+        }
+        *document = patched;
+        Ok(())
+    }
+}
+
+impl FromIterator<PatchOperation> for JsonPatch {
+    fn from_iter<I: IntoIterator<Item = PatchOperation>>(iter: I) -> Self {
+        Self {
+            operations: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl From<Change> for PatchOperation {
+    fn from(change: Change) -> Self {
+        match change {
+            Change::Added { path, value } => PatchOperation::Add { path, value },
+            Change::Removed { path, .. } => PatchOperation::Remove { path },
+            Change::Changed { path, to, .. } => PatchOperation::Replace { path, value: to },
+        }
+    }
+}
+
+impl FromIterator<Change> for JsonPatch {
+    fn from_iter<I: IntoIterator<Item = Change>>(iter: I) -> Self {
+        iter.into_iter().map(PatchOperation::from).collect()
+    }
+}
+
+enum Failure {
+    Pointer(PointerError),
+    Test(JsonPointer),
+    MoveIntoChild(JsonPointer),
+}
+
+impl From<PointerError> for Failure {
+    fn from(e: PointerError) -> Self {
+        Failure::Pointer(e)
+    }
+}
+
+fn apply_operation(document: &mut JsonValue, operation: &PatchOperation) -> Result<(), Failure> {
+    match operation {
+        PatchOperation::Add { path, value } => add(document, path, value.clone())?,
+        PatchOperation::Remove { path } => {
+            remove(document, path)?;
+        }
+        PatchOperation::Replace { path, value } => {
+            let target = path.get_mut(document).ok_or_else(|| PointerError::NotFound(path.to_string()))?;
+            *target = value.clone();
+        }
+        PatchOperation::Move { from, path } => {
+            if from == path {
+                return Ok(());
+            }
+            if path.tokens().starts_with(from.tokens()) {
+                return Err(Failure::MoveIntoChild(from.clone()));
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)?;
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = from.get(document).cloned().ok_or_else(|| PointerError::NotFound(from.to_string()))?;
+            add(document, path, value)?;
+        }
+        PatchOperation::Test { path, value } => {
+            if path.get(document) != Some(value) {
+                return Err(Failure::Test(path.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn add(document: &mut JsonValue, path: &JsonPointer, value: JsonValue) -> Result<(), PointerError> {
+    let Some((parent, last)) = path.split_last() else {
+        *document = value;
+        return Ok(());
+    };
+    match parent.get_mut(document) {
+        Some(JsonValue::Object(obj)) => {
+            obj.insert(last, value);
+            Ok(())
+        }
+        Some(JsonValue::Array(items)) => {
+            let index = match last {
+                "-" => Some(items.len()),
+                _ => array_index(last).filter(|&i| i <= items.len()),
+            };
+            let index = index.ok_or_else(|| PointerError::InvalidIndex(last.to_string()))?;
+            items.insert(index, value);
+            Ok(())
+        }
+        Some(_) => Err(PointerError::NotContainer(parent.to_string())),
+        None => Err(PointerError::NotFound(parent.to_string())),
+    }
+}
+
+fn remove(document: &mut JsonValue, path: &JsonPointer) -> Result<JsonValue, PointerError> {
+    path.remove(document).ok_or_else(|| PointerError::NotFound(path.to_string()))
+}
+
+impl ToJson for PatchOperation {
+    fn to_json(&self) -> JsonValue {
+        let (op, path, from, value) = match self {
+            PatchOperation::Add { path, value } => ("add", path, None, Some(value)),
+            PatchOperation::Remove { path } => ("remove", path, None, None),
+            PatchOperation::Replace { path, value } => ("replace", path, None, Some(value)),
+            PatchOperation::Move { from, path } => ("move", path, Some(from), None),
+            PatchOperation::Copy { from, path } => ("copy", path, Some(from), None),
+            PatchOperation::Test { path, value } => ("test", path, None, Some(value)),
+        };
+        let mut obj = JsonObject::new();
+        obj.insert("op", JsonValue::String(op.to_string()));
+        if let Some(from) = from {
+            obj.insert("from", JsonValue::String(from.to_string()));
+        }
+        obj.insert("path", JsonValue::String(path.to_string()));
+        if let Some(value) = value {
+            obj.insert("value", value.clone());
+        }
+        JsonValue::Object(obj)
+    }
+}
+
+impl FromJson for PatchOperation {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let obj = value.as_object().ok_or_else(|| FromJsonError::expected("object", value))?;
+        let pointer = |name: &str| -> Result<JsonPointer, FromJsonError> {
+            let text = obj.get(name).ok_or_else(|| FromJsonError::missing_field(name))?;
+            let text = text.as_str().ok_or_else(|| FromJsonError::expected("string", text).in_field(name))?;
+            JsonPointer::parse(text).map_err(|e| FromJsonError::new(e.to_string()).in_field(name))
+        };
+        let value = || obj.get("value").cloned().ok_or_else(|| FromJsonError::missing_field("value"));
+
+        let op = String::from_json(obj.get("op").unwrap_or(&JsonValue::Null)).map_err(|e| e.in_field("op"))?;
+        Ok(match op.as_str() {
+            "add" => PatchOperation::Add { path: pointer("path")?, value: value()? },
+            "remove" => PatchOperation::Remove { path: pointer("path")? },
+            "replace" => PatchOperation::Replace { path: pointer("path")?, value: value()? },
+            "move" => PatchOperation::Move { from: pointer("from")?, path: pointer("path")? },
+            "copy" => PatchOperation::Copy { from: pointer("from")?, path: pointer("path")? },
+            "test" => PatchOperation::Test { path: pointer("path")?, value: value()? },
+            other => return Err(FromJsonError::new(format!("unknown operation \"{}\"", other)).in_field("op")),
+        })
+    }
+}
+
+impl ToJson for JsonPatch {
+    fn to_json(&self) -> JsonValue {
+        self.operations.to_json()
+    }
+}
+
+impl FromJson for JsonPatch {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        Vec::from_json(value).map(|operations| Self { operations })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn json(text: &str) -> JsonValue {
+        parse(text).unwrap()
+    }
+
+    fn patch(text: &str) -> JsonPatch {
+        JsonPatch::from_json(&json(text)).unwrap()
+    }
+
+    #[test]
+    fn test_merge_patch_rfc7386_examples() {
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#, r#"{"a":{"b":"d"}}"#),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+        ];
+        for (target, patch, expected) in cases {
+            let mut value = json(target);
+            merge_patch(&mut value, &json(patch));
+            assert_eq!(value.to_string(), expected, "{} + {}", target, patch);
+        }
+
+        let from = json(r#"{"price": 1850000, "features": ["Gym"], "agent": {"name": "A", "phone": "1"}}"#);
+        let to = json(r#"{"price": 1800000, "features": ["Gym"], "agent": {"name": "A"}, "status": "reduced"}"#);
+        let update = merge_diff(&from, &to).unwrap();
+        assert_eq!(update.to_string(), r#"{"price":1800000,"agent":{"phone":null},"status":"reduced"}"#);
+        let mut value = from.clone();
+        merge_patch(&mut value, &update);
+        assert_eq!(value, to);
+        assert_eq!(merge_diff(&to, &to), None);
+    }
+
+    #[test]
+    fn test_json_patch_rfc6902_examples() {
+        let cases = [
+            (r#"{"foo":"bar"}"#, r#"[{"op":"add","path":"/baz","value":"qux"}]"#, r#"{"foo":"bar","baz":"qux"}"#),
+            (r#"{"foo":["bar","baz"]}"#, r#"[{"op":"add","path":"/foo/1","value":"qux"}]"#, r#"{"foo":["bar","qux","baz"]}"#),
+            (r#"{"baz":"qux","foo":"bar"}"#, r#"[{"op":"remove","path":"/baz"}]"#, r#"{"foo":"bar"}"#),
+            (r#"{"foo":["bar","qux","baz"]}"#, r#"[{"op":"remove","path":"/foo/1"}]"#, r#"{"foo":["bar","baz"]}"#),
+            (r#"{"baz":"qux","foo":"bar"}"#, r#"[{"op":"replace","path":"/baz","value":"boo"}]"#, r#"{"baz":"boo","foo":"bar"}"#),
+            (
+                r#"{"foo":{"bar":"baz","waldo":"fred"},"qux":{"corge":"grault"}}"#,
+                r#"[{"op":"move","from":"/foo/waldo","path":"/qux/thud"}]"#,
+                r#"{"foo":{"bar":"baz"},"qux":{"corge":"grault","thud":"fred"}}"#,
+            ),
+            (r#"{"foo":["all","grass","cows","eat"]}"#, r#"[{"op":"move","from":"/foo/1","path":"/foo/3"}]"#, r#"{"foo":["all","cows","eat","grass"]}"#),
+            (r#"{"foo":["bar"]}"#, r#"[{"op":"add","path":"/foo/-","value":["abc","def"]}]"#, r#"{"foo":["bar",["abc","def"]]}"#),
+            (r#"{"foo":"bar"}"#, r#"[{"op":"copy","from":"/foo","path":"/baz"}]"#, r#"{"foo":"bar","baz":"bar"}"#),
+            (r#"{"baz":"qux","foo":["a",2,"c"]}"#, r#"[{"op":"test","path":"/baz","value":"qux"},{"op":"test","path":"/foo/1","value":2}]"#, r#"{"baz":"qux","foo":["a",2,"c"]}"#),
+        ];
+        for (document, operations, expected) in cases {
+            let mut value = json(document);
+            patch(operations).apply(&mut value).unwrap();
+            assert_eq!(value.to_string(), expected, "{}", operations);
+        }
+
+        // A failing operation leaves the document untouched
+        let mut value = json(r#"{"baz":"qux"}"#);
+        let failing = patch(r#"[{"op":"replace","path":"/baz","value":"boo"},{"op":"test","path":"/baz","value":"bar"}]"#);
+        assert_eq!(failing.apply(&mut value), Err(PatchError::TestFailed { index: 1, path: JsonPointer::parse("/baz").unwrap() }));
+        assert_eq!(value.to_string(), r#"{"baz":"qux"}"#);
+
+        let errors = [
+            (r#"[{"op":"add","path":"/missing/bat","value":"qux"}]"#, "Patch operation 0 failed: Nothing at JSON pointer \"/missing\""),
+            (r#"[{"op":"add","path":"/baz/bat","value":"qux"}]"#, "Patch operation 0 failed: Value at \"/baz\" is not an object or array"),
+            (r#"[{"op":"remove","path":"/nothing"}]"#, "Patch operation 0 failed: Nothing at JSON pointer \"/nothing\""),
+            (r#"[{"op":"add","path":"/list/5","value":1}]"#, "Patch operation 0 failed: Invalid array index \"5\""),
+            (r#"[{"op":"move","from":"/baz","path":"/baz/child"}]"#, "Patch operation 0: cannot move \"/baz\" into itself"),
+        ];
+        for (operations, message) in errors {
+            let mut value = json(r#"{"baz":"qux","list":[]}"#);
+            assert_eq!(patch(operations).apply(&mut value).unwrap_err().to_string(), message);
+        }
+
+        let bad = JsonPatch::from_json(&json(r#"[{"op":"add","path":"/a"}, {"op":"frobnicate","path":""}]"#));
+        assert_eq!(bad.unwrap_err().to_string(), "$[0].value: missing field");
+        let bad = JsonPatch::from_json(&json(r#"[{"op":"frobnicate","path":""}]"#));
+        assert_eq!(bad.unwrap_err().to_string(), "$[0].op: unknown operation \"frobnicate\"");
+    }
+
+    #[test]
+    fn test_diff_generates_patch() {
+        let from = json(include_str!("../../../docs/data/dubai-properties.json"));
+        let mut to = from.clone();
+        to.set_pointer("/properties/0/price", JsonValue::from(1_799_000)).unwrap();
+        to.set_pointer("/properties/1/features/0", JsonValue::String("Private lift".to_string())).unwrap();
+        to.remove_pointer("/properties/2/building");
+        to.remove_pointer("/properties/5");
+        to.set_pointer("/meta/updated", JsonValue::String("2025-02-01".to_string())).unwrap();
+
+        let changes = diff(&from, &to);
+        let lines: Vec<String> = changes.iter().map(ToString::to_string).collect();
+        assert_eq!(lines[0], "+ #/meta/updated: \"2025-02-01\"");
+        assert_eq!(lines[1], "~ #/properties/0/price: 1850000 → 1799000");
+        assert_eq!(lines[2], "~ #/properties/1/features/0: \"Burj Khalifa view\" → \"Private lift\"");
+        assert!(lines[3].starts_with("- #/properties/2/building: "));
+        assert!(lines[4].starts_with("- #/properties/5: {\"id\":\"dp-006\""));
+        assert_eq!(lines.len(), 5);
+
+        let patch = JsonPatch::diff(&from, &to);
+        let mut patched = from.clone();
+        patch.apply(&mut patched).unwrap();
+        assert_eq!(patched, to);
+
+        let roundtrip = JsonPatch::from_json(&patch.to_json()).unwrap();
+        assert_eq!(roundtrip, patch);
+        assert!(JsonPatch::diff(&to, &to).is_empty());
+
+        // Insertions and deletions inside arrays are single operations
+        let ops = |from: &str, to: &str| JsonPatch::diff(&json(from), &json(to)).to_json().to_string();
+        assert_eq!(ops(r#"["Gym","Parking"]"#, r#"["Sea view","Gym","Parking"]"#), r#"[{"op":"add","path":"/0","value":"Sea view"}]"#);
+        assert_eq!(ops(r#"["Gym","Pool","Parking"]"#, r#"["Gym","Parking"]"#), r#"[{"op":"remove","path":"/1"}]"#);
+    }
+}
@@ -0,0 +1,686 @@
+// AvilaJson Path - JSONPath query subset
+// Zero External Dependencies 🦀
+//
+// Supported syntax (RFC 9535 semantics where it applies):
+//   $                      root
+//   .name  ['name']        member
+//   .*  [*]                every member or element
+//   [2]  [-1]              array index (negative counts from the end)
+//   [1:5]  [::2]  [::-1]   array slice start:end:step
+//   [0,2]  ['a','b']       union
+//   ..name  ..*  ..[0]     recursive descent
+//   [?(@.bedrooms >= 2)]   filter on members/elements; operators == != < <= > >=,
+//                          && || ! and parentheses; `@.path` alone tests existence
+
+use crate::JsonValue;
+use std::cmp::Ordering;
+
+/// Compiled JSONPath expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPathError {
+    /// Character position in the expression
+    pub position: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "JSONPath error at {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for JsonPathError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Child(Selector),
+    Descendant(Selector),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice { start: Option<i64>, end: Option<i64>, step: i64 },
+    Union(Vec<Selector>),
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Or(Box<Filter>, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Exists(Vec<Key>),
+    Compare(Operand, CompareOp, Operand),
+}
+
+/// Step of a relative `@` path inside a filter
+#[derive(Debug, Clone, PartialEq)]
+enum Key {
+    Name(String),
+    Index(i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Current(Vec<Key>),
+    Literal(JsonValue),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl JsonPath {
+    pub fn parse(expression: &str) -> Result<Self, JsonPathError> {
+        PathParser::new(expression).parse_path()
+    }
+
+    /// Every value the expression selects, in document order per selector
+    pub fn query<'a>(&self, root: &'a JsonValue) -> Vec<&'a JsonValue> {
+        let mut current = vec![root];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for node in current {
+                match segment {
+                    Segment::Child(selector) => select(node, selector, &mut next),
+                    Segment::Descendant(selector) => {
+                        let mut stack = vec![node];
+                        while let Some(node) = stack.pop() {
+                            select(node, selector, &mut next);
+                            push_children_reversed(node, &mut stack);
+                        }
+                    }
+                }
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// Mutable references to the selected values, for patching in place
+    ///
+    /// Recursive descent is rejected because its matches may nest inside each
+    /// other. Each value is returned once, in document order.
+    pub fn query_mut<'a>(&self, root: &'a mut JsonValue) -> Result<Vec<&'a mut JsonValue>, JsonPathError> {
+        if self.segments.iter().any(|s| matches!(s, Segment::Descendant(_))) {
+            return Err(JsonPathError {
+                position: 0,
+                message: "recursive descent cannot be used for mutable queries".to_string(),
+            });
+        }
+
+        let mut current = vec![root];
+        for segment in &self.segments {
+            let Segment::Child(selector) = segment else { unreachable!() };
+            let mut next = Vec::new();
+            for node in current {
+                select_mut(node, selector, &mut next);
+            }
+            current = next;
+        }
+        Ok(current)
+    }
+}
+
+impl std::str::FromStr for JsonPath {
+    type Err = JsonPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Evaluation
+// ---------------------------------------------------------------------------
+
+fn push_children_reversed<'a>(node: &'a JsonValue, stack: &mut Vec<&'a JsonValue>) {
+    match node {
+        JsonValue::Array(items) => stack.extend(items.iter().rev()),
+        JsonValue::Object(obj) => stack.extend(obj.values().collect::<Vec<_>>().into_iter().rev()),
+        _ => {}
+    }
+}
+
+fn select<'a>(node: &'a JsonValue, selector: &Selector, out: &mut Vec<&'a JsonValue>) {
+    match (selector, node) {
+        (Selector::Union(selectors), _) => {
+            for selector in selectors {
+                select(node, selector, out);
+            }
+        }
+        (Selector::Name(name), JsonValue::Object(obj)) => out.extend(obj.get(name)),
+        (Selector::Wildcard, JsonValue::Object(obj)) => out.extend(obj.values()),
+        (Selector::Filter(filter), JsonValue::Object(obj)) => out.extend(obj.values().filter(|v| filter.matches(v))),
+        (Selector::Filter(filter), JsonValue::Array(items)) => out.extend(items.iter().filter(|v| filter.matches(v))),
+        (_, JsonValue::Array(items)) => out.extend(selector.array_indices(items.len()).into_iter().map(|i| &items[i])),
+        _ => {}
+    }
+}
+
+fn select_mut<'a>(node: &'a mut JsonValue, selector: &Selector, out: &mut Vec<&'a mut JsonValue>) {
+    match node {
+        JsonValue::Object(obj) => {
+            out.extend(obj.iter_mut().filter(|(k, v)| selector.matches_member(k, v)).map(|(_, v)| v));
+        }
+        JsonValue::Array(items) => {
+            let mut wanted = vec![false; items.len()];
+            for i in selector.array_indices(items.len()) {
+                wanted[i] = true;
+            }
+            for (i, item) in items.iter().enumerate() {
+                if selector.matches_element(item) {
+                    wanted[i] = true;
+                }
+            }
+            out.extend(items.iter_mut().zip(wanted).filter(|(_, w)| *w).map(|(v, _)| v));
+        }
+        _ => {}
+    }
+}
+
+impl Selector {
+    /// Object members picked by this selector
+    fn matches_member(&self, key: &str, value: &JsonValue) -> bool {
+        match self {
+            Selector::Name(name) => name == key,
+            Selector::Wildcard => true,
+            Selector::Filter(filter) => filter.matches(value),
+            Selector::Union(selectors) => selectors.iter().any(|s| s.matches_member(key, value)),
+            Selector::Index(_) | Selector::Slice { .. } => false,
+        }
+    }
+
+    /// Array elements picked by a filter, which depend on the element itself
+    fn matches_element(&self, value: &JsonValue) -> bool {
+        match self {
+            Selector::Filter(filter) => filter.matches(value),
+            Selector::Union(selectors) => selectors.iter().any(|s| s.matches_element(value)),
+            _ => false,
+        }
+    }
+
+    /// Array positions picked by index, slice and wildcard selectors, in selection order
+    fn array_indices(&self, len: usize) -> Vec<usize> {
+        let len = len as i64;
+        let normalize = |i: i64| if i < 0 { len + i } else { i };
+        match *self {
+            Selector::Wildcard => (0..len as usize).collect(),
+            Selector::Index(i) => {
+                let i = normalize(i);
+                if (0..len).contains(&i) {
+                    vec![i as usize]
+                } else {
+                    Vec::new()
+                }
+            }
+            Selector::Slice { start, end, step } => {
+                let mut indices = Vec::new();
+                if step > 0 {
+                    let mut i = start.map_or(0, normalize).clamp(0, len);
+                    let upper = end.map_or(len, normalize).clamp(0, len);
+                    while i < upper {
+                        indices.push(i as usize);
+                        match i.checked_add(step) {
+                            Some(next) => i = next,
+                            None => break,
+                        }
+                    }
+                } else if step < 0 {
+                    let mut i = start.map_or(len - 1, normalize).clamp(-1, len - 1);
+                    let lower = end.map_or(-1, normalize).clamp(-1, len - 1);
+                    while i > lower {
+                        indices.push(i as usize);
+                        match i.checked_add(step) {
+                            Some(next) => i = next,
+                            None => break,
+                        }
+                    }
+                }
+                indices
+            }
+            Selector::Union(ref selectors) => selectors.iter().flat_map(|s| s.array_indices(len as usize)).collect(),
+            Selector::Name(_) | Selector::Filter(_) => Vec::new(),
+        }
+    }
+}
+
+impl Filter {
+    fn matches(&self, current: &JsonValue) -> bool {
+        match self {
+            Filter::Or(a, b) => a.matches(current) || b.matches(current),
+            Filter::And(a, b) => a.matches(current) && b.matches(current),
+            Filter::Not(inner) => !inner.matches(current),
+            Filter::Exists(keys) => resolve(current, keys).is_some(),
+            Filter::Compare(left, op, right) => {
+                let left = left.resolve(current);
+                let right = right.resolve(current);
+                match op {
+                    CompareOp::Eq => left == right,
+                    CompareOp::Ne => left != right,
+                    CompareOp::Lt => compare(left, right) == Some(Ordering::Less),
+                    CompareOp::Gt => compare(left, right) == Some(Ordering::Greater),
+                    CompareOp::Le => left == right || compare(left, right) == Some(Ordering::Less),
+                    CompareOp::Ge => left == right || compare(left, right) == Some(Ordering::Greater),
+                }
+            }
+        }
+    }
+}
+
+impl Operand {
+    fn resolve<'a>(&'a self, current: &'a JsonValue) -> Option<&'a JsonValue> {
+        match self {
+            Operand::Current(keys) => resolve(current, keys),
+            Operand::Literal(value) => Some(value),
+        }
+    }
+}
+
+fn resolve<'a>(current: &'a JsonValue, keys: &[Key]) -> Option<&'a JsonValue> {
+    keys.iter().try_fold(current, |node, key| match (key, node) {
+        (Key::Name(name), JsonValue::Object(obj)) => obj.get(name),
+        (Key::Index(i), JsonValue::Array(items)) => {
+            let i = if *i < 0 { items.len() as i64 + i } else { *i };
+            usize::try_from(i).ok().and_then(|i| items.get(i))
+        }
+        _ => None,
+    })
+}
+
+/// Ordering for `<`/`>`: only numbers with numbers and strings with strings
+fn compare(left: Option<&JsonValue>, right: Option<&JsonValue>) -> Option<Ordering> {
+    match (left?, right?) {
+        (JsonValue::String(a), JsonValue::String(b)) => Some(a.cmp(b)),
+        (JsonValue::Number(a), JsonValue::Number(b)) => a.partial_cmp(b),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+struct PathParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl PathParser {
+    fn new(expression: &str) -> Self {
+        Self {
+            chars: expression.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, JsonPathError> {
+        Err(JsonPathError {
+            position: self.pos,
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let n = s.chars().count();
+        if self.chars[self.pos..].iter().take(n).copied().eq(s.chars()) {
+            self.pos += n;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), JsonPathError> {
+        self.skip_whitespace();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            self.error(format!("expected '{}'", c))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_path(mut self) -> Result<JsonPath, JsonPathError> {
+        if !self.eat('$') {
+            return self.error("expression must start with '$'");
+        }
+        let mut segments = Vec::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '.' if self.eat_str("..") => {
+                    let selector = if self.peek() == Some('[') {
+                        self.pos += 1;
+                        self.parse_bracket()?
+                    } else {
+                        self.parse_dot_selector()?
+                    };
+                    segments.push(Segment::Descendant(selector));
+                }
+                '.' => {
+                    self.pos += 1;
+                    segments.push(Segment::Child(self.parse_dot_selector()?));
+                }
+                '[' => {
+                    self.pos += 1;
+                    segments.push(Segment::Child(self.parse_bracket()?));
+                }
+                _ => return self.error(format!("unexpected '{}'", c)),
+            }
+        }
+        Ok(JsonPath { segments })
+    }
+
+    fn parse_dot_selector(&mut self) -> Result<Selector, JsonPathError> {
+        if self.eat('*') {
+            return Ok(Selector::Wildcard);
+        }
+        Ok(Selector::Name(self.parse_name()?))
+    }
+
+    fn parse_name(&mut self) -> Result<String, JsonPathError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '$') {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return self.error("expected a member name");
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// Selector list after `[`, up to and including `]`
+    fn parse_bracket(&mut self) -> Result<Selector, JsonPathError> {
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.parse_bracket_item()?);
+            self.skip_whitespace();
+            if self.eat(']') {
+                break;
+            }
+            self.expect(',')?;
+        }
+        Ok(if selectors.len() == 1 { selectors.remove(0) } else { Selector::Union(selectors) })
+    }
+
+    fn parse_bracket_item(&mut self) -> Result<Selector, JsonPathError> {
+        match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('\'') | Some('"') => Ok(Selector::Name(self.parse_quoted()?)),
+            Some('?') => {
+                self.pos += 1;
+                Ok(Selector::Filter(self.parse_or()?))
+            }
+            _ => {
+                let start = self.parse_optional_int()?;
+                self.skip_whitespace();
+                if !self.eat(':') {
+                    return match start {
+                        Some(i) => Ok(Selector::Index(i)),
+                        None => self.error("expected a selector"),
+                    };
+                }
+                let end = self.parse_optional_int()?;
+                self.skip_whitespace();
+                let step = if self.eat(':') { self.parse_optional_int()?.unwrap_or(1) } else { 1 };
+                Ok(Selector::Slice { start, end, step })
+            }
+        }
+    }
+
+    fn parse_optional_int(&mut self) -> Result<Option<i64>, JsonPathError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Ok(None);
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        match text.parse() {
+            Ok(n) => Ok(Some(n)),
+            Err(_) => {
+                self.pos = start;
+                self.error(format!("invalid integer '{}'", text))
+            }
+        }
+    }
+
+    fn parse_quoted(&mut self) -> Result<String, JsonPathError> {
+        let quote = self.chars[self.pos];
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return self.error("unterminated string"),
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(c @ ('\\' | '\'' | '"' | '/')) => out.push(c),
+                        Some('n') => out.push('\n'),
+                        Some('t') => out.push('\t'),
+                        _ => return self.error("invalid escape"),
+                    }
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, JsonPathError> {
+        let mut left = self.parse_and()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat_str("||") {
+                return Ok(left);
+            }
+            left = Filter::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, JsonPathError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat_str("&&") {
+                return Ok(left);
+            }
+            left = Filter::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, JsonPathError> {
+        self.skip_whitespace();
+        if self.peek() == Some('!') && self.chars.get(self.pos + 1) != Some(&'=') {
+            self.pos += 1;
+            return Ok(Filter::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat('(') {
+            let inner = self.parse_or()?;
+            self.expect(')')?;
+            return Ok(inner);
+        }
+
+        let left = self.parse_operand()?;
+        self.skip_whitespace();
+        let op = [
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ]
+        .into_iter()
+        .find(|(text, _)| self.eat_str(text));
+
+        match (op, left) {
+            (Some((_, op)), left) => Ok(Filter::Compare(left, op, self.parse_operand()?)),
+            (None, Operand::Current(keys)) => Ok(Filter::Exists(keys)),
+            (None, Operand::Literal(_)) => self.error("expected a comparison operator"),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, JsonPathError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('@') => {
+                self.pos += 1;
+                let mut keys = Vec::new();
+                loop {
+                    if self.eat('.') {
+                        keys.push(Key::Name(self.parse_name()?));
+                    } else if self.eat('[') {
+                        self.skip_whitespace();
+                        let key = match self.peek() {
+                            Some('\'') | Some('"') => Key::Name(self.parse_quoted()?),
+                            _ => match self.parse_optional_int()? {
+                                Some(i) => Key::Index(i),
+                                None => return self.error("expected a name or index"),
+                            },
+                        };
+                        self.expect(']')?;
+                        keys.push(key);
+                    } else {
+                        return Ok(Operand::Current(keys));
+                    }
+                }
+            }
+            Some('\'') | Some('"') => Ok(Operand::Literal(JsonValue::String(self.parse_quoted()?))),
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_alphanumeric() || "+-.".contains(c)) {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                match crate::parse(&text) {
+                    Ok(value @ (JsonValue::Number(_) | JsonValue::Bool(_) | JsonValue::Null)) => Ok(Operand::Literal(value)),
+                    _ => {
+                        self.pos = start;
+                        self.error("expected '@', a string, a number, true, false or null")
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn listings() -> JsonValue {
+        parse(
+            r#"{"properties": [
+                {"id": "dp-001", "bedrooms": 2, "price": 1850000, "location": "Dubai Marina"},
+                {"id": "dp-002", "bedrooms": 3, "price": 3200000, "location": "Downtown Dubai"},
+                {"id": "dp-003", "bedrooms": 1, "price": 950000, "location": "JLT", "tags": ["ready"]},
+                {"id": "dp-004", "price": 4100000}
+            ], "meta": {"currency": "AED"}}"#,
+        )
+        .unwrap()
+    }
+
+    fn ids(values: Vec<&JsonValue>) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_selectors() {
+        let doc = listings();
+        let q = |path: &str| ids(doc.query(path).unwrap());
+
+        assert_eq!(q("$.meta.currency"), [r#""AED""#]);
+        assert_eq!(q("$['meta']['currency']"), [r#""AED""#]);
+        assert_eq!(q("$.properties[-1].price"), ["4100000"]);
+        assert_eq!(q("$.properties[*].bedrooms"), ["2", "3", "1"]);
+        assert_eq!(q("$.properties[1:3].id"), [r#""dp-002""#, r#""dp-003""#]);
+        assert_eq!(q("$.properties[::-2].id"), [r#""dp-004""#, r#""dp-002""#]);
+        assert_eq!(q("$.properties[3,0].id"), [r#""dp-004""#, r#""dp-001""#]);
+        assert_eq!(q("$..tags[0]"), [r#""ready""#]);
+        assert_eq!(q("$..currency"), [r#""AED""#]);
+        assert_eq!(doc.query("$..*").unwrap().len(), 23);
+        assert!(q("$.properties[9]").is_empty());
+
+        // Steps past the end stop instead of overflowing
+        assert_eq!(q("$.properties[1::9223372036854775807].id"), [r#""dp-002""#]);
+        assert_eq!(q("$.properties[-2::-9223372036854775808].id"), [r#""dp-003""#]);
+    }
+
+    #[test]
+    fn test_filters() {
+        let doc = listings();
+        let q = |path: &str| ids(doc.query(path).unwrap());
+
+        assert_eq!(q("$.properties[?(@.bedrooms>=2)].price"), ["1850000", "3200000"]);
+        assert_eq!(q("$.properties[?(@.bedrooms < 2 || !@.bedrooms)].id"), [r#""dp-003""#, r#""dp-004""#]);
+        assert_eq!(q("$.properties[?@.location == 'JLT' && @.tags[0] == \"ready\"].id"), [r#""dp-003""#]);
+        assert_eq!(q("$.properties[?(@.tags)].id"), [r#""dp-003""#]);
+        assert_eq!(q("$.properties[?(@.location > 'E')].id"), [r#""dp-003""#]);
+
+        let err = doc.query("$.properties[?(@.price >> 1)]").unwrap_err();
+        assert_eq!(err.position, 24);
+        assert!(doc.query("properties").is_err());
+        assert!(doc.query("$.properties[1").is_err());
+    }
+
+    #[test]
+    fn test_query_mut() {
+        let mut doc = listings();
+        for price in doc.query_mut("$.properties[?(@.bedrooms >= 2)].price").unwrap() {
+            *price = JsonValue::from(price.as_i64().unwrap() + 50_000);
+        }
+        assert_eq!(ids(doc.query("$.properties[0,1,2].price").unwrap()), ["1900000", "3250000", "950000"]);
+
+        // Unions select each element once when borrowed mutably
+        assert_eq!(doc.query_mut("$.properties[0,0,-4]").unwrap().len(), 1);
+        assert!(doc.query_mut("$..price").is_err());
+    }
+}
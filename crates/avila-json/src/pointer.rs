@@ -0,0 +1,242 @@
+// AvilaJson Pointer - RFC 6901 JSON Pointer
+// Zero External Dependencies 🦀
+
+use crate::JsonValue;
+
+/// Parsed JSON Pointer such as `/properties/0/price`
+///
+/// The empty pointer `""` refers to the whole document. In reference tokens
+/// `~1` stands for `/` and `~0` for `~`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointerError {
+    /// Not a valid pointer string (missing leading `/` or a bad `~` escape)
+    Syntax(String),
+    /// A parent of the target does not exist
+    NotFound(String),
+    /// Token is not a valid index for the array it addresses
+    InvalidIndex(String),
+    /// Path walks through a scalar
+    NotContainer(String),
+}
+
+impl std::fmt::Display for PointerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PointerError::Syntax(msg) => write!(f, "Invalid JSON pointer: {}", msg),
+            PointerError::NotFound(ptr) => write!(f, "Nothing at JSON pointer \"{}\"", ptr),
+            PointerError::InvalidIndex(token) => write!(f, "Invalid array index \"{}\"", token),
+            PointerError::NotContainer(ptr) => write!(f, "Value at \"{}\" is not an object or array", ptr),
+        }
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+impl JsonPointer {
+    /// The empty pointer (whole document)
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(pointer: &str) -> Result<Self, PointerError> {
+        if pointer.is_empty() {
+            return Ok(Self::root());
+        }
+        let rest = pointer
+            .strip_prefix('/')
+            .ok_or_else(|| PointerError::Syntax(format!("\"{}\" must start with '/'", pointer)))?;
+
+        let tokens = rest.split('/').map(unescape).collect::<Result<_, _>>()?;
+        Ok(Self { tokens })
+    }
+
+    pub fn from_tokens<I, S>(tokens: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            tokens: tokens.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Append a reference token (an object key or an array index)
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    /// Pointer to the containing value and the last token, or `None` for the root
+    pub fn split_last(&self) -> Option<(JsonPointer, &str)> {
+        let (last, parent) = self.tokens.split_last()?;
+        Some((Self { tokens: parent.to_vec() }, last))
+    }
+
+    pub fn get<'a>(&self, value: &'a JsonValue) -> Option<&'a JsonValue> {
+        self.tokens.iter().try_fold(value, |current, token| match current {
+            JsonValue::Object(obj) => obj.get(token),
+            JsonValue::Array(items) => items.get(array_index(token)?),
+            _ => None,
+        })
+    }
+
+    pub fn get_mut<'a>(&self, value: &'a mut JsonValue) -> Option<&'a mut JsonValue> {
+        self.tokens.iter().try_fold(value, |current, token| match current {
+            JsonValue::Object(obj) => obj.get_mut(token),
+            JsonValue::Array(items) => items.get_mut(array_index(token)?),
+            _ => None,
+        })
+    }
+
+    /// Replace the target or add it to its parent, returning any previous value
+    ///
+    /// In arrays an index equal to the length, or `-`, appends. The parent must
+    /// already exist.
+    pub fn set(&self, value: &mut JsonValue, new: JsonValue) -> Result<Option<JsonValue>, PointerError> {
+        let Some((parent, last)) = self.split_last() else {
+            return Ok(Some(std::mem::replace(value, new)));
+        };
+        match parent.get_mut(value) {
+            Some(JsonValue::Object(obj)) => Ok(obj.insert(last, new)),
+            Some(JsonValue::Array(items)) => {
+                let index = match last {
+                    "-" => items.len(),
+                    _ => array_index(last).ok_or_else(|| PointerError::InvalidIndex(last.to_string()))?,
+                };
+                match index.cmp(&items.len()) {
+                    std::cmp::Ordering::Less => Ok(Some(std::mem::replace(&mut items[index], new))),
+                    std::cmp::Ordering::Equal => {
+                        items.push(new);
+                        Ok(None)
+                    }
+                    std::cmp::Ordering::Greater => Err(PointerError::InvalidIndex(last.to_string())),
+                }
+            }
+            Some(_) => Err(PointerError::NotContainer(parent.to_string())),
+            None => Err(PointerError::NotFound(parent.to_string())),
+        }
+    }
+
+    /// Remove the target from its parent; the root cannot be removed
+    pub fn remove(&self, value: &mut JsonValue) -> Option<JsonValue> {
+        let (parent, last) = self.split_last()?;
+        match parent.get_mut(value)? {
+            JsonValue::Object(obj) => obj.remove(last),
+            JsonValue::Array(items) => {
+                let index = array_index(last).filter(|&i| i < items.len())?;
+                Some(items.remove(index))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for token in &self.tokens {
+            write!(f, "/{}", token.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for JsonPointer {
+    type Err = PointerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn unescape(token: &str) -> Result<String, PointerError> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('0') => out.push('~'),
+            Some('1') => out.push('/'),
+            _ => return Err(PointerError::Syntax(format!("bad '~' escape in \"{}\"", token))),
+        }
+    }
+    Ok(out)
+}
+
+/// RFC 6901 array index: `0` or digits without a leading zero
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    let valid = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()) && (token == "0" || !token.starts_with('0'));
+    if valid {
+        token.parse().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_rfc6901_examples() {
+        let doc = parse(r#"{"foo": ["bar", "baz"], "": 0, "a/b": 1, "c%d": 2, "e^f": 3, "g|h": 4, "i\\j": 5, "k\"l": 6, " ": 7, "m~n": 8}"#).unwrap();
+        let cases = [
+            ("/foo/0", r#""bar""#),
+            ("/", "0"),
+            ("/a~1b", "1"),
+            ("/c%d", "2"),
+            ("/i\\j", "5"),
+            ("/k\"l", "6"),
+            ("/ ", "7"),
+            ("/m~0n", "8"),
+        ];
+        for (pointer, expected) in cases {
+            assert_eq!(doc.pointer(pointer).map(|v| v.to_string()).as_deref(), Some(expected), "{}", pointer);
+        }
+        assert_eq!(doc.pointer(""), Some(&doc));
+        assert_eq!(doc.pointer("/foo/01"), None);
+        assert_eq!(doc.pointer("/foo/2"), None);
+
+        let pointer = JsonPointer::parse("/m~0n/a~1b").unwrap();
+        assert_eq!(pointer.tokens(), ["m~n", "a/b"]);
+        assert_eq!(pointer.to_string(), "/m~0n/a~1b");
+        assert!(matches!(JsonPointer::parse("foo"), Err(PointerError::Syntax(_))));
+        assert!(matches!(JsonPointer::parse("/a~2"), Err(PointerError::Syntax(_))));
+    }
+
+    #[test]
+    fn test_set_and_remove() {
+        let mut doc = parse(r#"{"listing": {"price": 1, "features": ["Balcony"]}}"#).unwrap();
+
+        assert_eq!(doc.set_pointer("/listing/price", JsonValue::from(2)), Ok(Some(JsonValue::from(1))));
+        assert_eq!(doc.set_pointer("/listing/features/-", JsonValue::String("Gym".to_string())), Ok(None));
+        assert_eq!(doc.set_pointer("/listing/currency", JsonValue::String("AED".to_string())), Ok(None));
+        assert_eq!(doc.to_string(), r#"{"listing":{"price":2,"features":["Balcony","Gym"],"currency":"AED"}}"#);
+
+        assert_eq!(doc.set_pointer("/listing/features/5", JsonValue::Null), Err(PointerError::InvalidIndex("5".to_string())));
+        assert_eq!(doc.set_pointer("/missing/key", JsonValue::Null), Err(PointerError::NotFound("/missing".to_string())));
+        assert_eq!(doc.set_pointer("/listing/price/x", JsonValue::Null), Err(PointerError::NotContainer("/listing/price".to_string())));
+
+        if let Some(price) = doc.pointer_mut("/listing/price") {
+            *price = JsonValue::from(3);
+        }
+        assert_eq!(doc.remove_pointer("/listing/features/0"), Some(JsonValue::String("Balcony".to_string())));
+        assert_eq!(doc.remove_pointer("/listing/nothing"), None);
+        assert_eq!(doc.to_string(), r#"{"listing":{"price":3,"features":["Gym"],"currency":"AED"}}"#);
+    }
+}
@@ -0,0 +1,668 @@
+// AvilaJson Schema - JSON Schema validation (draft 2020-12 subset)
+// Zero External Dependencies 🦀
+//
+// Supported keywords:
+//   any value   type enum const allOf anyOf oneOf not if/then/else $ref $defs
+//   numbers     minimum maximum exclusiveMinimum exclusiveMaximum multipleOf
+//   strings     minLength maxLength format (date, date-time, email, uri)
+//   arrays      items prefixItems contains minItems maxItems uniqueItems
+//   objects     properties required additionalProperties propertyNames
+//               minProperties maxProperties dependentRequired
+// `$ref` must be a local pointer such as `#/$defs/price`. Regex keywords
+// (pattern, patternProperties) are rejected instead of silently ignored;
+// unknown keywords and annotations (title, description, ...) are skipped.
+
+use crate::{parse, JsonObject, JsonPointer, JsonValue};
+
+/// `$ref` hops allowed without moving into a child value (catches `$ref` cycles)
+const MAX_REF_CHAIN: usize = 32;
+
+const TYPE_NAMES: [&str; 7] = ["null", "boolean", "object", "array", "number", "integer", "string"];
+const UNSUPPORTED: [&str; 5] = ["pattern", "patternProperties", "$dynamicRef", "unevaluatedItems", "unevaluatedProperties"];
+
+/// Checked schema, ready to validate values
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonSchema {
+    root: JsonValue,
+}
+
+/// The schema itself is malformed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// Location in the schema document
+    pub location: JsonPointer,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Invalid schema at #{}: {}", self.location, self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// One way in which a value breaks the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Offending value in the validated document
+    pub instance_path: JsonPointer,
+    /// Keyword that failed, in the schema document
+    pub schema_path: JsonPointer,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "#{}: {}", self.instance_path, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl JsonSchema {
+    pub fn new(schema: JsonValue) -> Result<Self, SchemaError> {
+        check_schema(&schema, &schema, &JsonPointer::root())?;
+        Ok(Self { root: schema })
+    }
+
+    /// Parse and check a schema document
+    pub fn parse(text: &str) -> Result<Self, SchemaError> {
+        let schema = parse(text).map_err(|e| SchemaError {
+            location: JsonPointer::root(),
+            message: e.to_string(),
+        })?;
+        Self::new(schema)
+    }
+
+    pub fn schema(&self) -> &JsonValue {
+        &self.root
+    }
+
+    /// Every violation, in document order
+    pub fn validate(&self, instance: &JsonValue) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let root = JsonPointer::root();
+        self.check(&self.root, instance, &root, &root, 0, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn is_valid(&self, instance: &JsonValue) -> bool {
+        self.validate(instance).is_ok()
+    }
+
+    fn matches(&self, schema: &JsonValue, instance: &JsonValue, refs: usize) -> bool {
+        let mut errors = Vec::new();
+        let root = JsonPointer::root();
+        self.check(schema, instance, &root, &root, refs, &mut errors);
+        errors.is_empty()
+    }
+
+    fn check(
+        &self,
+        schema: &JsonValue,
+        instance: &JsonValue,
+        at: &JsonPointer,
+        schema_path: &JsonPointer,
+        refs: usize,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let keywords = match schema {
+            JsonValue::Object(keywords) => keywords,
+            JsonValue::Bool(false) => return fail(errors, at, schema_path.clone(), "no value is allowed here".to_string()),
+            _ => return,
+        };
+
+        for (keyword, value) in keywords {
+            let here = child(schema_path, keyword);
+
+            match (keyword.as_str(), instance) {
+                ("$ref", _) if refs >= MAX_REF_CHAIN => {
+                    fail(errors, at, here, "$ref chain too long (cycle in the schema?)".to_string());
+                }
+                ("$ref", _) => {
+                    if let Some(target) = value.as_str().and_then(|r| resolve(&self.root, r)) {
+                        self.check(target, instance, at, &here, refs + 1, errors);
+                    }
+                }
+
+                ("prefixItems", JsonValue::Array(items)) => {
+                    let schemas = value.as_array().map(Vec::as_slice).unwrap_or_default();
+                    for (i, (sub, item)) in schemas.iter().zip(items).enumerate() {
+                        self.check(sub, item, &child(at, i), &child(&here, i), 0, errors);
+                    }
+                }
+                ("items", JsonValue::Array(items)) => {
+                    let skip = keywords.get("prefixItems").and_then(JsonValue::as_array).map_or(0, Vec::len);
+                    for (i, item) in items.iter().enumerate().skip(skip) {
+                        self.check(value, item, &child(at, i), &here, 0, errors);
+                    }
+                }
+
+                ("required", JsonValue::Object(obj)) => {
+                    for name in value.as_array().into_iter().flatten().filter_map(JsonValue::as_str) {
+                        if !obj.contains_key(name) {
+                            fail(errors, at, here.clone(), format!("missing required property \"{}\"", name));
+                        }
+                    }
+                }
+                ("dependentRequired", JsonValue::Object(obj)) => {
+                    for (name, needed) in value.as_object().into_iter().flatten() {
+                        let needed = needed.as_array().into_iter().flatten().filter_map(JsonValue::as_str);
+                        for other in needed.filter(|other| obj.contains_key(name) && !obj.contains_key(other)) {
+                            fail(errors, at, here.clone(), format!("property \"{}\" requires \"{}\"", name, other));
+                        }
+                    }
+                }
+                ("properties", JsonValue::Object(obj)) => {
+                    for (name, sub) in value.as_object().into_iter().flatten() {
+                        if let Some(member) = obj.get(name) {
+                            self.check(sub, member, &child(at, name), &child(&here, name), 0, errors);
+                        }
+                    }
+                }
+                ("additionalProperties", JsonValue::Object(obj)) => {
+                    let declared = keywords.get("properties").and_then(JsonValue::as_object);
+                    for (name, member) in obj {
+                        if declared.is_some_and(|declared| declared.contains_key(name)) {
+                            continue;
+                        }
+                        if *value == JsonValue::Bool(false) {
+                            fail(errors, at, here.clone(), format!("property \"{}\" is not allowed", name));
+                        } else {
+                            self.check(value, member, &child(at, name), &here, 0, errors);
+                        }
+                    }
+                }
+                ("propertyNames", JsonValue::Object(obj)) => {
+                    for name in obj.keys() {
+                        if !self.matches(value, &JsonValue::String(name.clone()), 0) {
+                            fail(errors, at, here.clone(), format!("property name \"{}\" does not match the schema", name));
+                        }
+                    }
+                }
+
+                ("allOf", _) => {
+                    for (i, sub) in value.as_array().into_iter().flatten().enumerate() {
+                        self.check(sub, instance, at, &child(&here, i), refs, errors);
+                    }
+                }
+                ("if", _) => {
+                    let branch = if self.matches(value, instance, refs) { "then" } else { "else" };
+                    if let Some(sub) = keywords.get(branch) {
+                        self.check(sub, instance, at, &child(schema_path, branch), refs, errors);
+                    }
+                }
+
+                _ => {
+                    if let Some(message) = self.assertion(keyword, value, instance, refs) {
+                        fail(errors, at, here, message);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Keywords that fail at most once on the value itself
+    fn assertion(&self, keyword: &str, value: &JsonValue, instance: &JsonValue, refs: usize) -> Option<String> {
+        let limit = value.as_u64();
+        match (keyword, instance) {
+            ("type", _) => {
+                let names: Vec<&str> = match value {
+                    JsonValue::Array(names) => names.iter().filter_map(JsonValue::as_str).collect(),
+                    _ => value.as_str().into_iter().collect(),
+                };
+                let ok = names.iter().any(|name| has_type(instance, name));
+                (!ok).then(|| format!("expected {}, found {}", names.join(" or "), instance.type_name()))
+            }
+            ("enum", _) => {
+                let ok = value.as_array().is_some_and(|allowed| allowed.contains(instance));
+                (!ok).then(|| format!("{} is not one of {}", instance, value))
+            }
+            ("const", _) => (instance != value).then(|| format!("expected {}, found {}", value, instance)),
+
+            ("minimum", JsonValue::Number(n)) => value
+                .as_number()
+                .filter(|limit| n < *limit)
+                .map(|limit| format!("{} is less than the minimum {}", n, limit)),
+            ("maximum", JsonValue::Number(n)) => value
+                .as_number()
+                .filter(|limit| n > *limit)
+                .map(|limit| format!("{} is greater than the maximum {}", n, limit)),
+            ("exclusiveMinimum", JsonValue::Number(n)) => value
+                .as_number()
+                .filter(|limit| n <= *limit)
+                .map(|limit| format!("{} must be greater than {}", n, limit)),
+            ("exclusiveMaximum", JsonValue::Number(n)) => value
+                .as_number()
+                .filter(|limit| n >= *limit)
+                .map(|limit| format!("{} must be less than {}", n, limit)),
+            ("multipleOf", JsonValue::Number(n)) => {
+                let divisor = value.as_number()?;
+                let exact = match (n.as_decimal(), divisor.as_decimal()) {
+                    (Some(a), Some(b)) => a.is_multiple_of(&b),
+                    _ => None,
+                };
+                let multiple = exact.unwrap_or_else(|| {
+                    let quotient = n.as_f64() / divisor.as_f64();
+                    quotient.is_finite() && quotient.fract() == 0.0
+                });
+                (!multiple).then(|| format!("{} is not a multiple of {}", n, divisor))
+            }
+
+            ("minLength", JsonValue::String(s)) => limit
+                .filter(|&min| (s.chars().count() as u64) < min)
+                .map(|min| format!("string is shorter than {} characters", min)),
+            ("maxLength", JsonValue::String(s)) => limit
+                .filter(|&max| s.chars().count() as u64 > max)
+                .map(|max| format!("string is longer than {} characters", max)),
+            ("format", JsonValue::String(s)) => {
+                let format = value.as_str()?;
+                (check_format(format, s) == Some(false)).then(|| format!("\"{}\" is not a valid {}", s, format))
+            }
+
+            ("minItems", JsonValue::Array(items)) => limit
+                .filter(|&min| (items.len() as u64) < min)
+                .map(|min| format!("array has {} items, fewer than {}", items.len(), min)),
+            ("maxItems", JsonValue::Array(items)) => limit
+                .filter(|&max| items.len() as u64 > max)
+                .map(|max| format!("array has {} items, more than {}", items.len(), max)),
+            ("uniqueItems", JsonValue::Array(items)) if value.as_bool() == Some(true) => {
+                let duplicate = (0..items.len()).find_map(|j| (0..j).find(|&i| items[i] == items[j]).map(|i| (i, j)));
+                duplicate.map(|(i, j)| format!("items {} and {} are equal", i, j))
+            }
+            ("contains", JsonValue::Array(items)) => {
+                let found = items.iter().any(|item| self.matches(value, item, 0));
+                (!found).then(|| "no item matches the \"contains\" schema".to_string())
+            }
+
+            ("minProperties", JsonValue::Object(obj)) => limit
+                .filter(|&min| (obj.len() as u64) < min)
+                .map(|min| format!("object has {} properties, fewer than {}", obj.len(), min)),
+            ("maxProperties", JsonValue::Object(obj)) => limit
+                .filter(|&max| obj.len() as u64 > max)
+                .map(|max| format!("object has {} properties, more than {}", obj.len(), max)),
+
+            ("anyOf", _) => {
+                let ok = value.as_array().into_iter().flatten().any(|sub| self.matches(sub, instance, refs));
+                (!ok).then(|| "value does not match any schema in anyOf".to_string())
+            }
+            ("oneOf", _) => match value.as_array().into_iter().flatten().filter(|sub| self.matches(sub, instance, refs)).count() {
+                0 => Some("value does not match any schema in oneOf".to_string()),
+                1 => None,
+                count => Some(format!("value matches {} schemas in oneOf, expected exactly one", count)),
+            },
+            ("not", _) => self
+                .matches(value, instance, refs)
+                .then(|| "value must not match the schema in \"not\"".to_string()),
+
+            _ => None,
+        }
+    }
+}
+
+fn fail(errors: &mut Vec<ValidationError>, at: &JsonPointer, schema_path: JsonPointer, message: String) {
+    errors.push(ValidationError {
+        instance_path: at.clone(),
+        schema_path,
+        message,
+    });
+}
+
+fn child(pointer: &JsonPointer, token: impl ToString) -> JsonPointer {
+    let mut child = pointer.clone();
+    child.push(token.to_string());
+    child
+}
+
+fn has_type(value: &JsonValue, name: &str) -> bool {
+    match name {
+        "integer" => value.as_number().is_some_and(|n| n.has_integer_value()),
+        _ => value.type_name() == name,
+    }
+}
+
+/// Local reference such as `#/$defs/price` (`#` alone is the whole schema)
+fn resolve<'a>(root: &'a JsonValue, reference: &str) -> Option<&'a JsonValue> {
+    let fragment = reference.strip_prefix('#')?;
+    JsonPointer::parse(fragment).ok()?.get(root)
+}
+
+/// Reject malformed keyword values up front, so validation never has to
+fn check_schema(root: &JsonValue, schema: &JsonValue, location: &JsonPointer) -> Result<(), SchemaError> {
+    let keywords = match schema {
+        JsonValue::Bool(_) => return Ok(()),
+        JsonValue::Object(keywords) => keywords,
+        _ => return Err(invalid(location, "a schema must be an object or a boolean")),
+    };
+
+    for (keyword, value) in keywords {
+        let here = child(location, keyword);
+        let ok = match keyword.as_str() {
+            "type" => match value {
+                JsonValue::Array(names) => !names.is_empty() && names.iter().all(|n| n.as_str().is_some_and(is_type_name)),
+                _ => value.as_str().is_some_and(is_type_name),
+            },
+            "enum" => value.as_array().is_some(),
+            "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum" => value.as_number().is_some(),
+            "multipleOf" => value.as_f64().is_some_and(|n| n > 0.0),
+            "minLength" | "maxLength" | "minItems" | "maxItems" | "minProperties" | "maxProperties" => value.as_u64().is_some(),
+            "uniqueItems" => value.as_bool().is_some(),
+            "format" => value.as_str().is_some(),
+            "required" => is_string_array(value),
+            "dependentRequired" => value.as_object().is_some_and(|deps| deps.values().all(is_string_array)),
+            "$ref" => {
+                let reference = value.as_str().ok_or_else(|| invalid(&here, "$ref must be a string"))?;
+                if resolve(root, reference).is_none() {
+                    return Err(invalid(&here, &format!("cannot resolve \"{}\" (only local \"#/...\" references are supported)", reference)));
+                }
+                true
+            }
+            "properties" | "$defs" => {
+                let subschemas = value.as_object().ok_or_else(|| invalid(&here, "expected an object of schemas"))?;
+                check_members(root, subschemas, &here)?;
+                true
+            }
+            "prefixItems" | "allOf" | "anyOf" | "oneOf" => {
+                let subschemas = value.as_array().filter(|s| !s.is_empty());
+                let subschemas = subschemas.ok_or_else(|| invalid(&here, "expected a non-empty array of schemas"))?;
+                for (i, sub) in subschemas.iter().enumerate() {
+                    check_schema(root, sub, &child(&here, i))?;
+                }
+                true
+            }
+            "items" | "contains" | "additionalProperties" | "propertyNames" | "not" | "if" | "then" | "else" => {
+                check_schema(root, value, &here)?;
+                true
+            }
+            name if UNSUPPORTED.contains(&name) => {
+                return Err(invalid(&here, &format!("\"{}\" is not supported", name)));
+            }
+            _ => true,
+        };
+        if !ok {
+            return Err(invalid(&here, &format!("invalid value {} for \"{}\"", value, keyword)));
+        }
+    }
+    Ok(())
+}
+
+fn check_members(root: &JsonValue, subschemas: &JsonObject, location: &JsonPointer) -> Result<(), SchemaError> {
+    for (name, sub) in subschemas {
+        check_schema(root, sub, &child(location, name))?;
+    }
+    Ok(())
+}
+
+fn invalid(location: &JsonPointer, message: &str) -> SchemaError {
+    SchemaError {
+        location: location.clone(),
+        message: message.to_string(),
+    }
+}
+
+fn is_type_name(name: &str) -> bool {
+    TYPE_NAMES.contains(&name)
+}
+
+fn is_string_array(value: &JsonValue) -> bool {
+    value.as_array().is_some_and(|items| items.iter().all(|item| item.as_str().is_some()))
+}
+
+/// `None` for formats that are only annotations here
+fn check_format(format: &str, s: &str) -> Option<bool> {
+    Some(match format {
+        "date" => is_date(s),
+        "date-time" => is_date_time(s),
+        "email" => is_email(s),
+        "uri" => is_uri(s),
+        _ => return None,
+    })
+}
+
+fn number(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// RFC 3339 full-date: `2025-11-20`
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    if b.len() != 10 || !s.is_ascii() || b[4] != b'-' || b[7] != b'-' {
+        return false;
+    }
+    let (Some(year), Some(month), Some(day)) = (number(&s[..4]), number(&s[5..7]), number(&s[8..])) else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// RFC 3339 date-time: `2025-01-01T00:00:00Z`, `2025-01-01T04:00:00.5+04:00`
+fn is_date_time(s: &str) -> bool {
+    let Some((date, time)) = s.split_once(['T', 't']) else {
+        return false;
+    };
+    let (clock, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => return false,
+    };
+    let offset_ok = match offset {
+        "Z" | "z" => true,
+        _ => offset.len() == 6 && is_hh_mm(&offset[1..], 23),
+    };
+    let (hms, fraction) = clock.split_once('.').unwrap_or((clock, "0"));
+    is_date(date)
+        && offset_ok
+        && hms.len() == 8
+        && hms.is_ascii()
+        && is_hh_mm(&hms[..5], 23)
+        && hms.as_bytes()[5] == b':'
+        && number(&hms[6..]).is_some_and(|sec| sec <= 60)
+        && !fraction.is_empty()
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_hh_mm(s: &str, max_hour: u32) -> bool {
+    s.len() == 5
+        && s.is_ascii()
+        && s.as_bytes()[2] == b':'
+        && number(&s[..2]).is_some_and(|h| h <= max_hour)
+        && number(&s[3..]).is_some_and(|m| m <= 59)
+}
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !s.contains(char::is_whitespace)
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+}
+
+/// Absolute URI: a scheme, `:` and something after it
+fn is_uri(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once(':') else {
+        return false;
+    };
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !s.contains(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(schema: &str, instance: &str) -> Vec<String> {
+        let schema = JsonSchema::parse(schema).unwrap();
+        match schema.validate(&parse(instance).unwrap()) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_scalar_keywords() {
+        let price = r#"{"type": "integer", "minimum": 0, "exclusiveMaximum": 1e9, "multipleOf": 1000}"#;
+        assert!(errors(price, "1850000").is_empty());
+        assert!(errors(price, "1850000.0").is_empty());
+        assert_eq!(errors(price, "-500"), ["#: -500 is less than the minimum 0", "#: -500 is not a multiple of 1000"]);
+        assert_eq!(errors(price, r#""1850000""#), ["#: expected integer, found string"]);
+
+        let amount = r#"{"type": ["number", "null"], "multipleOf": 0.01}"#;
+        assert!(errors(amount, "2.60").is_empty());
+        assert!(errors(amount, "0.07").is_empty());
+        assert!(errors(amount, "null").is_empty());
+        assert_eq!(errors(amount, "2.605"), ["#: 2.605 is not a multiple of 0.01"]);
+
+        let kind = r#"{"enum": ["apartment", "villa"], "minLength": 5}"#;
+        assert_eq!(errors(kind, r#""loft""#), [r#"#: "loft" is not one of ["apartment","villa"]"#, "#: string is shorter than 5 characters"]);
+
+        let date = r#"{"format": "date"}"#;
+        assert!(errors(date, r#""2024-02-29""#).is_empty());
+        assert_eq!(errors(date, r#""2025-02-29""#), [r#"#: "2025-02-29" is not a valid date"#]);
+        assert!(errors(r#"{"format": "date-time"}"#, r#""2025-01-01T00:00:00Z""#).is_empty());
+        assert!(!errors(r#"{"format": "date-time"}"#, r#""2025-01-01 00:00""#).is_empty());
+        assert!(errors(r#"{"format": "uri"}"#, r#""https://www.dmcc.ae""#).is_empty());
+        assert!(errors(r#"{"format": "color"}"#, r#""blue""#).is_empty());
+    }
+
+    #[test]
+    fn test_reports_every_violation_with_pointer() {
+        let schema = r#"{
+            "type": "object",
+            "required": ["properties"],
+            "properties": {
+                "properties": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "price"],
+                        "properties": {
+                            "id": {"type": "string"},
+                            "price": {"type": "number", "minimum": 0},
+                            "features": {"type": "array", "items": {"type": "string"}, "uniqueItems": true}
+                        },
+                        "additionalProperties": false
+                    }
+                }
+            }
+        }"#;
+        let doc = r#"{"properties": [
+            {"id": "dp-001", "price": 1850000},
+            {"id": 2, "price": -1, "features": ["Gym", 3, "Gym"]},
+            {"price": 10, "colour": "red"}
+        ]}"#;
+        assert_eq!(
+            errors(schema, doc),
+            [
+                "#/properties/1/id: expected string, found number",
+                "#/properties/1/price: -1 is less than the minimum 0",
+                "#/properties/1/features/1: expected string, found number",
+                "#/properties/1/features: items 0 and 2 are equal",
+                "#/properties/2: missing required property \"id\"",
+                "#/properties/2: property \"colour\" is not allowed",
+            ]
+        );
+
+        let schema = JsonSchema::parse(schema).unwrap();
+        let errors = schema.validate(&parse(doc).unwrap()).unwrap_err();
+        assert_eq!(errors[1].schema_path.to_string(), "/properties/properties/items/properties/price/minimum");
+        assert_eq!(errors[5].schema_path.to_string(), "/properties/properties/items/additionalProperties");
+    }
+
+    #[test]
+    fn test_combinators_and_refs() {
+        let schema = r##"{
+            "$defs": {
+                "coordinates": {
+                    "type": "object",
+                    "required": ["lat", "lon"],
+                    "properties": {"lat": {"maximum": 90, "minimum": -90}, "lon": {"maximum": 180, "minimum": -180}}
+                },
+                "node": {"type": "object", "properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/node"}}}}
+            },
+            "properties": {
+                "coordinates": {"$ref": "#/$defs/coordinates"},
+                "contact": {"oneOf": [{"required": ["email"]}, {"required": ["phone"]}]},
+                "tree": {"$ref": "#/$defs/node"},
+                "status": {"not": {"const": "sold"}},
+                "price": {"if": {"type": "string"}, "then": {"format": "date"}, "else": {"type": "number"}}
+            }
+        }"##;
+        assert!(errors(schema, r#"{"coordinates": {"lat": 25.08, "lon": 55.14}, "contact": {"email": "a@b.ae"}, "tree": {"children": [{"children": []}]}}"#).is_empty());
+        assert_eq!(
+            errors(schema, r#"{"coordinates": {"lat": 125}, "contact": {"email": "a@b.ae", "phone": "1"}, "tree": {"children": [{"children": 1}]}, "status": "sold", "price": true}"#),
+            [
+                "#/coordinates: missing required property \"lon\"",
+                "#/coordinates/lat: 125 is greater than the maximum 90",
+                "#/contact: value matches 2 schemas in oneOf, expected exactly one",
+                "#/tree/children/0/children: expected array, found number",
+                "#/status: value must not match the schema in \"not\"",
+                "#/price: expected number, found boolean",
+            ]
+        );
+
+        let cycle = JsonSchema::parse(r##"{"$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"}"##).unwrap();
+        assert!(cycle.validate(&JsonValue::Null).unwrap_err()[0].message.contains("cycle"));
+        assert!(JsonSchema::parse("false").unwrap().validate(&JsonValue::Null).is_err());
+    }
+
+    #[test]
+    fn test_invalid_schemas() {
+        let error = |schema: &str| JsonSchema::parse(schema).unwrap_err().to_string();
+        assert_eq!(error(r#"{"type": "text"}"#), r#"Invalid schema at #/type: invalid value "text" for "type""#);
+        assert_eq!(error(r#"{"properties": {"a": {"minLength": -1}}}"#), r#"Invalid schema at #/properties/a/minLength: invalid value -1 for "minLength""#);
+        assert_eq!(
+            error(r##"{"$ref": "#/$defs/missing"}"##),
+            r##"Invalid schema at #/$ref: cannot resolve "#/$defs/missing" (only local "#/..." references are supported)"##
+        );
+        assert_eq!(error(r#"{"pattern": "^dp-"}"#), r#"Invalid schema at #/pattern: "pattern" is not supported"#);
+        assert_eq!(error(r#"{"anyOf": []}"#), "Invalid schema at #/anyOf: expected a non-empty array of schemas");
+        assert!(error("{").starts_with("Invalid schema at #: "));
+    }
+
+    #[test]
+    fn test_docs_data_schemas() {
+        let cases = [
+            (
+                include_str!("../../../docs/data/schemas/dubai-properties.schema.json"),
+                include_str!("../../../docs/data/dubai-properties.json"),
+                "/properties/3/property_type",
+            ),
+            (
+                include_str!("../../../docs/data/schemas/invoice.schema.json"),
+                include_str!("../../../docs/data/invoice-edp-2025-11.json"),
+                "/billing_period/end",
+            ),
+        ];
+        for (schema, data, pointer) in cases {
+            let schema = JsonSchema::parse(schema).unwrap();
+            let mut doc = parse(data).unwrap();
+            assert_eq!(schema.validate(&doc), Ok(()));
+
+            doc.set_pointer(pointer, JsonValue::from(978)).unwrap();
+            let errors = schema.validate(&doc).unwrap_err();
+            assert_eq!(errors[0].instance_path.to_string(), pointer);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+// AvilaJson Serializer - Compact and pretty output
+// Zero External Dependencies 🦀
+
+use crate::JsonValue;
+
+/// How a `JsonValue` is written out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SerializeOptions {
+    /// Spaces per nesting level; `None` writes everything on one line
+    pub indent: Option<usize>,
+    /// Write object keys in byte order instead of insertion order
+    pub sort_keys: bool,
+    /// Escape every non-ASCII character as `\uXXXX`
+    pub ascii_only: bool,
+    /// When indenting, keep arrays of scalars on one line if the line stays
+    /// within this many characters (the Prettier convention)
+    pub line_width: Option<usize>,
+}
+
+impl SerializeOptions {
+    pub fn compact() -> Self {
+        Self::default()
+    }
+
+    /// Two-space indentation with 80-column inline arrays, the layout of the files in `docs/data`
+    pub fn pretty() -> Self {
+        Self {
+            indent: Some(2),
+            line_width: Some(80),
+            ..Self::default()
+        }
+    }
+
+    pub fn indent(mut self, width: usize) -> Self {
+        self.indent = Some(width);
+        self
+    }
+
+    pub fn sort_keys(mut self, sort: bool) -> Self {
+        self.sort_keys = sort;
+        self
+    }
+
+    pub fn ascii_only(mut self, ascii: bool) -> Self {
+        self.ascii_only = ascii;
+        self
+    }
+
+    pub fn line_width(mut self, width: Option<usize>) -> Self {
+        self.line_width = width;
+        self
+    }
+}
+
+pub(crate) fn write_value(out: &mut String, value: &JsonValue, options: &SerializeOptions, depth: usize) {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        JsonValue::Number(n) => out.push_str(n.as_str()),
+        JsonValue::String(s) => write_string(out, s, options.ascii_only),
+        JsonValue::Array(items) => {
+            if items.is_empty() {
+                out.push_str("[]");
+                return;
+            }
+            if let Some(inline) = inline_array(out, items, options) {
+                out.push_str(&inline);
+                return;
+            }
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, options, depth + 1);
+                write_value(out, item, options, depth + 1);
+            }
+            newline(out, options, depth);
+            out.push(']');
+        }
+        JsonValue::Object(obj) => {
+            if obj.is_empty() {
+                out.push_str("{}");
+                return;
+            }
+            let mut entries: Vec<(&String, &JsonValue)> = obj.iter().collect();
+            if options.sort_keys {
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+            }
+
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, options, depth + 1);
+                write_string(out, key, options.ascii_only);
+                out.push(':');
+                if options.indent.is_some() {
+                    out.push(' ');
+                }
+                write_value(out, item, options, depth + 1);
+            }
+            newline(out, options, depth);
+            out.push('}');
+        }
+    }
+}
+
+/// `[a, b, c]` for an array of scalars that fits on the current line
+fn inline_array(out: &str, items: &[JsonValue], options: &SerializeOptions) -> Option<String> {
+    let width = options.line_width.filter(|_| options.indent.is_some())?;
+    if items.iter().any(|v| matches!(v, JsonValue::Array(_) | JsonValue::Object(_))) {
+        return None;
+    }
+
+    let mut inline = String::from("[");
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            inline.push_str(", ");
+        }
+        write_value(&mut inline, item, options, 0);
+    }
+    inline.push(']');
+
+    let column = out[out.rfind('\n').map_or(0, |i| i + 1)..].chars().count();
+    (column + inline.chars().count() <= width).then_some(inline)
+}
+
+fn newline(out: &mut String, options: &SerializeOptions, depth: usize) {
+    if let Some(width) = options.indent {
+        out.push('\n');
+        out.extend(std::iter::repeat_n(' ', width * depth));
+    }
+}
+
+/// Quote and escape a string per RFC 8259: quotes, backslashes and every
+/// control character below U+0020 (short forms where JSON has them)
+pub(crate) fn write_string(out: &mut String, s: &str, ascii_only: bool) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 || (ascii_only && !c.is_ascii()) => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    out.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, JsonObject};
+
+    #[test]
+    fn test_pretty_and_sorted() {
+        let mut obj = JsonObject::new();
+        obj.insert("b", JsonValue::Array(vec![JsonValue::from(1), JsonValue::Array(vec![])]));
+        obj.insert("a", JsonValue::Object(JsonObject::new()));
+        let value = JsonValue::Object(obj);
+
+        assert_eq!(value.to_string(), r#"{"b":[1,[]],"a":{}}"#);
+        assert_eq!(value.to_pretty_string(), "{\n  \"b\": [\n    1,\n    []\n  ],\n  \"a\": {}\n}");
+        assert_eq!(
+            value.to_string_with(&SerializeOptions::pretty().indent(1).sort_keys(true)),
+            "{\n \"a\": {},\n \"b\": [\n  1,\n  []\n ]\n}"
+        );
+
+        let tags = JsonValue::Array(vec![JsonValue::String("IT".to_string()), JsonValue::from(2.5)]);
+        assert_eq!(tags.to_pretty_string(), "[\"IT\", 2.5]");
+        assert_eq!(
+            tags.to_string_with(&SerializeOptions::pretty().line_width(Some(8))),
+            "[\n  \"IT\",\n  2.5\n]"
+        );
+    }
+
+    #[test]
+    fn test_ascii_only() {
+        let value = JsonValue::String("Ação 🏠 دبي".to_string());
+        let ascii = value.to_string_with(&SerializeOptions::compact().ascii_only(true));
+        assert_eq!(ascii, r#""A\u00e7\u00e3o \ud83c\udfe0 \u062f\u0628\u064a""#);
+        assert_eq!(parse(&ascii).unwrap(), value);
+    }
+
+    #[test]
+    fn test_docs_data_roundtrip() {
+        let text = include_str!("../../../docs/data/dubai-properties.json");
+        let value = parse(text).unwrap();
+        assert_eq!(value.to_pretty_string(), text.trim_end());
+    }
+
+    #[test]
+    fn test_number_literals_preserved() {
+        let text = include_str!("../../../docs/data/invoice-edp-2025-11.json");
+        let pretty = parse(text).unwrap().to_pretty_string();
+        for literal in [r#""taxes_and_fees_eur": 2.60"#, r#""amount_eur": -1.50"#, r#""base_eur": 0.00"#, r#""unit_price_eur": -0.0484"#] {
+            assert!(pretty.contains(literal), "{}", literal);
+        }
+
+        let ids = parse(r#"[1850000, 12345678901234567890, 1.5e6]"#).unwrap();
+        assert_eq!(ids.to_string(), "[1850000,12345678901234567890,1.5e6]");
+        assert_eq!(JsonValue::from(1_850_000.0).to_string(), "1850000");
+        assert_eq!(JsonValue::from(f64::NAN), JsonValue::Null);
+    }
+}
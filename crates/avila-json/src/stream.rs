@@ -0,0 +1,633 @@
+// AvilaJson Stream - Pull parser over io::Read and NDJSON lines
+// Zero External Dependencies 🦀
+
+use crate::{is_number_byte, is_whitespace_byte, unescape, JsonError, JsonErrorKind, JsonNumber, JsonObject, JsonValue, ToJson, MAX_DEPTH};
+use std::io::{self, BufRead, Read, Write};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+/// Bytes of the current line kept for error context
+const CONTEXT_SIZE: usize = 64;
+
+/// Failure while reading JSON from a stream
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Json(JsonError),
+}
+
+impl std::fmt::Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "I/O error: {}", e),
+            StreamError::Json(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+impl From<JsonError> for StreamError {
+    fn from(e: JsonError) -> Self {
+        StreamError::Json(e)
+    }
+}
+
+/// Token produced by `JsonReader`
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonEvent {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    /// Object member name; the member's value follows
+    Key(String),
+    Null,
+    Bool(bool),
+    Number(JsonNumber),
+    String(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Object,
+    Array,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Value,
+    FirstKey,
+    Key,
+    FirstItem,
+    AfterValue,
+}
+
+/// Pull parser reading bytes from any `Read`
+///
+/// Memory use is bounded by the read buffer, the nesting depth and the
+/// largest single string or number, not by the document size. The input may
+/// hold several whitespace-separated top-level values; `next_event` returns
+/// `None` once all of them are consumed. Nesting deeper than 128 levels
+/// (see `with_max_depth`) is rejected with `NestingTooDeep`.
+pub struct JsonReader<R> {
+    reader: R,
+    buffer: Box<[u8]>,
+    start: usize,
+    end: usize,
+    offset: usize,
+    line: usize,
+    column: usize,
+    context: Vec<u8>,
+    stack: Vec<Container>,
+    max_depth: usize,
+    state: State,
+}
+
+impl<R: Read> JsonReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: vec![0; BUFFER_SIZE].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            offset: 0,
+            line: 1,
+            column: 1,
+            context: Vec::new(),
+            stack: Vec::new(),
+            max_depth: MAX_DEPTH,
+            state: State::Value,
+        }
+    }
+
+    /// Change the nesting limit
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Nesting depth of the position just read (0 between top-level values)
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    pub fn next_event(&mut self) -> Result<Option<JsonEvent>, StreamError> {
+        loop {
+            self.skip_whitespace()?;
+            let Some(b) = self.peek()? else {
+                if self.stack.is_empty() && self.state == State::Value {
+                    return Ok(None);
+                }
+                return Err(self.error(JsonErrorKind::UnexpectedEnd));
+            };
+
+            match self.state {
+                State::Value => return self.read_value_start(b).map(Some),
+                State::FirstKey if b == b'}' => {
+                    self.bump();
+                    return Ok(Some(self.close()));
+                }
+                State::FirstKey | State::Key => {
+                    if b != b'"' {
+                        return Err(self.error(JsonErrorKind::InvalidKey));
+                    }
+                    let key = self.read_string()?;
+                    self.skip_whitespace()?;
+                    if self.peek()? != Some(b':') {
+                        return Err(self.error(JsonErrorKind::ExpectedColon));
+                    }
+                    self.bump();
+                    self.state = State::Value;
+                    return Ok(Some(JsonEvent::Key(key)));
+                }
+                State::FirstItem if b == b']' => {
+                    self.bump();
+                    return Ok(Some(self.close()));
+                }
+                State::FirstItem => self.state = State::Value,
+                State::AfterValue => {
+                    let in_object = self.stack.last() == Some(&Container::Object);
+                    match (b, in_object) {
+                        (b',', true) => self.state = State::Key,
+                        (b',', false) => self.state = State::Value,
+                        (b'}', true) | (b']', false) => {
+                            self.bump();
+                            return Ok(Some(self.close()));
+                        }
+                        (_, true) => return Err(self.error(JsonErrorKind::InvalidObject)),
+                        (_, false) => return Err(self.error(JsonErrorKind::InvalidArray)),
+                    }
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    /// Assemble the next complete value
+    ///
+    /// Returns `None` at the end of the input or, inside an array, when the
+    /// array closes (its `EndArray` is consumed). Typical use is reading the
+    /// elements of a large top-level array one at a time.
+    pub fn read_value(&mut self) -> Result<Option<JsonValue>, StreamError> {
+        match self.next_event()? {
+            None | Some(JsonEvent::EndArray) | Some(JsonEvent::EndObject) => Ok(None),
+            Some(JsonEvent::Key(_)) => Err(self.error(JsonErrorKind::InvalidValue)),
+            Some(event) => self.build(event).map(Some),
+        }
+    }
+
+    fn build(&mut self, event: JsonEvent) -> Result<JsonValue, StreamError> {
+        Ok(match event {
+            JsonEvent::Null => JsonValue::Null,
+            JsonEvent::Bool(b) => JsonValue::Bool(b),
+            JsonEvent::Number(n) => JsonValue::Number(n),
+            JsonEvent::String(s) => JsonValue::String(s),
+            JsonEvent::StartArray => {
+                let mut items = Vec::new();
+                loop {
+                    match self.next_event()? {
+                        Some(JsonEvent::EndArray) => break,
+                        Some(event) => items.push(self.build(event)?),
+                        None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+                    }
+                }
+                JsonValue::Array(items)
+            }
+            JsonEvent::StartObject => {
+                let mut object = JsonObject::new();
+                loop {
+                    match self.next_event()? {
+                        Some(JsonEvent::EndObject) => break,
+                        Some(JsonEvent::Key(key)) => {
+                            let event = self.next_event()?.ok_or_else(|| self.error(JsonErrorKind::UnexpectedEnd))?;
+                            object.insert(key, self.build(event)?);
+                        }
+                        _ => return Err(self.error(JsonErrorKind::InvalidKey)),
+                    }
+                }
+                JsonValue::Object(object)
+            }
+            JsonEvent::EndArray | JsonEvent::EndObject | JsonEvent::Key(_) => {
+                return Err(self.error(JsonErrorKind::InvalidValue))
+            }
+        })
+    }
+
+    fn read_value_start(&mut self, b: u8) -> Result<JsonEvent, StreamError> {
+        let event = match b {
+            b'{' | b'[' => {
+                if self.stack.len() >= self.max_depth {
+                    return Err(self.error(JsonErrorKind::NestingTooDeep));
+                }
+                self.bump();
+                let (container, state, event) = if b == b'{' {
+                    (Container::Object, State::FirstKey, JsonEvent::StartObject)
+                } else {
+                    (Container::Array, State::FirstItem, JsonEvent::StartArray)
+                };
+                self.stack.push(container);
+                self.state = state;
+                return Ok(event);
+            }
+            b'"' => JsonEvent::String(self.read_string()?),
+            b't' => self.read_literal("true", JsonEvent::Bool(true))?,
+            b'f' => self.read_literal("false", JsonEvent::Bool(false))?,
+            b'n' => self.read_literal("null", JsonEvent::Null)?,
+            b'0'..=b'9' | b'-' => self.read_number()?,
+            _ => return Err(self.error(JsonErrorKind::InvalidValue)),
+        };
+        self.state = if self.stack.is_empty() { State::Value } else { State::AfterValue };
+        Ok(event)
+    }
+
+    fn close(&mut self) -> JsonEvent {
+        let container = self.stack.pop();
+        self.state = if self.stack.is_empty() { State::Value } else { State::AfterValue };
+        match container {
+            Some(Container::Object) => JsonEvent::EndObject,
+            _ => JsonEvent::EndArray,
+        }
+    }
+
+    fn read_literal(&mut self, literal: &str, event: JsonEvent) -> Result<JsonEvent, StreamError> {
+        for expected in literal.bytes() {
+            match self.peek()? {
+                Some(b) if b == expected => self.bump(),
+                Some(_) => return Err(self.error(JsonErrorKind::InvalidValue)),
+                None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+            }
+        }
+        Ok(event)
+    }
+
+    fn read_number(&mut self) -> Result<JsonEvent, StreamError> {
+        let error = self.error(JsonErrorKind::InvalidNumber);
+        let mut text = String::new();
+        while let Some(b) = self.peek()?.filter(|&b| is_number_byte(b)) {
+            text.push(b as char);
+            self.bump();
+        }
+        JsonNumber::from_literal(&text).map(JsonEvent::Number).ok_or(error)
+    }
+
+    /// String starting at the opening quote
+    fn read_string(&mut self) -> Result<String, StreamError> {
+        self.bump();
+        let mut bytes = Vec::new();
+        loop {
+            let b = self.peek()?.ok_or_else(|| self.error(JsonErrorKind::UnexpectedEnd))?;
+            match b {
+                b'"' => {
+                    self.bump();
+                    break;
+                }
+                b'\\' => {
+                    self.bump();
+                    let b = self.peek()?.ok_or_else(|| self.error(JsonErrorKind::UnexpectedEnd))?;
+                    let c = match b {
+                        b'u' => self.read_unicode_escape()?,
+                        _ => unescape(b).ok_or_else(|| self.error(JsonErrorKind::InvalidEscape))?,
+                    };
+                    self.bump();
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                0..=0x1F => return Err(self.error(JsonErrorKind::ControlCharacter)),
+                _ => {
+                    bytes.push(b);
+                    self.bump();
+                }
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error(JsonErrorKind::InvalidUtf8))
+    }
+
+    /// `uXXXX` with the reader on the `u`; leaves it on the last hex digit
+    fn read_unicode_escape(&mut self) -> Result<char, StreamError> {
+        let high = self.read_hex4()?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                for expected in [b'\\', b'u'] {
+                    self.bump();
+                    if self.peek()? != Some(expected) {
+                        return Err(self.error(JsonErrorKind::InvalidUnicodeEscape));
+                    }
+                }
+                let low = self.read_hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error(JsonErrorKind::InvalidUnicodeEscape));
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            0xDC00..=0xDFFF => return Err(self.error(JsonErrorKind::InvalidUnicodeEscape)),
+            code => code,
+        };
+        char::from_u32(code).ok_or_else(|| self.error(JsonErrorKind::InvalidUnicodeEscape))
+    }
+
+    fn read_hex4(&mut self) -> Result<u32, StreamError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            self.bump();
+            let b = self.peek()?.ok_or_else(|| self.error(JsonErrorKind::UnexpectedEnd))?;
+            let digit = (b as char).to_digit(16).ok_or_else(|| self.error(JsonErrorKind::InvalidUnicodeEscape))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), StreamError> {
+        while self.peek()?.is_some_and(is_whitespace_byte) {
+            self.bump();
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        if self.start == self.end {
+            self.start = 0;
+            self.end = loop {
+                match self.reader.read(&mut self.buffer) {
+                    Ok(n) => break n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            };
+        }
+        Ok((self.start < self.end).then(|| self.buffer[self.start]))
+    }
+
+    /// Consume the byte returned by the last `peek`
+    fn bump(&mut self) {
+        let b = self.buffer[self.start];
+        self.start += 1;
+        self.offset += 1;
+        if b == b'\n' {
+            self.line += 1;
+            self.column = 1;
+            self.context.clear();
+            return;
+        }
+        // Columns count characters: skip UTF-8 continuation bytes
+        if b & 0xC0 != 0x80 {
+            self.column += 1;
+        }
+        if self.context.len() == CONTEXT_SIZE {
+            self.context.drain(..CONTEXT_SIZE / 2);
+        }
+        self.context.push(b);
+    }
+
+    /// Error at the next unread byte; only the input before it is known
+    fn error(&self, kind: JsonErrorKind) -> StreamError {
+        StreamError::Json(JsonError {
+            kind,
+            offset: self.offset,
+            line: self.line,
+            column: self.column,
+            context: String::from_utf8_lossy(&self.context).trim().to_string(),
+        })
+    }
+}
+
+/// Read exactly one JSON value from `reader`
+pub fn from_reader<R: Read>(reader: R) -> Result<JsonValue, StreamError> {
+    let mut reader = JsonReader::new(reader);
+    let value = reader.read_value()?.ok_or_else(|| reader.error(JsonErrorKind::UnexpectedEnd))?;
+    reader.skip_whitespace()?;
+    if reader.peek()?.is_some() {
+        return Err(reader.error(JsonErrorKind::TrailingCharacters));
+    }
+    Ok(value)
+}
+
+/// Newline-delimited JSON reader: one value per line, blank lines skipped
+///
+/// Only one line is held in memory at a time. Errors report the NDJSON line
+/// number and the byte offset in the whole stream; iteration can continue
+/// past a bad line.
+pub struct NdjsonReader<R> {
+    reader: R,
+    line: Vec<u8>,
+    line_number: usize,
+    offset: usize,
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            line_number: 0,
+            offset: 0,
+        }
+    }
+
+    /// Number of lines read so far
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = Result<JsonValue, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            let read = match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(n) => n,
+                Err(e) => return Some(Err(e.into())),
+            };
+            let line_offset = self.offset;
+            self.offset += read;
+            self.line_number += 1;
+
+            if self.line.iter().all(|&b| is_whitespace_byte(b)) {
+                continue;
+            }
+
+            let result = match std::str::from_utf8(&self.line) {
+                Ok(text) => crate::parse(text),
+                Err(e) => Err(JsonError {
+                    kind: JsonErrorKind::InvalidUtf8,
+                    offset: e.valid_up_to(),
+                    line: 1,
+                    column: 1,
+                    context: String::new(),
+                }),
+            };
+            return Some(result.map_err(|mut e| {
+                e.offset += line_offset;
+                e.line = self.line_number;
+                StreamError::Json(e)
+            }));
+        }
+    }
+}
+
+/// Newline-delimited JSON writer: each value compact on its own line
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+    count: usize,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, count: 0 }
+    }
+
+    pub fn write_value(&mut self, value: &JsonValue) -> io::Result<()> {
+        // Compact output escapes newlines inside strings, so a value never spans lines
+        writeln!(self.writer, "{}", value)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn write<T: ToJson + ?Sized>(&mut self, value: &T) -> io::Result<()> {
+        self.write_value(&value.to_json())
+    }
+
+    /// Number of values written
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    /// Reader handing out one byte per `read` call, to cross every buffer boundary
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((&b, rest)) if !buf.is_empty() => {
+                    buf[0] = b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_events() {
+        let mut reader = JsonReader::new(Trickle(br#"{"id": "dp-001", "tags": ["Sea view", "\u00c1gua \ud83c\udfe0"], "price": 1.5e6, "ok": true, "x": null}"#));
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_event().unwrap() {
+            events.push(event);
+        }
+        use JsonEvent::*;
+        assert_eq!(
+            events,
+            [
+                StartObject,
+                Key("id".into()),
+                String("dp-001".into()),
+                Key("tags".into()),
+                StartArray,
+                String("Sea view".into()),
+                String("Água 🏠".into()),
+                EndArray,
+                Key("price".into()),
+                Number(1_500_000.into()),
+                Key("ok".into()),
+                Bool(true),
+                Key("x".into()),
+                Null,
+                EndObject,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_in_memory_parser() {
+        let text = include_str!("../../../docs/data/dubai-properties.json");
+        assert_eq!(from_reader(Trickle(text.as_bytes())).unwrap(), parse(text).unwrap());
+
+        // Elements of a top-level array, one at a time
+        let mut reader = JsonReader::new(&b"[{\"a\": 1}, [2], 3]"[..]);
+        assert_eq!(reader.next_event().unwrap(), Some(JsonEvent::StartArray));
+        let mut items = Vec::new();
+        while let Some(item) = reader.read_value().unwrap() {
+            items.push(item.to_string());
+        }
+        assert_eq!(items, [r#"{"a":1}"#, "[2]", "3"]);
+        assert_eq!(reader.next_event().unwrap(), None);
+
+        for bad in ["[1, 2", "{\"a\" 1}", "[1,]", "{\"a\": 1,}", "\"\\x\"", "[1] 2x", "\"\u{1}\""] {
+            let expected = parse(bad).unwrap_err();
+            match from_reader(bad.as_bytes()) {
+                Err(StreamError::Json(e)) => assert_eq!((e.kind, e.offset, e.line, e.column), (expected.kind, expected.offset, expected.line, expected.column), "{}", bad),
+                other => panic!("{}: {:?}", bad, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let deep = "[".repeat(100_000);
+        match from_reader(deep.as_bytes()) {
+            Err(StreamError::Json(e)) => assert_eq!((e.kind, e.column), (JsonErrorKind::NestingTooDeep, 129)),
+            other => panic!("{:?}", other),
+        }
+
+        let mut reader = JsonReader::new(&b"[[1]]"[..]).with_max_depth(2);
+        assert_eq!(reader.read_value().unwrap().unwrap().to_string(), "[[1]]");
+        let mut reader = JsonReader::new(&b"{\"a\": [1]}"[..]).with_max_depth(1);
+        assert!(reader.read_value().is_err());
+
+        // A hostile NDJSON line fails on its own instead of overflowing the stack
+        let input = format!("{{\"ok\": 1}}\n{}\n[2]\n", "[".repeat(200_000));
+        let results: Vec<_> = NdjsonReader::new(input.as_bytes()).collect();
+        assert_eq!(results.len(), 3);
+        match &results[1] {
+            Err(StreamError::Json(e)) => assert_eq!((e.kind, e.line), (JsonErrorKind::NestingTooDeep, 2)),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(results[2].as_ref().unwrap().to_string(), "[2]");
+    }
+
+    #[test]
+    fn test_ndjson_roundtrip() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        writer.write_value(&parse(r#"{"title": "Line\nbreak", "price": 1}"#).unwrap()).unwrap();
+        writer.write(&vec![1u32, 2]).unwrap();
+        assert_eq!(writer.count(), 2);
+        let bytes = writer.into_inner();
+        assert_eq!(String::from_utf8(bytes.clone()).unwrap(), "{\"title\":\"Line\\nbreak\",\"price\":1}\n[1,2]\n");
+
+        let input = [&bytes[..], b"\n{\"broken\": }\r\n\"last\""].concat();
+        let results: Vec<_> = NdjsonReader::new(&input[..]).collect();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[1].as_ref().unwrap().to_string(), "[1,2]");
+        match &results[2] {
+            Err(StreamError::Json(e)) => assert_eq!((e.kind, e.line, e.offset), (JsonErrorKind::InvalidValue, 4, bytes.len() + 12)),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(results[3].as_ref().unwrap(), &JsonValue::String("last".to_string()));
+    }
+}
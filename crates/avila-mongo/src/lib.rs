@@ -2,9 +2,11 @@
 // Zero external dependencies, built on top of Avila HTTP/TLS stack 🦀
 
 use std::collections::HashMap;
+use std::io::BufRead;
 
+use avila_crypto::vault::Vault;
 use avila_http::{HttpClient, HttpError};
-use avila_json::{self, JsonValue};
+use avila_json::{self, FromJson, FromJsonError, JsonObject, JsonValue, NdjsonReader, ToJson};
 
 /// MongoDB Atlas client powered by the Avila native stack.
 ///
@@ -65,6 +67,25 @@ impl MongoAtlasClient {
         Ok(Self::new(app_id, cluster, database, collection, api_key))
     }
 
+    /// Instantiate the client from secrets stored in an encrypted vault,
+    /// using the same names as `from_env`.
+    pub fn from_vault(vault: &Vault) -> Result<Self, MongoAtlasError> {
+        let read = |name: &'static str| {
+            vault
+                .get(name)
+                .map(str::to_string)
+                .ok_or(MongoAtlasError::MissingSecret(name))
+        };
+
+        Ok(Self::new(
+            read("MONGODB_ATLAS_APP_ID")?,
+            read("MONGODB_ATLAS_CLUSTER")?,
+            read("MONGODB_ATLAS_DATABASE")?,
+            read("MONGODB_ATLAS_COLLECTION")?,
+            read("MONGODB_ATLAS_API_KEY")?,
+        ))
+    }
+
     /// Change the target collection.
     pub fn with_collection(mut self, collection: impl Into<String>) -> Self {
         self.collection = collection.into();
@@ -92,7 +113,7 @@ impl MongoAtlasClient {
         &self,
         document: &MongoDocument,
     ) -> Result<MongoInsertOneResult, MongoAtlasError> {
-        let mut payload = HashMap::new();
+        let mut payload = JsonObject::new();
         payload.insert(
             "dataSource".to_string(),
             JsonValue::String(self.cluster.clone()),
@@ -111,9 +132,86 @@ impl MongoAtlasClient {
         parse_insert_one_response(response)
     }
 
+    /// Insert several documents with one request; returns how many were inserted.
+    pub fn insert_many(&self, documents: &[MongoDocument]) -> Result<usize, MongoAtlasError> {
+        let mut payload = JsonObject::new();
+        payload.insert(
+            "dataSource".to_string(),
+            JsonValue::String(self.cluster.clone()),
+        );
+        payload.insert(
+            "database".to_string(),
+            JsonValue::String(self.database.clone()),
+        );
+        payload.insert(
+            "collection".to_string(),
+            JsonValue::String(self.collection.clone()),
+        );
+        payload.insert(
+            "documents".to_string(),
+            JsonValue::Array(documents.iter().map(MongoDocument::to_json).collect()),
+        );
+
+        let response = self.post_json("insertMany", JsonValue::Object(payload))?;
+        response
+            .pointer("/insertedIds")
+            .and_then(JsonValue::as_array)
+            .map(Vec::len)
+            .ok_or_else(|| MongoAtlasError::Parse("insertMany response missing insertedIds".to_string()))
+    }
+
+    /// Stream an NDJSON export into the collection, `batch_size` documents per
+    /// request, holding only one batch in memory.
+    ///
+    /// Batches are committed as they go, so on failure the error carries how
+    /// many documents were already inserted, the failing line and the first
+    /// line not imported; resume from there rather than re-running the file.
+    pub fn import_ndjson<R: BufRead>(&self, reader: R, batch_size: usize) -> Result<usize, NdjsonImportError> {
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut batch_line = 0;
+        let mut inserted = 0;
+        let mut lines = NdjsonReader::new(reader);
+
+        while let Some(value) = lines.next() {
+            let line = lines.line_number();
+            let fail = |error| NdjsonImportError {
+                inserted,
+                line,
+                resume_from: if batch.is_empty() { line } else { batch_line },
+                error,
+            };
+            let value = value.map_err(|e| fail(MongoAtlasError::Parse(e.to_string())))?;
+            let document = MongoDocument::from_json(&value)
+                .map_err(|e| fail(MongoAtlasError::Parse(format!("line {}: {}", line, e))))?;
+            if batch.is_empty() {
+                batch_line = line;
+            }
+            batch.push(document);
+
+            if batch.len() >= batch_size.max(1) {
+                inserted += self.insert_many(&batch).map_err(|error| NdjsonImportError {
+                    inserted,
+                    line: batch_line,
+                    resume_from: batch_line,
+                    error,
+                })?;
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() {
+            inserted += self.insert_many(&batch).map_err(|error| NdjsonImportError {
+                inserted,
+                line: batch_line,
+                resume_from: batch_line,
+                error,
+            })?;
+        }
+        Ok(inserted)
+    }
+
     /// Find a single document using the provided filter.
     pub fn find_one(&self, filter: &MongoDocument) -> Result<Option<JsonValue>, MongoAtlasError> {
-        let mut payload = HashMap::new();
+        let mut payload = JsonObject::new();
         payload.insert(
             "dataSource".to_string(),
             JsonValue::String(self.cluster.clone()),
@@ -132,9 +230,66 @@ impl MongoAtlasClient {
         parse_find_one_response(response)
     }
 
+    /// Up to `limit` documents matching the filter.
+    pub fn find(&self, filter: &MongoDocument, limit: usize) -> Result<Vec<JsonValue>, MongoAtlasError> {
+        let mut payload = JsonObject::new();
+        payload.insert(
+            "dataSource".to_string(),
+            JsonValue::String(self.cluster.clone()),
+        );
+        payload.insert(
+            "database".to_string(),
+            JsonValue::String(self.database.clone()),
+        );
+        payload.insert(
+            "collection".to_string(),
+            JsonValue::String(self.collection.clone()),
+        );
+        payload.insert("filter".to_string(), filter.to_json());
+        payload.insert("limit".to_string(), JsonValue::from(limit));
+
+        let response = self.post_json("find", JsonValue::Object(payload))?;
+        parse_find_response(response)
+    }
+
+    /// Apply an update document (`$set`, `$unset`, ...) to the first match.
+    pub fn update_one(
+        &self,
+        filter: &MongoDocument,
+        update: &JsonValue,
+    ) -> Result<MongoUpdateResult, MongoAtlasError> {
+        let mut payload = JsonObject::new();
+        payload.insert(
+            "dataSource".to_string(),
+            JsonValue::String(self.cluster.clone()),
+        );
+        payload.insert(
+            "database".to_string(),
+            JsonValue::String(self.database.clone()),
+        );
+        payload.insert(
+            "collection".to_string(),
+            JsonValue::String(self.collection.clone()),
+        );
+        payload.insert("filter".to_string(), filter.to_json());
+        payload.insert("update".to_string(), update.clone());
+
+        let response = self.post_json("updateOne", JsonValue::Object(payload))?;
+        let count = |name: &str| {
+            response
+                .pointer(name)
+                .and_then(JsonValue::as_u64)
+                .ok_or_else(|| MongoAtlasError::Parse(format!("updateOne response missing {}", &name[1..])))
+        };
+        Ok(MongoUpdateResult {
+            matched_count: count("/matchedCount")?,
+            modified_count: count("/modifiedCount")?,
+        })
+    }
+
     /// Ping command to verify connectivity.
     pub fn ping(&self) -> Result<(), MongoAtlasError> {
-        let mut payload = HashMap::new();
+        let mut payload = JsonObject::new();
         payload.insert(
             "dataSource".to_string(),
             JsonValue::String(self.cluster.clone()),
@@ -151,12 +306,12 @@ impl MongoAtlasClient {
         // The Data API exposes a "ping" action via the admin endpoint.
         // We emulate it using an aggregation that returns a constant value.
         let pipeline = JsonValue::Array(vec![JsonValue::Object({
-            let mut stage = HashMap::new();
+            let mut stage = JsonObject::new();
             stage.insert(
                 "$project".to_string(),
                 JsonValue::Object({
-                    let mut proj = HashMap::new();
-                    proj.insert("alive".to_string(), JsonValue::Number(1.0));
+                    let mut proj = JsonObject::new();
+                    proj.insert("alive".to_string(), JsonValue::from(1));
                     proj
                 }),
             );
@@ -195,8 +350,8 @@ impl MongoAtlasClient {
             )));
         }
 
-        avila_json::parse(&response.body).map_err(|_| {
-            MongoAtlasError::Parse(format!("Invalid JSON response: {}", response.body))
+        avila_json::parse(&response.body).map_err(|e| {
+            MongoAtlasError::Parse(format!("Invalid JSON response ({}): {}", e, response.body))
         })
     }
 }
@@ -204,16 +359,21 @@ impl MongoAtlasClient {
 /// Simple BSON-like document representation leveraging JsonValue.
 #[derive(Debug, Clone, Default)]
 pub struct MongoDocument {
-    fields: HashMap<String, JsonValue>,
+    fields: JsonObject,
 }
 
 impl MongoDocument {
     pub fn new() -> Self {
         Self {
-            fields: HashMap::new(),
+            fields: JsonObject::new(),
         }
     }
 
+    /// Build a document from any value that serializes to a JSON object
+    pub fn from_value<T: ToJson + ?Sized>(value: &T) -> Result<Self, FromJsonError> {
+        Self::from_json(&value.to_json())
+    }
+
     pub fn insert_string(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
         self.fields
             .insert(key.into(), JsonValue::String(value.into()));
@@ -221,7 +381,13 @@ impl MongoDocument {
     }
 
     pub fn insert_number(&mut self, key: impl Into<String>, value: f64) -> &mut Self {
-        self.fields.insert(key.into(), JsonValue::Number(value));
+        self.fields.insert(key.into(), JsonValue::from(value));
+        self
+    }
+
+    /// Exact integer field; unlike `insert_number` large IDs keep every digit
+    pub fn insert_integer(&mut self, key: impl Into<String>, value: i64) -> &mut Self {
+        self.fields.insert(key.into(), JsonValue::from(value));
         self
     }
 
@@ -238,11 +404,55 @@ impl MongoDocument {
     pub fn to_json(&self) -> JsonValue {
         JsonValue::Object(self.fields.clone())
     }
+
+    /// `$set`/`$unset` update turning the stored `previous` version into this
+    /// document, or `None` when nothing changed.
+    ///
+    /// Changed top-level fields are set whole, so nested objects and arrays
+    /// never end up half-merged. `_id` is left alone.
+    pub fn update_from(&self, previous: &JsonValue) -> Option<JsonValue> {
+        let changes = avila_json::merge_diff(previous, &self.to_json())?;
+        let mut set = JsonObject::new();
+        let mut unset = JsonObject::new();
+        for (key, change) in changes.as_object()?.iter().filter(|(key, _)| *key != "_id") {
+            match self.fields.get(key) {
+                Some(value) if !change.is_null() => set.insert(key.clone(), value.clone()),
+                _ => unset.insert(key.clone(), JsonValue::String(String::new())),
+            };
+        }
+
+        let mut update = JsonObject::new();
+        if !set.is_empty() {
+            update.insert("$set", JsonValue::Object(set));
+        }
+        if !unset.is_empty() {
+            update.insert("$unset", JsonValue::Object(unset));
+        }
+        (!update.is_empty()).then_some(JsonValue::Object(update))
+    }
+}
+
+impl ToJson for MongoDocument {
+    fn to_json(&self) -> JsonValue {
+        MongoDocument::to_json(self)
+    }
+}
+
+impl FromJson for MongoDocument {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        JsonObject::from_json(value).map(Self::from)
+    }
 }
 
 impl From<HashMap<String, JsonValue>> for MongoDocument {
     fn from(map: HashMap<String, JsonValue>) -> Self {
-        Self { fields: map }
+        Self { fields: map.into() }
+    }
+}
+
+impl From<JsonObject> for MongoDocument {
+    fn from(fields: JsonObject) -> Self {
+        Self { fields }
     }
 }
 
@@ -251,9 +461,16 @@ pub struct MongoInsertOneResult {
     pub inserted_id: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct MongoUpdateResult {
+    pub matched_count: u64,
+    pub modified_count: u64,
+}
+
 #[derive(Debug)]
 pub enum MongoAtlasError {
     MissingEnv(&'static str),
+    MissingSecret(&'static str),
     Http(HttpError),
     Api(String),
     Parse(String),
@@ -264,6 +481,7 @@ impl std::fmt::Display for MongoAtlasError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             MongoAtlasError::MissingEnv(var) => write!(f, "Missing environment variable: {}", var),
+            MongoAtlasError::MissingSecret(name) => write!(f, "Missing vault secret: {}", name),
             MongoAtlasError::Http(err) => write!(f, "HTTP error: {}", err),
             MongoAtlasError::Api(msg) => write!(f, "Atlas API error: {}", msg),
             MongoAtlasError::Parse(msg) => write!(f, "Parse error: {}", msg),
@@ -274,6 +492,34 @@ impl std::fmt::Display for MongoAtlasError {
 
 impl std::error::Error for MongoAtlasError {}
 
+/// Failure partway through `import_ndjson`.
+#[derive(Debug)]
+pub struct NdjsonImportError {
+    /// Documents inserted by the batches that succeeded
+    pub inserted: usize,
+    /// 1-based line of the bad document, or the first line of the rejected batch
+    pub line: usize,
+    /// 1-based line of the first document that was not inserted
+    pub resume_from: usize,
+    pub error: MongoAtlasError,
+}
+
+impl std::fmt::Display for NdjsonImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "import stopped at line {} after {} documents: {}",
+            self.line, self.inserted, self.error
+        )
+    }
+}
+
+impl std::error::Error for NdjsonImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
 fn read_env(name: &'static str) -> Result<String, MongoAtlasError> {
     std::env::var(name).map_err(|_| MongoAtlasError::MissingEnv(name))
 }
@@ -330,6 +576,15 @@ fn parse_find_one_response(value: JsonValue) -> Result<Option<JsonValue>, MongoA
     }
 }
 
+fn parse_find_response(value: JsonValue) -> Result<Vec<JsonValue>, MongoAtlasError> {
+    match value.pointer("/documents") {
+        Some(JsonValue::Array(documents)) => Ok(documents.clone()),
+        _ => Err(MongoAtlasError::Parse(
+            "find response missing documents".to_string(),
+        )),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,9 +604,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn document_from_value() {
+        let value: Vec<(String, f64)> = vec![("price".to_string(), 1.5)];
+        let err = MongoDocument::from_value(&value).unwrap_err();
+        assert_eq!(err.to_string(), "$: expected object, found array");
+
+        let doc = MongoDocument::from_value(&avila_json::parse(r#"{"b": 1, "a": 2}"#).unwrap()).unwrap();
+        assert_eq!(doc.to_json().to_string(), r#"{"b":1,"a":2}"#);
+    }
+
+    #[test]
+    fn import_ndjson_reports_failing_line() {
+        let client = MongoAtlasClient::new("app", "cluster", "db", "coll", "key");
+        let input = "{\"a\": 1}\n\n{\"a\": \n{\"a\": 3}\n";
+        let err = client.import_ndjson(input.as_bytes(), 10).unwrap_err();
+        assert_eq!((err.inserted, err.line, err.resume_from), (0, 3, 1));
+        assert!(matches!(err.error, MongoAtlasError::Parse(_)));
+    }
+
+    #[test]
+    fn update_from_previous_version() {
+        let previous = avila_json::parse(
+            r#"{"_id": {"$oid": "507f1f77bcf86cd799439011"}, "title": "Marina 2BR", "price": 1850000, "agent": {"name": "A", "phone": "1"}, "building": "Pinnacle"}"#,
+        )
+        .unwrap();
+        let current = MongoDocument::from_value(&avila_json::parse(
+            r#"{"title": "Marina 2BR", "price": 1799000, "agent": {"name": "A"}, "ready": true}"#,
+        ).unwrap())
+        .unwrap();
+
+        let update = current.update_from(&previous).unwrap();
+        assert_eq!(
+            update.to_string(),
+            r#"{"$set":{"price":1799000,"agent":{"name":"A"},"ready":true},"$unset":{"building":""}}"#
+        );
+
+        let mut unchanged = previous.clone();
+        unchanged.as_object_mut().unwrap().remove("_id");
+        assert!(MongoDocument::from_value(&unchanged).unwrap().update_from(&previous).is_none());
+    }
+
     #[test]
     fn parse_insert_response_with_string_id() {
-        let mut obj = HashMap::new();
+        let mut obj = JsonObject::new();
         obj.insert(
             "insertedId".to_string(),
             JsonValue::String("abc123".to_string()),
@@ -362,10 +658,10 @@ mod tests {
 
     #[test]
     fn parse_insert_response_with_oid() {
-        let mut oid = HashMap::new();
+        let mut oid = JsonObject::new();
         oid.insert("$oid".to_string(), JsonValue::String("507f1f77bcf86cd799439011".to_string()));
 
-        let mut obj = HashMap::new();
+        let mut obj = JsonObject::new();
         obj.insert("insertedId".to_string(), JsonValue::Object(oid));
 
         let result = parse_insert_one_response(JsonValue::Object(obj)).unwrap();
@@ -374,10 +670,10 @@ mod tests {
 
     #[test]
     fn parse_find_response_document() {
-        let mut doc = HashMap::new();
+        let mut doc = JsonObject::new();
         doc.insert("name".to_string(), JsonValue::String("Dubai".to_string()));
 
-        let mut resp = HashMap::new();
+        let mut resp = JsonObject::new();
         resp.insert("document".to_string(), JsonValue::Object(doc.clone()));
 
         let parsed = parse_find_one_response(JsonValue::Object(resp)).unwrap();
@@ -386,4 +682,13 @@ mod tests {
             assert_eq!(map.get("name"), Some(&JsonValue::String("Dubai".to_string())));
         }
     }
+
+    #[test]
+    fn parse_find_many_response() {
+        let response = avila_json::parse(r#"{"documents": [{"key_id": "k1"}, {"key_id": "k2"}]}"#).unwrap();
+        let documents = parse_find_response(response).unwrap();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[1].pointer("/key_id").and_then(|v| v.as_str()), Some("k2"));
+        assert!(parse_find_response(JsonValue::Null).is_err());
+    }
 }
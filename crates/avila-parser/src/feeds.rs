@@ -0,0 +1,391 @@
+// AvilaParser Feeds - sitemaps, RSS and Atom
+// Zero External Dependencies 🦀
+
+use crate::xml::{XmlDocument, XmlElement, XmlError};
+use std::fmt;
+
+const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+const DUBLIN_CORE_NAMESPACE: &str = "http://purl.org/dc/elements/1.1/";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedError {
+    Xml(XmlError),
+    /// The document is well-formed but its root is not the expected format
+    UnexpectedRoot(String),
+}
+
+impl fmt::Display for FeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedError::Xml(err) => write!(f, "{}", err),
+            FeedError::UnexpectedRoot(root) => write!(f, "Unexpected root element <{}>", root),
+        }
+    }
+}
+
+impl std::error::Error for FeedError {}
+
+impl From<XmlError> for FeedError {
+    fn from(err: XmlError) -> Self {
+        FeedError::Xml(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapEntry {
+    pub loc: String,
+    /// W3C datetime as published (`2024-05-01` or `2024-05-01T10:00:00+04:00`)
+    pub lastmod: Option<String>,
+    pub changefreq: Option<String>,
+    pub priority: Option<f64>,
+}
+
+/// A `<urlset>` of pages or a `<sitemapindex>` pointing at further sitemaps
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sitemap {
+    UrlSet(Vec<SitemapEntry>),
+    Index(Vec<SitemapEntry>),
+}
+
+impl SitemapEntry {
+    /// `lastmod` as Unix seconds; a date without a time means midnight UTC
+    pub fn lastmod_epoch(&self) -> Option<i64> {
+        self.lastmod.as_deref().and_then(w3c_datetime_epoch)
+    }
+
+    /// True when the entry changed at or after `epoch`, or its age is unknown
+    pub fn modified_since(&self, epoch: i64) -> bool {
+        self.lastmod_epoch().is_none_or(|lastmod| lastmod >= epoch)
+    }
+}
+
+impl Sitemap {
+    pub fn parse(xml: &str) -> Result<Self, FeedError> {
+        let document = XmlDocument::parse(xml)?;
+        let root = &document.root;
+        let (is_index, entry_name) = match root.local_name.as_str() {
+            "urlset" => (false, "url"),
+            "sitemapindex" => (true, "sitemap"),
+            _ => return Err(FeedError::UnexpectedRoot(root.qualified_name())),
+        };
+
+        let entries = root
+            .children_ns(root.namespace.as_deref(), entry_name)
+            .filter_map(|entry| {
+                Some(SitemapEntry {
+                    loc: child_text(entry, "loc")?,
+                    lastmod: child_text(entry, "lastmod"),
+                    changefreq: child_text(entry, "changefreq"),
+                    priority: child_text(entry, "priority").and_then(|p| p.parse().ok()),
+                })
+            })
+            .collect();
+        Ok(if is_index {
+            Sitemap::Index(entries)
+        } else {
+            Sitemap::UrlSet(entries)
+        })
+    }
+
+    pub fn entries(&self) -> &[SitemapEntry] {
+        match self {
+            Sitemap::UrlSet(entries) | Sitemap::Index(entries) => entries,
+        }
+    }
+
+    pub fn is_index(&self) -> bool {
+        matches!(self, Sitemap::Index(_))
+    }
+
+    /// Entries changed since the last crawl, for incremental discovery
+    pub fn modified_since(&self, epoch: i64) -> impl Iterator<Item = &SitemapEntry> {
+        self.entries()
+            .iter()
+            .filter(move |entry| entry.modified_since(epoch))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeedKind {
+    Rss,
+    Atom,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feed {
+    pub kind: FeedKind,
+    pub title: String,
+    pub link: Option<String>,
+    pub items: Vec<FeedItem>,
+}
+
+/// An RSS `<item>` or Atom `<entry>`; dates are kept as published
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FeedItem {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    /// RSS `guid` or Atom `id`
+    pub id: Option<String>,
+    pub published: Option<String>,
+    pub updated: Option<String>,
+    pub summary: Option<String>,
+}
+
+impl Feed {
+    /// Reads RSS 2.0, RSS 1.0 (RDF) and Atom 1.0
+    pub fn parse(xml: &str) -> Result<Self, FeedError> {
+        let document = XmlDocument::parse(xml)?;
+        let root = &document.root;
+        match root.local_name.as_str() {
+            "feed" if root.namespace.as_deref() == Some(ATOM_NAMESPACE) => Ok(atom_feed(root)),
+            "rss" | "RDF" => rss_feed(root),
+            _ => Err(FeedError::UnexpectedRoot(root.qualified_name())),
+        }
+    }
+}
+
+fn rss_feed(root: &XmlElement) -> Result<Feed, FeedError> {
+    let channel = root
+        .elements()
+        .find(|e| e.local_name == "channel")
+        .ok_or_else(|| FeedError::UnexpectedRoot(root.qualified_name()))?;
+    // RSS 2.0 nests items in the channel, RSS 1.0 puts them beside it
+    let items = channel
+        .elements()
+        .chain(root.elements())
+        .filter(|e| e.local_name == "item")
+        .map(|item| {
+            let dc_date = item
+                .child_ns(Some(DUBLIN_CORE_NAMESPACE), "date")
+                .map(|d| d.text().trim().to_string());
+            FeedItem {
+                title: child_text(item, "title"),
+                link: child_text(item, "link"),
+                id: child_text(item, "guid"),
+                published: child_text(item, "pubDate").or_else(|| dc_date.clone()),
+                updated: dc_date,
+                summary: child_text(item, "description"),
+            }
+        })
+        .collect();
+    Ok(Feed {
+        kind: FeedKind::Rss,
+        title: child_text(channel, "title").unwrap_or_default(),
+        link: child_text(channel, "link"),
+        items,
+    })
+}
+
+fn atom_feed(root: &XmlElement) -> Feed {
+    let items = root
+        .children_ns(Some(ATOM_NAMESPACE), "entry")
+        .map(|entry| FeedItem {
+            title: child_text(entry, "title"),
+            link: atom_link(entry),
+            id: child_text(entry, "id"),
+            published: child_text(entry, "published"),
+            updated: child_text(entry, "updated"),
+            summary: child_text(entry, "summary").or_else(|| child_text(entry, "content")),
+        })
+        .collect();
+    Feed {
+        kind: FeedKind::Atom,
+        title: child_text(root, "title").unwrap_or_default(),
+        link: atom_link(root),
+        items,
+    }
+}
+
+/// The `alternate` link (the default relation) of an Atom feed or entry
+fn atom_link(element: &XmlElement) -> Option<String> {
+    element
+        .children_ns(Some(ATOM_NAMESPACE), "link")
+        .find(|link| link.attribute("rel").is_none_or(|rel| rel == "alternate"))
+        .and_then(|link| link.attribute("href"))
+        .map(str::to_string)
+}
+
+/// Trimmed text of the first child with `local_name`, in the parent's namespace
+/// or in none (RSS 2.0 has no namespace, RSS 1.0 does)
+fn child_text(parent: &XmlElement, local_name: &str) -> Option<String> {
+    parent
+        .child(local_name)
+        .or_else(|| parent.child_ns(None, local_name))
+        .map(|e| e.text().trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+/// `YYYY`, `YYYY-MM`, `YYYY-MM-DD` or `YYYY-MM-DDThh:mm[:ss[.f]]` with `Z` or `±hh:mm`
+fn w3c_datetime_epoch(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let mut parts = date.split('-');
+    let year: i64 = parts.next().filter(|y| y.len() == 4)?.parse().ok()?;
+    let month: i64 = parts.next().map_or(Some(1), |m| m.parse().ok())?;
+    let day: i64 = parts.next().map_or(Some(1), |d| d.parse().ok())?;
+    if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut seconds = days_from_civil(year, month, day) * 86_400;
+    if let Some(time) = time {
+        let (clock, offset) = match time.find(['Z', '+', '-']) {
+            Some(i) => (&time[..i], &time[i..]),
+            None => (time, "Z"),
+        };
+        let mut fields = clock.split(':');
+        let hours: i64 = fields.next()?.parse().ok()?;
+        let minutes: i64 = fields.next()?.parse().ok()?;
+        let secs: f64 = fields.next().map_or(Some(0.0), |s| s.parse().ok())?;
+        if hours > 23 || minutes > 59 || !(0.0..61.0).contains(&secs) {
+            return None;
+        }
+        seconds += hours * 3600 + minutes * 60 + secs as i64;
+
+        if offset != "Z" {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (oh, om) = offset[1..].split_once(':')?;
+            let offset_seconds = oh.parse::<i64>().ok()? * 3600 + om.parse::<i64>().ok()? * 60;
+            seconds -= sign * offset_seconds;
+        }
+    }
+    Some(seconds)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sitemap_and_index() {
+        let index = r#"<?xml version="1.0" encoding="UTF-8"?>
+<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://www.bayut.com/sitemaps/for-sale-1.xml</loc><lastmod>2024-05-01T10:00:00+04:00</lastmod></sitemap>
+  <sitemap><loc>https://www.bayut.com/sitemaps/for-sale-2.xml</loc><lastmod>2024-04-01</lastmod></sitemap>
+  <sitemap><loc>https://www.bayut.com/sitemaps/new.xml</loc></sitemap>
+</sitemapindex>"#;
+        let sitemap = Sitemap::parse(index).unwrap();
+        assert!(sitemap.is_index());
+        assert_eq!(sitemap.entries().len(), 3);
+        assert_eq!(sitemap.entries()[0].lastmod_epoch(), Some(1_714_543_200));
+
+        // 2024-04-15: the April sitemap is skipped, the one without lastmod kept
+        let changed: Vec<_> = sitemap
+            .modified_since(1_713_139_200)
+            .map(|e| e.loc.as_str())
+            .collect();
+        assert_eq!(
+            changed,
+            vec![
+                "https://www.bayut.com/sitemaps/for-sale-1.xml",
+                "https://www.bayut.com/sitemaps/new.xml"
+            ]
+        );
+
+        let urlset = r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc> https://www.bayut.com/property/details-8812.html </loc><changefreq>daily</changefreq><priority>0.8</priority></url>
+  <url><lastmod>2024-01-01</lastmod></url>
+</urlset>"#;
+        let sitemap = Sitemap::parse(urlset).unwrap();
+        assert_eq!(
+            sitemap.entries(),
+            &[SitemapEntry {
+                loc: "https://www.bayut.com/property/details-8812.html".to_string(),
+                lastmod: None,
+                changefreq: Some("daily".to_string()),
+                priority: Some(0.8),
+            }]
+        );
+        assert!(matches!(
+            Sitemap::parse("<rss/>"),
+            Err(FeedError::UnexpectedRoot(root)) if root == "rss"
+        ));
+        assert!(matches!(Sitemap::parse("<urlset>"), Err(FeedError::Xml(_))));
+    }
+
+    #[test]
+    fn test_rss_and_atom_feeds() {
+        let rss = r#"<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/"><channel>
+  <title>Dubai Land Department</title><link>https://dubailand.gov.ae</link>
+  <item><title>Q2 transactions</title><link>https://dubailand.gov.ae/news/1</link>
+    <guid>news-1</guid><pubDate>Tue, 02 Jul 2024 08:00:00 GMT</pubDate>
+    <description><![CDATA[<p>Record quarter</p>]]></description></item>
+  <item><title>Rental index</title><dc:date>2024-07-05</dc:date></item>
+</channel></rss>"#;
+        let feed = Feed::parse(rss).unwrap();
+        assert_eq!(feed.kind, FeedKind::Rss);
+        assert_eq!(feed.title, "Dubai Land Department");
+        assert_eq!(feed.items.len(), 2);
+        assert_eq!(
+            feed.items[0].summary.as_deref(),
+            Some("<p>Record quarter</p>")
+        );
+        assert_eq!(
+            feed.items[0].published.as_deref(),
+            Some("Tue, 02 Jul 2024 08:00:00 GMT")
+        );
+        assert_eq!(feed.items[1].updated.as_deref(), Some("2024-07-05"));
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>New listings</title>
+  <link rel="self" href="https://example.com/feed.atom"/><link href="https://example.com/"/>
+  <entry><title>Palm Villa</title><id>urn:listing:42</id><updated>2024-07-01T12:00:00Z</updated>
+    <link rel="alternate" type="text/html" href="https://example.com/listing/42"/><content>6 BR</content></entry>
+</feed>"#;
+        let feed = Feed::parse(atom).unwrap();
+        assert_eq!(feed.kind, FeedKind::Atom);
+        assert_eq!(feed.link.as_deref(), Some("https://example.com/"));
+        assert_eq!(
+            feed.items,
+            vec![FeedItem {
+                title: Some("Palm Villa".to_string()),
+                link: Some("https://example.com/listing/42".to_string()),
+                id: Some("urn:listing:42".to_string()),
+                updated: Some("2024-07-01T12:00:00Z".to_string()),
+                summary: Some("6 BR".to_string()),
+                ..FeedItem::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_w3c_datetimes() {
+        assert_eq!(w3c_datetime_epoch("1970-01-01"), Some(0));
+        assert_eq!(
+            w3c_datetime_epoch("2024-02-29T23:59:59Z"),
+            Some(1_709_251_199)
+        );
+        assert_eq!(
+            w3c_datetime_epoch("2024-03-01T03:59:59.5+04:00"),
+            Some(1_709_251_199)
+        );
+        assert_eq!(
+            w3c_datetime_epoch("2024-07"),
+            w3c_datetime_epoch("2024-07-01")
+        );
+        assert_eq!(w3c_datetime_epoch("2024-07-01T10:00"), Some(1_719_828_000));
+        for invalid in [
+            "",
+            "24-07-01",
+            "2024-13-01",
+            "2024-07-01T25:00Z",
+            "yesterday",
+        ] {
+            assert_eq!(w3c_datetime_epoch(invalid), None, "{}", invalid);
+        }
+    }
+}
@@ -1,17 +1,60 @@
 // AvilaParser - Native HTML/XML Parser
 // Zero External Dependencies 🦀
 
-#[derive(Debug, Clone)]
+mod entities;
+mod feeds;
+mod links;
+mod sanitize;
+mod select;
+mod serialize;
+mod stream;
+mod structured;
+mod table;
+mod tokenizer;
+mod tree;
+mod xml;
+
+pub use entities::decode_entities;
+pub use feeds::{Feed, FeedError, FeedItem, FeedKind, Sitemap, SitemapEntry};
+pub use links::{resolve_url, Link, LinkKind, PageLinks, Pagination};
+pub use sanitize::Sanitizer;
+pub use select::{Selector, SelectorError};
+pub use stream::{parse_reader, ElementCapture, HtmlReader, HtmlStream};
+pub use structured::{MicrodataItem, MicrodataValue, StructuredData};
+pub use table::{CellValue, ColumnType, Table};
+pub use tokenizer::Token;
+pub use xml::{XmlAttribute, XmlDocument, XmlElement, XmlError, XmlNode, XML_NAMESPACE, XMLNS_NAMESPACE};
+
+/// Elements rendered on their own line by `text_content_normalized`
+const BLOCK_ELEMENTS: [&str; 38] = [
+    "address", "article", "aside", "blockquote", "body", "caption", "dd", "details", "dialog",
+    "div", "dl", "dt", "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4",
+    "h5", "h6", "header", "hgroup", "hr", "li", "main", "nav", "ol", "p", "pre", "section",
+    "summary", "table", "tr", "ul", "option",
+];
+
+/// Elements whose content is never visible text
+const HIDDEN_ELEMENTS: [&str; 6] = ["head", "noscript", "script", "style", "template", "title"];
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Element {
     pub tag: String,
     pub attributes: Vec<(String, String)>,
     pub children: Vec<Node>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Node {
     Element(Element),
     Text(String),
+    Comment(String),
+}
+
+/// A parsed HTML document: the optional `<!DOCTYPE>` and the `<html>` root
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub doctype: Option<String>,
+    pub root: Element,
 }
 
 impl Element {
@@ -68,171 +111,141 @@ impl Element {
         }
     }
 
+    /// Descendants matching a CSS selector, in document order
+    pub fn select(&self, selector: &str) -> Result<Vec<&Element>, SelectorError> {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+
+    pub fn select_first(&self, selector: &str) -> Result<Option<&Element>, SelectorError> {
+        Ok(Selector::parse(selector)?.select_first(self))
+    }
+
     pub fn text_content(&self) -> String {
         let mut text = String::new();
         self.collect_text(&mut text);
         text
     }
 
+    /// Visible text with whitespace collapsed: block elements and `<br>` start a
+    /// new line, table cells are separated by a space, and script, style and head
+    /// content is skipped
+    pub fn text_content_normalized(&self) -> String {
+        let mut writer = NormalizedText::default();
+        writer.element(self);
+        writer.text.truncate(writer.text.trim_end().len());
+        writer.text
+    }
+
     fn collect_text(&self, text: &mut String) {
         for child in &self.children {
             match child {
                 Node::Text(t) => text.push_str(t),
                 Node::Element(elem) => elem.collect_text(text),
+                Node::Comment(_) => {}
             }
         }
     }
 }
 
-pub struct HtmlParser {
-    input: Vec<char>,
-    pos: usize,
+#[derive(Default)]
+struct NormalizedText {
+    text: String,
+    pending_space: bool,
+    pending_break: bool,
 }
 
-impl HtmlParser {
-    pub fn new(html: &str) -> Self {
-        Self {
-            input: html.chars().collect(),
-            pos: 0,
+impl NormalizedText {
+    fn element(&mut self, element: &Element) {
+        let tag = element.tag.as_str();
+        if HIDDEN_ELEMENTS.contains(&tag) {
+            return;
         }
-    }
-
-    pub fn parse(&mut self) -> Result<Element, ParseError> {
-        self.skip_whitespace();
-        self.parse_element()
-    }
-
-    fn parse_element(&mut self) -> Result<Element, ParseError> {
-        // Skip to '<'
-        while self.pos < self.input.len() && self.current_char() != '<' {
-            self.pos += 1;
-        }
-
-        if self.pos >= self.input.len() {
-            return Err(ParseError::UnexpectedEnd);
+        if tag == "br" {
+            self.text.push('\n');
+            self.pending_space = false;
+            self.pending_break = false;
+            return;
         }
-
-        self.pos += 1; // skip '<'
-
-        // Parse tag name
-        let tag = self.parse_tag_name()?;
-        let mut element = Element::new(tag.clone());
-
-        // Parse attributes
-        loop {
-            self.skip_whitespace();
-
-            if self.pos >= self.input.len() {
-                return Err(ParseError::UnexpectedEnd);
+        let block = BLOCK_ELEMENTS.contains(&tag);
+        let cell = matches!(tag, "td" | "th");
+        self.pending_break |= block;
+        self.pending_space |= cell;
+        for child in &element.children {
+            match child {
+                Node::Element(child) => self.element(child),
+                Node::Text(text) => self.text(text),
+                Node::Comment(_) => {}
             }
+        }
+        self.pending_break |= block;
+        self.pending_space |= cell;
+    }
 
-            if self.current_char() == '>' {
-                self.pos += 1;
-                break;
+    fn text(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_whitespace() {
+                self.pending_space = true;
+                continue;
             }
-
-            if self.current_char() == '/' {
-                self.pos += 1;
-                if self.current_char() == '>' {
-                    self.pos += 1;
-                    return Ok(element); // Self-closing tag
+            if self.pending_break && !self.text.is_empty() {
+                if !self.text.ends_with('\n') {
+                    self.text.push('\n');
                 }
+            } else if self.pending_space && !self.text.is_empty() && !self.text.ends_with('\n') {
+                self.text.push(' ');
             }
-
-            let (name, value) = self.parse_attribute()?;
-            element.attributes.push((name, value));
+            self.pending_space = false;
+            self.pending_break = false;
+            self.text.push(c);
         }
-
-        // Parse children (simplified)
-        // TODO: Implement proper child parsing
-
-        Ok(element)
     }
+}
 
-    fn parse_tag_name(&mut self) -> Result<String, ParseError> {
-        let mut name = String::new();
-
-        while self.pos < self.input.len() {
-            let c = self.current_char();
-            if c.is_whitespace() || c == '>' || c == '/' {
-                break;
-            }
-            name.push(c);
-            self.pos += 1;
-        }
+/// Builds a full DOM tree, recovering from malformed markup the way browsers do:
+/// missing end tags are implied, stray ones ignored, misnested formatting tags
+/// such as `<b><i>x</b>y</i>` are reopened, and `<html>`, `<head>` and `<body>`
+/// are synthesized when absent.
+pub struct HtmlParser {
+    input: String,
+}
 
-        if name.is_empty() {
-            Err(ParseError::InvalidTag)
-        } else {
-            Ok(name)
+impl HtmlParser {
+    pub fn new(html: &str) -> Self {
+        Self {
+            input: html.to_string(),
         }
     }
 
-    fn parse_attribute(&mut self) -> Result<(String, String), ParseError> {
-        let mut name = String::new();
-
-        // Parse attribute name
-        while self.pos < self.input.len() {
-            let c = self.current_char();
-            if c == '=' || c.is_whitespace() {
-                break;
-            }
-            name.push(c);
-            self.pos += 1;
-        }
-
-        self.skip_whitespace();
-
-        if self.current_char() != '=' {
-            return Ok((name, String::new()));
-        }
-
-        self.pos += 1; // skip '='
-        self.skip_whitespace();
-
-        // Parse attribute value
-        let quote = self.current_char();
-        if quote != '"' && quote != '\'' {
-            return Err(ParseError::InvalidAttribute);
-        }
-
-        self.pos += 1; // skip opening quote
-        let mut value = String::new();
-
-        while self.pos < self.input.len() {
-            if self.current_char() == quote {
-                self.pos += 1;
-                break;
-            }
-            value.push(self.current_char());
-            self.pos += 1;
-        }
-
-        Ok((name, value))
+    /// Parses the input and returns the `<html>` root element
+    pub fn parse(&mut self) -> Result<Element, ParseError> {
+        self.parse_document().map(|document| document.root)
     }
 
-    fn current_char(&self) -> char {
-        self.input[self.pos]
+    pub fn parse_document(&mut self) -> Result<Document, ParseError> {
+        Ok(tree::build_document(&self.input))
     }
 
-    fn skip_whitespace(&mut self) {
-        while self.pos < self.input.len() && self.current_char().is_whitespace() {
-            self.pos += 1;
-        }
+    /// Parses the input as the content of a `<body>`, without synthesizing a document
+    pub fn parse_fragment(&mut self) -> Vec<Node> {
+        tree::build_fragment(&self.input)
     }
 }
 
+/// `HtmlParser` recovers from malformed markup instead of failing, so this is
+/// not produced today; the `Result` keeps room for limits such as input size
 #[derive(Debug)]
 pub enum ParseError {
     UnexpectedEnd,
-    InvalidTag,
-    InvalidAttribute,
 }
 
 pub fn parse_html(html: &str) -> Result<Element, ParseError> {
     HtmlParser::new(html).parse()
 }
 
+pub fn parse_fragment(html: &str) -> Vec<Node> {
+    HtmlParser::new(html).parse_fragment()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -262,4 +275,107 @@ mod tests {
         let spans = parent.find_by_tag("span");
         assert_eq!(spans.len(), 1);
     }
+
+    #[test]
+    fn test_parse_nested_tree() {
+        let html = r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><title>Listings</title></head>
+<body>
+  <!-- results -->
+  <article class="property-card"><h2>Marina View</h2><img src="a.jpg"><span class="price">AED 1,500,000</span></article>
+  <article class="property-card"><h2>Palm Villa</h2></article>
+</body>
+</html>"#;
+        let document = HtmlParser::new(html).parse_document().unwrap();
+        assert_eq!(document.doctype.as_deref(), Some("html"));
+        assert_eq!(document.root.get_attribute("lang"), Some("en"));
+
+        let articles = document.root.find_by_tag("article");
+        assert_eq!(articles.len(), 2);
+        assert_eq!(articles[0].find_by_tag("h2")[0].text_content(), "Marina View");
+        assert_eq!(articles[0].find_by_class("price")[0].text_content(), "AED 1,500,000");
+        assert!(articles[0].find_by_tag("img")[0].children.is_empty());
+
+        let body = document.root.find_by_tag("body")[0];
+        assert!(body.children.iter().any(|n| matches!(n, Node::Comment(c) if c == " results ")));
+    }
+
+    #[test]
+    fn test_entities_and_unquoted_attributes() {
+        let html = "<a href=/search?page=2&amp;sort=price data-id=42 title='R&amp;D'>Next &raquo;</a>\
+                    <span class=price>AED&nbsp;&#1633;&#1634;&#1635; &#x62F;.&#x625;</span>\
+                    <title>Tom &amp; Jerry</title><script>a && b &amp;</script>";
+        let nodes = parse_fragment(html);
+        let Node::Element(a) = &nodes[0] else { panic!("expected element") };
+        assert_eq!(a.get_attribute("href"), Some("/search?page=2&sort=price"));
+        assert_eq!(a.get_attribute("data-id"), Some("42"));
+        assert_eq!(a.get_attribute("title"), Some("R&D"));
+        assert_eq!(a.text_content(), "Next \u{bb}");
+
+        let Node::Element(span) = &nodes[1] else { panic!("expected element") };
+        assert_eq!(span.get_attribute("class"), Some("price"));
+        assert_eq!(span.text_content(), "AED\u{a0}\u{661}\u{662}\u{663} \u{62f}.\u{625}");
+
+        let Node::Element(title) = &nodes[2] else { panic!("expected element") };
+        assert_eq!(title.text_content(), "Tom & Jerry");
+        let Node::Element(script) = &nodes[3] else { panic!("expected element") };
+        assert_eq!(script.text_content(), "a && b &amp;");
+    }
+
+    #[test]
+    fn test_text_content_normalized() {
+        let html = "<head><title>Ignored</title></head><body>
+            <article>
+              <h2>  Marina   Gate\n Tower </h2>
+              <div class=price>AED&nbsp;1,850,000</div><p>2 <b>Beds</b><br>3 Baths</p>
+              <script>track()</script>
+              <table><tr><td>Area</td><td>1,200 sqft</td></tr></table>
+            </article>
+            <span>Dubai</span><span>Marina</span></body>";
+        let root = parse_html(html).unwrap();
+        assert_eq!(
+            root.text_content_normalized(),
+            "Marina Gate Tower\nAED 1,850,000\n2 Beds\n3 Baths\nArea 1,200 sqft\nDubaiMarina"
+        );
+    }
+
+    #[test]
+    fn test_raw_text_elements() {
+        let html = "<div><script>if (a < b && c > d) { x = '</div>'; }</script><style>p > a {}</style>1 < 2</div>";
+        let nodes = parse_fragment(html);
+        let Node::Element(div) = &nodes[0] else { panic!("expected element") };
+        assert_eq!(div.find_by_tag("script")[0].text_content(), "if (a < b && c > d) { x = '</div>'; }");
+        assert_eq!(div.find_by_tag("style")[0].text_content(), "p > a {}");
+        let root = parse_html("<script>let s = '<p>';</script><p>x</p>").unwrap();
+        assert_eq!(root.find_by_tag("script")[0].text_content(), "let s = '<p>';");
+        assert_eq!(root.find_by_tag("p").len(), 1);
+
+        let nodes = parse_fragment("<p>1 < 2 and 3<4</p>");
+        let Node::Element(p) = &nodes[0] else { panic!("expected element") };
+        assert_eq!(p.text_content(), "1 < 2 and 3<4");
+    }
+
+    #[test]
+    fn test_unclosed_and_truncated_markup() {
+        let root = parse_html("<div class=\"card\"><p>One<p>Two<span>cut").unwrap();
+        let card = root.find_by_class("card")[0];
+        assert_eq!(card.find_by_tag("p").len(), 2);
+        assert_eq!(card.text_content(), "OneTwocut");
+        assert_eq!(parse_html("").unwrap().find_by_tag("body").len(), 1);
+    }
+
+    #[test]
+    fn test_deeply_nested_markup() {
+        let html = format!(
+            "{}<a href=\"/property/8812\" itemscope itemtype=\"https://schema.org/Residence\">Marina</a>",
+            "<div>".repeat(20_000)
+        );
+        let root = parse_html(&html).unwrap();
+        assert_eq!(root.select("div a[href]").unwrap().len(), 1);
+        assert!(root.to_html().ends_with("</div></div></body></html>"));
+        let links = PageLinks::extract(&root, "https://www.bayut.com/");
+        assert_eq!(links.links.len(), 1);
+        assert_eq!(StructuredData::extract(&root).microdata.len(), 1);
+    }
 }
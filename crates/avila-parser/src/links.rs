@@ -0,0 +1,474 @@
+// AvilaParser Links - Hyperlinks, images and pagination resolved to absolute URLs
+// Zero External Dependencies 🦀
+
+use crate::{Element, Node};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// `<a href>` and `<area href>`
+    Anchor,
+    /// `<link href>` in the head: stylesheets, canonical, alternate, pagination
+    Link,
+    /// `<img>` and `<source>`, from `src` and each `srcset` candidate
+    Image,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    /// Absolute URL, fragment included
+    pub url: String,
+    pub kind: LinkKind,
+    /// Lowercased `rel` tokens
+    pub rel: Vec<String>,
+    /// Anchor text, or the `alt` of an image
+    pub text: String,
+}
+
+/// Where the neighbouring pages of a paginated listing are
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pagination {
+    pub next: Option<String>,
+    pub prev: Option<String>,
+    /// Numbered page links inside a pagination block, in document order
+    pub pages: Vec<String>,
+}
+
+/// Every link on a page, resolved against the document base
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageLinks {
+    /// `<base href>` resolved against the page URL, or the page URL itself
+    pub base: String,
+    pub links: Vec<Link>,
+    pub pagination: Pagination,
+}
+
+impl PageLinks {
+    /// `page_url` must be absolute; links that cannot be resolved are skipped
+    pub fn extract(root: &Element, page_url: &str) -> Self {
+        let base = root
+            .find_by_tag("base")
+            .into_iter()
+            .find_map(|base| base.get_attribute("href"))
+            .and_then(|href| resolve_url(page_url, href))
+            .unwrap_or_else(|| page_url.to_string());
+
+        let mut page = PageLinks {
+            base,
+            links: Vec::new(),
+            pagination: Pagination::default(),
+        };
+        page.collect(root, false);
+        page
+    }
+
+    pub fn anchors(&self) -> impl Iterator<Item = &Link> {
+        self.of_kind(LinkKind::Anchor)
+    }
+
+    pub fn images(&self) -> impl Iterator<Item = &Link> {
+        self.of_kind(LinkKind::Image)
+    }
+
+    fn of_kind(&self, kind: LinkKind) -> impl Iterator<Item = &Link> {
+        self.links.iter().filter(move |link| link.kind == kind)
+    }
+
+    fn collect(&mut self, element: &Element, in_pagination: bool) {
+        let in_pagination = in_pagination || is_pagination_block(element);
+        match element.tag.as_str() {
+            "a" | "area" | "link" => {
+                if let Some(url) = element
+                    .get_attribute("href")
+                    .and_then(|href| resolve_url(&self.base, href))
+                {
+                    let kind = if element.tag == "link" {
+                        LinkKind::Link
+                    } else {
+                        LinkKind::Anchor
+                    };
+                    let link = Link {
+                        url,
+                        kind,
+                        rel: element
+                            .get_attribute("rel")
+                            .map(|rel| {
+                                rel.split_whitespace()
+                                    .map(str::to_ascii_lowercase)
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                        text: element.text_content_normalized().replace('\n', " "),
+                    };
+                    self.paginate(element, &link, in_pagination);
+                    self.links.push(link);
+                }
+            }
+            "img" | "source" => {
+                let alt = element.get_attribute("alt").unwrap_or_default();
+                let srcset = element.get_attribute("srcset").unwrap_or_default();
+                let sources = element
+                    .get_attribute("src")
+                    .into_iter()
+                    .chain(srcset_urls(srcset));
+                for source in sources {
+                    if let Some(url) = resolve_url(&self.base, source) {
+                        self.links.push(Link {
+                            url,
+                            kind: LinkKind::Image,
+                            rel: Vec::new(),
+                            text: alt.to_string(),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+        for child in &element.children {
+            if let Node::Element(child) = child {
+                self.collect(child, in_pagination);
+            }
+        }
+    }
+
+    /// `rel="next"`/`rel="prev"` win; inside a pagination block the usual
+    /// "Next", "›" and "»" labels and numbered pages are recognised too
+    fn paginate(&mut self, element: &Element, link: &Link, in_pagination: bool) {
+        let has_rel = |name: &str| link.rel.iter().any(|rel| rel == name);
+        if has_rel("next") {
+            self.pagination.next = Some(link.url.clone());
+            return;
+        }
+        if has_rel("prev") || has_rel("previous") {
+            self.pagination.prev = Some(link.url.clone());
+            return;
+        }
+        if !in_pagination || link.kind != LinkKind::Anchor {
+            return;
+        }
+        let label = element
+            .get_attribute("aria-label")
+            .unwrap_or(&link.text)
+            .trim()
+            .to_lowercase();
+        if label.starts_with("next") || matches!(label.as_str(), "›" | "»" | ">" | ">>") {
+            self.pagination.next.get_or_insert_with(|| link.url.clone());
+        } else if label.starts_with("prev") || matches!(label.as_str(), "‹" | "«" | "<" | "<<") {
+            self.pagination.prev.get_or_insert_with(|| link.url.clone());
+        } else if !link.text.is_empty()
+            && link.text.trim().bytes().all(|b| b.is_ascii_digit())
+            && !self.pagination.pages.contains(&link.url)
+        {
+            self.pagination.pages.push(link.url.clone());
+        }
+    }
+}
+
+/// Anything whose class, `aria-label` or test id mentions `pagination` or `pager`
+fn is_pagination_block(element: &Element) -> bool {
+    let mentions = |value: Option<&str>| {
+        value.is_some_and(|v| {
+            let v = v.to_ascii_lowercase();
+            v.contains("pagination") || v.contains("pager")
+        })
+    };
+    mentions(element.get_attribute("class"))
+        || mentions(element.get_attribute("aria-label"))
+        || mentions(element.get_attribute("data-testid"))
+}
+
+/// URLs of a `srcset`, without their width or density descriptors
+pub(crate) fn srcset_urls(srcset: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        if rest.is_empty() {
+            return urls;
+        }
+        let end = rest
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let url = &rest[..end];
+        rest = &rest[end..];
+        // A URL ending in commas has no descriptors
+        let trimmed = url.trim_end_matches(',');
+        if trimmed.len() == url.len() {
+            let descriptors = rest.find(',').unwrap_or(rest.len());
+            rest = &rest[descriptors..];
+        }
+        urls.push(trimmed);
+    }
+}
+
+/// Resolves `reference` against the absolute URL `base` (RFC 3986, section 5.2).
+/// Surrounding whitespace and embedded tabs and newlines are dropped, and in
+/// http(s) URLs `\` counts as `/`, as browsers do. `None` when `base` has no scheme.
+pub fn resolve_url(base: &str, reference: &str) -> Option<String> {
+    let base = UrlParts::parse(base);
+    let base_scheme = base.scheme?;
+    let mut cleaned: String = reference
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    let special = is_special(UrlParts::parse(&cleaned).scheme.unwrap_or(base_scheme));
+    if special {
+        cleaned = cleaned.replace('\\', "/");
+    }
+    let mut reference = UrlParts::parse(&cleaned);
+    // "http:page.html" is relative to an http page, as in browsers
+    if reference.authority.is_none()
+        && reference
+            .scheme
+            .is_some_and(|scheme| is_special(scheme) && scheme.eq_ignore_ascii_case(base_scheme))
+    {
+        reference.scheme = None;
+    }
+
+    let (scheme, authority, path, query);
+    if let Some(s) = reference.scheme {
+        scheme = s;
+        authority = reference.authority;
+        path = remove_dot_segments(reference.path);
+        query = reference.query;
+    } else {
+        scheme = base_scheme;
+        if reference.authority.is_some() {
+            authority = reference.authority;
+            path = remove_dot_segments(reference.path);
+            query = reference.query;
+        } else {
+            authority = base.authority;
+            if reference.path.is_empty() {
+                path = base.path.to_string();
+                query = reference.query.or(base.query);
+            } else {
+                path = if reference.path.starts_with('/') {
+                    remove_dot_segments(reference.path)
+                } else {
+                    remove_dot_segments(&merge(&base, reference.path))
+                };
+                query = reference.query;
+            }
+        }
+    }
+
+    let mut url = scheme.to_ascii_lowercase();
+    url.push(':');
+    if let Some(authority) = authority {
+        url.push_str("//");
+        url.push_str(authority);
+        if path.is_empty() && special {
+            url.push('/');
+        }
+    }
+    url.push_str(&path);
+    if let Some(query) = query {
+        url.push('?');
+        url.push_str(query);
+    }
+    if let Some(fragment) = reference.fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    Some(url)
+}
+
+struct UrlParts<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+impl<'a> UrlParts<'a> {
+    /// The generic split `scheme:[//authority]path[?query][#fragment]`
+    fn parse(url: &'a str) -> Self {
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (url, None),
+        };
+        let (url, query) = match url.split_once('?') {
+            Some((url, query)) => (url, Some(query)),
+            None => (url, None),
+        };
+        let (scheme, rest) = match url.split_once(':') {
+            Some((scheme, rest)) if is_scheme(scheme) => (Some(scheme), rest),
+            _ => (None, url),
+        };
+        let (authority, path) = match rest.strip_prefix("//") {
+            Some(rest) => {
+                let end = rest.find('/').unwrap_or(rest.len());
+                (Some(&rest[..end]), &rest[end..])
+            }
+            None => (None, rest),
+        };
+        UrlParts {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        }
+    }
+}
+
+fn is_scheme(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+fn is_special(scheme: &str) -> bool {
+    scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")
+}
+
+fn merge(base: &UrlParts, path: &str) -> String {
+    if base.authority.is_some() && base.path.is_empty() {
+        return format!("/{}", path);
+    }
+    match base.path.rfind('/') {
+        Some(i) => format!("{}{}", &base.path[..=i], path),
+        None => path.to_string(),
+    }
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let mut output: Vec<&str> = Vec::new();
+    let segments: Vec<&str> = path.split('/').skip(usize::from(absolute)).collect();
+    for (i, segment) in segments.iter().enumerate() {
+        let last = i + 1 == segments.len();
+        match *segment {
+            "." => {
+                if last {
+                    output.push("");
+                }
+            }
+            ".." => {
+                output.pop();
+                if last {
+                    output.push("");
+                }
+            }
+            segment => output.push(segment),
+        }
+    }
+    let joined = output.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_html;
+
+    #[test]
+    fn test_resolve_url() {
+        let base = "https://www.bayut.com/for-sale/property/dubai/?page=2#top";
+        let cases = [
+            ("/to-rent/", "https://www.bayut.com/to-rent/"),
+            (
+                "details-123.html",
+                "https://www.bayut.com/for-sale/property/dubai/details-123.html",
+            ),
+            ("../../", "https://www.bayut.com/for-sale/"),
+            (
+                "./a/./b/../c",
+                "https://www.bayut.com/for-sale/property/dubai/a/c",
+            ),
+            (
+                "?page=3",
+                "https://www.bayut.com/for-sale/property/dubai/?page=3",
+            ),
+            (
+                "#map",
+                "https://www.bayut.com/for-sale/property/dubai/?page=2#map",
+            ),
+            ("", "https://www.bayut.com/for-sale/property/dubai/?page=2"),
+            ("//cdn.bayut.com/x.jpg", "https://cdn.bayut.com/x.jpg"),
+            ("HTTPS://Example.com", "https://Example.com/"),
+            ("  /a\n/b ", "https://www.bayut.com/a/b"),
+            ("\\\\evil.example\\x", "https://evil.example/x"),
+            ("mailto:sales@bayut.com", "mailto:sales@bayut.com"),
+            ("javascript:void(0)", "javascript:void(0)"),
+            ("/../../x", "https://www.bayut.com/x"),
+        ];
+        for (reference, expected) in cases {
+            assert_eq!(
+                resolve_url(base, reference).as_deref(),
+                Some(expected),
+                "{:?}",
+                reference
+            );
+        }
+        assert_eq!(resolve_url("/relative/base", "x"), None);
+    }
+
+    #[test]
+    fn test_page_links() {
+        let html = r#"<html><head>
+            <base href="/en/">
+            <link rel="stylesheet" href="/static/app.css">
+            <link rel="next" href="search?page=3">
+        </head><body>
+            <a href="property/details-1.html">Marina Gate <b>2 BR</b></a>
+            <img src="img/1.jpg" srcset="img/1-480.jpg 480w, img/1,800.jpg 800w,img/1-2x.jpg 2x" alt="Living room">
+            <picture><source srcset="img/1.webp"></picture>
+            <a>No href</a>
+            <nav class="pagination">
+              <a href="search?page=1" aria-label="Previous page">‹</a>
+              <a href="search?page=1">1</a><a href="search?page=2">2</a><a href="search?page=3">3</a>
+              <a href="search?page=3">Next</a>
+            </nav>
+        </body></html>"#;
+        let page = PageLinks::extract(
+            &parse_html(html).unwrap(),
+            "https://www.bayut.com/en/search?page=2",
+        );
+        assert_eq!(page.base, "https://www.bayut.com/en/");
+
+        let anchors: Vec<&Link> = page.anchors().collect();
+        assert_eq!(
+            anchors[0].url,
+            "https://www.bayut.com/en/property/details-1.html"
+        );
+        assert_eq!(anchors[0].text, "Marina Gate 2 BR");
+        assert_eq!(anchors.len(), 6);
+
+        let images: Vec<&str> = page.images().map(|l| l.url.as_str()).collect();
+        assert_eq!(
+            images,
+            vec![
+                "https://www.bayut.com/en/img/1.jpg",
+                "https://www.bayut.com/en/img/1-480.jpg",
+                "https://www.bayut.com/en/img/1,800.jpg",
+                "https://www.bayut.com/en/img/1-2x.jpg",
+                "https://www.bayut.com/en/img/1.webp",
+            ]
+        );
+        assert_eq!(page.images().next().unwrap().text, "Living room");
+
+        let stylesheet = &page.links[0];
+        assert_eq!(stylesheet.kind, LinkKind::Link);
+        assert_eq!(stylesheet.rel, vec!["stylesheet"]);
+
+        assert_eq!(
+            page.pagination,
+            Pagination {
+                next: Some("https://www.bayut.com/en/search?page=3".to_string()),
+                prev: Some("https://www.bayut.com/en/search?page=1".to_string()),
+                pages: vec![
+                    "https://www.bayut.com/en/search?page=1".to_string(),
+                    "https://www.bayut.com/en/search?page=2".to_string(),
+                    "https://www.bayut.com/en/search?page=3".to_string(),
+                ],
+            }
+        );
+    }
+}
@@ -0,0 +1,322 @@
+// AvilaParser Sanitizer - Allowlist cleanup of untrusted HTML
+// Zero External Dependencies 🦀
+
+use crate::links::srcset_urls;
+use crate::{parse_fragment, Element, Node};
+
+const DEFAULT_TAGS: [&str; 39] = [
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "code",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+];
+
+/// `(tag, attribute)`; `*` stands for every allowed tag
+const DEFAULT_ATTRIBUTES: [(&str, &str); 12] = [
+    ("*", "dir"),
+    ("*", "lang"),
+    ("*", "title"),
+    ("a", "href"),
+    ("img", "alt"),
+    ("img", "height"),
+    ("img", "src"),
+    ("img", "width"),
+    ("td", "colspan"),
+    ("td", "rowspan"),
+    ("th", "colspan"),
+    ("th", "rowspan"),
+];
+
+const DEFAULT_SCHEMES: [&str; 4] = ["http", "https", "mailto", "tel"];
+
+/// Disallowed elements are normally replaced by their content; these go
+/// together with it
+const DROP_WITH_CONTENT: [&str; 19] = [
+    "applet",
+    "embed",
+    "frameset",
+    "head",
+    "iframe",
+    "math",
+    "noembed",
+    "noframes",
+    "noscript",
+    "object",
+    "plaintext",
+    "script",
+    "select",
+    "style",
+    "svg",
+    "template",
+    "textarea",
+    "title",
+    "xmp",
+];
+
+/// Attributes whose value is fetched or navigated to
+const URL_ATTRIBUTES: [&str; 9] = [
+    "action",
+    "background",
+    "cite",
+    "data",
+    "formaction",
+    "href",
+    "longdesc",
+    "poster",
+    "src",
+];
+
+/// Allowlist-based cleanup of untrusted HTML, such as listing descriptions
+///
+/// Only allowed tags and attributes are kept. Other elements are replaced by
+/// their content, except scripts, styles, frames, embedded objects and the
+/// like, which are removed whole. Comments are removed, event handler
+/// attributes (`on*`) are never kept, and URLs must be relative or use an
+/// allowed scheme, so `javascript:` and `data:` links are dropped.
+#[derive(Debug, Clone)]
+pub struct Sanitizer {
+    tags: Vec<String>,
+    attributes: Vec<(String, String)>,
+    schemes: Vec<String>,
+}
+
+impl Sanitizer {
+    /// Formatting, lists, tables, links and images; http(s), `mailto:` and `tel:` URLs
+    pub fn new() -> Self {
+        Self {
+            tags: DEFAULT_TAGS.iter().map(|t| t.to_string()).collect(),
+            attributes: DEFAULT_ATTRIBUTES
+                .iter()
+                .map(|(tag, name)| (tag.to_string(), name.to_string()))
+                .collect(),
+            schemes: DEFAULT_SCHEMES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Nothing allowed: only text survives
+    pub fn text_only() -> Self {
+        Self {
+            tags: Vec::new(),
+            attributes: Vec::new(),
+            schemes: Vec::new(),
+        }
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_ascii_lowercase());
+        self
+    }
+
+    pub fn without_tag(mut self, tag: &str) -> Self {
+        self.tags.retain(|t| !t.eq_ignore_ascii_case(tag));
+        self
+    }
+
+    /// Allows `attribute` on `tag`, or on every allowed tag when `tag` is `*`
+    pub fn with_attribute(mut self, tag: &str, attribute: &str) -> Self {
+        self.attributes
+            .push((tag.to_ascii_lowercase(), attribute.to_ascii_lowercase()));
+        self
+    }
+
+    pub fn with_scheme(mut self, scheme: &str) -> Self {
+        self.schemes.push(scheme.to_ascii_lowercase());
+        self
+    }
+
+    /// Parses `html` as a fragment and returns its sanitized serialization
+    pub fn clean(&self, html: &str) -> String {
+        self.sanitize(&parse_fragment(html))
+            .iter()
+            .map(Node::to_html)
+            .collect()
+    }
+
+    pub fn sanitize(&self, nodes: &[Node]) -> Vec<Node> {
+        let mut clean = Vec::new();
+        for node in nodes {
+            self.sanitize_node(node, &mut clean);
+        }
+        clean
+    }
+
+    fn sanitize_node(&self, node: &Node, clean: &mut Vec<Node>) {
+        match node {
+            Node::Text(text) => match clean.last_mut() {
+                // Unwrapped elements can leave text next to text
+                Some(Node::Text(existing)) => existing.push_str(text),
+                _ => clean.push(Node::Text(text.clone())),
+            },
+            Node::Comment(_) => {}
+            Node::Element(element) => {
+                let tag = element.tag.as_str();
+                if DROP_WITH_CONTENT.contains(&tag) && !self.allows_tag(tag) {
+                    return;
+                }
+                if !self.allows_tag(tag) {
+                    for child in &element.children {
+                        self.sanitize_node(child, clean);
+                    }
+                    return;
+                }
+                clean.push(Node::Element(Element {
+                    tag: element.tag.clone(),
+                    attributes: element
+                        .attributes
+                        .iter()
+                        .filter(|(name, value)| self.allows_attribute(tag, name, value))
+                        .cloned()
+                        .collect(),
+                    children: self.sanitize(&element.children),
+                }));
+            }
+        }
+    }
+
+    fn allows_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    fn allows_attribute(&self, tag: &str, name: &str, value: &str) -> bool {
+        if name.starts_with("on") {
+            return false;
+        }
+        let allowed = self
+            .attributes
+            .iter()
+            .any(|(t, n)| n == name && (t == tag || t == "*"));
+        if !allowed {
+            return false;
+        }
+        if URL_ATTRIBUTES.contains(&name) {
+            return self.allows_url(value);
+        }
+        if name == "srcset" {
+            return srcset_urls(value)
+                .into_iter()
+                .all(|url| self.allows_url(url));
+        }
+        true
+    }
+
+    /// Relative URLs, or absolute ones with an allowed scheme
+    fn allows_url(&self, url: &str) -> bool {
+        // Browsers ignore control characters and whitespace here, so
+        // "java\tscript:" is still a javascript: URL
+        let url: String = url.chars().filter(|c| *c > ' ').collect();
+        let scheme_end = url.find([':', '/', '?', '#']);
+        match scheme_end {
+            Some(i) if url[i..].starts_with(':') => {
+                let scheme = url[..i].to_ascii_lowercase();
+                self.schemes.contains(&scheme)
+            }
+            _ => true,
+        }
+    }
+}
+
+impl Default for Sanitizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_and_handlers_removed() {
+        let sanitizer = Sanitizer::new().with_attribute("*", "onclick");
+        let html = sanitizer.clean(
+            "<p onclick=\"steal()\" onmouseover=x title=Sea>Sea view<script>alert(1)</script></p>\
+             <style>p{}</style><iframe src=//evil.example></iframe>\
+             <svg><a href=x><script>alert(2)</script></a></svg><!-- hidden -->",
+        );
+        assert_eq!(html, "<p title=\"Sea\">Sea view</p>");
+    }
+
+    #[test]
+    fn test_dangerous_urls_removed() {
+        let sanitizer = Sanitizer::new();
+        let html = sanitizer.clean(
+            "<a href=\"javascript:alert(1)\">a</a>\
+             <a href=\" JaVa&#x09;ScRiPt:alert(1)\">b</a>\
+             <a href=\"&#106;avascript:alert(1)\">c</a>\
+             <img src=\"data:image/svg+xml;base64,PHN2Zz4=\" alt=d>\
+             <a href=\"/for-sale/?page=2\">e</a><a href=\"mailto:sales@bayut.com\">f</a>\
+             <a href=\"https://www.bayut.com/a:b\">g</a><a href=\"vbscript:x\">h</a>",
+        );
+        assert_eq!(
+            html,
+            "<a>a</a><a>b</a><a>c</a><img alt=\"d\">\
+             <a href=\"/for-sale/?page=2\">e</a><a href=\"mailto:sales@bayut.com\">f</a>\
+             <a href=\"https://www.bayut.com/a:b\">g</a><a>h</a>"
+        );
+    }
+
+    #[test]
+    fn test_unknown_elements_unwrapped() {
+        let html = Sanitizer::new().clean(
+            "<font color=red><b>Brand new</b> &lt;3</font> <form action=/x><input value=1>2 BR</form>",
+        );
+        assert_eq!(html, "<b>Brand new</b> &lt;3 2 BR");
+
+        let text = Sanitizer::text_only().clean("<p>Only <b>text</b><script>x</script></p>");
+        assert_eq!(text, "Only text");
+
+        let cells = Sanitizer::new()
+            .without_tag("img")
+            .clean("<td colspan=2 style=x><img src=/a.jpg>1</td><table><td rowspan=3 bgcolor=red>2</table>");
+        assert_eq!(
+            cells,
+            "1<table><tbody><tr><td rowspan=\"3\">2</td></tr></tbody></table>"
+        );
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        // The parser caps nesting, so neither the sanitizer nor the serializer
+        // recurses deeper than that
+        let html = Sanitizer::new().clean(&format!("{}Sea view", "<div><font>".repeat(20_000)));
+        assert_eq!(html.matches("<div>").count(), 20_000);
+        assert_eq!(html.matches("</div>").count(), 20_000);
+        assert!(!html.contains("font"));
+        assert!(html.contains("Sea view"));
+    }
+}
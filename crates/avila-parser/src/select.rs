@@ -0,0 +1,755 @@
+// AvilaParser Selectors - CSS selector matching
+// Zero External Dependencies 🦀
+
+use crate::{Element, Node};
+use std::fmt;
+
+/// A compiled CSS selector list, reusable across documents
+///
+/// Supports type, universal, `#id`, `.class` and attribute selectors
+/// (`[a]`, `=`, `~=`, `|=`, `^=`, `$=`, `*=`, with an optional `i` flag),
+/// the descendant, `>`, `+` and `~` combinators, selector lists and the
+/// pseudo-classes `:first-child`, `:last-child`, `:only-child`,
+/// `:first-of-type`, `:last-of-type`, `:only-of-type`, `:nth-child()`,
+/// `:nth-last-child()`, `:nth-of-type()`, `:nth-last-of-type()`, `:empty`
+/// and `:not()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    alternatives: Vec<Complex>,
+}
+
+/// Compounds and combinators stored right to left, the order they are matched in
+#[derive(Debug, Clone, PartialEq)]
+struct Complex {
+    compounds: Vec<Compound>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+    NextSibling,
+    SubsequentSibling,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Compound {
+    tag: Option<String>,
+    conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Id(String),
+    Class(String),
+    Attribute {
+        name: String,
+        op: AttributeOp,
+        value: String,
+        ignore_case: bool,
+    },
+    /// `an+b`, counting among all siblings or siblings of the same type
+    Nth {
+        a: i64,
+        b: i64,
+        of_type: bool,
+        from_end: bool,
+    },
+    Empty,
+    Not(Selector),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AttributeOp {
+    Exists,
+    Equals,
+    Includes,
+    DashMatch,
+    Prefix,
+    Suffix,
+    Substring,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectorError {
+    pub selector: String,
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid selector '{}' at {}: {}",
+            self.selector, self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        let mut parser = SelectorParser {
+            input: selector,
+            pos: 0,
+        };
+        let parsed = parser.selector_list()?;
+        parser.skip_whitespace();
+        if parser.pos < selector.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        Ok(parsed)
+    }
+
+    /// All descendants of `root` (not `root` itself) that match, in document order
+    pub fn select<'a>(&self, root: &'a Element) -> Vec<&'a Element> {
+        let mut results = Vec::new();
+        let mut path = vec![root];
+        self.collect(&mut path, &mut results, false);
+        results
+    }
+
+    pub fn select_first<'a>(&self, root: &'a Element) -> Option<&'a Element> {
+        let mut results = Vec::new();
+        let mut path = vec![root];
+        self.collect(&mut path, &mut results, true);
+        results.pop()
+    }
+
+    /// Whether `element` matches, with `root` as the topmost ancestor considered
+    pub fn matches(&self, root: &Element, element: &Element) -> bool {
+        fn find(path: &mut Vec<&Element>, target: &Element) -> bool {
+            if std::ptr::eq(*path.last().expect("path is never empty"), target) {
+                return true;
+            }
+            let parent = *path.last().expect("path is never empty");
+            for child in child_elements(parent) {
+                path.push(child);
+                if find(path, target) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+
+        let mut path = vec![root];
+        find(&mut path, element) && self.matches_path(&path)
+    }
+
+    /// Whether a start tag matches on its own, as `ElementCapture` sees it:
+    /// alternatives with combinators never match, and the element has no
+    /// siblings or children for pseudo-classes to look at
+    pub fn matches_tag(&self, name: &str, attributes: &[(String, String)]) -> bool {
+        let mut candidates = self.alternatives.iter().filter(|complex| {
+            complex.combinators.is_empty()
+                && complex.compounds[0]
+                    .tag
+                    .as_ref()
+                    .is_none_or(|tag| tag == name)
+        });
+        let Some(first) = candidates.next() else {
+            return false;
+        };
+        let element = Element {
+            tag: name.to_string(),
+            attributes: attributes.to_vec(),
+            children: Vec::new(),
+        };
+        std::iter::once(first)
+            .chain(candidates)
+            .any(|complex| complex.compounds[0].matches(&[&element]))
+    }
+
+    fn collect<'a>(
+        &self,
+        path: &mut Vec<&'a Element>,
+        results: &mut Vec<&'a Element>,
+        first: bool,
+    ) {
+        let parent = *path.last().expect("path is never empty");
+        for child in child_elements(parent) {
+            if first && !results.is_empty() {
+                return;
+            }
+            path.push(child);
+            if self.matches_path(path) {
+                results.push(child);
+            }
+            self.collect(path, results, first);
+            path.pop();
+        }
+    }
+
+    /// `path` runs from the root to the candidate element
+    fn matches_path(&self, path: &[&Element]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|complex| complex.matches_from(0, path))
+    }
+}
+
+impl Complex {
+    fn matches_from(&self, index: usize, path: &[&Element]) -> bool {
+        if !self.compounds[index].matches(path) {
+            return false;
+        }
+        let Some(combinator) = self.combinators.get(index) else {
+            return true;
+        };
+        let parent_path = &path[..path.len() - 1];
+        match combinator {
+            Combinator::Child => {
+                !parent_path.is_empty() && self.matches_from(index + 1, parent_path)
+            }
+            Combinator::Descendant => (1..path.len())
+                .rev()
+                .any(|n| self.matches_from(index + 1, &path[..n])),
+            Combinator::NextSibling | Combinator::SubsequentSibling => {
+                let Some((siblings, position)) = siblings(path) else {
+                    return false;
+                };
+                let earlier = siblings[..position].iter().rev();
+                let take = if *combinator == Combinator::NextSibling {
+                    1
+                } else {
+                    position
+                };
+                earlier.take(take).any(|sibling| {
+                    let mut sibling_path = parent_path.to_vec();
+                    sibling_path.push(sibling);
+                    self.matches_from(index + 1, &sibling_path)
+                })
+            }
+        }
+    }
+}
+
+impl Compound {
+    fn matches(&self, path: &[&Element]) -> bool {
+        let element = path[path.len() - 1];
+        if self.tag.as_ref().is_some_and(|tag| *tag != element.tag) {
+            return false;
+        }
+        self.conditions
+            .iter()
+            .all(|condition| condition.matches(path))
+    }
+}
+
+impl Condition {
+    fn matches(&self, path: &[&Element]) -> bool {
+        let element = path[path.len() - 1];
+        match self {
+            Condition::Id(id) => element.get_attribute("id") == Some(id),
+            Condition::Class(class) => element
+                .get_attribute("class")
+                .is_some_and(|classes| classes.split_whitespace().any(|c| c == class)),
+            Condition::Attribute {
+                name,
+                op,
+                value,
+                ignore_case,
+            } => element
+                .get_attribute(name)
+                .is_some_and(|actual| op.matches(actual, value, *ignore_case)),
+            Condition::Nth {
+                a,
+                b,
+                of_type,
+                from_end,
+            } => {
+                let (siblings, position) = siblings(path).unwrap_or((vec![element], 0));
+                let counted: Vec<_> = siblings
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| !of_type || s.tag == element.tag)
+                    .map(|(i, _)| i)
+                    .collect();
+                let index = counted.iter().position(|&i| i == position).unwrap_or(0);
+                let nth = if *from_end {
+                    counted.len() - index
+                } else {
+                    index + 1
+                };
+                nth_matches(*a, *b, nth as i64)
+            }
+            Condition::Empty => element
+                .children
+                .iter()
+                .all(|child| matches!(child, Node::Comment(_))),
+            Condition::Not(selector) => !selector.matches_path(path),
+        }
+    }
+}
+
+impl AttributeOp {
+    fn matches(self, actual: &str, expected: &str, ignore_case: bool) -> bool {
+        let (actual, expected) = if ignore_case {
+            (actual.to_lowercase(), expected.to_lowercase())
+        } else {
+            (actual.to_string(), expected.to_string())
+        };
+        match self {
+            AttributeOp::Exists => true,
+            AttributeOp::Equals => actual == expected,
+            AttributeOp::Includes => actual.split_whitespace().any(|word| word == expected),
+            AttributeOp::DashMatch => {
+                actual == expected || actual.starts_with(&format!("{}-", expected))
+            }
+            AttributeOp::Prefix => !expected.is_empty() && actual.starts_with(&expected),
+            AttributeOp::Suffix => !expected.is_empty() && actual.ends_with(&expected),
+            AttributeOp::Substring => !expected.is_empty() && actual.contains(&expected),
+        }
+    }
+}
+
+/// Whether some `n >= 0` gives `a*n + b == position`
+fn nth_matches(a: i64, b: i64, position: i64) -> bool {
+    if a == 0 {
+        return position == b;
+    }
+    // Out-of-range `a` and `b` from the selector text match nothing
+    let Some(diff) = position.checked_sub(b) else {
+        return false;
+    };
+    diff.checked_rem(a) == Some(0) && diff.checked_div(a).is_some_and(|n| n >= 0)
+}
+
+fn child_elements(element: &Element) -> impl Iterator<Item = &Element> {
+    element.children.iter().filter_map(|child| match child {
+        Node::Element(e) => Some(e),
+        _ => None,
+    })
+}
+
+/// The element siblings of the last element in `path` and its index among them
+fn siblings<'a>(path: &[&'a Element]) -> Option<(Vec<&'a Element>, usize)> {
+    let element = path[path.len() - 1];
+    let parent = *path.get(path.len().checked_sub(2)?)?;
+    let siblings: Vec<&Element> = child_elements(parent).collect();
+    let position = siblings.iter().position(|s| std::ptr::eq(*s, element))?;
+    Some((siblings, position))
+}
+
+struct SelectorParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl SelectorParser<'_> {
+    fn error(&self, message: &str) -> SelectorError {
+        SelectorError {
+            selector: self.input.to_string(),
+            position: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SelectorError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+        self.pos > start
+    }
+
+    fn identifier(&mut self) -> Result<String, SelectorError> {
+        let rest = &self.input[self.pos..];
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error("expected identifier"));
+        }
+        self.pos += end;
+        Ok(rest[..end].to_string())
+    }
+
+    fn selector_list(&mut self) -> Result<Selector, SelectorError> {
+        let mut alternatives = vec![self.complex()?];
+        loop {
+            self.skip_whitespace();
+            if !self.eat(',') {
+                return Ok(Selector { alternatives });
+            }
+            alternatives.push(self.complex()?);
+        }
+    }
+
+    fn complex(&mut self) -> Result<Complex, SelectorError> {
+        self.skip_whitespace();
+        let mut compounds = vec![self.compound()?];
+        let mut combinators = Vec::new();
+        loop {
+            let had_space = self.skip_whitespace();
+            let combinator = match self.peek() {
+                Some('>') => Combinator::Child,
+                Some('+') => Combinator::NextSibling,
+                Some('~') => Combinator::SubsequentSibling,
+                Some(',' | ')') | None => break,
+                Some(_) if had_space => {
+                    combinators.push(Combinator::Descendant);
+                    compounds.push(self.compound()?);
+                    continue;
+                }
+                Some(_) => return Err(self.error("unexpected character")),
+            };
+            self.pos += 1;
+            self.skip_whitespace();
+            combinators.push(combinator);
+            compounds.push(self.compound()?);
+        }
+        compounds.reverse();
+        combinators.reverse();
+        Ok(Complex {
+            compounds,
+            combinators,
+        })
+    }
+
+    fn compound(&mut self) -> Result<Compound, SelectorError> {
+        let mut compound = Compound::default();
+        let universal = self.eat('*');
+        if !universal && self.peek().is_some_and(|c| c.is_alphabetic()) {
+            compound.tag = Some(self.identifier()?.to_ascii_lowercase());
+        }
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.pos += 1;
+                    compound.conditions.push(Condition::Id(self.identifier()?));
+                }
+                Some('.') => {
+                    self.pos += 1;
+                    compound
+                        .conditions
+                        .push(Condition::Class(self.identifier()?));
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    compound.conditions.push(self.attribute()?);
+                }
+                Some(':') => {
+                    self.pos += 1;
+                    compound.conditions.extend(self.pseudo_class()?);
+                }
+                _ => break,
+            }
+        }
+        if !universal && compound.tag.is_none() && compound.conditions.is_empty() {
+            return Err(self.error("expected selector"));
+        }
+        Ok(compound)
+    }
+
+    fn attribute(&mut self) -> Result<Condition, SelectorError> {
+        self.skip_whitespace();
+        let name = self.identifier()?.to_ascii_lowercase();
+        self.skip_whitespace();
+        let op = match self.peek() {
+            Some(']') => {
+                self.pos += 1;
+                return Ok(Condition::Attribute {
+                    name,
+                    op: AttributeOp::Exists,
+                    value: String::new(),
+                    ignore_case: false,
+                });
+            }
+            Some('=') => AttributeOp::Equals,
+            Some('~') => AttributeOp::Includes,
+            Some('|') => AttributeOp::DashMatch,
+            Some('^') => AttributeOp::Prefix,
+            Some('$') => AttributeOp::Suffix,
+            Some('*') => AttributeOp::Substring,
+            _ => return Err(self.error("expected attribute operator")),
+        };
+        self.pos += 1;
+        if op != AttributeOp::Equals {
+            self.expect('=')?;
+        }
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let rest = &self.input[self.pos..];
+                let end = rest
+                    .find(quote)
+                    .ok_or_else(|| self.error("unterminated string"))?;
+                self.pos += end + 1;
+                rest[..end].to_string()
+            }
+            _ => self.identifier()?,
+        };
+        self.skip_whitespace();
+        let ignore_case = self.eat('i') || self.eat('I');
+        self.skip_whitespace();
+        self.expect(']')?;
+        Ok(Condition::Attribute {
+            name,
+            op,
+            value,
+            ignore_case,
+        })
+    }
+
+    fn pseudo_class(&mut self) -> Result<Vec<Condition>, SelectorError> {
+        let start = self.pos;
+        let name = self.identifier()?.to_ascii_lowercase();
+        let nth = |a, b, of_type, from_end| Condition::Nth {
+            a,
+            b,
+            of_type,
+            from_end,
+        };
+        let conditions = match name.as_str() {
+            "first-child" => vec![nth(0, 1, false, false)],
+            "last-child" => vec![nth(0, 1, false, true)],
+            "only-child" => vec![nth(0, 1, false, false), nth(0, 1, false, true)],
+            "first-of-type" => vec![nth(0, 1, true, false)],
+            "last-of-type" => vec![nth(0, 1, true, true)],
+            "only-of-type" => vec![nth(0, 1, true, false), nth(0, 1, true, true)],
+            "empty" => vec![Condition::Empty],
+            "nth-child" | "nth-last-child" | "nth-of-type" | "nth-last-of-type" => {
+                self.expect('(')?;
+                let (a, b) = self.nth_expression()?;
+                self.expect(')')?;
+                vec![nth(a, b, name.ends_with("of-type"), name.contains("last"))]
+            }
+            "not" => {
+                self.expect('(')?;
+                let selector = self.selector_list()?;
+                self.skip_whitespace();
+                self.expect(')')?;
+                vec![Condition::Not(selector)]
+            }
+            _ => {
+                self.pos = start;
+                return Err(self.error(&format!("unsupported pseudo-class ':{}'", name)));
+            }
+        };
+        Ok(conditions)
+    }
+
+    /// `odd`, `even`, `b`, `an`, `an+b`, with optional signs and whitespace
+    fn nth_expression(&mut self) -> Result<(i64, i64), SelectorError> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let end = rest.find(')').ok_or_else(|| self.error("expected ')'"))?;
+        let expression: String = rest[..end]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        let parse_int = |s: &str| {
+            s.parse::<i64>()
+                .map_err(|_| self.error("invalid nth expression"))
+        };
+        let parsed = match expression.as_str() {
+            "odd" => (2, 1),
+            "even" => (2, 0),
+            _ => match expression.split_once('n') {
+                Some((a, b)) => {
+                    let a = match a {
+                        "" | "+" => 1,
+                        "-" => -1,
+                        _ => parse_int(a)?,
+                    };
+                    let b = if b.is_empty() {
+                        0
+                    } else if b.starts_with(['+', '-']) {
+                        parse_int(b.trim_start_matches('+'))?
+                    } else {
+                        return Err(self.error("invalid nth expression"));
+                    };
+                    (a, b)
+                }
+                None => (0, parse_int(expression.trim_start_matches('+'))?),
+            },
+        };
+        self.pos += end;
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_html;
+
+    const LISTINGS: &str = r#"
+        <div id="results">
+          <article class="card featured" data-testid="listing-1">
+            <h2>Marina View</h2>
+            <span class="price" data-testid="price">AED 1,500,000</span>
+            <a href="/property/details-1.html">View</a>
+          </article>
+          <article class="card" data-testid="listing-2">
+            <h2>Palm Villa</h2>
+            <div class="meta"><span class="price">AED 9,000,000</span></div>
+            <a href="https://example.com/ad">Ad</a>
+          </article>
+        </div>"#;
+
+    fn texts(elements: &[&Element]) -> Vec<String> {
+        elements
+            .iter()
+            .map(|e| e.text_content().trim().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_simple_and_attribute_selectors() {
+        let root = parse_html(LISTINGS).unwrap();
+        assert_eq!(root.select("article").unwrap().len(), 2);
+        assert_eq!(
+            root.select("#results > .card.featured h2")
+                .map(|e| texts(&e))
+                .unwrap(),
+            vec!["Marina View"]
+        );
+        assert_eq!(
+            texts(&root.select(r#"[data-testid="price"]"#).unwrap()),
+            vec!["AED 1,500,000"]
+        );
+        assert_eq!(root.select("[data-testid^=listing]").unwrap().len(), 2);
+        assert_eq!(root.select("a[href*='details']").unwrap().len(), 1);
+        assert_eq!(root.select("a[href$='.html']").unwrap().len(), 1);
+        assert_eq!(root.select("[class~=CARD i]").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_combinators_and_lists() {
+        let root = parse_html(LISTINGS).unwrap();
+        assert_eq!(
+            texts(&root.select("article > .price").unwrap()),
+            vec!["AED 1,500,000"]
+        );
+        assert_eq!(root.select("article .price").unwrap().len(), 2);
+        assert_eq!(
+            texts(&root.select("h2 + span").unwrap()),
+            vec!["AED 1,500,000"]
+        );
+        assert_eq!(root.select("h2 ~ a").unwrap().len(), 2);
+        assert_eq!(
+            texts(&root.select("h2, .meta .price").unwrap()),
+            vec!["Marina View", "Palm Villa", "AED 9,000,000"]
+        );
+    }
+
+    #[test]
+    fn test_pseudo_classes() {
+        let root = parse_html("<ul><li>1</li><li>2</li><li>3</li><li>4</li><li>5</li></ul><p></p>")
+            .unwrap();
+        assert_eq!(
+            texts(&root.select("li:nth-child(odd)").unwrap()),
+            vec!["1", "3", "5"]
+        );
+        assert_eq!(
+            texts(&root.select("li:nth-child(2n + 2)").unwrap()),
+            vec!["2", "4"]
+        );
+        assert_eq!(
+            texts(&root.select("li:nth-child(-n+2)").unwrap()),
+            vec!["1", "2"]
+        );
+        assert_eq!(
+            texts(&root.select("li:nth-last-child(1)").unwrap()),
+            vec!["5"]
+        );
+        assert_eq!(
+            texts(&root.select("li:first-of-type, li:last-child").unwrap()),
+            vec!["1", "5"]
+        );
+        assert_eq!(
+            texts(
+                &root
+                    .select("li:not(:first-child):not(:nth-child(n+3))")
+                    .unwrap()
+            ),
+            vec!["2"]
+        );
+        assert_eq!(root.select("p:empty").unwrap().len(), 1);
+        assert_eq!(root.select("ul:empty").unwrap().len(), 0);
+
+        // Extreme coefficients match nothing rather than overflowing
+        for selector in [
+            "li:nth-child(-n+-9223372036854775807)",
+            "li:nth-child(-1n-9223372036854775808)",
+            "li:nth-child(9223372036854775807n+9223372036854775807)",
+        ] {
+            assert!(root.select(selector).unwrap().is_empty(), "{}", selector);
+        }
+    }
+
+    #[test]
+    fn test_compiled_selector_reuse() {
+        let selector = Selector::parse("article h2").unwrap();
+        let first = parse_html(LISTINGS).unwrap();
+        let second = parse_html("<article><h2>Other</h2></article>").unwrap();
+        assert_eq!(selector.select(&first).len(), 2);
+        assert_eq!(
+            selector.select_first(&second).map(|e| e.text_content()),
+            Some("Other".to_string())
+        );
+
+        let h2 = first.select_first("h2").unwrap().unwrap();
+        assert!(selector.matches(&first, h2));
+        assert!(!Selector::parse("div > h2").unwrap().matches(&first, h2));
+
+        let card = Selector::parse("article h2, li[role=article][aria-label=Listing i]").unwrap();
+        let attributes = [
+            ("role".to_string(), "article".to_string()),
+            ("aria-label".to_string(), "listing".to_string()),
+        ];
+        assert!(card.matches_tag("li", &attributes));
+        assert!(!card.matches_tag("li", &attributes[..1]));
+        assert!(!card.matches_tag("h2", &[]));
+    }
+
+    #[test]
+    fn test_invalid_selectors() {
+        for invalid in [
+            "",
+            "div >",
+            "[data-x",
+            "a:hover",
+            "li:nth-child(x)",
+            ".",
+            "a[href=]",
+            "div,",
+        ] {
+            assert!(Selector::parse(invalid).is_err(), "{invalid} should fail");
+        }
+        let error = Selector::parse("a:hover").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid selector 'a:hover' at 2: unsupported pseudo-class ':hover'"
+        );
+    }
+}
@@ -0,0 +1,166 @@
+// AvilaParser Serializer - DOM back to HTML
+// Zero External Dependencies 🦀
+
+use crate::tree::VOID_ELEMENTS;
+use crate::{Document, Element, Node};
+
+/// Elements whose text is written as-is, as the tokenizer reads it back raw
+const RAW_TEXT: [&str; 8] = [
+    "iframe",
+    "noembed",
+    "noframes",
+    "noscript",
+    "plaintext",
+    "script",
+    "style",
+    "xmp",
+];
+
+impl Element {
+    /// The element and its content as HTML, like `outerHTML`
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        write_element(self, &mut html);
+        html
+    }
+
+    /// The content alone, like `innerHTML`
+    pub fn inner_html(&self) -> String {
+        let mut html = String::new();
+        write_children(self, &mut html);
+        html
+    }
+}
+
+impl Node {
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        write_node(self, false, &mut html);
+        html
+    }
+}
+
+impl Document {
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        if let Some(doctype) = &self.doctype {
+            html.push_str("<!DOCTYPE ");
+            html.push_str(doctype);
+            html.push('>');
+        }
+        write_element(&self.root, &mut html);
+        html
+    }
+}
+
+fn write_element(element: &Element, html: &mut String) {
+    html.push('<');
+    html.push_str(&element.tag);
+    for (name, value) in &element.attributes {
+        html.push(' ');
+        html.push_str(name);
+        html.push_str("=\"");
+        escape(value, true, html);
+        html.push('"');
+    }
+    html.push('>');
+    if VOID_ELEMENTS.contains(&element.tag.as_str()) {
+        return;
+    }
+    // The parser drops a newline right after these start tags, so a leading
+    // newline in the content needs a second one to survive a round trip
+    if matches!(element.tag.as_str(), "pre" | "listing" | "textarea") {
+        if let Some(Node::Text(text)) = element.children.first() {
+            if text.starts_with('\n') {
+                html.push('\n');
+            }
+        }
+    }
+    write_children(element, html);
+    html.push_str("</");
+    html.push_str(&element.tag);
+    html.push('>');
+}
+
+fn write_children(element: &Element, html: &mut String) {
+    let raw = RAW_TEXT.contains(&element.tag.as_str());
+    for child in &element.children {
+        write_node(child, raw, html);
+    }
+}
+
+fn write_node(node: &Node, raw: bool, html: &mut String) {
+    match node {
+        Node::Element(element) => write_element(element, html),
+        Node::Text(text) if raw => html.push_str(text),
+        Node::Text(text) => escape(text, false, html),
+        Node::Comment(comment) => {
+            html.push_str("<!--");
+            html.push_str(comment);
+            html.push_str("-->");
+        }
+    }
+}
+
+/// `&`, `<`, `>` and no-break spaces always; `"` in attribute values
+fn escape(text: &str, attribute: bool, html: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '\u{a0}' => html.push_str("&nbsp;"),
+            '"' if attribute => html.push_str("&quot;"),
+            c => html.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_fragment, HtmlParser};
+
+    fn round_trip(html: &str) {
+        let document = HtmlParser::new(html).parse_document().unwrap();
+        let serialized = document.to_html();
+        let reparsed = HtmlParser::new(&serialized).parse_document().unwrap();
+        assert_eq!(reparsed, document, "{}", serialized);
+        assert_eq!(reparsed.to_html(), serialized);
+    }
+
+    #[test]
+    fn test_serialize_fragment() {
+        let nodes = parse_fragment(
+            "<p class='a \"b\"' data-x=1>Fish &amp; chips &lt;3&nbsp;AED<br><img src=x.jpg alt=''></p>\
+             <!-- note --><script>if (a < b && c) {}</script>",
+        );
+        let html: String = nodes.iter().map(|node| node.to_html()).collect();
+        assert_eq!(
+            html,
+            "<p class=\"a &quot;b&quot;\" data-x=\"1\">Fish &amp; chips &lt;3&nbsp;AED<br>\
+             <img src=\"x.jpg\" alt=\"\"></p><!-- note --><script>if (a < b && c) {}</script>"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_documents() {
+        round_trip(
+            "<!DOCTYPE html><html lang=en><head><title>R&amp;D</title></head><body></body></html>",
+        );
+        round_trip(
+            "<p>Unclosed <b>bold <i>both</b> italic<li>item<table><td>cell<tr><td>x</table>\
+             <textarea>\n\nkept</textarea><pre>\nline</pre><style>a > b { }</style>",
+        );
+        round_trip("<div title='&lt;script&gt;'>&#x1F3D9; &copy; \u{a0}</div><!---->");
+    }
+
+    #[test]
+    fn test_inner_and_outer_html() {
+        let nodes = parse_fragment("<ul><li>One<li>Two</ul>");
+        let crate::Node::Element(list) = &nodes[0] else {
+            panic!("expected an element");
+        };
+        assert_eq!(list.inner_html(), "<li>One</li><li>Two</li>");
+        assert_eq!(list.to_html(), "<ul><li>One</li><li>Two</li></ul>");
+    }
+}
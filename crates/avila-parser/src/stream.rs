@@ -0,0 +1,389 @@
+// AvilaParser Stream - Incremental tokenizer fed with byte chunks
+// Zero External Dependencies 🦀
+
+use crate::tokenizer::{Token, Tokenizer};
+use crate::tree::{TreeBuilder, MAX_DEPTH, VOID_ELEMENTS};
+use crate::{Document, Element, Node};
+use std::io::{self, Read};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// Bytes that must follow a token before it is known to be complete: enough
+/// to see a whole raw-text end tag such as `</noframes>` behind its text
+const LOOKAHEAD: usize = 12;
+
+/// Push tokenizer: bytes go in with `feed` as they arrive, tokens come out
+/// with `next_event` as soon as they are complete
+///
+/// Only the unconsumed tail of the input is kept, so memory use is bounded by
+/// the largest single token (usually a `<script>` body or a run of text), not
+/// by the page size. Chunks may split tags, character references and UTF-8
+/// sequences anywhere; invalid UTF-8 becomes U+FFFD. Tokens are the same as
+/// `HtmlParser` sees, except that text may arrive in several pieces.
+#[derive(Debug, Default)]
+pub struct HtmlStream {
+    buffer: String,
+    pos: usize,
+    /// Trailing bytes of an incomplete UTF-8 sequence
+    pending: Vec<u8>,
+    raw_text: Option<String>,
+    /// Unconsumed input when the last scan ran out before the token ended;
+    /// it is not rescanned until it has doubled, so a token arriving in many
+    /// small chunks costs linear rather than quadratic time
+    stalled: usize,
+    finished: bool,
+}
+
+impl HtmlStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.buffer.drain(..self.pos);
+        self.pos = 0;
+        self.pending.extend_from_slice(chunk);
+        let mut start = 0;
+        loop {
+            match std::str::from_utf8(&self.pending[start..]) {
+                Ok(text) => {
+                    self.buffer.push_str(text);
+                    start = self.pending.len();
+                    break;
+                }
+                Err(err) => {
+                    let valid = start + err.valid_up_to();
+                    // Checked by from_utf8 just above
+                    self.buffer
+                        .push_str(std::str::from_utf8(&self.pending[start..valid]).unwrap());
+                    match err.error_len() {
+                        Some(len) => {
+                            self.buffer.push('\u{FFFD}');
+                            start = valid + len;
+                        }
+                        None => {
+                            start = valid;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.pending.drain(..start);
+    }
+
+    /// Marks the end of the input; everything buffered becomes available
+    pub fn finish(&mut self) {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            self.buffer.push('\u{FFFD}');
+        }
+        self.finished = true;
+    }
+
+    /// The next complete token, or `None` when more input is needed (or, after
+    /// `finish`, when the input is exhausted)
+    pub fn next_event(&mut self) -> Option<Token> {
+        let rest = &self.buffer[self.pos..];
+        if !self.finished && rest.len() < 2 * self.stalled {
+            return None;
+        }
+        let mut tokenizer = Tokenizer::resume(rest, self.raw_text.clone());
+        let token = tokenizer.next();
+        let consumed = tokenizer.position();
+        if !self.finished && rest.len() - consumed < LOOKAHEAD {
+            self.stalled = rest.len();
+            return None;
+        }
+        self.stalled = 0;
+        self.pos += consumed;
+        self.raw_text = tokenizer.into_raw_text();
+        token
+    }
+
+    /// The tokens available from the input fed so far
+    pub fn events(&mut self) -> impl Iterator<Item = Token> + '_ {
+        std::iter::from_fn(move || self.next_event())
+    }
+
+    /// Input held while waiting for a token to complete
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() - self.pos + self.pending.len()
+    }
+}
+
+/// Tokens read from any `Read` (a socket, a TLS stream, a file) in 8 KiB chunks
+pub struct HtmlReader<R> {
+    reader: R,
+    stream: HtmlStream,
+    chunk: Box<[u8]>,
+}
+
+impl<R: Read> HtmlReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            stream: HtmlStream::new(),
+            chunk: vec![0; BUFFER_SIZE].into_boxed_slice(),
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for HtmlReader<R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<io::Result<Token>> {
+        loop {
+            if let Some(token) = self.stream.next_event() {
+                return Some(Ok(token));
+            }
+            if self.stream.finished {
+                return None;
+            }
+            match self.reader.read(&mut self.chunk) {
+                Ok(0) => self.stream.finish(),
+                Ok(n) => self.stream.feed(&self.chunk[..n]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Builds the full tree from a reader without first collecting the page into a string
+pub fn parse_reader<R: Read>(reader: R) -> io::Result<Document> {
+    let mut builder = TreeBuilder::document();
+    for token in HtmlReader::new(reader) {
+        builder.process(token?);
+    }
+    let (doctype, root) = builder.finish();
+    Ok(Document { doctype, root })
+}
+
+/// Turns the subtrees whose start tag satisfies a predicate into elements,
+/// ignoring everything else, so listing cards can be pulled from a page
+/// without building its DOM
+///
+/// Unlike `HtmlParser` there is no error recovery beyond closing elements left
+/// open when an ancestor's end tag arrives. Nesting is capped like the tree
+/// builder's: past that depth start tags are added as empty children.
+pub struct ElementCapture<F> {
+    matches: F,
+    /// The captured element and its open descendants
+    open: Vec<Element>,
+}
+
+impl<F: FnMut(&str, &[(String, String)]) -> bool> ElementCapture<F> {
+    pub fn new(matches: F) -> Self {
+        Self {
+            matches,
+            open: Vec::new(),
+        }
+    }
+
+    /// Whether a match is currently being captured
+    pub fn is_capturing(&self) -> bool {
+        !self.open.is_empty()
+    }
+
+    /// Feeds one token; returns an element once its end tag has been seen
+    pub fn push(&mut self, token: Token) -> Option<Element> {
+        match token {
+            Token::StartTag {
+                name,
+                attributes,
+                self_closing,
+            } => {
+                if self.open.is_empty() && !(self.matches)(&name, &attributes) {
+                    return None;
+                }
+                let void = self_closing || VOID_ELEMENTS.contains(&name.as_str());
+                let element = Element {
+                    tag: name,
+                    attributes,
+                    children: Vec::new(),
+                };
+                if !void && self.open.len() < MAX_DEPTH {
+                    self.open.push(element);
+                    return None;
+                }
+                match self.open.last_mut() {
+                    Some(parent) => {
+                        parent.children.push(Node::Element(element));
+                        None
+                    }
+                    None => Some(element),
+                }
+            }
+            Token::EndTag(name) => {
+                let index = self.open.iter().rposition(|e| e.tag == name)?;
+                while self.open.len() > index + 1 {
+                    self.close();
+                }
+                if index == 0 {
+                    return self.open.pop();
+                }
+                self.close();
+                None
+            }
+            Token::Text(text) => {
+                if let Some(parent) = self.open.last_mut() {
+                    match parent.children.last_mut() {
+                        Some(Node::Text(existing)) => existing.push_str(&text),
+                        _ => parent.children.push(Node::Text(text)),
+                    }
+                }
+                None
+            }
+            Token::Comment(comment) => {
+                if let Some(parent) = self.open.last_mut() {
+                    parent.children.push(Node::Comment(comment));
+                }
+                None
+            }
+            Token::Doctype(_) => None,
+        }
+    }
+
+    /// Moves the innermost open element into its parent
+    fn close(&mut self) {
+        if let Some(element) = self.open.pop() {
+            if let Some(parent) = self.open.last_mut() {
+                parent.children.push(Node::Element(element));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_html;
+
+    const PAGE: &str = "<!DOCTYPE html><html><head><title>R&amp;D</title>\
+        <script>if (a </b) { x = '</scrip' }</script></head><body>\
+        <article class=\"card\" data-id=\"1\"><h2>Marina Gate – 2 BR</h2>\
+        <span class=\"price\">AED&nbsp;1,850,000</span><img src=\"/1.jpg\"></article>\
+        <!-- ad --><article class=\"card\" data-id=\"2\"><h2>Palm Villa</h2>\
+        <ul><li>5 beds<li>Sea view</ul></article></body></html>";
+
+    fn tokens_in_chunks(html: &[u8], size: usize) -> Vec<Token> {
+        let mut stream = HtmlStream::new();
+        let mut tokens = Vec::new();
+        for chunk in html.chunks(size) {
+            stream.feed(chunk);
+            tokens.extend(stream.events());
+        }
+        stream.finish();
+        tokens.extend(stream.events());
+        tokens
+    }
+
+    /// Adjacent text tokens joined, as the tree builder sees them
+    fn merged(tokens: Vec<Token>) -> Vec<Token> {
+        let mut out: Vec<Token> = Vec::new();
+        for token in tokens {
+            match (out.last_mut(), token) {
+                (Some(Token::Text(a)), Token::Text(b)) => a.push_str(&b),
+                (_, token) => out.push(token),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_any_chunking_gives_the_same_tokens() {
+        let whole: Vec<Token> = Tokenizer::new(PAGE).collect();
+        for size in [1, 2, 3, 7, 64, PAGE.len()] {
+            assert_eq!(
+                merged(tokens_in_chunks(PAGE.as_bytes(), size)),
+                whole,
+                "chunk size {}",
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn test_buffer_holds_only_the_pending_token() {
+        let mut stream = HtmlStream::new();
+        let mut page = String::from("<ul>");
+        for i in 0..2000 {
+            page.push_str(&format!("<li class=\"item\">Listing {}</li>", i));
+        }
+        let mut peak = 0;
+        let mut count = 0;
+        for chunk in page.as_bytes().chunks(100) {
+            stream.feed(chunk);
+            count += stream.events().count();
+            peak = peak.max(stream.buffered());
+        }
+        stream.finish();
+        count += stream.events().count();
+        assert_eq!(count, 1 + 2000 * 3);
+        assert!(peak < 200, "peak buffer {}", peak);
+    }
+
+    #[test]
+    fn test_long_token_in_small_chunks() {
+        let script = format!(
+            "<script>{}</script><p>done</p>",
+            "var x = 1;".repeat(100_000)
+        );
+        // 1 MB in 64-byte chunks: rescanning the whole script on every feed
+        // would tokenize about 8 GB
+        assert_eq!(
+            merged(tokens_in_chunks(script.as_bytes(), 64)),
+            Tokenizer::new(&script).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_invalid_and_split_utf8() {
+        let bytes = "<p>é\u{1F3D9}</p>".as_bytes();
+        let tokens = merged(tokens_in_chunks(bytes, 1));
+        assert_eq!(tokens[1], Token::Text("é\u{1F3D9}".to_string()));
+
+        let tokens = merged(tokens_in_chunks(b"<p>a\xFFb\xE2\x82", 2));
+        assert_eq!(tokens[1], Token::Text("a\u{FFFD}b\u{FFFD}".to_string()));
+    }
+
+    #[test]
+    fn test_reader_and_capture() {
+        let document = parse_reader(PAGE.as_bytes()).unwrap();
+        assert_eq!(document.root.find_by_tag("article").len(), 2);
+        assert_eq!(document.doctype.as_deref(), Some("html"));
+
+        let mut capture = ElementCapture::new(|name, attributes| {
+            name == "article" && attributes.iter().any(|(k, v)| k == "class" && v == "card")
+        });
+        let cards: Vec<Element> = HtmlReader::new(PAGE.as_bytes())
+            .filter_map(|token| capture.push(token.unwrap()))
+            .collect();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].get_attribute("data-id"), Some("1"));
+        assert_eq!(
+            cards[0]
+                .select_first(".price")
+                .unwrap()
+                .unwrap()
+                .text_content(),
+            "AED\u{a0}1,850,000"
+        );
+        assert_eq!(cards[0].find_by_tag("img").len(), 1);
+        assert_eq!(
+            cards[1].text_content_normalized(),
+            "Palm Villa\n5 beds\nSea view"
+        );
+
+        let full = parse_html(PAGE).unwrap();
+        assert_eq!(
+            full.find_by_tag("article")[0].text_content(),
+            cards[0].text_content()
+        );
+    }
+}
@@ -0,0 +1,353 @@
+// AvilaParser Structured Data - JSON-LD, microdata and OpenGraph
+// Zero External Dependencies 🦀
+
+use crate::{Element, Node};
+use avila_json::{JsonError, JsonObject, JsonValue, ToJson};
+
+/// Machine-readable data embedded in a page
+#[derive(Debug, Clone, Default)]
+pub struct StructuredData {
+    /// Every `<script type="application/ld+json">` block, in document order
+    pub json_ld: Vec<JsonValue>,
+    /// Blocks that are not valid JSON
+    pub json_ld_errors: Vec<JsonError>,
+    /// Top-level microdata items (`itemscope` without `itemprop`)
+    pub microdata: Vec<MicrodataItem>,
+    /// `og:*` meta properties in document order; repeated keys such as `og:image` are kept
+    pub open_graph: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MicrodataItem {
+    pub item_type: Vec<String>,
+    pub item_id: Option<String>,
+    pub properties: Vec<(String, MicrodataValue)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MicrodataValue {
+    Text(String),
+    Item(MicrodataItem),
+}
+
+impl StructuredData {
+    pub fn extract(root: &Element) -> Self {
+        let mut data = StructuredData::default();
+        walk(root, &mut |element| match element.tag.as_str() {
+            "script" if is_json_ld(element) => {
+                match avila_json::parse(json_ld_source(&element.text_content())) {
+                    Ok(value) => data.json_ld.push(value),
+                    Err(err) => data.json_ld_errors.push(err),
+                }
+            }
+            "meta" => {
+                let property = element
+                    .get_attribute("property")
+                    .or_else(|| element.get_attribute("name"));
+                if let (Some(property), Some(content)) = (property, element.get_attribute("content")) {
+                    if property.starts_with("og:") {
+                        data.open_graph.push((property.to_string(), content.to_string()));
+                    }
+                }
+                item_scope(element, &mut data.microdata);
+            }
+            _ => item_scope(element, &mut data.microdata),
+        });
+        data
+    }
+
+    /// First value of an OpenGraph property such as `og:title`
+    pub fn open_graph(&self, property: &str) -> Option<&str> {
+        self.open_graph
+            .iter()
+            .find(|(key, _)| key == property)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// JSON-LD objects whose `@type` is `type_name`, searched through arrays,
+    /// `@graph` and nested values. `schema:Offer` and `https://schema.org/Offer`
+    /// both match `Offer`.
+    pub fn json_ld_of_type(&self, type_name: &str) -> Vec<&JsonValue> {
+        let mut found = Vec::new();
+        for block in &self.json_ld {
+            collect_of_type(block, type_name, &mut found);
+        }
+        found
+    }
+}
+
+impl MicrodataItem {
+    pub fn get(&self, name: &str) -> Option<&MicrodataValue> {
+        self.properties
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            MicrodataValue::Text(text) => Some(text),
+            MicrodataValue::Item(_) => None,
+        }
+    }
+
+    pub fn item(&self, name: &str) -> Option<&MicrodataItem> {
+        match self.get(name)? {
+            MicrodataValue::Item(item) => Some(item),
+            MicrodataValue::Text(_) => None,
+        }
+    }
+
+    pub fn has_type(&self, type_name: &str) -> bool {
+        self.item_type.iter().any(|t| type_matches(t, type_name))
+    }
+}
+
+/// In JSON-LD form: `@type`, `@id` and one key per property, with an array
+/// when a property repeats
+impl ToJson for MicrodataItem {
+    fn to_json(&self) -> JsonValue {
+        let mut object = JsonObject::new();
+        match self.item_type.as_slice() {
+            [] => {}
+            [single] => {
+                object.insert("@type", JsonValue::String(single.clone()));
+            }
+            types => {
+                object.insert("@type", types.to_vec().to_json());
+            }
+        }
+        if let Some(id) = &self.item_id {
+            object.insert("@id", JsonValue::String(id.clone()));
+        }
+        for (name, value) in &self.properties {
+            let value = value.to_json();
+            match object.get_mut(name) {
+                Some(JsonValue::Array(values)) => values.push(value),
+                Some(existing) => {
+                    let first = std::mem::replace(existing, JsonValue::Null);
+                    *existing = JsonValue::Array(vec![first, value]);
+                }
+                None => {
+                    object.insert(name.clone(), value);
+                }
+            }
+        }
+        JsonValue::Object(object)
+    }
+}
+
+impl ToJson for MicrodataValue {
+    fn to_json(&self) -> JsonValue {
+        match self {
+            MicrodataValue::Text(text) => JsonValue::String(text.clone()),
+            MicrodataValue::Item(item) => item.to_json(),
+        }
+    }
+}
+
+fn walk<'a>(element: &'a Element, visit: &mut impl FnMut(&'a Element)) {
+    visit(element);
+    for child in &element.children {
+        if let Node::Element(child) = child {
+            walk(child, visit);
+        }
+    }
+}
+
+fn is_json_ld(script: &Element) -> bool {
+    script
+        .get_attribute("type")
+        .is_some_and(|t| t.trim().eq_ignore_ascii_case("application/ld+json"))
+}
+
+/// Some sites wrap the JSON in an HTML comment or CDATA section
+fn json_ld_source(text: &str) -> &str {
+    let text = text.trim();
+    let text = text.strip_prefix("<!--").unwrap_or(text);
+    let text = text.strip_suffix("-->").unwrap_or(text).trim();
+    let text = text.strip_prefix("<![CDATA[").unwrap_or(text);
+    text.strip_suffix("]]>").unwrap_or(text).trim()
+}
+
+fn collect_of_type<'a>(value: &'a JsonValue, type_name: &str, found: &mut Vec<&'a JsonValue>) {
+    match value {
+        JsonValue::Array(values) => {
+            for value in values {
+                collect_of_type(value, type_name, found);
+            }
+        }
+        JsonValue::Object(object) => {
+            let matches = match object.get("@type") {
+                Some(JsonValue::String(t)) => type_matches(t, type_name),
+                Some(JsonValue::Array(types)) => types
+                    .iter()
+                    .any(|t| t.as_str().is_some_and(|t| type_matches(t, type_name))),
+                _ => false,
+            };
+            if matches {
+                found.push(value);
+            }
+            for nested in object.values() {
+                collect_of_type(nested, type_name, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Compares the last segment of a type IRI or compact name
+fn type_matches(declared: &str, type_name: &str) -> bool {
+    let local = declared.rsplit(['/', '#', ':']).next().unwrap_or(declared);
+    local == type_name || declared == type_name
+}
+
+fn item_scope(element: &Element, items: &mut Vec<MicrodataItem>) {
+    if element.get_attribute("itemscope").is_some() && element.get_attribute("itemprop").is_none() {
+        items.push(microdata_item(element));
+    }
+}
+
+fn microdata_item(element: &Element) -> MicrodataItem {
+    let mut properties = Vec::new();
+    collect_properties(element, &mut properties);
+    MicrodataItem {
+        item_type: element
+            .get_attribute("itemtype")
+            .map(|t| t.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+        item_id: element.get_attribute("itemid").map(str::to_string),
+        properties,
+    }
+}
+
+/// Properties below `element`, not descending into nested items
+fn collect_properties(element: &Element, properties: &mut Vec<(String, MicrodataValue)>) {
+    for child in &element.children {
+        let Node::Element(child) = child else {
+            continue;
+        };
+        let nested_item = child.get_attribute("itemscope").is_some();
+        if let Some(names) = child.get_attribute("itemprop") {
+            let value = property_value(child);
+            for name in names.split_whitespace() {
+                properties.push((name.to_string(), value.clone()));
+            }
+        }
+        if !nested_item {
+            collect_properties(child, properties);
+        }
+    }
+}
+
+fn property_value(element: &Element) -> MicrodataValue {
+    if element.get_attribute("itemscope").is_some() {
+        return MicrodataValue::Item(microdata_item(element));
+    }
+    let attribute = match element.tag.as_str() {
+        "meta" => Some("content"),
+        "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => Some("src"),
+        "a" | "area" | "link" => Some("href"),
+        "object" => Some("data"),
+        "data" | "meter" => Some("value"),
+        "time" if element.get_attribute("datetime").is_some() => Some("datetime"),
+        _ => None,
+    };
+    let text = match attribute {
+        Some(name) => element.get_attribute(name).unwrap_or_default().to_string(),
+        None => element.text_content_normalized(),
+    };
+    MicrodataValue::Text(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_html;
+    use avila_json::JsonErrorKind;
+
+    const LISTING_PAGE: &str = r#"<!DOCTYPE html>
+<html><head>
+  <meta property="og:title" content="2 BR Apartment in Marina Gate">
+  <meta property="og:image" content="https://img.example/1.jpg">
+  <meta property="og:image" content="https://img.example/2.jpg">
+  <meta name="description" content="Not OpenGraph">
+  <script type="application/ld+json">
+  {"@context": "https://schema.org", "@graph": [
+    {"@type": "BreadcrumbList", "itemListElement": []},
+    {"@type": ["Residence", "Apartment"], "name": "Marina Gate",
+     "offers": {"@type": "schema:Offer", "price": "1850000", "priceCurrency": "AED"}}
+  ]}
+  </script>
+  <script type="application/ld+json"><!-- {"@type": "Organization", "name": "Bayut"} --></script>
+  <script type="application/ld+json">{broken</script>
+</head><body>
+  <div itemscope itemtype="https://schema.org/Offer">
+    <span itemprop="name">Palm Villa</span>
+    <meta itemprop="priceCurrency" content="AED">
+    <data itemprop="price" value="9000000">AED 9M</data>
+    <div itemprop="seller" itemscope itemtype="https://schema.org/RealEstateAgent">
+      <a itemprop="url" href="/agents/7">Agent</a><span itemprop="name">Emaar</span>
+    </div>
+    <span itemprop="areaServed category">Palm Jumeirah</span>
+  </div>
+</body></html>"#;
+
+    #[test]
+    fn test_json_ld_blocks() {
+        let data = StructuredData::extract(&parse_html(LISTING_PAGE).unwrap());
+        assert_eq!(data.json_ld.len(), 2);
+        assert_eq!(data.json_ld_errors.len(), 1);
+
+        let residences = data.json_ld_of_type("Residence");
+        assert_eq!(residences.len(), 1);
+        assert_eq!(residences[0].pointer("/name").and_then(|v| v.as_str()), Some("Marina Gate"));
+
+        let offers = data.json_ld_of_type("Offer");
+        assert_eq!(offers[0].pointer("/price").and_then(|v| v.as_str()), Some("1850000"));
+        assert_eq!(data.json_ld_of_type("Organization").len(), 1);
+    }
+
+    #[test]
+    fn test_deep_json_ld_skipped() {
+        let html = format!(
+            r#"<script type="application/ld+json">{}</script>
+            <script type="application/ld+json">{{"@type": "Residence"}}</script>"#,
+            "[".repeat(100_000)
+        );
+        let data = StructuredData::extract(&parse_html(&html).unwrap());
+        assert_eq!(data.json_ld_errors.len(), 1);
+        assert_eq!(data.json_ld_errors[0].kind, JsonErrorKind::NestingTooDeep);
+        assert_eq!(data.json_ld_of_type("Residence").len(), 1);
+    }
+
+    #[test]
+    fn test_open_graph() {
+        let data = StructuredData::extract(&parse_html(LISTING_PAGE).unwrap());
+        assert_eq!(data.open_graph.len(), 3);
+        assert_eq!(data.open_graph("og:title"), Some("2 BR Apartment in Marina Gate"));
+        assert_eq!(data.open_graph("og:image"), Some("https://img.example/1.jpg"));
+        assert_eq!(data.open_graph("description"), None);
+    }
+
+    #[test]
+    fn test_microdata_items() {
+        let data = StructuredData::extract(&parse_html(LISTING_PAGE).unwrap());
+        assert_eq!(data.microdata.len(), 1);
+        let offer = &data.microdata[0];
+        assert!(offer.has_type("Offer"));
+        assert_eq!(offer.text("name"), Some("Palm Villa"));
+        assert_eq!(offer.text("priceCurrency"), Some("AED"));
+        assert_eq!(offer.text("price"), Some("9000000"));
+        assert_eq!(offer.text("category"), Some("Palm Jumeirah"));
+
+        let seller = offer.item("seller").unwrap();
+        assert_eq!(seller.text("url"), Some("/agents/7"));
+        assert_eq!(seller.text("name"), Some("Emaar"));
+        assert_eq!(offer.text("url"), None);
+
+        let json = offer.to_json();
+        assert_eq!(json.pointer("/@type").and_then(|v| v.as_str()), Some("https://schema.org/Offer"));
+        assert_eq!(json.pointer("/seller/name").and_then(|v| v.as_str()), Some("Emaar"));
+    }
+}
@@ -0,0 +1,559 @@
+// AvilaParser Tables - HTML tables as header-aware grids
+// Zero External Dependencies 🦀
+
+use crate::{Element, Node};
+use avila_json::Decimal;
+use std::collections::HashMap;
+
+/// Larger spans are clamped, like browsers do
+const MAX_COLSPAN: usize = 1000;
+const MAX_ROWSPAN: usize = 65534;
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// A `<table>` flattened into a rectangular grid: every cell covered by a
+/// `colspan`/`rowspan` repeats the spanning cell's text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub caption: Option<String>,
+    /// One name per column; stacked header rows are joined with `" / "`
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Text,
+    Integer,
+    /// Amounts marked `AED`, `Dh`, `Dhs` or `د.إ`, or in a column whose header names the currency
+    Aed,
+    Date,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Empty,
+    Text(String),
+    Integer(i64),
+    Aed(Decimal),
+    Date { year: i32, month: u8, day: u8 },
+}
+
+struct Cell {
+    text: String,
+    header: bool,
+}
+
+impl Table {
+    /// `None` unless `element` is a `<table>`
+    pub fn from_element(element: &Element) -> Option<Table> {
+        if element.tag != "table" {
+            return None;
+        }
+        let mut caption = None;
+        let (mut head, mut body, mut foot) = (Vec::new(), Vec::new(), Vec::new());
+        let mut loose_rows = Vec::new();
+        for child in child_elements(element) {
+            match child.tag.as_str() {
+                "caption" if caption.is_none() => {
+                    caption = Some(cell_text(child));
+                }
+                "tr" => loose_rows.push(child),
+                section @ ("thead" | "tbody" | "tfoot") => {
+                    if !loose_rows.is_empty() {
+                        body.push(std::mem::take(&mut loose_rows));
+                    }
+                    let rows: Vec<&Element> = child_elements(child)
+                        .filter(|row| row.tag == "tr")
+                        .collect();
+                    match section {
+                        "thead" => head.push(rows),
+                        "tbody" => body.push(rows),
+                        _ => foot.push(rows),
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !loose_rows.is_empty() {
+            body.push(loose_rows);
+        }
+
+        let head_rows = head.iter().map(Vec::len).sum::<usize>();
+        let grid = fill_grid(head.iter().chain(&body).chain(&foot));
+        let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+
+        // Without a <thead>, leading rows made only of <th> are the header
+        let header_rows = if head_rows > 0 {
+            head_rows
+        } else {
+            grid.iter()
+                .take_while(|row| {
+                    !row.is_empty()
+                        && row
+                            .iter()
+                            .all(|cell| cell.as_ref().is_some_and(|c| c.header))
+                })
+                .count()
+        };
+
+        let headers = header_names(&grid[..header_rows], width);
+        let rows = grid[header_rows..]
+            .iter()
+            .filter(|row| !row.is_empty())
+            .map(|row| {
+                (0..width)
+                    .map(|col| match row.get(col) {
+                        Some(Some(cell)) => cell.text.clone(),
+                        _ => String::new(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some(Table {
+            caption,
+            headers,
+            rows,
+        })
+    }
+
+    /// Every table below `root`, nested ones included, in document order
+    pub fn find_all(root: &Element) -> Vec<Table> {
+        root.find_by_tag("table")
+            .into_iter()
+            .filter_map(Table::from_element)
+            .collect()
+    }
+
+    pub fn column(&self, header: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == header)
+    }
+
+    /// One map per row, keyed by header
+    pub fn to_records(&self) -> Vec<HashMap<String, String>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                self.headers
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().cloned())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// RFC 4180 CSV with a header line and CRLF line endings
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for line in std::iter::once(&self.headers).chain(&self.rows) {
+            let fields: Vec<String> = line.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+
+    /// The narrowest type that every non-empty cell of each column parses as
+    pub fn column_types(&self) -> Vec<ColumnType> {
+        (0..self.headers.len())
+            .map(|col| {
+                let aed_header = mentions_aed(&self.headers[col]);
+                let mut cells = self
+                    .rows
+                    .iter()
+                    .map(|row| row[col].as_str())
+                    .filter(|cell| !cell.trim().is_empty())
+                    .peekable();
+                if cells.peek().is_none() {
+                    return ColumnType::Text;
+                }
+                let cells: Vec<&str> = cells.collect();
+                if cells
+                    .iter()
+                    .all(|cell| parse_aed(cell, aed_header).is_some())
+                {
+                    ColumnType::Aed
+                } else if cells.iter().all(|cell| parse_integer(cell).is_some()) {
+                    ColumnType::Integer
+                } else if cells.iter().all(|cell| parse_date(cell).is_some()) {
+                    ColumnType::Date
+                } else {
+                    ColumnType::Text
+                }
+            })
+            .collect()
+    }
+
+    /// Rows converted with `column_types`
+    pub fn typed_rows(&self) -> Vec<Vec<CellValue>> {
+        let types = self.column_types();
+        let aed_headers: Vec<bool> = self.headers.iter().map(|h| mentions_aed(h)).collect();
+        self.rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(col, text)| {
+                        if text.trim().is_empty() {
+                            return CellValue::Empty;
+                        }
+                        let value = match types[col] {
+                            ColumnType::Aed => {
+                                parse_aed(text, aed_headers[col]).map(CellValue::Aed)
+                            }
+                            ColumnType::Integer => parse_integer(text).map(CellValue::Integer),
+                            ColumnType::Date => parse_date(text)
+                                .map(|(year, month, day)| CellValue::Date { year, month, day }),
+                            ColumnType::Text => None,
+                        };
+                        value.unwrap_or_else(|| CellValue::Text(text.clone()))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn child_elements(element: &Element) -> impl Iterator<Item = &Element> {
+    element.children.iter().filter_map(|child| match child {
+        Node::Element(child) => Some(child),
+        _ => None,
+    })
+}
+
+fn cell_text(element: &Element) -> String {
+    element.text_content_normalized().replace('\n', " ")
+}
+
+fn span(cell: &Element, name: &str) -> Option<usize> {
+    cell.get_attribute(name)?.trim().parse().ok()
+}
+
+/// Lays the rows out on a grid, one row group at a time. A `rowspan` never
+/// reaches past its group, and `rowspan="0"` runs to the group's end.
+fn fill_grid<'a>(groups: impl Iterator<Item = &'a Vec<&'a Element>>) -> Vec<Vec<Option<Cell>>> {
+    let mut grid: Vec<Vec<Option<Cell>>> = Vec::new();
+    for group in groups {
+        let start = grid.len();
+        let end = start + group.len();
+        grid.resize_with(end, Vec::new);
+        for (offset, row) in group.iter().enumerate() {
+            let r = start + offset;
+            let mut col = 0;
+            for cell in child_elements(row).filter(|c| matches!(c.tag.as_str(), "td" | "th")) {
+                while grid[r].get(col).is_some_and(Option::is_some) {
+                    col += 1;
+                }
+                let colspan = span(cell, "colspan").unwrap_or(1).clamp(1, MAX_COLSPAN);
+                let rowspan = match span(cell, "rowspan").unwrap_or(1) {
+                    0 => end - r,
+                    n => n.min(MAX_ROWSPAN).min(end - r),
+                };
+                let text = cell_text(cell);
+                for covered in &mut grid[r..r + rowspan] {
+                    if covered.len() < col + colspan {
+                        covered.resize_with(col + colspan, || None);
+                    }
+                    for slot in &mut covered[col..col + colspan] {
+                        *slot = Some(Cell {
+                            text: text.clone(),
+                            header: cell.tag == "th",
+                        });
+                    }
+                }
+                col += colspan;
+            }
+        }
+    }
+    grid
+}
+
+fn header_names(header_rows: &[Vec<Option<Cell>>], width: usize) -> Vec<String> {
+    let mut names: Vec<String> = (0..width)
+        .map(|col| {
+            let mut parts: Vec<&str> = Vec::new();
+            for row in header_rows {
+                if let Some(Some(cell)) = row.get(col) {
+                    // A cell spanning several header rows is named once
+                    if !cell.text.is_empty() && parts.last() != Some(&cell.text.as_str()) {
+                        parts.push(&cell.text);
+                    }
+                }
+            }
+            if parts.is_empty() {
+                format!("Column {}", col + 1)
+            } else {
+                parts.join(" / ")
+            }
+        })
+        .collect();
+
+    for i in 1..names.len() {
+        if names[..i].contains(&names[i]) {
+            let base = names[i].clone();
+            let mut n = 2;
+            while names.contains(&format!("{} ({})", base, n)) {
+                n += 1;
+            }
+            names[i] = format!("{} ({})", base, n);
+        }
+    }
+    names
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn mentions_aed(header: &str) -> bool {
+    header.contains("د.إ")
+        || header
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| matches!(word.to_ascii_lowercase().as_str(), "aed" | "dh" | "dhs"))
+}
+
+/// `AED 1,250.50`, `1250 Dhs`, `د.إ 300`; the marker can be left out when the
+/// header names the currency
+fn parse_aed(text: &str, aed_header: bool) -> Option<Decimal> {
+    let text = text.trim();
+    let mut amount = text
+        .trim_start_matches("د.إ")
+        .trim_end_matches("د.إ")
+        .trim();
+    let mut marked = amount.len() != text.len();
+    for marker in ["aed", "dhs", "dh"] {
+        if let Some(rest) = strip_prefix_ignore_case(amount, marker) {
+            amount = rest.trim_start();
+            marked = true;
+        }
+        if let Some(rest) = strip_suffix_ignore_case(amount, marker) {
+            amount = rest.trim_end();
+            marked = true;
+        }
+    }
+    if !marked && !aed_header {
+        return None;
+    }
+    parse_decimal(amount)
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    let rest = &text[prefix.len()..];
+    // "AED1,000" is fine, "Dhabi" is not a marker
+    let boundary = !rest.starts_with(|c: char| c.is_alphabetic());
+    (head.eq_ignore_ascii_case(prefix) && boundary).then_some(rest)
+}
+
+fn strip_suffix_ignore_case<'a>(text: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = text.len().checked_sub(suffix.len())?;
+    let tail = text.get(split..)?;
+    let rest = &text[..split];
+    let boundary = !rest.ends_with(|c: char| c.is_alphabetic());
+    (tail.eq_ignore_ascii_case(suffix) && boundary).then_some(rest)
+}
+
+/// Digits with optional `,` thousands separators
+fn grouped_digits(text: &str) -> Option<String> {
+    if text.is_empty() {
+        return None;
+    }
+    let groups: Vec<&str> = text.split(',').collect();
+    let valid = groups
+        .iter()
+        .all(|g| !g.is_empty() && g.bytes().all(|b| b.is_ascii_digit()))
+        && (groups.len() == 1
+            || (groups[0].len() <= 3 && groups[1..].iter().all(|g| g.len() == 3)));
+    valid.then(|| groups.concat())
+}
+
+fn parse_integer(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let value: i64 = grouped_digits(digits)?.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+fn parse_decimal(text: &str) -> Option<Decimal> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (int, frac) = text.split_once('.').unwrap_or((text, ""));
+    if !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let digits = grouped_digits(int)? + frac;
+    let mantissa: i128 = digits.parse().ok()?;
+    Some(Decimal::new(
+        if negative { -mantissa } else { mantissa },
+        frac.len() as u32,
+    ))
+}
+
+/// `2024-07-01`, `01/07/2024` (day first, as published in the UAE),
+/// `1 July 2024` and `01-Jul-2024`
+fn parse_date(text: &str) -> Option<(i32, u8, u8)> {
+    let text = text.trim();
+    let parts: Vec<&str> = text
+        .split(['-', '/', ' '])
+        .filter(|p| !p.is_empty())
+        .collect();
+    let [a, b, c] = parts.as_slice() else {
+        return None;
+    };
+    let numeric = |s: &str, len: std::ops::RangeInclusive<usize>| -> Option<u32> {
+        (len.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit())).then(|| s.parse().ok())?
+    };
+    let (year, month, day) = if text.contains('-') && a.len() == 4 {
+        (numeric(a, 4..=4)?, numeric(b, 1..=2)?, numeric(c, 1..=2)?)
+    } else if text.contains('/') {
+        (numeric(c, 4..=4)?, numeric(b, 1..=2)?, numeric(a, 1..=2)?)
+    } else {
+        (numeric(c, 4..=4)?, month_number(b)?, numeric(a, 1..=2)?)
+    };
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days_in_month)
+        .contains(&day)
+        .then_some((year as i32, month as u8, day as u8))
+}
+
+/// Full English month names or their first three letters
+fn month_number(name: &str) -> Option<u32> {
+    let name = name.trim_end_matches('.').to_ascii_lowercase();
+    if name.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| *month == name || (name.len() == 3 && month.starts_with(&name)))
+        .map(|index| index as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_html;
+
+    fn first_table(html: &str) -> Table {
+        let root = parse_html(html).unwrap();
+        Table::find_all(&root).remove(0)
+    }
+
+    #[test]
+    fn test_spans_fill_the_grid() {
+        let table = first_table(
+            r#"<table><caption>Visa fees</caption>
+            <tr><th rowspan="2">Visa</th><th colspan="2">Fee</th></tr>
+            <tr><th>Adult</th><th>Child</th></tr>
+            <tr><td rowspan="2">Tourist</td><td>AED 300</td><td>AED 150</td></tr>
+            <tr><td colspan="2">AED 600</td></tr>
+            <tr><td>Golden</td><td>AED 2,800</td></tr>
+            </table>"#,
+        );
+        assert_eq!(table.caption.as_deref(), Some("Visa fees"));
+        assert_eq!(table.headers, vec!["Visa", "Fee / Adult", "Fee / Child"]);
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["Tourist", "AED 300", "AED 150"],
+                vec!["Tourist", "AED 600", "AED 600"],
+                vec!["Golden", "AED 2,800", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_headers_from_thead_and_fallbacks() {
+        let table = first_table(
+            "<table><thead><tr><td>Zone</td><td>Zone</td><td></td></tr></thead>
+             <tbody><tr><td>DMCC</td><td>JLT</td><td>x</td></tr></tbody></table>",
+        );
+        assert_eq!(table.headers, vec!["Zone", "Zone (2)", "Column 3"]);
+
+        let table = first_table("<table><tr><td>a</td><td>b</td></tr></table>");
+        assert_eq!(table.headers, vec!["Column 1", "Column 2"]);
+        assert_eq!(table.rows, vec![vec!["a", "b"]]);
+        assert_eq!(table.to_records()[0]["Column 2"], "b");
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let table = first_table(
+            r#"<table><tr><th>Area</th><th>Note</th></tr>
+            <tr><td>Dubai Marina</td><td>Sea view, "premium"</td></tr></table>"#,
+        );
+        assert_eq!(
+            table.to_csv(),
+            "Area,Note\r\nDubai Marina,\"Sea view, \"\"premium\"\"\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_column_type_inference() {
+        let table = first_table(
+            "<table><tr><th>Date</th><th>Units</th><th>Value (AED)</th><th>Fee</th><th>Area</th></tr>
+             <tr><td>01/07/2024</td><td>1,204</td><td>1,850,000</td><td>Dhs 4,020.50</td><td>JVC</td></tr>
+             <tr><td>2 Jul 2024</td><td>87</td><td>920000.75</td><td>AED 580</td><td>Marina</td></tr>
+             <tr><td>2024-07-03</td><td></td><td>-</td><td>د.إ 40</td><td>12</td></tr></table>",
+        );
+        assert_eq!(
+            table.column_types(),
+            vec![
+                ColumnType::Date,
+                ColumnType::Integer,
+                ColumnType::Text,
+                ColumnType::Aed,
+                ColumnType::Text,
+            ]
+        );
+        let rows = table.typed_rows();
+        assert_eq!(
+            rows[0][0],
+            CellValue::Date {
+                year: 2024,
+                month: 7,
+                day: 1
+            }
+        );
+        assert_eq!(rows[0][1], CellValue::Integer(1204));
+        assert_eq!(rows[2][1], CellValue::Empty);
+        assert_eq!(rows[0][3], CellValue::Aed(Decimal::new(402050, 2)));
+        assert_eq!(rows[2][3], CellValue::Aed(Decimal::new(40, 0)));
+
+        let values = first_table(
+            "<table><tr><th>Value (AED)</th></tr><tr><td>1,850,000</td></tr><tr><td>920000.75</td></tr></table>",
+        );
+        assert_eq!(values.column_types(), vec![ColumnType::Aed]);
+        assert_eq!(parse_date("31/02/2024"), None);
+        assert_eq!(parse_aed("Abu Dhabi", false), None);
+    }
+}
@@ -0,0 +1,311 @@
+// AvilaParser Tokenizer - HTML tokens from text
+// Zero External Dependencies 🦀
+
+use crate::entities::decode;
+
+/// Elements whose content is text up to the matching end tag
+const RAW_TEXT: [&str; 9] = [
+    "script", "style", "xmp", "iframe", "noembed", "noframes", "noscript", "textarea", "title",
+];
+
+/// One piece of markup, as produced by `HtmlStream`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Doctype(String),
+    StartTag {
+        name: String,
+        attributes: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    EndTag(String),
+    Comment(String),
+    Text(String),
+}
+
+/// HTML5-style tokenizer: never fails, malformed markup becomes text or comments
+pub(crate) struct Tokenizer<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Inside a raw-text element: everything up to `</name` is text
+    raw_text: Option<String>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            raw_text: None,
+        }
+    }
+
+    /// Continues in the raw-text state left by an earlier tokenizer
+    pub(crate) fn resume(input: &'a str, raw_text: Option<String>) -> Self {
+        Self {
+            input,
+            pos: 0,
+            raw_text,
+        }
+    }
+
+    /// Bytes consumed so far
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn into_raw_text(self) -> Option<String> {
+        self.raw_text
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn starts_with_ignore_case(&self, prefix: &str) -> bool {
+        self.rest()
+            .as_bytes()
+            .get(..prefix.len())
+            .is_some_and(|b| b.eq_ignore_ascii_case(prefix.as_bytes()))
+    }
+
+    /// Text up to (not including) `end`, or the rest of the input; skips past `end`
+    fn take_until(&mut self, end: &str) -> &'a str {
+        let rest = self.rest();
+        match rest.find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                &rest[..i]
+            }
+            None => {
+                self.pos = self.input.len();
+                rest
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(is_space) {
+            self.pos += 1;
+        }
+    }
+
+    fn read_raw_text(&mut self, name: &str) -> Option<Token> {
+        let rest = self.rest();
+        let bytes = rest.as_bytes();
+        let mut from = 0;
+        let end = loop {
+            let Some(i) = rest[from..].find("</").map(|i| i + from) else {
+                break rest.len();
+            };
+            let after = i + 2 + name.len();
+            let name_matches = bytes
+                .get(i + 2..after)
+                .is_some_and(|b| b.eq_ignore_ascii_case(name.as_bytes()));
+            if name_matches
+                && bytes
+                    .get(after)
+                    .is_none_or(|&b| is_space(b) || b == b'/' || b == b'>')
+            {
+                break i;
+            }
+            from = i + 2;
+        };
+        self.pos += end;
+        let text = &rest[..end];
+        // RCDATA elements decode character references; script and style do not
+        (end > 0).then(|| match name {
+            "title" | "textarea" => Token::Text(decode(text, false)),
+            _ => Token::Text(text.to_string()),
+        })
+    }
+
+    fn read_text(&mut self) -> Token {
+        let rest = self.rest();
+        // A '<' that does not open markup is ordinary text
+        let end = rest
+            .match_indices('<')
+            .map(|(i, _)| i)
+            .find(|&i| i > 0 && opens_markup(&rest.as_bytes()[i..]))
+            .unwrap_or(rest.len());
+        self.pos += end;
+        Token::Text(decode(&rest[..end], false))
+    }
+
+    fn read_markup(&mut self) -> Option<Token> {
+        // At '<' followed by a letter, '/', '!' or '?'
+        self.pos += 1;
+        match self.peek() {
+            Some(b'!') => {
+                self.pos += 1;
+                if self.rest().starts_with("--") {
+                    self.pos += 2;
+                    // `<!-->` and `<!--->` are empty comments
+                    for empty in [">", "->"] {
+                        if self.rest().starts_with(empty) {
+                            self.pos += empty.len();
+                            return Some(Token::Comment(String::new()));
+                        }
+                    }
+                    return Some(Token::Comment(self.take_until("-->").to_string()));
+                }
+                if self.starts_with_ignore_case("doctype") {
+                    self.pos += "doctype".len();
+                    return Some(Token::Doctype(self.take_until(">").trim().to_string()));
+                }
+                Some(Token::Comment(self.take_until(">").to_string()))
+            }
+            Some(b'?') => Some(Token::Comment(self.take_until(">").to_string())),
+            Some(b'/') => {
+                self.pos += 1;
+                match self.peek() {
+                    Some(b) if b.is_ascii_alphabetic() => {
+                        let name = self.read_tag_name();
+                        // Attributes on end tags are ignored
+                        self.read_attributes();
+                        Some(Token::EndTag(name))
+                    }
+                    Some(b'>') => {
+                        self.pos += 1;
+                        None
+                    }
+                    _ => Some(Token::Comment(self.take_until(">").to_string())),
+                }
+            }
+            _ => {
+                let name = self.read_tag_name();
+                let (attributes, self_closing) = self.read_attributes();
+                if RAW_TEXT.contains(&name.as_str()) {
+                    self.raw_text = Some(name.clone());
+                } else if name == "plaintext" {
+                    self.raw_text = Some(String::new());
+                }
+                Some(Token::StartTag {
+                    name,
+                    attributes,
+                    self_closing,
+                })
+            }
+        }
+    }
+
+    fn read_tag_name(&mut self) -> String {
+        let rest = self.rest();
+        let end = rest
+            .bytes()
+            .position(|b| is_space(b) || b == b'/' || b == b'>')
+            .unwrap_or(rest.len());
+        self.pos += end;
+        rest[..end].to_ascii_lowercase()
+    }
+
+    /// Attributes up to and including the closing `>`; duplicates keep the first value
+    fn read_attributes(&mut self) -> (Vec<(String, String)>, bool) {
+        let mut attributes: Vec<(String, String)> = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return (attributes, false),
+                Some(b'>') => {
+                    self.pos += 1;
+                    return (attributes, false);
+                }
+                Some(b'/') => {
+                    self.pos += 1;
+                    if self.peek() == Some(b'>') {
+                        self.pos += 1;
+                        return (attributes, true);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let rest = self.rest();
+            // The first character may be '=' (it is then part of the name)
+            let first = rest.chars().next().map_or(1, char::len_utf8);
+            let end = first
+                + rest.as_bytes()[first..]
+                    .iter()
+                    .position(|&b| is_space(b) || matches!(b, b'/' | b'>' | b'='))
+                    .unwrap_or(rest.len() - first);
+            let name = rest[..end].to_ascii_lowercase();
+            self.pos += end;
+
+            self.skip_whitespace();
+            let value = if self.peek() == Some(b'=') {
+                self.pos += 1;
+                self.skip_whitespace();
+                self.read_attribute_value()
+            } else {
+                String::new()
+            };
+
+            if !attributes.iter().any(|(existing, _)| *existing == name) {
+                attributes.push((name, value));
+            }
+        }
+    }
+
+    fn read_attribute_value(&mut self) -> String {
+        match self.peek() {
+            Some(quote @ (b'"' | b'\'')) => {
+                self.pos += 1;
+                decode(self.take_until(if quote == b'"' { "\"" } else { "'" }), true)
+            }
+            _ => {
+                let rest = self.rest();
+                let end = rest
+                    .bytes()
+                    .position(|b| is_space(b) || b == b'>')
+                    .unwrap_or(rest.len());
+                self.pos += end;
+                decode(&rest[..end], true)
+            }
+        }
+    }
+}
+
+impl Iterator for Tokenizer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        loop {
+            if let Some(name) = self.raw_text.take() {
+                if name.is_empty() {
+                    // <plaintext>: the rest of the document is text
+                    let rest = self.rest();
+                    self.pos = self.input.len();
+                    return (!rest.is_empty()).then(|| Token::Text(rest.to_string()));
+                }
+                if let Some(text) = self.read_raw_text(&name) {
+                    return Some(text);
+                }
+            }
+
+            self.peek()?;
+            if !opens_markup(self.rest().as_bytes()) {
+                return Some(self.read_text());
+            }
+            if let Some(token) = self.read_markup() {
+                return Some(token);
+            }
+        }
+    }
+}
+
+/// `<` followed by a letter, `/`, `!` or `?`
+fn opens_markup(bytes: &[u8]) -> bool {
+    bytes.first() == Some(&b'<')
+        && bytes
+            .get(1)
+            .is_some_and(|&b| b.is_ascii_alphabetic() || matches!(b, b'/' | b'!' | b'?'))
+}
+
+/// HTML whitespace: space, tab, LF, FF, CR
+pub(crate) fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\x0c' | b'\r')
+}
@@ -0,0 +1,956 @@
+// AvilaParser Tree Builder - HTML5-style tree construction
+// Zero External Dependencies 🦀
+
+use crate::tokenizer::{Token, Tokenizer};
+use crate::{Document, Element, Node};
+
+/// Open elements allowed at once; deeper start tags are added as children of
+/// the innermost element instead, so the tree can be walked recursively
+pub(crate) const MAX_DEPTH: usize = 512;
+
+/// Elements that never have children or an end tag
+pub(crate) const VOID_ELEMENTS: [&str; 15] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "keygen", "link", "meta", "param",
+    "source", "track", "wbr",
+];
+
+/// Elements that belong in `<head>` when they appear before any body content
+const HEAD_CONTENT: [&str; 11] = [
+    "base", "basefont", "bgsound", "link", "meta", "noframes", "noscript", "script", "style",
+    "template", "title",
+];
+
+/// Start tags that close an open `<p>`
+const CLOSES_P: [&str; 36] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "center",
+    "details",
+    "dialog",
+    "dir",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "listing",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "plaintext",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+/// Elements that stop the search for a `<li>`, `<dd>` or `<dt>` to close
+const SPECIAL: [&str; 30] = [
+    "applet",
+    "blockquote",
+    "body",
+    "button",
+    "caption",
+    "center",
+    "dd",
+    "details",
+    "dir",
+    "dl",
+    "dt",
+    "fieldset",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "html",
+    "li",
+    "main",
+    "menu",
+    "nav",
+    "object",
+    "ol",
+    "section",
+    "select",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+const SCOPE_BOUNDARY: [&str; 11] = [
+    "applet", "caption", "html", "table", "td", "th", "marquee", "object", "template", "svg",
+    "math",
+];
+
+const HEADINGS: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
+
+const TABLE_SECTIONS: [&str; 3] = ["tbody", "thead", "tfoot"];
+
+/// Special elements not listed above, for the adoption agency algorithm
+const SPECIAL_OTHER: [&str; 8] = [
+    "colgroup", "frameset", "head", "iframe", "marquee", "noembed", "textarea", "xmp",
+];
+
+/// Elements tracked in the list of active formatting elements
+const FORMATTING: [&str; 14] = [
+    "a", "b", "big", "code", "em", "font", "i", "nobr", "s", "small", "strike", "strong", "tt", "u",
+];
+
+/// Elements whose content starts a new run of formatting elements
+const MARKERS: [&str; 7] = [
+    "applet", "caption", "marquee", "object", "td", "th", "template",
+];
+
+/// An entry in the list of active formatting elements
+enum Formatting {
+    Marker,
+    /// `id` names the open element; once it is closed early the entry is
+    /// reopened as a copy before the next text or inline start tag
+    Element {
+        id: usize,
+        tag: String,
+        attributes: Vec<(String, String)>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scope {
+    Default,
+    Button,
+    ListItem,
+    Table,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    /// Before any body content: head elements go into `<head>`
+    InHead,
+    InBody,
+}
+
+pub(crate) struct TreeBuilder {
+    /// Open elements; index 0 is `<html>` (or the fragment container) and is never popped
+    stack: Vec<Element>,
+    /// Identity of each open element, parallel to `stack`
+    ids: Vec<usize>,
+    next_id: usize,
+    formatting: Vec<Formatting>,
+    mode: Mode,
+    doctype: Option<String>,
+    has_head: bool,
+    fragment: bool,
+    /// Drop a newline directly after `<pre>`, `<listing>` or `<textarea>`
+    skip_newline: bool,
+}
+
+impl TreeBuilder {
+    pub(crate) fn document() -> Self {
+        Self {
+            stack: vec![Element::new("html".to_string())],
+            ids: vec![0],
+            next_id: 1,
+            formatting: Vec::new(),
+            mode: Mode::InHead,
+            doctype: None,
+            has_head: false,
+            fragment: false,
+            skip_newline: false,
+        }
+    }
+
+    fn fragment() -> Self {
+        Self {
+            stack: vec![Element::new(String::new())],
+            ids: vec![0],
+            next_id: 1,
+            formatting: Vec::new(),
+            mode: Mode::InBody,
+            doctype: None,
+            has_head: false,
+            fragment: true,
+            skip_newline: false,
+        }
+    }
+
+    fn current(&mut self) -> &mut Element {
+        self.stack.last_mut().expect("root element is never popped")
+    }
+
+    fn current_tag(&self) -> &str {
+        self.stack.last().map_or("", |e| e.tag.as_str())
+    }
+
+    pub(crate) fn process(&mut self, token: Token) {
+        let token = match self.mode {
+            Mode::InHead => match self.in_head(token) {
+                Some(token) => token,
+                None => return,
+            },
+            Mode::InBody => token,
+        };
+        self.in_body(token);
+    }
+
+    /// Handles a token before body content; returns it if it starts the body
+    fn in_head(&mut self, token: Token) -> Option<Token> {
+        let in_head_child = self.stack.len() > 2;
+        match token {
+            Token::Doctype(doctype) => {
+                self.doctype.get_or_insert(doctype);
+                None
+            }
+            Token::Comment(comment) => {
+                self.current().children.push(Node::Comment(comment));
+                None
+            }
+            Token::Text(text) if in_head_child => {
+                self.insert_text(&text);
+                None
+            }
+            Token::Text(text) => {
+                let text = text.trim_start_matches(['\t', '\n', '\x0c', '\r', ' ']);
+                if text.is_empty() {
+                    return None;
+                }
+                self.start_body(Vec::new());
+                Some(Token::Text(text.to_string()))
+            }
+            Token::StartTag {
+                name, attributes, ..
+            } if name == "html" => {
+                merge_attributes(&mut self.stack[0], attributes);
+                None
+            }
+            Token::StartTag {
+                name, attributes, ..
+            } if name == "head" => {
+                if !self.has_head {
+                    let mut head = Element::new(name);
+                    head.attributes = attributes;
+                    self.push(head);
+                    self.has_head = true;
+                }
+                None
+            }
+            Token::StartTag {
+                name, attributes, ..
+            } if name == "body" => {
+                self.start_body(attributes);
+                None
+            }
+            Token::StartTag {
+                name, attributes, ..
+            } if !in_head_child && HEAD_CONTENT.contains(&name.as_str()) => {
+                self.ensure_head();
+                self.insert(name, attributes, false);
+                None
+            }
+            Token::EndTag(name) if in_head_child => {
+                if self.current_tag() == name {
+                    self.pop();
+                }
+                None
+            }
+            Token::EndTag(name) if name == "br" => {
+                self.start_body(Vec::new());
+                Some(Token::EndTag(name))
+            }
+            // `</head>` keeps later head content in the head; other stray end tags are ignored
+            Token::EndTag(_) => None,
+            token => {
+                self.start_body(Vec::new());
+                Some(token)
+            }
+        }
+    }
+
+    fn in_body(&mut self, token: Token) {
+        match token {
+            Token::Doctype(_) => {}
+            Token::Comment(comment) => self.current().children.push(Node::Comment(comment)),
+            Token::Text(text) => {
+                self.reconstruct_formatting();
+                self.insert_text(&text);
+            }
+            Token::StartTag {
+                name,
+                attributes,
+                self_closing,
+            } => self.start_tag(name, attributes, self_closing),
+            Token::EndTag(name) => self.end_tag(&name),
+        }
+    }
+
+    fn start_tag(&mut self, name: String, attributes: Vec<(String, String)>, self_closing: bool) {
+        let tag = name.as_str();
+        match tag {
+            "html" | "body" if self.fragment => {}
+            "html" => merge_attributes(&mut self.stack[0], attributes),
+            "body" => {
+                if let Some(body) = self.stack.get_mut(1).filter(|e| e.tag == "body") {
+                    merge_attributes(body, attributes);
+                }
+            }
+            "head" => {}
+            "li" | "dd" | "dt" => {
+                let closes: &[&str] = if tag == "li" { &["li"] } else { &["dd", "dt"] };
+                for i in (0..self.stack.len()).rev() {
+                    let open = self.stack[i].tag.as_str();
+                    if closes.contains(&open) {
+                        self.pop_to(i);
+                        break;
+                    }
+                    if SPECIAL.contains(&open) {
+                        break;
+                    }
+                }
+                self.close_p();
+                self.insert(name, attributes, false);
+            }
+            "a" => {
+                // An unclosed <a> is closed before another one opens
+                if let Some(entry) = self.active_formatting("a") {
+                    let id = self.formatting_id(entry);
+                    self.adoption_agency("a");
+                    if let Some(entry) = self.formatting_index(id) {
+                        self.formatting.remove(entry);
+                    }
+                    if let Some(i) = self.ids.iter().position(|&open| open == id) {
+                        self.pop_to(i);
+                    }
+                }
+                self.reconstruct_formatting();
+                self.insert_formatting(name, attributes);
+            }
+            "nobr" => {
+                self.reconstruct_formatting();
+                if self.in_scope(&["nobr"], Scope::Default).is_some() {
+                    self.adoption_agency("nobr");
+                    self.reconstruct_formatting();
+                }
+                self.insert_formatting(name, attributes);
+            }
+            _ if FORMATTING.contains(&tag) => {
+                self.reconstruct_formatting();
+                self.insert_formatting(name, attributes);
+            }
+            "button" => {
+                if let Some(i) = self.in_scope(&["button"], Scope::Default) {
+                    self.pop_to(i);
+                }
+                self.close_p();
+                self.reconstruct_formatting();
+                self.insert(name, attributes, false);
+            }
+            "caption" | "colgroup" | "col" | "tbody" | "thead" | "tfoot" | "tr" | "td" | "th" => {
+                self.table_start_tag(name, attributes)
+            }
+            "option" | "optgroup" => {
+                if self.current_tag() == "option" {
+                    self.pop();
+                }
+                if tag == "optgroup" && self.current_tag() == "optgroup" {
+                    self.pop();
+                }
+                self.reconstruct_formatting();
+                self.insert(name, attributes, false);
+            }
+            "select" => {
+                if let Some(i) = self.in_scope(&["select"], Scope::Default) {
+                    // A nested <select> closes the open one
+                    self.pop_to(i);
+                } else {
+                    self.reconstruct_formatting();
+                    self.insert(name, attributes, false);
+                }
+            }
+            "image" => {
+                self.reconstruct_formatting();
+                self.insert("img".to_string(), attributes, false);
+            }
+            _ => {
+                if CLOSES_P.contains(&tag) {
+                    self.close_p();
+                }
+                if HEADINGS.contains(&tag) && HEADINGS.contains(&self.current_tag()) {
+                    self.pop();
+                }
+                if matches!(tag, "pre" | "listing" | "textarea") {
+                    self.skip_newline = true;
+                }
+                let block = CLOSES_P.contains(&tag) || HEADINGS.contains(&tag);
+                if !block && !HEAD_CONTENT.contains(&tag) && tag != "textarea" {
+                    self.reconstruct_formatting();
+                }
+                // Self-closing syntax only counts inside SVG and MathML
+                let foreign = self.stack.iter().any(|e| e.tag == "svg" || e.tag == "math");
+                self.insert(name, attributes, self_closing && foreign);
+            }
+        }
+    }
+
+    /// Table structure tags; outside a table they are ignored
+    fn table_start_tag(&mut self, name: String, attributes: Vec<(String, String)>) {
+        if self.in_scope(&["table"], Scope::Table).is_none() {
+            return;
+        }
+        match name.as_str() {
+            "caption" | "colgroup" | "tbody" | "thead" | "tfoot" => {
+                self.clear_to(&["table"]);
+                self.insert(name, attributes, false);
+            }
+            "col" => {
+                self.clear_to(&["table", "colgroup"]);
+                if self.current_tag() == "table" {
+                    self.insert("colgroup".to_string(), Vec::new(), false);
+                }
+                self.insert(name, attributes, false);
+            }
+            "tr" => {
+                self.clear_to(&["table", "tbody", "thead", "tfoot"]);
+                if self.current_tag() == "table" {
+                    self.insert("tbody".to_string(), Vec::new(), false);
+                }
+                self.insert(name, attributes, false);
+            }
+            _ => {
+                // td / th: close the open cell, supplying missing rows and sections
+                self.clear_to(&["table", "tbody", "thead", "tfoot", "tr"]);
+                if self.current_tag() == "table" {
+                    self.insert("tbody".to_string(), Vec::new(), false);
+                }
+                if self.current_tag() != "tr" {
+                    self.insert("tr".to_string(), Vec::new(), false);
+                }
+                self.insert(name, attributes, false);
+            }
+        }
+    }
+
+    fn end_tag(&mut self, name: &str) {
+        match name {
+            "html" | "body" => {}
+            "br" => {
+                self.reconstruct_formatting();
+                self.insert(name.to_string(), Vec::new(), false);
+            }
+            "p" => {
+                if self.in_scope(&["p"], Scope::Button).is_none() {
+                    // A stray </p> produces an empty paragraph
+                    self.insert(name.to_string(), Vec::new(), true);
+                }
+                self.close_p();
+            }
+            "li" => self.close_in_scope(&["li"], Scope::ListItem),
+            _ if HEADINGS.contains(&name) => self.close_in_scope(&HEADINGS, Scope::Default),
+            "table" | "caption" | "tr" | "td" | "th" => self.close_in_scope(&[name], Scope::Table),
+            _ if TABLE_SECTIONS.contains(&name) => self.close_in_scope(&[name], Scope::Table),
+            _ if FORMATTING.contains(&name) => self.adoption_agency(name),
+            _ => self.close_in_scope(&[name], Scope::Default),
+        }
+    }
+
+    fn start_body(&mut self, attributes: Vec<(String, String)>) {
+        self.ensure_head();
+        self.pop_to(1);
+        let mut body = Element::new("body".to_string());
+        body.attributes = attributes;
+        self.push(body);
+        self.mode = Mode::InBody;
+    }
+
+    fn ensure_head(&mut self) {
+        if !self.has_head {
+            self.push(Element::new("head".to_string()));
+            self.has_head = true;
+        }
+    }
+
+    fn insert(&mut self, name: String, attributes: Vec<(String, String)>, self_closing: bool) {
+        let void = self_closing || VOID_ELEMENTS.contains(&name.as_str());
+        let mut element = Element::new(name);
+        element.attributes = attributes;
+        if void || self.stack.len() >= MAX_DEPTH {
+            self.current().children.push(Node::Element(element));
+        } else {
+            if MARKERS.contains(&element.tag.as_str()) {
+                self.formatting.push(Formatting::Marker);
+            }
+            self.push(element);
+        }
+    }
+
+    fn push(&mut self, element: Element) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.stack.push(element);
+        self.ids.push(id);
+        id
+    }
+
+    fn insert_formatting(&mut self, name: String, attributes: Vec<(String, String)>) {
+        // At most three identical entries since the last marker; the oldest goes
+        let start = self.formatting_start();
+        let identical: Vec<usize> = (start..self.formatting.len())
+            .filter(|&i| {
+                matches!(&self.formatting[i], Formatting::Element { tag, attributes: attrs, .. }
+                    if *tag == name && *attrs == attributes)
+            })
+            .collect();
+        if identical.len() >= 3 {
+            self.formatting.remove(identical[0]);
+        }
+        let mut element = Element::new(name.clone());
+        element.attributes = attributes.clone();
+        if self.stack.len() >= MAX_DEPTH {
+            self.current().children.push(Node::Element(element));
+            return;
+        }
+        let id = self.push(element);
+        self.formatting.push(Formatting::Element {
+            id,
+            tag: name,
+            attributes,
+        });
+    }
+
+    /// Index just after the last marker in the list of active formatting elements
+    fn formatting_start(&self) -> usize {
+        self.formatting
+            .iter()
+            .rposition(|entry| matches!(entry, Formatting::Marker))
+            .map_or(0, |i| i + 1)
+    }
+
+    /// List index of the latest active formatting element named `tag`
+    fn active_formatting(&self, tag: &str) -> Option<usize> {
+        let start = self.formatting_start();
+        (start..self.formatting.len()).rev().find(
+            |&i| matches!(&self.formatting[i], Formatting::Element { tag: t, .. } if t == tag),
+        )
+    }
+
+    fn formatting_index(&self, id: usize) -> Option<usize> {
+        self.formatting
+            .iter()
+            .position(|entry| matches!(entry, Formatting::Element { id: e, .. } if *e == id))
+    }
+
+    fn formatting_id(&self, entry: usize) -> usize {
+        match &self.formatting[entry] {
+            Formatting::Element { id, .. } => *id,
+            Formatting::Marker => unreachable!("markers have no element"),
+        }
+    }
+
+    /// Reopens formatting elements that were closed before their end tag,
+    /// so `<p><b>x<p>y` makes "y" bold too
+    fn reconstruct_formatting(&mut self) {
+        let start = self.formatting_start();
+        let mut first = self.formatting.len();
+        while first > start {
+            match &self.formatting[first - 1] {
+                Formatting::Element { id, .. } if !self.ids.contains(id) => first -= 1,
+                _ => break,
+            }
+        }
+        for entry in first..self.formatting.len() {
+            if self.stack.len() >= MAX_DEPTH {
+                break;
+            }
+            let Formatting::Element {
+                tag, attributes, ..
+            } = &self.formatting[entry]
+            else {
+                continue;
+            };
+            let mut element = Element::new(tag.clone());
+            element.attributes = attributes.clone();
+            let reopened = self.push(element);
+            if let Formatting::Element { id, .. } = &mut self.formatting[entry] {
+                *id = reopened;
+            }
+        }
+    }
+
+    /// The HTML adoption agency algorithm for a formatting end tag: the element
+    /// closes, and the block elements opened inside it are moved out with a copy
+    /// of it around their content, so `<b><i>x</b>y</i>` keeps "y" italic
+    fn adoption_agency(&mut self, subject: &str) {
+        let current_active = self
+            .ids
+            .last()
+            .is_some_and(|&id| self.formatting_index(id).is_some());
+        if self.current_tag() == subject && !current_active {
+            self.pop();
+            return;
+        }
+        for _ in 0..8 {
+            let Some(entry) = self.active_formatting(subject) else {
+                self.close_in_scope(&[subject], Scope::Default);
+                return;
+            };
+            let id = self.formatting_id(entry);
+            let Some(index) = self.ids.iter().position(|&open| open == id) else {
+                self.formatting.remove(entry);
+                return;
+            };
+            let boundary = self.stack[index + 1..]
+                .iter()
+                .any(|e| SCOPE_BOUNDARY.contains(&e.tag.as_str()));
+            if boundary {
+                return;
+            }
+            let furthest = (index + 1..self.stack.len()).find(|&i| is_special(&self.stack[i].tag));
+            match furthest {
+                Some(furthest) => self.adopt(index, furthest),
+                None => {
+                    self.pop_to(index);
+                    self.formatting.remove(entry);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Closes the formatting element at stack `index` and moves the block at
+    /// `furthest`, with a copy of the formatting element around its content,
+    /// out to the formatting element's parent
+    fn adopt(&mut self, index: usize, furthest: usize) {
+        let mut above = self.stack.split_off(furthest + 1);
+        let mut above_ids = self.ids.split_off(furthest + 1);
+        let mut block = self.stack.pop().expect("furthest block is open");
+        let block_id = self.ids.pop().expect("furthest block is open");
+        let mut between = self.stack.split_off(index);
+        let between_ids = self.ids.split_off(index);
+        let formatting_id = between_ids[0];
+
+        // Formatting elements between the two are reopened around the block;
+        // anything else stays where it is
+        let mut reopened = Vec::new();
+        let mut bookmark = None;
+        for (inner, i) in (1..between.len()).rev().enumerate() {
+            let Some(entry) = self.formatting_index(between_ids[i]) else {
+                continue;
+            };
+            if inner >= 3 {
+                self.formatting.remove(entry);
+                continue;
+            }
+            let id = self.next_id;
+            self.next_id += 1;
+            if let Formatting::Element { id: entry_id, .. } = &mut self.formatting[entry] {
+                *entry_id = id;
+            }
+            bookmark.get_or_insert(id);
+            let mut copy = Element::new(between[i].tag.clone());
+            copy.attributes = between[i].attributes.clone();
+            reopened.push((copy, id));
+        }
+
+        while between.len() > 1 {
+            let element = between.pop().expect("more than one element");
+            let parent = between.last_mut().expect("more than one element");
+            parent.children.push(Node::Element(element));
+        }
+        let element = between.pop().expect("formatting element");
+        let mut copy = Element::new(element.tag.clone());
+        copy.attributes = element.attributes.clone();
+        copy.children = std::mem::take(&mut block.children);
+        let (tag, attributes) = (copy.tag.clone(), copy.attributes.clone());
+        self.current().children.push(Node::Element(element));
+
+        for (element, id) in reopened.into_iter().rev() {
+            self.stack.push(element);
+            self.ids.push(id);
+        }
+        self.stack.push(block);
+        self.ids.push(block_id);
+        let id = self.push(copy);
+        self.stack.append(&mut above);
+        self.ids.append(&mut above_ids);
+
+        let position = match bookmark.and_then(|id| self.formatting_index(id)) {
+            Some(entry) => entry + 1,
+            None => self
+                .formatting_index(formatting_id)
+                .expect("formatting element is active"),
+        };
+        self.formatting.insert(
+            position,
+            Formatting::Element {
+                id,
+                tag,
+                attributes,
+            },
+        );
+        if let Some(entry) = self.formatting_index(formatting_id) {
+            self.formatting.remove(entry);
+        }
+    }
+
+    fn insert_text(&mut self, text: &str) {
+        let text = if std::mem::take(&mut self.skip_newline) {
+            text.strip_prefix('\n').unwrap_or(text)
+        } else {
+            text
+        };
+        if text.is_empty() {
+            return;
+        }
+        let children = &mut self.current().children;
+        match children.last_mut() {
+            Some(Node::Text(existing)) => existing.push_str(text),
+            _ => children.push(Node::Text(text.to_string())),
+        }
+    }
+
+    /// Index of the innermost open element named in `tags`, unless a scope boundary comes first
+    fn in_scope(&self, tags: &[&str], scope: Scope) -> Option<usize> {
+        for (i, element) in self.stack.iter().enumerate().rev() {
+            let tag = element.tag.as_str();
+            if tags.contains(&tag) {
+                return Some(i);
+            }
+            let boundary = match scope {
+                Scope::Table => matches!(tag, "html" | "table" | "template"),
+                Scope::Button => tag == "button" || SCOPE_BOUNDARY.contains(&tag),
+                Scope::ListItem => matches!(tag, "ol" | "ul") || SCOPE_BOUNDARY.contains(&tag),
+                Scope::Default => SCOPE_BOUNDARY.contains(&tag),
+            };
+            if boundary {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn close_in_scope(&mut self, tags: &[&str], scope: Scope) {
+        if let Some(i) = self.in_scope(tags, scope) {
+            self.pop_to(i);
+        }
+    }
+
+    fn close_p(&mut self) {
+        self.close_in_scope(&["p"], Scope::Button);
+    }
+
+    /// Pops until the current element is one of `tags` (or the root)
+    fn clear_to(&mut self, tags: &[&str]) {
+        while self.stack.len() > 1 && !tags.contains(&self.current_tag()) {
+            self.pop();
+        }
+    }
+
+    /// Closes the element at `index` and everything opened after it
+    fn pop_to(&mut self, index: usize) {
+        while self.stack.len() > index.max(1) {
+            self.pop();
+        }
+    }
+
+    fn pop(&mut self) {
+        if self.stack.len() > 1 {
+            let element = self.stack.pop().expect("stack has more than one element");
+            self.ids.pop();
+            if MARKERS.contains(&element.tag.as_str()) {
+                let marker = self.formatting_start().checked_sub(1);
+                self.formatting
+                    .truncate(marker.unwrap_or(self.formatting.len()));
+            }
+            self.current().children.push(Node::Element(element));
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> (Option<String>, Element) {
+        if !self.fragment && self.mode == Mode::InHead {
+            self.start_body(Vec::new());
+        }
+        self.pop_to(1);
+        let root = self.stack.pop().expect("root element is never popped");
+        (self.doctype, root)
+    }
+}
+
+/// The HTML "special" category: a formatting end tag moves these out rather than closing them
+fn is_special(tag: &str) -> bool {
+    SPECIAL.contains(&tag)
+        || CLOSES_P.contains(&tag)
+        || HEADINGS.contains(&tag)
+        || HEAD_CONTENT.contains(&tag)
+        || TABLE_SECTIONS.contains(&tag)
+        || SPECIAL_OTHER.contains(&tag)
+}
+
+/// Attributes from a repeated `<html>` or `<body>` tag are added unless already present
+fn merge_attributes(element: &mut Element, attributes: Vec<(String, String)>) {
+    for (name, value) in attributes {
+        if element.get_attribute(&name).is_none() {
+            element.attributes.push((name, value));
+        }
+    }
+}
+
+pub(crate) fn build_document(html: &str) -> Document {
+    let mut builder = TreeBuilder::document();
+    for token in Tokenizer::new(html) {
+        builder.process(token);
+    }
+    let (doctype, root) = builder.finish();
+    Document { doctype, root }
+}
+
+pub(crate) fn build_fragment(html: &str) -> Vec<Node> {
+    let mut builder = TreeBuilder::fragment();
+    for token in Tokenizer::new(html) {
+        builder.process(token);
+    }
+    builder.finish().1.children
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(nodes: &[Node]) -> Vec<&str> {
+        nodes
+            .iter()
+            .filter_map(|n| match n {
+                Node::Element(e) => Some(e.tag.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_document_structure_synthesized() {
+        let doc = build_document("<title>Dubai</title><p>Marina</p>");
+        assert_eq!(doc.root.tag, "html");
+        assert_eq!(tags(&doc.root.children), vec!["head", "body"]);
+        assert_eq!(doc.root.find_by_tag("title")[0].text_content(), "Dubai");
+        assert_eq!(doc.root.find_by_tag("body")[0].find_by_tag("p").len(), 1);
+    }
+
+    #[test]
+    fn test_implied_end_tags() {
+        let nodes = build_fragment("<ul><li>One<li>Two</ul><p>A<p>B<div>C</div>");
+        assert_eq!(tags(&nodes), vec!["ul", "p", "p", "div"]);
+        let Node::Element(ul) = &nodes[0] else {
+            panic!()
+        };
+        assert_eq!(tags(&ul.children), vec!["li", "li"]);
+        assert_eq!(ul.children.len(), 2);
+    }
+
+    #[test]
+    fn test_table_sections_inserted() {
+        let nodes = build_fragment("<table><tr><td>1<td>2<tr><th>3</table>");
+        let Node::Element(table) = &nodes[0] else {
+            panic!()
+        };
+        assert_eq!(tags(&table.children), vec!["tbody"]);
+        let rows = table.find_by_tag("tr");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(tags(&rows[0].children), vec!["td", "td"]);
+        assert_eq!(rows[1].find_by_tag("th")[0].text_content(), "3");
+    }
+
+    #[test]
+    fn test_misnested_and_stray_end_tags() {
+        let nodes = build_fragment("<div><span>Price</div></span><b>AED</em> 1,000</b></p>");
+        assert_eq!(tags(&nodes), vec!["div", "b", "p"]);
+        let Node::Element(div) = &nodes[0] else {
+            panic!()
+        };
+        assert_eq!(div.find_by_tag("span")[0].text_content(), "Price");
+        let Node::Element(b) = &nodes[1] else {
+            panic!()
+        };
+        assert_eq!(b.text_content(), "AED 1,000");
+    }
+
+    #[test]
+    fn test_misnested_formatting_reopened() {
+        let nodes = build_fragment("<b><i>x</b>y</i>");
+        assert_eq!(tags(&nodes), vec!["b", "i"]);
+        let Node::Element(b) = &nodes[0] else {
+            panic!()
+        };
+        assert_eq!(tags(&b.children), vec!["i"]);
+        assert_eq!(b.text_content(), "x");
+        let Node::Element(i) = &nodes[1] else {
+            panic!()
+        };
+        assert_eq!(i.text_content(), "y");
+
+        // A block inside the formatting element is moved out with a copy of it
+        let nodes = build_fragment("<b>1<p>2</b>3</p>");
+        assert_eq!(tags(&nodes), vec!["b", "p"]);
+        let Node::Element(p) = &nodes[1] else {
+            panic!()
+        };
+        assert_eq!(tags(&p.children), vec!["b"]);
+        assert_eq!(p.find_by_tag("b")[0].text_content(), "2");
+        assert_eq!(p.text_content(), "23");
+
+        // Formatting closed by a block end tag carries over into the next block
+        let nodes = build_fragment("<p><em>Off plan<p>Handover 2027</em>");
+        let Node::Element(second) = &nodes[1] else {
+            panic!()
+        };
+        assert_eq!(second.find_by_tag("em")[0].text_content(), "Handover 2027");
+
+        // Table cells start a fresh run of formatting elements
+        let nodes = build_fragment("<b><table><tr><td>Price</td></tr></table>AED</b>");
+        let Node::Element(b) = &nodes[0] else {
+            panic!()
+        };
+        assert!(b.find_by_tag("td")[0].find_by_tag("b").is_empty());
+        assert_eq!(b.text_content(), "PriceAED");
+    }
+
+    #[test]
+    fn test_nesting_capped() {
+        fn depth(element: &Element) -> usize {
+            let children = element.children.iter().filter_map(|n| match n {
+                Node::Element(e) => Some(depth(e)),
+                _ => None,
+            });
+            1 + children.max().unwrap_or(0)
+        }
+
+        let html = format!("{}<b><i>deep</b>end", "<div>".repeat(20_000));
+        let doc = build_document(&html);
+        assert!(depth(&doc.root) <= MAX_DEPTH + 1);
+        assert_eq!(doc.root.find_by_tag("div").len(), 20_000);
+        assert_eq!(doc.root.text_content(), "deepend");
+    }
+
+    #[test]
+    fn test_self_closing_only_in_foreign_content() {
+        let nodes = build_fragment("<div/>text<svg><path d=\"M0\"/><circle/></svg>");
+        let Node::Element(div) = &nodes[0] else {
+            panic!()
+        };
+        assert_eq!(div.text_content(), "text");
+        let svg = div.find_by_tag("svg")[0];
+        assert_eq!(tags(&svg.children), vec!["path", "circle"]);
+    }
+}
@@ -0,0 +1,838 @@
+// AvilaParser XML - strict XML 1.0 with namespaces
+// Zero External Dependencies 🦀
+
+use std::fmt;
+
+/// Namespace bound to the reserved `xml` prefix
+pub const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+/// Namespace of `xmlns` and `xmlns:*` declarations
+pub const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns/";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlDocument {
+    /// From the `<?xml ...?>` declaration
+    pub version: Option<String>,
+    pub encoding: Option<String>,
+    pub doctype: Option<String>,
+    /// Comments and processing instructions before the root element
+    pub prolog: Vec<XmlNode>,
+    pub root: XmlElement,
+    /// Comments and processing instructions after the root element
+    pub epilog: Vec<XmlNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlElement {
+    pub prefix: Option<String>,
+    pub local_name: String,
+    /// Resolved namespace URI, `None` when the element is in no namespace
+    pub namespace: Option<String>,
+    pub attributes: Vec<XmlAttribute>,
+    pub children: Vec<XmlNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlAttribute {
+    pub prefix: Option<String>,
+    pub local_name: String,
+    /// Unprefixed attributes are in no namespace, whatever the default namespace is
+    pub namespace: Option<String>,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+    CData(String),
+    Comment(String),
+    ProcessingInstruction { target: String, data: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlError {
+    pub message: String,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "XML error at line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+impl XmlDocument {
+    /// Parses a complete document, rejecting anything that is not well-formed
+    pub fn parse(xml: &str) -> Result<Self, XmlError> {
+        XmlParser::new(xml).document()
+    }
+}
+
+impl XmlElement {
+    /// The name as written, e.g. `atom:link`
+    pub fn qualified_name(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.local_name),
+            None => self.local_name.clone(),
+        }
+    }
+
+    pub fn is(&self, namespace: Option<&str>, local_name: &str) -> bool {
+        self.local_name == local_name && self.namespace.as_deref() == namespace
+    }
+
+    /// Attribute by its name as written (`href`, `xml:lang`)
+    pub fn attribute(&self, qualified_name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| match &a.prefix {
+                Some(prefix) => qualified_name
+                    .split_once(':')
+                    .is_some_and(|(p, l)| p == prefix && l == a.local_name),
+                None => a.local_name == qualified_name,
+            })
+            .map(|a| a.value.as_str())
+    }
+
+    pub fn attribute_ns(&self, namespace: &str, local_name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.local_name == local_name && a.namespace.as_deref() == Some(namespace))
+            .map(|a| a.value.as_str())
+    }
+
+    /// Child elements, skipping text, comments and processing instructions
+    pub fn elements(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|child| match child {
+            XmlNode::Element(element) => Some(element),
+            _ => None,
+        })
+    }
+
+    /// Child elements with `local_name` in the given namespace
+    pub fn children_ns<'a>(
+        &'a self,
+        namespace: Option<&'a str>,
+        local_name: &'a str,
+    ) -> impl Iterator<Item = &'a XmlElement> {
+        self.elements().filter(move |e| e.is(namespace, local_name))
+    }
+
+    pub fn child_ns(&self, namespace: Option<&str>, local_name: &str) -> Option<&XmlElement> {
+        self.elements().find(|e| e.is(namespace, local_name))
+    }
+
+    /// First child element with `local_name` in this element's own namespace
+    pub fn child(&self, local_name: &str) -> Option<&XmlElement> {
+        self.child_ns(self.namespace.as_deref(), local_name)
+    }
+
+    /// Concatenated text and CDATA content of all descendants
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.collect_text(&mut text);
+        text
+    }
+
+    fn collect_text(&self, text: &mut String) {
+        for child in &self.children {
+            match child {
+                XmlNode::Text(t) | XmlNode::CData(t) => text.push_str(t),
+                XmlNode::Element(element) => element.collect_text(text),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// An element being built, with the name it was opened with and the
+/// namespace scope to restore when it closes
+struct OpenElement {
+    element: XmlElement,
+    raw_name: String,
+    scope: usize,
+}
+
+struct XmlParser<'a> {
+    input: &'a str,
+    pos: usize,
+    /// In-scope declarations; the empty prefix is the default namespace
+    namespaces: Vec<(String, String)>,
+}
+
+impl<'a> XmlParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            namespaces: vec![
+                ("xml".to_string(), XML_NAMESPACE.to_string()),
+                ("xmlns".to_string(), XMLNS_NAMESPACE.to_string()),
+            ],
+        }
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> XmlError {
+        let before = &self.input[..pos.min(self.input.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        XmlError {
+            message: message.into(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> XmlError {
+        self.error_at(self.pos, message)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), XmlError> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", s)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().is_some_and(is_xml_space) {
+            self.pos += 1;
+        }
+        self.pos > start
+    }
+
+    /// Text up to `end`, which must be present; skips past it
+    fn take_until(&mut self, end: &str, what: &str) -> Result<&'a str, XmlError> {
+        let rest = self.rest();
+        let i = rest
+            .find(end)
+            .ok_or_else(|| self.error(format!("unterminated {}", what)))?;
+        self.pos += i + end.len();
+        Ok(&rest[..i])
+    }
+
+    fn name(&mut self) -> Result<&'a str, XmlError> {
+        let rest = self.rest();
+        let mut chars = rest.char_indices();
+        match chars.next() {
+            Some((_, c)) if is_name_start(c) => {}
+            _ => return Err(self.error("expected a name")),
+        }
+        let end = chars
+            .find(|&(_, c)| !is_name_char(c))
+            .map_or(rest.len(), |(i, _)| i);
+        self.pos += end;
+        Ok(&rest[..end])
+    }
+
+    fn document(mut self) -> Result<XmlDocument, XmlError> {
+        self.eat("\u{feff}");
+        let (version, encoding) = if self.rest().starts_with("<?xml")
+            && self.rest()[5..].starts_with(|c: char| is_xml_space(c))
+        {
+            self.pos += 5;
+            self.declaration()?
+        } else {
+            (None, None)
+        };
+
+        let mut prolog = Vec::new();
+        let mut doctype = None;
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<!DOCTYPE") {
+                if doctype.is_some() {
+                    return Err(self.error("duplicate DOCTYPE"));
+                }
+                doctype = Some(self.doctype()?);
+            } else if let Some(node) = self.misc()? {
+                prolog.push(node);
+            } else {
+                break;
+            }
+        }
+
+        if !self.rest().starts_with('<') {
+            return Err(self.error(if self.pos == self.input.len() {
+                "missing root element"
+            } else {
+                "text outside the root element"
+            }));
+        }
+        let root = self.root_element()?;
+
+        let mut epilog = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.misc()? {
+                Some(node) => epilog.push(node),
+                None if self.pos == self.input.len() => break,
+                None => return Err(self.error("content after the root element")),
+            }
+        }
+
+        Ok(XmlDocument {
+            version,
+            encoding,
+            doctype,
+            prolog,
+            root,
+            epilog,
+        })
+    }
+
+    /// `version`, `encoding` and `standalone` pseudo-attributes after `<?xml`
+    fn declaration(&mut self) -> Result<(Option<String>, Option<String>), XmlError> {
+        let (mut version, mut encoding) = (None, None);
+        loop {
+            self.skip_whitespace();
+            if self.eat("?>") {
+                break;
+            }
+            let start = self.pos;
+            let name = self.name()?;
+            let value = self.attribute_value_after_name()?;
+            match name {
+                "version" if version.is_none() && encoding.is_none() => version = Some(value),
+                "encoding" if version.is_some() && encoding.is_none() => encoding = Some(value),
+                "standalone" if version.is_some() => {}
+                _ => {
+                    return Err(
+                        self.error_at(start, format!("unexpected '{}' in XML declaration", name))
+                    )
+                }
+            }
+        }
+        if version.is_none() {
+            return Err(self.error("XML declaration without version"));
+        }
+        Ok((version, encoding))
+    }
+
+    fn doctype(&mut self) -> Result<String, XmlError> {
+        self.pos += "<!DOCTYPE".len();
+        let start = self.pos;
+        // Skip the internal subset and quoted literals, which may contain '>'
+        let mut quote = None;
+        let mut depth = 0usize;
+        for (i, c) in self.rest().char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '[') => depth += 1,
+                (None, ']') => depth = depth.saturating_sub(1),
+                (None, '>') if depth == 0 => {
+                    self.pos += i + 1;
+                    return Ok(self.input[start..start + i].trim().to_string());
+                }
+                _ => {}
+            }
+        }
+        Err(self.error_at(start, "unterminated DOCTYPE"))
+    }
+
+    /// A comment or processing instruction, if one starts here
+    fn misc(&mut self) -> Result<Option<XmlNode>, XmlError> {
+        if self.eat("<!--") {
+            return self.comment().map(Some);
+        }
+        if self.eat("<?") {
+            return self.processing_instruction().map(Some);
+        }
+        Ok(None)
+    }
+
+    fn comment(&mut self) -> Result<XmlNode, XmlError> {
+        let start = self.pos;
+        let text = self.take_until("-->", "comment")?;
+        if let Some(i) = text.find("--") {
+            return Err(self.error_at(start + i, "'--' inside a comment"));
+        }
+        if text.ends_with('-') {
+            return Err(self.error_at(start + text.len() - 1, "comment ends with '--->'"));
+        }
+        Ok(XmlNode::Comment(text.to_string()))
+    }
+
+    fn processing_instruction(&mut self) -> Result<XmlNode, XmlError> {
+        let start = self.pos;
+        let target = self.name()?;
+        if target.eq_ignore_ascii_case("xml") {
+            return Err(self.error_at(start, "XML declaration is only allowed at the start"));
+        }
+        let data = if self.eat("?>") {
+            ""
+        } else {
+            if !self.skip_whitespace() {
+                return Err(self.error("expected whitespace after processing instruction target"));
+            }
+            self.take_until("?>", "processing instruction")?
+        };
+        Ok(XmlNode::ProcessingInstruction {
+            target: target.to_string(),
+            data: data.to_string(),
+        })
+    }
+
+    fn root_element(&mut self) -> Result<XmlElement, XmlError> {
+        let mut stack: Vec<OpenElement> = Vec::new();
+        loop {
+            let rest = self.rest();
+            let closed = if rest.is_empty() {
+                let open = stack.last().map_or("", |e| e.raw_name.as_str());
+                return Err(self.error(format!("unclosed element <{}>", open)));
+            } else if rest.starts_with("</") {
+                Some(self.end_tag(&mut stack)?)
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += "<![CDATA[".len();
+                let start = self.pos;
+                let data = self.take_until("]]>", "CDATA section")?;
+                self.check_chars(data, start)?;
+                push_node(&mut stack, XmlNode::CData(normalize_newlines(data)));
+                None
+            } else if self.eat("<!--") {
+                let comment = self.comment()?;
+                push_node(&mut stack, comment);
+                None
+            } else if self.eat("<?") {
+                let instruction = self.processing_instruction()?;
+                push_node(&mut stack, instruction);
+                None
+            } else if rest.starts_with("<!") {
+                return Err(self.error("unexpected markup declaration"));
+            } else if rest.starts_with('<') {
+                self.start_tag(&mut stack)?
+            } else {
+                self.text(&mut stack)?;
+                None
+            };
+
+            if let Some(element) = closed {
+                match stack.last_mut() {
+                    Some(parent) => parent.element.children.push(XmlNode::Element(element)),
+                    None => return Ok(element),
+                }
+            }
+        }
+    }
+
+    /// Opens an element; returns it directly when it is self-closing
+    fn start_tag(&mut self, stack: &mut Vec<OpenElement>) -> Result<Option<XmlElement>, XmlError> {
+        let tag_start = self.pos;
+        self.pos += 1;
+        let raw_name = self.name()?;
+        let scope = self.namespaces.len();
+
+        let mut raw_attributes: Vec<(&str, String, usize)> = Vec::new();
+        let self_closing = loop {
+            let had_space = self.skip_whitespace();
+            if self.eat("/>") {
+                break true;
+            }
+            if self.eat(">") {
+                break false;
+            }
+            if self.pos == self.input.len() {
+                return Err(self.error(format!("unterminated start tag <{}>", raw_name)));
+            }
+            if !had_space {
+                return Err(self.error("expected whitespace between attributes"));
+            }
+            let start = self.pos;
+            let name = self.name()?;
+            let value = self.attribute_value_after_name()?;
+            if raw_attributes
+                .iter()
+                .any(|(existing, _, _)| *existing == name)
+            {
+                return Err(self.error_at(start, format!("duplicate attribute '{}'", name)));
+            }
+            raw_attributes.push((name, value, start));
+        };
+
+        // Namespace declarations apply to the element that carries them
+        for (name, value, start) in &raw_attributes {
+            if *name == "xmlns" {
+                self.namespaces.push((String::new(), value.clone()));
+            } else if let Some(prefix) = name.strip_prefix("xmlns:") {
+                if value.is_empty() {
+                    return Err(
+                        self.error_at(*start, format!("empty namespace for prefix '{}'", prefix))
+                    );
+                }
+                if prefix == "xmlns" || (prefix == "xml") != (value == XML_NAMESPACE) {
+                    return Err(self.error_at(*start, format!("reserved prefix '{}'", prefix)));
+                }
+                self.namespaces.push((prefix.to_string(), value.clone()));
+            }
+        }
+
+        let (prefix, local_name) = self.split_name(raw_name, tag_start + 1)?;
+        let namespace = self.resolve(prefix.as_deref().unwrap_or(""), tag_start + 1)?;
+        let mut attributes: Vec<XmlAttribute> = Vec::with_capacity(raw_attributes.len());
+        for (name, value, start) in raw_attributes {
+            let (prefix, local_name) = self.split_name(name, start)?;
+            let namespace = match (&prefix, local_name.as_str()) {
+                (None, "xmlns") => Some(XMLNS_NAMESPACE.to_string()),
+                (None, _) => None,
+                (Some(prefix), _) => self.resolve(prefix, start)?,
+            };
+            if attributes.iter().any(|a| {
+                a.namespace.is_some() && a.namespace == namespace && a.local_name == local_name
+            }) {
+                return Err(self.error_at(start, format!("duplicate attribute '{}'", name)));
+            }
+            attributes.push(XmlAttribute {
+                prefix,
+                local_name,
+                namespace,
+                value,
+            });
+        }
+
+        let element = XmlElement {
+            prefix,
+            local_name,
+            namespace,
+            attributes,
+            children: Vec::new(),
+        };
+        if self_closing {
+            self.namespaces.truncate(scope);
+            return Ok(Some(element));
+        }
+        stack.push(OpenElement {
+            element,
+            raw_name: raw_name.to_string(),
+            scope,
+        });
+        Ok(None)
+    }
+
+    fn end_tag(&mut self, stack: &mut Vec<OpenElement>) -> Result<XmlElement, XmlError> {
+        let start = self.pos;
+        self.pos += 2;
+        let name = self.name()?;
+        self.skip_whitespace();
+        self.expect(">")?;
+        let open = stack
+            .pop()
+            .ok_or_else(|| self.error_at(start, format!("unexpected </{}>", name)))?;
+        if open.raw_name != name {
+            return Err(self.error_at(
+                start,
+                format!("</{}> does not match <{}>", name, open.raw_name),
+            ));
+        }
+        self.namespaces.truncate(open.scope);
+        Ok(open.element)
+    }
+
+    fn text(&mut self, stack: &mut [OpenElement]) -> Result<(), XmlError> {
+        let start = self.pos;
+        let rest = self.rest();
+        let end = rest.find('<').unwrap_or(rest.len());
+        let raw = &rest[..end];
+        if let Some(i) = raw.find("]]>") {
+            return Err(self.error_at(start + i, "']]>' is not allowed in text"));
+        }
+        let text = self.decode(raw, start)?;
+        self.pos += end;
+        if let Some(parent) = stack.last_mut() {
+            match parent.element.children.last_mut() {
+                Some(XmlNode::Text(existing)) => existing.push_str(&text),
+                _ => parent.element.children.push(XmlNode::Text(text)),
+            }
+        }
+        Ok(())
+    }
+
+    /// `= "value"` after an attribute name, decoded and normalized
+    fn attribute_value_after_name(&mut self) -> Result<String, XmlError> {
+        self.skip_whitespace();
+        self.expect("=")?;
+        self.skip_whitespace();
+        let quote = match self.peek() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => return Err(self.error("attribute value must be quoted")),
+        };
+        self.pos += 1;
+        let start = self.pos;
+        let rest = self.rest();
+        let end = rest
+            .find(quote)
+            .ok_or_else(|| self.error("unterminated attribute value"))?;
+        let raw = &rest[..end];
+        if let Some(i) = raw.find('<') {
+            return Err(self.error_at(start + i, "'<' is not allowed in attribute values"));
+        }
+        // Attribute-value normalization: literal whitespace becomes a space,
+        // while character references such as `&#10;` are kept
+        let normalized = normalize_newlines(raw).replace(['\t', '\n'], " ");
+        let value = self.decode(&normalized, start)?;
+        self.pos += end + 1;
+        Ok(value)
+    }
+
+    /// Predefined entities and character references; anything else is an error
+    fn decode(&self, raw: &str, start: usize) -> Result<String, XmlError> {
+        self.check_chars(raw, start)?;
+        let raw = normalize_newlines(raw);
+        if !raw.contains('&') {
+            return Ok(raw);
+        }
+        let mut decoded = String::with_capacity(raw.len());
+        let mut rest = raw.as_str();
+        while let Some(i) = rest.find('&') {
+            decoded.push_str(&rest[..i]);
+            let offset = start + (raw.len() - rest.len()) + i;
+            let after = &rest[i + 1..];
+            let end = after
+                .find(';')
+                .ok_or_else(|| self.error_at(offset, "unterminated reference"))?;
+            let reference = &after[..end];
+            let c = match reference {
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                "apos" => '\'',
+                "quot" => '"',
+                _ => {
+                    let code = if let Some(hex) = reference.strip_prefix("#x") {
+                        u32::from_str_radix(hex, 16).ok()
+                    } else if let Some(decimal) = reference.strip_prefix('#') {
+                        decimal.parse().ok()
+                    } else {
+                        return Err(
+                            self.error_at(offset, format!("undefined entity '&{};'", reference))
+                        );
+                    };
+                    code.and_then(char::from_u32)
+                        .filter(|&c| is_xml_char(c))
+                        .ok_or_else(|| {
+                            self.error_at(
+                                offset,
+                                format!("invalid character reference '&{};'", reference),
+                            )
+                        })?
+                }
+            };
+            decoded.push(c);
+            rest = &after[end + 1..];
+        }
+        decoded.push_str(rest);
+        Ok(decoded)
+    }
+
+    /// Rejects characters outside XML's `Char` production, such as C0 controls
+    fn check_chars(&self, raw: &str, start: usize) -> Result<(), XmlError> {
+        match raw.char_indices().find(|&(_, c)| !is_xml_char(c)) {
+            Some((i, c)) => Err(self.error_at(
+                start + i,
+                format!("character U+{:04X} is not allowed", c as u32),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn split_name(&self, name: &str, pos: usize) -> Result<(Option<String>, String), XmlError> {
+        match name.split_once(':') {
+            None => Ok((None, name.to_string())),
+            Some((prefix, local))
+                if !prefix.is_empty() && !local.is_empty() && !local.contains(':') =>
+            {
+                Ok((Some(prefix.to_string()), local.to_string()))
+            }
+            Some(_) => Err(self.error_at(pos, format!("invalid qualified name '{}'", name))),
+        }
+    }
+
+    /// Namespace for a prefix (`""` for the default namespace)
+    fn resolve(&self, prefix: &str, pos: usize) -> Result<Option<String>, XmlError> {
+        match self.namespaces.iter().rev().find(|(p, _)| p == prefix) {
+            Some((_, uri)) if uri.is_empty() => Ok(None),
+            Some((_, uri)) => Ok(Some(uri.clone())),
+            None if prefix.is_empty() => Ok(None),
+            None => Err(self.error_at(pos, format!("undeclared namespace prefix '{}'", prefix))),
+        }
+    }
+}
+
+fn push_node(stack: &mut [OpenElement], node: XmlNode) {
+    if let Some(parent) = stack.last_mut() {
+        parent.element.children.push(node);
+    }
+}
+
+/// `\r\n` and lone `\r` become `\n`, as XML requires
+fn normalize_newlines(text: &str) -> String {
+    if text.contains('\r') {
+        text.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        text.to_string()
+    }
+}
+
+fn is_xml_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{fffd}' | '\u{10000}'..)
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == ':' || (!c.is_ascii() && c.is_alphabetic())
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start(c)
+        || c.is_ascii_digit()
+        || matches!(c, '-' | '.')
+        || (!c.is_ascii() && c.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATOM: &str = "http://www.w3.org/2005/Atom";
+
+    #[test]
+    fn test_namespaces() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- feed -->
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:geo="http://www.w3.org/2003/01/geo/wgs84_pos#">
+  <entry xml:lang="en">
+    <title type="text">Marina &amp; JBR</title>
+    <geo:lat>25.08</geo:lat>
+    <x:extra xmlns:x="urn:extra" x:flag="1" plain="2"/>
+    <untagged xmlns="">no namespace</untagged>
+  </entry>
+</feed>"#;
+        let doc = XmlDocument::parse(xml).unwrap();
+        assert_eq!(doc.version.as_deref(), Some("1.0"));
+        assert_eq!(doc.encoding.as_deref(), Some("UTF-8"));
+        assert_eq!(doc.prolog, vec![XmlNode::Comment(" feed ".to_string())]);
+        assert!(doc.root.is(Some(ATOM), "feed"));
+
+        let entry = doc.root.child("entry").unwrap();
+        assert_eq!(entry.attribute("xml:lang"), Some("en"));
+        assert_eq!(entry.attribute_ns(XML_NAMESPACE, "lang"), Some("en"));
+        assert_eq!(entry.child("title").unwrap().text(), "Marina & JBR");
+        assert_eq!(
+            entry.child("title").unwrap().attribute("type"),
+            Some("text")
+        );
+        let lat = entry
+            .child_ns(Some("http://www.w3.org/2003/01/geo/wgs84_pos#"), "lat")
+            .unwrap();
+        assert_eq!(
+            (lat.qualified_name(), lat.text()),
+            ("geo:lat".to_string(), "25.08".to_string())
+        );
+
+        let extra = entry.child_ns(Some("urn:extra"), "extra").unwrap();
+        assert_eq!(extra.attribute_ns("urn:extra", "flag"), Some("1"));
+        assert_eq!(extra.attributes[2].namespace, None);
+        assert!(entry.child_ns(None, "untagged").is_some());
+    }
+
+    #[test]
+    fn test_cdata_processing_instructions_and_references() {
+        let xml = "<?xml version='1.0'?><?xml-stylesheet href=\"s.xsl\"?>\r\n<!DOCTYPE root [<!ELEMENT root ANY>]>\
+                   <root a=\"x&#10;y\tz\"><![CDATA[<b>AED</b> & more]]>&#x62F;&lt;&#65;<?php echo 1; ?></root>";
+        let doc = XmlDocument::parse(xml).unwrap();
+        assert_eq!(doc.doctype.as_deref(), Some("root [<!ELEMENT root ANY>]"));
+        assert_eq!(
+            doc.prolog[0],
+            XmlNode::ProcessingInstruction {
+                target: "xml-stylesheet".to_string(),
+                data: "href=\"s.xsl\"".to_string()
+            }
+        );
+        assert_eq!(doc.root.attribute("a"), Some("x\ny z"));
+        assert_eq!(
+            doc.root.children[0],
+            XmlNode::CData("<b>AED</b> & more".to_string())
+        );
+        assert_eq!(doc.root.text(), "<b>AED</b> & more\u{62f}<A");
+        assert!(
+            matches!(&doc.root.children[2], XmlNode::ProcessingInstruction { target, .. } if target == "php")
+        );
+    }
+
+    #[test]
+    fn test_well_formedness_errors() {
+        let cases = [
+            ("<a><b></a></b>", "</a> does not match <b>"),
+            ("<a>", "unclosed element <a>"),
+            ("<a/><b/>", "content after the root element"),
+            ("text<a/>", "text outside the root element"),
+            ("", "missing root element"),
+            ("<a x='1' x='2'/>", "duplicate attribute 'x'"),
+            ("<a x=1/>", "attribute value must be quoted"),
+            ("<a>&nbsp;</a>", "undefined entity '&nbsp;'"),
+            ("<a>R&D</a>", "unterminated reference"),
+            ("<p:a/>", "undeclared namespace prefix 'p'"),
+            (
+                "<a xmlns:p='u' xmlns:q='u' p:x='1' q:x='2'/>",
+                "duplicate attribute 'q:x'",
+            ),
+            ("<a><!-- a -- b --></a>", "'--' inside a comment"),
+            ("<a>]]></a>", "']]>' is not allowed in text"),
+            (
+                "<a><?xml version='1.0'?></a>",
+                "XML declaration is only allowed at the start",
+            ),
+            ("<a x='<'/>", "'<' is not allowed in attribute values"),
+            ("<a>x\u{1}</a>", "character U+0001 is not allowed"),
+            ("<a x='\u{1b}'/>", "character U+001B is not allowed"),
+            (
+                "<a><![CDATA[\u{8}]]></a>",
+                "character U+0008 is not allowed",
+            ),
+            ("<a>&#1;</a>", "invalid character reference '&#1;'"),
+            ("<a>\u{fffe}</a>", "character U+FFFE is not allowed"),
+        ];
+        for (xml, message) in cases {
+            let error = XmlDocument::parse(xml).unwrap_err();
+            assert_eq!(error.message, message, "{}", xml);
+        }
+
+        let error = XmlDocument::parse("<root>\n  <item>\n</root>").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(
+            error.to_string(),
+            "XML error at line 3, column 1: </root> does not match <item>"
+        );
+    }
+}
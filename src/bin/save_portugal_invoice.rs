@@ -2,6 +2,9 @@ use std::error::Error;
 use std::path::Path;
 
 use avila_db::Database;
+use avila_json::{parse, JsonSchema};
+
+const INVOICE_SCHEMA: &str = include_str!("../../docs/data/schemas/invoice.schema.json");
 
 fn main() -> Result<(), Box<dyn Error>> {
     let db_path = "data/portugal/energia.db";
@@ -229,6 +232,14 @@ fn main() -> Result<(), Box<dyn Error>> {
   ]
 }"#;
 
+    let schema = JsonSchema::parse(INVOICE_SCHEMA)?;
+    if let Err(errors) = schema.validate(&parse(payload)?) {
+        for error in &errors {
+            eprintln!("❌ {}", error);
+        }
+        return Err(format!("fatura fora do schema ({} erros), nada foi salvo", errors.len()).into());
+    }
+
     db.set(key, payload.as_bytes())?;
 
     if let Some(saved) = db.get(key)? {
@@ -0,0 +1,181 @@
+// Avila Vault - Manage the encrypted secrets file
+// Replaces plaintext .env files with an AEAD-encrypted vault
+//
+// Uso:
+//   avila-vault [--vault PATH] [--key-file PATH] <comando>
+//
+// A chave vem de --key-file, AVILA_VAULT_KEY_FILE, AVILA_VAULT_PASSPHRASE
+// ou é pedida no terminal.
+
+use std::io::{self, BufRead, Write};
+use std::process::Command;
+
+use avila_crypto::vault::{self, KeySource, Vault};
+
+const USAGE: &str = "\
+Uso: avila-vault [--vault PATH] [--key-file PATH] <comando>
+
+Comandos:
+  init                    cria um vault vazio
+  set NOME [VALOR]        grava um segredo (lê o valor da entrada padrão se omitido)
+  get NOME                mostra um segredo
+  remove NOME             apaga um segredo
+  list                    lista os nomes guardados
+  import ARQUIVO.env      importa linhas NOME=valor de um arquivo .env
+  rotate [--new-key-file PATH]
+                          recriptografa com nova senha ou arquivo de chave
+  export                  imprime linhas `export NOME='valor'` para eval no shell
+  run -- CMD [ARGS...]    executa um comando com os segredos no ambiente";
+
+struct Options {
+    vault_path: String,
+    key_file: Option<String>,
+    command: Vec<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut vault_path = std::env::var("AVILA_VAULT_PATH").unwrap_or_else(|_| vault::DEFAULT_PATH.to_string());
+    let mut key_file = std::env::var("AVILA_VAULT_KEY_FILE").ok();
+    let mut command = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if !command.is_empty() {
+            command.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "--vault" => vault_path = args.next().ok_or("--vault precisa de um caminho")?,
+            "--key-file" => key_file = Some(args.next().ok_or("--key-file precisa de um caminho")?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => command.push(arg),
+        }
+    }
+
+    if command.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(Options {
+        vault_path,
+        key_file,
+        command,
+    })
+}
+
+fn read_line(prompt: &str) -> Result<String, String> {
+    eprint!("{}", prompt);
+    io::stderr().flush().ok();
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| format!("erro ao ler entrada: {}", e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn key_source(key_file: &Option<String>, passphrase_var: &str, prompt: &str) -> Result<KeySource, String> {
+    if let Some(path) = key_file {
+        return KeySource::key_file(path).map_err(|e| e.to_string());
+    }
+    let passphrase = match std::env::var(passphrase_var) {
+        Ok(p) => p,
+        Err(_) => read_line(prompt)?,
+    };
+    if passphrase.is_empty() {
+        return Err("senha vazia".to_string());
+    }
+    Ok(KeySource::Passphrase(passphrase))
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let path = options.vault_path.as_str();
+    let args: Vec<&str> = options.command.iter().map(|s| s.as_str()).collect();
+    let source = key_source(&options.key_file, "AVILA_VAULT_PASSPHRASE", "🔑 Senha do vault: ")?;
+
+    if args[0] == "init" {
+        if std::path::Path::new(path).exists() {
+            return Err(format!("{} já existe", path));
+        }
+        let vault = Vault::create(&source).map_err(|e| e.to_string())?;
+        vault.save(path).map_err(|e| e.to_string())?;
+        eprintln!("✅ Vault criado: {}", path);
+        return Ok(());
+    }
+
+    let mut vault = Vault::open(path, &source).map_err(|e| e.to_string())?;
+
+    match args.as_slice() {
+        ["set", name, rest @ ..] => {
+            let value = match rest {
+                [value] => value.to_string(),
+                [] => read_line(&format!("Valor de {}: ", name))?,
+                _ => return Err(USAGE.to_string()),
+            };
+            vault.set(*name, value);
+            vault.save(path).map_err(|e| e.to_string())?;
+            eprintln!("✅ {} gravado", name);
+        }
+        ["get", name] => match vault.get(name) {
+            Some(value) => println!("{}", value),
+            None => return Err(format!("{} não encontrado", name)),
+        },
+        ["remove", name] => {
+            if !vault.remove(name) {
+                return Err(format!("{} não encontrado", name));
+            }
+            vault.save(path).map_err(|e| e.to_string())?;
+            eprintln!("🗑️  {} removido", name);
+        }
+        ["list"] => {
+            for name in vault.names() {
+                println!("{}", name);
+            }
+        }
+        ["import", file] => {
+            let text = std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?;
+            let count = vault.import_dotenv(&text);
+            vault.save(path).map_err(|e| e.to_string())?;
+            eprintln!("✅ {} segredos importados de {}", count, file);
+            eprintln!("   Apague o arquivo em texto puro depois de conferir: rm {}", file);
+        }
+        ["rotate", rest @ ..] => {
+            let new_key_file = match rest {
+                ["--new-key-file", file] => Some(file.to_string()),
+                [] => None,
+                _ => return Err(USAGE.to_string()),
+            };
+            let new_source = key_source(&new_key_file, "AVILA_VAULT_NEW_PASSPHRASE", "🔑 Nova senha: ")?;
+            vault.rotate(&new_source).map_err(|e| e.to_string())?;
+            vault.save(path).map_err(|e| e.to_string())?;
+            eprintln!("🔄 Vault recriptografado com a nova chave");
+        }
+        ["export"] => {
+            for name in vault.names() {
+                println!("export {}={}", name, shell_quote(vault.get(name).unwrap_or_default()));
+            }
+        }
+        ["run", "--", program, program_args @ ..] => {
+            let mut command = Command::new(program);
+            command.args(program_args);
+            for name in vault.names() {
+                command.env(name, vault.get(name).unwrap_or_default());
+            }
+            let status = command.status().map_err(|e| format!("{}: {}", program, e))?;
+            std::process::exit(status.code().unwrap_or(1));
+        }
+        _ => return Err(USAGE.to_string()),
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = parse_args().and_then(run);
+    if let Err(message) = result {
+        eprintln!("❌ {}", message);
+        std::process::exit(1);
+    }
+}
@@ -3,11 +3,15 @@
 // 100% Rust, zero dependências externas
 
 use crate::property_search::*;
-use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use avila_json::JsonValue;
+use avila_crypto::envelope::{Envelope, Keyring};
+use avila_crypto::vault::{self, KeySource, Vault};
+use avila_extract::{BayutAdapter, SiteAdapter};
+use avila_json::{Change, JsonPatch, JsonValue, NdjsonWriter, ToJson};
 use avila_mongo::{MongoAtlasClient, MongoAtlasError, MongoDocument};
+use avila_parser::Sitemap;
 
 /// Sistema completo de scraping e processamento de dados reais de Dubai
 pub struct DubaiDataPipeline {
@@ -17,10 +21,39 @@ pub struct DubaiDataPipeline {
     search_index: avila_search::SearchIndex,
     pub job_queue: avila_queue::Queue,
     mongo: Option<MongoAtlasClient>,
+    /// `None` when no key source is configured: encrypted stats are disabled
+    stats_keys: Option<Keyring>,
+    /// Vault file `<db>.keys` holding the keyring; `None` when keys come from `AVILA_STATS_KEYS`
+    stats_vault: Option<StatsVault>,
 }
 
+/// Vault holding the stats keyring, and the path it is saved to
+type StatsVault = (Vault, String);
+
+/// Campos que mudam a cada ingestão (ou são do Atlas) e não contam como alteração;
+/// `index` é só a posição na página de busca
+const VOLATILE_FIELDS: [&str; 3] = ["_id", "ingested_at_epoch", "index"];
+
+/// Prefix of AvilaDB keys holding encrypted stats envelopes
+const STATS_PREFIX: &str = "stats:";
+const STATS_KEY: &str = "stats:data";
+/// Vault entry (and environment variable) holding the stats keyring spec
+const STATS_KEYS_ENTRY: &str = "AVILA_STATS_KEYS";
+const STATS_KEYS_MISSING: &str =
+    "Estatísticas criptografadas desativadas - defina AVILA_STATS_KEYS ou AVILA_VAULT_KEY_FILE/AVILA_VAULT_PASSPHRASE";
+/// Prefix of AvilaDB keys holding property change records (one JSON Patch each)
+const AUDIT_PREFIX: &str = "audit:property:";
+/// Tie-breaker for audit keys written within the same millisecond
+static AUDIT_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
 impl DubaiDataPipeline {
+    /// Chaves das estatísticas de `AVILA_STATS_KEYS` ou do vault `<db>.keys`,
+    /// aberto com `AVILA_VAULT_KEY_FILE` / `AVILA_VAULT_PASSPHRASE`
     pub fn new(db_path: &str) -> std::io::Result<Self> {
+        Self::with_stats_source(db_path, vault::source_from_env().ok())
+    }
+
+    fn with_stats_source(db_path: &str, stats_source: Option<KeySource>) -> std::io::Result<Self> {
         println!("🚀 Inicializando Pipeline de Dados de Dubai");
         let http_client = avila_http::HttpClient::new();
         let cache = avila_cache::Cache::new(1000);
@@ -55,6 +88,8 @@ impl DubaiDataPipeline {
             }
         };
 
+        let (stats_keys, stats_vault) = Self::load_stats_keyring(db_path, stats_source)?;
+
         Ok(Self {
             http_client,
             cache,
@@ -62,9 +97,84 @@ impl DubaiDataPipeline {
             search_index,
             job_queue,
             mongo,
+            stats_keys,
+            stats_vault,
         })
     }
 
+    /// Carregar o keyring das estatísticas: `AVILA_STATS_KEYS` (ex.: exportado do avila-vault)
+    /// ou o arquivo `<db>.keys` gerenciado pelo pipeline, um vault cifrado com a chave de
+    /// `source` (`AVILA_VAULT_KEY_FILE` / `AVILA_VAULT_PASSPHRASE`). Sem nenhuma das duas,
+    /// as estatísticas criptografadas ficam desativadas - a chave nunca é gravada em texto puro.
+    fn load_stats_keyring(
+        db_path: &str,
+        source: Option<KeySource>,
+    ) -> std::io::Result<(Option<Keyring>, Option<StatsVault>)> {
+        let to_io = |e: avila_crypto::CryptoError| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string());
+
+        if let Ok(spec) = std::env::var(STATS_KEYS_ENTRY) {
+            let keyring = Keyring::from_spec(&spec).map_err(to_io)?;
+            println!("🔑 Chaves das estatísticas carregadas de AVILA_STATS_KEYS (ativa: {})", keyring.active_id());
+            return Ok((Some(keyring), None));
+        }
+
+        let path = format!("{}.keys", db_path);
+        let existing = match std::fs::read_to_string(&path) {
+            Ok(text) => Some(text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        let Some(source) = source else {
+            if existing.is_some() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{} exige a chave do vault: defina AVILA_VAULT_KEY_FILE ou AVILA_VAULT_PASSPHRASE", path),
+                ));
+            }
+            println!("ℹ️ {}.", STATS_KEYS_MISSING);
+            return Ok((None, None));
+        };
+
+        let vault_header = format!("-----BEGIN {}-----", vault::PEM_LABEL);
+        let (mut vault, keyring) = match existing {
+            Some(text) if text.contains(&vault_header) => {
+                let vault = Vault::from_pem(&text, &source).map_err(to_io)?;
+                let spec = vault
+                    .get(STATS_KEYS_ENTRY)
+                    .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{} sem {}", path, STATS_KEYS_ENTRY)))?;
+                let keyring = Keyring::from_spec(spec).map_err(to_io)?;
+                return Ok((Some(keyring), Some((vault, path))));
+            }
+            // Keyring em texto puro da versão anterior: passa a ser guardado no vault
+            Some(text) => {
+                let keyring = Keyring::from_spec(&text).map_err(to_io)?;
+                println!("🔑 Chaves das estatísticas em {} migradas para um vault cifrado", path);
+                (Vault::create(&source).map_err(to_io)?, keyring)
+            }
+            None => {
+                let keyring = Keyring::generate("k1").map_err(to_io)?;
+                println!("🔑 Nova chave das estatísticas criada em {}", path);
+                (Vault::create(&source).map_err(to_io)?, keyring)
+            }
+        };
+        vault.set(STATS_KEYS_ENTRY, keyring.to_spec());
+        vault.save(&path).map_err(to_io)?;
+        Ok((Some(keyring), Some((vault, path))))
+    }
+
+    fn stats_keys(&self) -> Result<&Keyring, String> {
+        self.stats_keys.as_ref().ok_or_else(|| STATS_KEYS_MISSING.to_string())
+    }
+
+    /// Regravar o keyring no vault `<db>.keys` (sem vault, as chaves vêm de `AVILA_STATS_KEYS`)
+    fn save_stats_keyring(&mut self) -> Result<(), String> {
+        let (Some(keyring), Some((vault, path))) = (&self.stats_keys, &mut self.stats_vault) else {
+            return Ok(());
+        };
+        vault.set(STATS_KEYS_ENTRY, keyring.to_spec());
+        vault.save(path.as_str()).map_err(|e| format!("Crypto error: {}", e))
+    }
+
     /// Scrape REAL de propriedades do Bayut.com
     pub fn scrape_bayut(&mut self, area: &str) -> Result<Vec<PropertyListing>, String> {
         println!("🔍 Scraping Bayut para área: {}", area);
@@ -76,8 +186,9 @@ impl DubaiDataPipeline {
             return self.parse_cached_properties(&cached);
         }
 
-        // URL real do Bayut (adaptado para scraping)
-        let url = format!("https://www.bayut.com/for-sale/property/{}/", area);
+        // URL real do Bayut, montada pelo adaptador
+        let criteria = avila_extract::SearchCriteria::new().with_location(area.to_string());
+        let url = BayutAdapter.search_url(&criteria, 1);
 
         // Fazer requisição HTTP real
         match self.http_client.get(&url) {
@@ -89,7 +200,7 @@ impl DubaiDataPipeline {
                 self.cache.set(cache_key.clone(), html.as_bytes().to_vec(), Some(Duration::from_secs(3600)));
 
                 // Parsear HTML com AvilaParser
-                let properties = self.parse_bayut_html(html)?;
+                let properties = self.parse_bayut_html(html, &url)?;
 
                 let mut persisted = 0usize;
                 for (i, prop) in properties.iter().enumerate() {
@@ -120,85 +231,81 @@ impl DubaiDataPipeline {
         }
     }
 
-    /// Parse HTML do Bayut usando AvilaParser
-    fn parse_bayut_html(&self, html: &str) -> Result<Vec<PropertyListing>, String> {
-        let mut properties = Vec::new();
-
-        // Usar AvilaParser para extrair elementos
-        let mut parser = avila_parser::HtmlParser::new(html);
-        let root = match parser.parse() {
-            Ok(element) => element,
-            Err(err) => return Err(format!("Parser error: {:?}", err)),
-        };
-        let elements = vec![root];
-
-        // Procurar por elementos de propriedade (estrutura real do Bayut)
-        for elem in &elements {
-            if elem.tag == "article" {
-                // Buscar classe nos atributos
-                let has_property_class = elem.attributes.iter()
-                    .any(|(k, v)| k == "class" && v.contains("property"));
-
-                if !has_property_class {
-                    continue;
+    /// Descobre URLs de anúncios a partir de um sitemap ou índice de sitemaps;
+    /// com `since_epoch`, só o que mudou desde a última varredura
+    pub fn discover_listing_urls(&self, sitemap_url: &str, since_epoch: Option<i64>) -> Result<Vec<String>, String> {
+        println!("🗺️  Lendo sitemap: {}", sitemap_url);
+        let since = since_epoch.unwrap_or(i64::MIN);
+        let sitemap = self.fetch_sitemap(sitemap_url)?;
+
+        let mut urls = Vec::new();
+        match &sitemap {
+            Sitemap::Index(_) => {
+                for child in sitemap.modified_since(since) {
+                    match self.fetch_sitemap(&child.loc) {
+                        Ok(Sitemap::UrlSet(entries)) => urls.extend(
+                            entries
+                                .iter()
+                                .filter(|entry| entry.modified_since(since))
+                                .map(|entry| entry.loc.clone()),
+                        ),
+                        // Um índice não pode apontar para outro índice
+                        Ok(Sitemap::Index(_)) => println!("⚠️ Índice aninhado ignorado: {}", child.loc),
+                        Err(err) => println!("⚠️ {}", err),
+                    }
                 }
-
-                // Extrair dados da propriedade
-                let title = self.extract_text(&elem, "h2");
-                let price_text = self.extract_text(&elem, ".price");
-                let location = self.extract_text(&elem, ".location");
-                let bedrooms_text = self.extract_text(&elem, ".bedrooms");
-
-                // Parse de preço (formato: "AED 1,500,000")
-                let price = self.parse_price(&price_text);
-                let bedrooms = self.parse_bedrooms(&bedrooms_text);
-
-                properties.push(PropertyListing {
-                    title,
-                    price,
-                    currency: "AED".to_string(),
-                    location,
-                    coordinates: None, // TODO: geocode com AvilaGeo
-                    bedrooms,
-                    bathrooms: 2, // TODO: extrair
-                    area_sqm: 100.0, // TODO: extrair
-                    property_type: PropertyType::Apartment,
-                    url: {
-                        let id = elem.attributes.iter()
-                            .find(|(k, _)| k == "id")
-                            .map(|(_, v)| v.as_str())
-                            .unwrap_or("");
-                        format!("https://www.bayut.com/property/{}", id)
-                    },
-                    distance_to_burj_khalifa: None,
-                });
             }
+            Sitemap::UrlSet(_) => urls.extend(sitemap.modified_since(since).map(|entry| entry.loc.clone())),
         }
 
-        println!("📊 Parsed {} properties from HTML", properties.len());
-        Ok(properties)
+        println!("✅ {} URLs novas ou alteradas", urls.len());
+        Ok(urls)
     }
 
-    fn extract_text(&self, _elem: &avila_parser::Element, _selector: &str) -> String {
-        // TODO: Implementar seletor CSS real
-        "Sample Text".to_string()
+    fn fetch_sitemap(&self, url: &str) -> Result<Sitemap, String> {
+        let response = self
+            .http_client
+            .get(url)
+            .map_err(|e| format!("Falha ao baixar sitemap {}: {}", url, e))?;
+        Sitemap::parse(&response.body).map_err(|e| format!("Sitemap inválido {}: {}", url, e))
     }
 
-    fn parse_price(&self, text: &str) -> f64 {
-        // Remove "AED" e vírgulas, converte para número
-        text.replace("AED", "")
-            .replace(",", "")
-            .trim()
-            .parse()
-            .unwrap_or(0.0)
+    /// Parse HTML do Bayut com o adaptador do AvilaExtract (JSON-LD primeiro,
+    /// cards como alternativa); links relativos são resolvidos contra `page_url`
+    fn parse_bayut_html(&self, html: &str, page_url: &str) -> Result<Vec<PropertyListing>, String> {
+        let root = avila_parser::parse_html(html).map_err(|err| format!("Parser error: {:?}", err))?;
+
+        let properties: Vec<PropertyListing> = BayutAdapter
+            .parse_cards(&root, page_url)
+            .into_iter()
+            .map(|property| self.listing_from(property))
+            .collect();
+
+        println!("📊 Parsed {} properties from HTML", properties.len());
+        Ok(properties)
     }
 
-    fn parse_bedrooms(&self, text: &str) -> u32 {
-        text.chars()
-            .filter(|c| c.is_numeric())
-            .collect::<String>()
-            .parse()
-            .unwrap_or(0)
+    /// `Property` do AvilaExtract como `PropertyListing`, com a distância ao Burj Khalifa
+    fn listing_from(&self, property: avila_extract::Property) -> PropertyListing {
+        PropertyListing {
+            title: property.title,
+            price: property.price,
+            currency: property.currency,
+            location: property.location,
+            coordinates: property.coordinates,
+            bedrooms: property.bedrooms,
+            bathrooms: property.bathrooms,
+            area_sqm: property.area_sqm,
+            property_type: match property.property_type {
+                avila_extract::PropertyType::Apartment => PropertyType::Apartment,
+                avila_extract::PropertyType::Villa => PropertyType::Villa,
+                avila_extract::PropertyType::Townhouse => PropertyType::Townhouse,
+                avila_extract::PropertyType::Penthouse => PropertyType::Penthouse,
+                avila_extract::PropertyType::Office => PropertyType::Office,
+            },
+            url: property.url,
+            distance_to_burj_khalifa: property.coordinates.map(|c| self.calculate_distance_to_burj(c)),
+        }
     }
 
     fn parse_cached_properties(&self, _data: &[u8]) -> Result<Vec<PropertyListing>, String> {
@@ -213,15 +320,15 @@ impl DubaiDataPipeline {
         index: usize,
         property: &PropertyListing,
     ) -> Result<(), String> {
-        let document = self.build_property_document(source, area, index, property);
+        let document = self.build_property_document(source, area, index, property)?;
+        let listing_id = Self::listing_id(source, area, index, property);
 
         if let Some(mongo) = &self.mongo {
-            match mongo.insert_document(&document) {
-                Ok(result) => {
-                    println!(
-                        "🟢 MongoDB Atlas inseriu documento {} ({}:{}:{})",
-                        result.inserted_id, source, area, index
-                    );
+            match Self::sync_property_to_atlas(mongo, &document) {
+                Ok(previous) => {
+                    if let Some(previous) = previous {
+                        self.record_property_changes(&listing_id, &previous, &document.to_json())?;
+                    }
                     return Ok(());
                 }
                 Err(err) => {
@@ -233,91 +340,182 @@ impl DubaiDataPipeline {
             }
         }
 
-        self.persist_property_local(&document, source, area, index)
+        self.persist_property_local(&document, &listing_id)
+    }
+
+    /// Identificador estável do anúncio: a URL no portal. Sem URL, cai para a
+    /// posição na busca, que muda quando a ordem dos resultados muda.
+    fn listing_id(source: &str, area: &str, index: usize, property: &PropertyListing) -> String {
+        if property.url.is_empty() {
+            format!("{}-{}-{}", source, area.replace('/', "-"), index)
+        } else {
+            property.url.clone()
+        }
+    }
+
+    /// Inserir o anúncio no Atlas ou, se já existe (mesmo `listing_id`), enviar
+    /// só os campos alterados. Devolve a versão anterior, quando havia uma.
+    fn sync_property_to_atlas(
+        mongo: &MongoAtlasClient,
+        document: &MongoDocument,
+    ) -> Result<Option<JsonValue>, MongoAtlasError> {
+        let listing_id = document.to_json().pointer("/listing_id").cloned().unwrap_or(JsonValue::Null);
+        let mut filter = MongoDocument::new();
+        filter.insert_value("listing_id", listing_id.clone());
+
+        let Some(previous) = mongo.find_one(&filter)? else {
+            let result = mongo.insert_document(document)?;
+            println!("🟢 MongoDB Atlas inseriu documento {} ({})", result.inserted_id, listing_id);
+            return Ok(None);
+        };
+
+        if property_changes(&previous, &document.to_json()).is_empty() {
+            println!("⚪ {} sem alterações", listing_id);
+        } else if let Some(update) = document.update_from(&previous) {
+            let result = mongo.update_one(&filter, &update)?;
+            println!("🟢 MongoDB Atlas atualizou {} ({} campo(s) modificado(s))", listing_id, result.modified_count);
+        }
+        Ok(Some(previous))
+    }
+
+    /// Registrar no AvilaDB o JSON Patch entre duas versões de um anúncio e
+    /// avisar sobre mudanças de preço. Devolve as mudanças encontradas.
+    fn record_property_changes(
+        &mut self,
+        listing_id: &str,
+        previous: &JsonValue,
+        current: &JsonValue,
+    ) -> Result<Vec<Change>, String> {
+        let changes = property_changes(previous, current);
+        if changes.is_empty() {
+            return Ok(changes);
+        }
+
+        for change in &changes {
+            if let Change::Changed { path, from, to } = change {
+                if path.to_string() == "/price" {
+                    println!("💰 Preço alterado em {}: {} → {}", listing_id, from, to);
+                }
+            }
+        }
+
+        let changed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0))
+            .as_millis();
+        let mut entry = MongoDocument::new();
+        entry.insert_string("property", listing_id);
+        entry.insert_value("changed_at_ms", JsonValue::from(changed_at));
+        entry.insert_value("patch", changes.iter().cloned().collect::<JsonPatch>().to_json());
+
+        // Milissegundos e sequência com zeros à esquerda: a ordem das chaves é a ordem
+        // cronológica, e duas alterações no mesmo milissegundo não se sobrescrevem
+        let sequence = AUDIT_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let key = format!("{}{}:{:015}:{:020}", AUDIT_PREFIX, listing_id, changed_at, sequence);
+        self.db
+            .set(&key, entry.to_json().to_string().as_bytes())
+            .map_err(|e| format!("DB error: {}", e))?;
+        Ok(changes)
+    }
+
+    /// Histórico de alterações de um anúncio, do mais antigo ao mais recente
+    pub fn property_audit_log(&mut self, listing_id: &str) -> Result<Vec<JsonValue>, String> {
+        let prefix = format!("{}{}:", AUDIT_PREFIX, listing_id);
+        let mut keys: Vec<String> = self.db.keys().into_iter().filter(|key| key.starts_with(&prefix)).collect();
+        keys.sort();
+
+        let mut entries = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(bytes) = self.db.get(&key).map_err(|e| format!("DB error: {}", e))? {
+                let entry = avila_json::from_reader(bytes.as_slice())
+                    .map_err(|e| format!("Registro de auditoria inválido em {}: {}", key, e))?;
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
     }
 
     fn persist_property_local(
         &mut self,
         document: &MongoDocument,
-        source: &str,
-        area: &str,
-        index: usize,
+        listing_id: &str,
     ) -> Result<(), String> {
-        let key = format!("property:{}:{}:{}", source, area, index);
-        let json = document.to_json().to_string();
+        let key = format!("property:{}", listing_id);
+        let current = document.to_json();
+
+        if let Some(bytes) = self.db.get(&key).map_err(|e| format!("DB error: {}", e))? {
+            let previous = avila_json::from_reader(bytes.as_slice())
+                .map_err(|e| format!("Documento inválido em {}: {}", key, e))?;
+            if self.record_property_changes(listing_id, &previous, &current)?.is_empty() {
+                return Ok(());
+            }
+        }
+
         self.db
-            .set(&key, json.as_bytes())
+            .set(&key, current.to_string().as_bytes())
             .map_err(|e| format!("DB error: {}", e))
     }
 
+    /// Exportar as propriedades guardadas no AvilaDB como NDJSON, um documento
+    /// por linha e um de cada vez em memória (entrada de `import_ndjson`)
+    pub fn export_properties_ndjson<W: std::io::Write>(&mut self, writer: W) -> Result<usize, String> {
+        let mut keys: Vec<String> = self
+            .db
+            .keys()
+            .into_iter()
+            .filter(|key| key.starts_with("property:"))
+            .collect();
+        keys.sort();
+
+        let mut out = NdjsonWriter::new(writer);
+        for key in keys {
+            let Some(bytes) = self.db.get(&key).map_err(|e| format!("DB error: {}", e))? else {
+                continue;
+            };
+            let document = avila_json::from_reader(bytes.as_slice())
+                .map_err(|e| format!("Documento inválido em {}: {}", key, e))?;
+            out.write_value(&document)
+                .map_err(|e| format!("Falha ao escrever NDJSON: {}", e))?;
+        }
+        out.flush().map_err(|e| format!("Falha ao escrever NDJSON: {}", e))?;
+        Ok(out.count())
+    }
+
     fn build_property_document(
         &self,
         source: &str,
         area: &str,
         index: usize,
         property: &PropertyListing,
-    ) -> MongoDocument {
-        let mut document = MongoDocument::new();
+    ) -> Result<MongoDocument, String> {
+        let mut document = MongoDocument::from_value(property).map_err(|e| e.to_string())?;
 
         document.insert_string("doc_type", "property");
         document.insert_string("source", source);
         document.insert_string("area", area);
-        document.insert_string("title", property.title.clone());
-        document.insert_number("price", property.price);
-        document.insert_string("currency", property.currency.clone());
-        document.insert_string("location", property.location.clone());
-        document.insert_number("bedrooms", property.bedrooms as f64);
-        document.insert_number("bathrooms", property.bathrooms as f64);
-        document.insert_number("area_sqm", property.area_sqm);
-        document.insert_string("url", property.url.clone());
-        document.insert_string(
-            "slug",
-            format!("{}-{}-{}", source, area.replace('/', "-"), index),
-        );
+        document.insert_string("listing_id", Self::listing_id(source, area, index, property));
         document.insert_number("index", index as f64);
 
-        if let Some(distance) = property.distance_to_burj_khalifa {
-            document.insert_number("distance_to_burj_khalifa_km", distance);
-        }
-
-        if let Some((lat, lon)) = property.coordinates {
-            let mut coords = HashMap::new();
-            coords.insert("lat".to_string(), JsonValue::Number(lat));
-            coords.insert("lon".to_string(), JsonValue::Number(lon));
-            document.insert_value("coordinates", JsonValue::Object(coords));
-        }
-
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_else(|_| Duration::from_secs(0))
             .as_secs_f64();
         document.insert_number("ingested_at_epoch", timestamp);
 
-        document.insert_value(
-            "property_type",
-            JsonValue::String(match property.property_type {
-                PropertyType::Apartment => "apartment".to_string(),
-                PropertyType::Villa => "villa".to_string(),
-                PropertyType::Townhouse => "townhouse".to_string(),
-                PropertyType::Penthouse => "penthouse".to_string(),
-                PropertyType::Office => "office".to_string(),
-            }),
-        );
-
         document.insert_number("price_per_sqm", if property.area_sqm > 0.0 {
             property.price / property.area_sqm
         } else {
             0.0
         });
 
-        document
+        Ok(document)
     }
 
-    fn build_stats_document(&self, stats: &str, hash: &str) -> MongoDocument {
+    fn build_stats_document(&self, envelope: &Envelope) -> MongoDocument {
         let mut document = MongoDocument::new();
         document.insert_string("doc_type", "stats");
-        document.insert_string("payload", stats);
-        document.insert_string("hash", hash);
+        document.insert_string("key_id", &envelope.key_id);
+        document.insert_string("envelope", avila_crypto::base64::encode(&envelope.to_bytes()));
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_else(|_| Duration::from_secs(0))
@@ -464,24 +662,23 @@ impl DubaiDataPipeline {
         property.distance_to(&burj_khalifa)
     }
 
-    /// Salvar estatísticas criptografadas
+    /// Salvar estatísticas criptografadas (ChaCha20-Poly1305 com a chave ativa do keyring)
     pub fn save_encrypted_stats(&mut self, stats: &str) -> Result<(), String> {
         println!("🔒 Salvando estatísticas criptografadas");
 
-        // Gerar hash SHA-256 para verificação de integridade
-        let hash = avila_crypto::sha256::hash(stats.as_bytes());
-        let hash_str = hash.iter()
-            .map(|b| format!("{:02x}", b))
-            .collect::<String>();
+        let envelope = self
+            .stats_keys()?
+            .seal(STATS_KEY.as_bytes(), stats.as_bytes())
+            .map_err(|e| format!("Crypto error: {}", e))?;
 
-        // Salvar dados + hash
-        self.db.set("stats:data", stats.as_bytes())
+        self.db.set(STATS_KEY, &envelope.to_bytes())
             .map_err(|e| format!("DB error: {}", e))?;
-        self.db.set("stats:hash", hash_str.as_bytes())
+        // Hash sem chave da versão antiga permitia confirmar palpites sobre o conteúdo
+        self.db.delete("stats:hash")
             .map_err(|e| format!("DB error: {}", e))?;
 
         if let Some(mongo) = &self.mongo {
-            let stats_doc = self.build_stats_document(stats, &hash_str);
+            let stats_doc = self.build_stats_document(&envelope);
             if let Err(err) = mongo.insert_document(&stats_doc) {
                 println!(
                     "⚠️ Falha ao sincronizar estatísticas com MongoDB Atlas: {}",
@@ -490,25 +687,229 @@ impl DubaiDataPipeline {
             }
         }
 
-        println!("✅ Stats salvas com hash: {}", &hash_str[..16]);
+        println!("✅ Stats salvas com a chave {}", envelope.key_id);
         Ok(())
     }
+
+    /// Ler e verificar as estatísticas salvas; `None` se ainda não existem
+    pub fn load_encrypted_stats(&mut self) -> Result<Option<String>, String> {
+        let Some(data) = self.db.get(STATS_KEY).map_err(|e| format!("DB error: {}", e))? else {
+            return Ok(None);
+        };
+
+        let envelope = Envelope::from_bytes(&data).map_err(|e| format!("Crypto error: {}", e))?;
+        let plaintext = self
+            .stats_keys()?
+            .open(&envelope, STATS_KEY.as_bytes())
+            .map_err(|e| format!("Crypto error: {}", e))?;
+
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|_| "Stats descriptografadas não são UTF-8".to_string())
+    }
+
+    /// Recriptografar com a chave ativa todos os envelopes de estatísticas gravados
+    /// com chaves antigas. Registros em texto puro da versão anterior também são migrados.
+    pub fn reencrypt_stats(&mut self) -> Result<usize, String> {
+        // Emprestado direto do campo: `self.db` é alterado no laço
+        let keyring = self.stats_keys.as_ref().ok_or(STATS_KEYS_MISSING)?;
+        let keys: Vec<String> = self
+            .db
+            .keys()
+            .into_iter()
+            .filter(|k| k.starts_with(STATS_PREFIX) && k != "stats:hash")
+            .collect();
+
+        let mut updated = 0;
+        for key in keys {
+            let Some(data) = self.db.get(&key).map_err(|e| format!("DB error: {}", e))? else {
+                continue;
+            };
+
+            let reencrypted = match Envelope::from_bytes(&data) {
+                Ok(envelope) => keyring.reencrypt(&envelope, key.as_bytes()),
+                Err(_) => keyring.seal(key.as_bytes(), &data).map(Some),
+            }
+            .map_err(|e| format!("Crypto error em {}: {}", key, e))?;
+
+            if let Some(envelope) = reencrypted {
+                self.db.set(&key, &envelope.to_bytes())
+                    .map_err(|e| format!("DB error: {}", e))?;
+                updated += 1;
+            }
+        }
+        self.db.delete("stats:hash")
+            .map_err(|e| format!("DB error: {}", e))?;
+
+        Ok(updated)
+    }
+
+    /// Recriptografar com a chave ativa os documentos de estatísticas do Atlas
+    /// gravados com alguma das chaves `key_ids`; sem Atlas configurado, nada a fazer
+    fn reencrypt_remote_stats(&self, key_ids: &[String]) -> Result<usize, String> {
+        const BATCH: usize = 100;
+        let Some(mongo) = &self.mongo else {
+            return Ok(0);
+        };
+
+        let mut updated = 0;
+        for key_id in key_ids {
+            let mut filter = MongoDocument::new();
+            filter.insert_string("doc_type", "stats");
+            filter.insert_string("key_id", key_id.as_str());
+
+            // Documentos migrados saem do filtro, então cada lote traz os próximos
+            loop {
+                let documents = mongo.find(&filter, BATCH).map_err(|e| format!("MongoDB error: {}", e))?;
+                if documents.is_empty() {
+                    break;
+                }
+                let mut migrated = 0;
+                for document in &documents {
+                    let (Some(id), Some(encoded)) = (
+                        document.pointer("/_id"),
+                        document.pointer("/envelope").and_then(|v| v.as_str()),
+                    ) else {
+                        return Err("Documento de estatísticas sem _id ou envelope".to_string());
+                    };
+                    let bytes = avila_crypto::base64::decode(encoded)
+                        .map_err(|e| format!("Envelope inválido: {}", e))?;
+                    let envelope = Envelope::from_bytes(&bytes).map_err(|e| format!("Crypto error: {}", e))?;
+                    let Some(envelope) = self
+                        .stats_keys()?
+                        .reencrypt(&envelope, STATS_KEY.as_bytes())
+                        .map_err(|e| format!("Crypto error: {}", e))?
+                    else {
+                        continue;
+                    };
+
+                    let mut by_id = MongoDocument::new();
+                    by_id.insert_value("_id", id.clone());
+                    let fresh = self.build_stats_document(&envelope);
+                    let mut set = avila_json::JsonObject::new();
+                    for field in ["key_id", "envelope"] {
+                        if let Some(value) = fresh.to_json().pointer(&format!("/{}", field)) {
+                            set.insert(field.to_string(), value.clone());
+                        }
+                    }
+                    let mut update = avila_json::JsonObject::new();
+                    update.insert("$set".to_string(), JsonValue::Object(set));
+                    let result = mongo
+                        .update_one(&by_id, &JsonValue::Object(update))
+                        .map_err(|e| format!("MongoDB error: {}", e))?;
+                    migrated += result.modified_count as usize;
+                }
+                if migrated == 0 {
+                    return Err(format!("Nenhum documento com a chave {} pôde ser migrado", key_id));
+                }
+                updated += migrated;
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Rotacionar a chave das estatísticas: cria uma nova chave ativa, recriptografa
+    /// os envelopes antigos e só então descarta as chaves aposentadas.
+    ///
+    /// Com `AVILA_STATS_KEYS`, a rotação é feita no vault (nova chave primeiro na lista)
+    /// seguida de [`DubaiDataPipeline::reencrypt_stats`].
+    pub fn rotate_stats_key(&mut self) -> Result<String, String> {
+        if self.stats_keys.is_some() && self.stats_vault.is_none() {
+            return Err("Chaves vêm de AVILA_STATS_KEYS - rotacione no vault e chame reencrypt_stats".to_string());
+        }
+
+        let last = self
+            .stats_keys()?
+            .key_ids()
+            .iter()
+            .filter_map(|id| id.strip_prefix('k')?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0);
+        let new_id = format!("k{}", last + 1);
+        if let Some(keyring) = self.stats_keys.as_mut() {
+            keyring.rotate(&new_id).map_err(|e| format!("Crypto error: {}", e))?;
+        }
+        // Persistir antes de recriptografar: uma falha no meio não pode perder a chave nova
+        self.save_stats_keyring()?;
+
+        let updated = self.reencrypt_stats()?;
+
+        let retired: Vec<String> = self
+            .stats_keys()?
+            .key_ids()
+            .into_iter()
+            .filter(|id| *id != new_id)
+            .map(String::from)
+            .collect();
+        // Envelopes já enviados ao Atlas só podem perder a chave antiga depois de migrados
+        match self.reencrypt_remote_stats(&retired) {
+            Ok(remote) => {
+                println!("☁️  {} envelopes recriptografados no MongoDB Atlas", remote);
+                if let Some(keyring) = self.stats_keys.as_mut() {
+                    for id in &retired {
+                        keyring.remove(id);
+                    }
+                }
+                self.save_stats_keyring()?;
+            }
+            Err(err) => println!(
+                "⚠️ Chaves antigas mantidas até a migração do Atlas ser concluída: {}",
+                err
+            ),
+        }
+
+        println!("🔄 Chave das estatísticas rotacionada para {} ({} envelopes recriptografados)", new_id, updated);
+        Ok(new_id)
+    }
+}
+
+/// Diferenças entre duas versões de um anúncio, ignorando os campos voláteis
+fn property_changes(previous: &JsonValue, current: &JsonValue) -> Vec<Change> {
+    let stable = |value: &JsonValue| {
+        let mut value = value.clone();
+        if let Some(fields) = value.as_object_mut() {
+            for field in VOLATILE_FIELDS {
+                fields.remove(field);
+            }
+        }
+        value
+    };
+    avila_json::diff(&stable(previous), &stable(current))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use avila_json::FromJson;
     use std::fs;
 
+    /// Caminho de banco de teste no diretório temporário, fora do repositório
+    fn temp_db(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("{}-{}.db", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Chave do vault `<db>.keys` nos testes; um arquivo de chave evita o custo do Argon2
+    fn test_key() -> KeySource {
+        KeySource::KeyFile(vec![0x5a; 32])
+    }
+
     #[test]
     fn test_pipeline_creation() {
-        let pipeline = DubaiDataPipeline::new("test_pipeline.db");
+        let path = temp_db("test_pipeline");
+        let pipeline = DubaiDataPipeline::new(&path);
         assert!(pipeline.is_ok());
+        drop(pipeline);
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
     }
 
     #[test]
     fn test_distance_calculation() {
-        let pipeline = DubaiDataPipeline::new("test_dist.db").unwrap();
+        let path = temp_db("test_dist");
+        let pipeline = DubaiDataPipeline::new(&path).unwrap();
 
         // Dubai Marina coordinates
         let marina = (25.0801, 55.1378);
@@ -517,19 +918,78 @@ mod tests {
         // Should be around 12-15 km (distância real)
         println!("Distância Dubai Marina -> Burj Khalifa: {:.2} km", distance);
         assert!(distance > 10.0 && distance < 20.0);
+        drop(pipeline);
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
     }
 
     #[test]
-    fn test_price_parsing() {
-        let pipeline = DubaiDataPipeline::new("test_price.db").unwrap();
+    fn test_parse_bayut_html() {
+        let path = temp_db("test_bayut_html");
+        let pipeline = DubaiDataPipeline::new(&path).unwrap();
+        let html = r#"<html><body><ul>
+            <li><article class="property-card" id="8812">
+                <h2> Marina Gate Tower 1 </h2>
+                <span data-testid="price">AED&nbsp;1,850,000</span>
+                <div class="location">Dubai&nbsp;Marina</div>
+                <span class="bedrooms">2 Beds</span>
+            </article></li>
+            <li><article class="ad-banner"><h2>Patrocinado</h2></article></li>
+            <li><article class="property-card">
+                <a aria-label="Listing link" href="details-9.html"></a>
+                <h2>JBR Studio</h2><span data-testid="price">AED 720,000</span>
+            </article></li>
+        </ul></body></html>"#;
+
+        let page_url = "https://www.bayut.com/for-sale/property/dubai/dubai-marina/";
+        let properties = pipeline.parse_bayut_html(html, page_url).unwrap();
+        assert_eq!(properties.len(), 2);
+        assert_eq!(properties[1].url, format!("{}details-9.html", page_url));
+        assert_eq!(properties[0].title, "Marina Gate Tower 1");
+        assert_eq!(properties[0].price, 1_850_000.0);
+        assert_eq!(properties[0].location, "Dubai Marina");
+        assert_eq!(properties[0].bedrooms, 2);
+        assert_eq!(properties[0].url, "https://www.bayut.com/property/8812");
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
+    }
 
-        let price = pipeline.parse_price("AED 1,500,000");
-        assert_eq!(price, 1500000.0);
+    #[test]
+    fn test_parse_bayut_json_ld() {
+        let path = temp_db("test_bayut_json_ld");
+        let pipeline = DubaiDataPipeline::new(&path).unwrap();
+        let html = r#"<html><head><script type="application/ld+json">
+            {"@context": "https://schema.org", "@graph": [
+              {"@type": ["Apartment", "Residence"], "name": "Marina Gate Tower 1",
+               "url": "https://www.bayut.com/property/details-8812.html",
+               "numberOfRooms": 2, "numberOfBathroomsTotal": 3,
+               "floorSize": {"@type": "QuantitativeValue", "value": 1200, "unitCode": "FTK"},
+               "address": {"@type": "PostalAddress", "addressLocality": "Dubai Marina"},
+               "geo": {"@type": "GeoCoordinates", "latitude": 25.0801, "longitude": 55.1378},
+               "offers": [{"@type": "Offer", "price": "1,850,000", "priceCurrency": "AED"}]},
+              {"@type": "House", "name": "Sem preço"}
+            ]}
+        </script></head><body><article class="property-card"><h2>Ignorado</h2></article></body></html>"#;
+
+        let properties = pipeline.parse_bayut_html(html, "https://www.bayut.com/").unwrap();
+        assert_eq!(properties.len(), 1);
+        let listing = &properties[0];
+        assert_eq!(listing.title, "Marina Gate Tower 1");
+        assert_eq!(listing.price, 1_850_000.0);
+        assert_eq!(listing.location, "Dubai Marina");
+        assert_eq!((listing.bedrooms, listing.bathrooms), (2, 3));
+        assert!((listing.area_sqm - 111.48).abs() < 0.01);
+        assert_eq!(listing.coordinates, Some((25.0801, 55.1378)));
+        assert!(listing.distance_to_burj_khalifa.unwrap() > 10.0);
+        assert_eq!(listing.url, "https://www.bayut.com/property/details-8812.html");
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
     }
 
     #[test]
     fn test_job_queue() {
-        let mut pipeline = DubaiDataPipeline::new("test_queue.db").unwrap();
+        let path = temp_db("test_queue");
+        let mut pipeline = DubaiDataPipeline::new(&path).unwrap();
 
         pipeline.queue_area_scraping(vec![
             "dubai-marina".to_string(),
@@ -537,11 +997,15 @@ mod tests {
         ]);
 
         assert_eq!(pipeline.job_queue.len(), 2);
+        drop(pipeline);
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
     }
 
     #[test]
     fn test_property_document_building() {
-        let pipeline = DubaiDataPipeline::new("test_property_doc.db").unwrap();
+        let path = temp_db("test_property_doc");
+        let pipeline = DubaiDataPipeline::new(&path).unwrap();
 
         let property = PropertyListing {
             title: "Test Property".to_string(),
@@ -557,22 +1021,26 @@ mod tests {
             distance_to_burj_khalifa: Some(12.5),
         };
 
-        let document = pipeline.build_property_document("bayut", "dubai-marina", 0, &property);
+        let document = pipeline.build_property_document("bayut", "dubai-marina", 0, &property).unwrap();
         let json = document.to_json().to_string();
         assert!(json.contains("\"doc_type\":\"property\""));
         assert!(json.contains("\"source\":\"bayut\""));
         assert!(json.contains("\"title\":\"Test Property\""));
+        assert!(json.contains("\"property_type\":\"Apartment\""));
+        assert!(json.contains("\"coordinates\":{\"lat\":25,\"lon\":55}"));
+        assert!(json.contains("\"distance_to_burj_khalifa_km\":12.5"));
 
         drop(pipeline);
-        fs::remove_file("test_property_doc.db").ok();
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
     }
 
     #[test]
     fn test_property_persistence_fallback() {
-        let path = "test_property_store.db";
+        let path = temp_db("test_property_store");
 
         {
-            let mut pipeline = DubaiDataPipeline::new(path).unwrap();
+            let mut pipeline = DubaiDataPipeline::new(&path).unwrap();
             pipeline.mongo = None; // garantir fallback local
 
             let property = PropertyListing {
@@ -595,11 +1063,203 @@ mod tests {
 
             let stored = pipeline
                 .db
-                .get("property:bayut:downtown-dubai:0")
+                .get("property:https://example.com/fallback")
                 .expect("db access");
             assert!(stored.is_some());
+
+            let mut export = Vec::new();
+            assert_eq!(pipeline.export_properties_ndjson(&mut export), Ok(1));
+            let line = String::from_utf8(export).unwrap();
+            assert!(line.ends_with("}\n") && line.contains("\"title\":\"Fallback Test\""));
+        }
+
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
+    }
+
+    #[test]
+    fn test_property_change_audit() {
+        let path = temp_db("test_property_audit");
+
+        {
+            let mut pipeline = DubaiDataPipeline::new(&path).unwrap();
+            pipeline.mongo = None;
+
+            let mut property = PropertyListing {
+                title: "Marina 2BR".to_string(),
+                price: 1_850_000.0,
+                currency: "AED".to_string(),
+                location: "Dubai Marina".to_string(),
+                coordinates: Some((25.0805, 55.1399)),
+                bedrooms: 2,
+                bathrooms: 2,
+                area_sqm: 120.0,
+                property_type: PropertyType::Apartment,
+                url: "https://example.com/marina".to_string(),
+                distance_to_burj_khalifa: Some(14.2),
+            };
+
+            pipeline.persist_property("bayut", "dubai-marina", 0, &property).unwrap();
+            pipeline.persist_property("bayut", "dubai-marina", 0, &property).unwrap();
+            assert!(pipeline.property_audit_log("https://example.com/marina").unwrap().is_empty());
+
+            property.price = 1_799_000.0;
+            pipeline.persist_property("bayut", "dubai-marina", 0, &property).unwrap();
+
+            let log = pipeline.property_audit_log("https://example.com/marina").unwrap();
+            assert_eq!(log.len(), 1);
+            let patch = JsonPatch::from_json(log[0].pointer("/patch").unwrap()).unwrap();
+            let expected = r#"[{"op":"replace","path":"/price","value":1799000},{"op":"replace","path":"/price_per_sqm","value":14991.666666666666}]"#;
+            assert_eq!(patch.to_json().to_string(), expected);
+
+            let stored = pipeline.db.get("property:https://example.com/marina").unwrap().unwrap();
+            let stored = avila_json::from_reader(stored.as_slice()).unwrap();
+            assert_eq!(stored.pointer("/price").and_then(JsonValue::as_i64), Some(1_799_000));
+
+            // Outra posição na busca, mesmo anúncio; alterações seguidas não se sobrescrevem
+            property.price = 1_780_000.0;
+            pipeline.persist_property("bayut", "dubai-marina", 7, &property).unwrap();
+            property.price = 1_760_000.0;
+            pipeline.persist_property("bayut", "dubai-marina", 7, &property).unwrap();
+            let log = pipeline.property_audit_log("https://example.com/marina").unwrap();
+            assert_eq!(log.len(), 3);
+            assert_eq!(log[2].pointer("/patch/0/value").and_then(JsonValue::as_i64), Some(1_760_000));
+        }
+
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
+    }
+
+    #[test]
+    fn test_property_reorder_not_audited() {
+        let path = temp_db("test_property_reorder");
+
+        {
+            let mut pipeline = DubaiDataPipeline::new(&path).unwrap();
+            pipeline.mongo = None;
+
+            let listings: Vec<PropertyListing> = ["marina", "jvc"]
+                .iter()
+                .map(|slug| PropertyListing {
+                    title: format!("Listing {}", slug),
+                    price: 1_000_000.0,
+                    currency: "AED".to_string(),
+                    location: "Dubai".to_string(),
+                    coordinates: None,
+                    bedrooms: 1,
+                    bathrooms: 1,
+                    area_sqm: 70.0,
+                    property_type: PropertyType::Apartment,
+                    url: format!("https://example.com/{}", slug),
+                    distance_to_burj_khalifa: None,
+                })
+                .collect();
+
+            for (index, listing) in listings.iter().enumerate() {
+                pipeline.persist_property("bayut", "dubai", index, listing).unwrap();
+            }
+            // Mesma página com a ordem invertida
+            for (index, listing) in listings.iter().rev().enumerate() {
+                pipeline.persist_property("bayut", "dubai", index, listing).unwrap();
+            }
+
+            for listing in &listings {
+                assert!(pipeline.property_audit_log(&listing.url).unwrap().is_empty());
+            }
+        }
+
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
+    }
+
+    #[test]
+    fn test_encrypted_stats_roundtrip() {
+        let path = temp_db("test_stats_crypto");
+        let stats = r#"{"total_properties": 1500}"#;
+
+        {
+            let mut pipeline = DubaiDataPipeline::with_stats_source(&path, Some(test_key())).unwrap();
+            pipeline.mongo = None;
+            assert_eq!(pipeline.load_encrypted_stats().unwrap(), None);
+
+            pipeline.save_encrypted_stats(stats).unwrap();
+            let raw = pipeline.db.get(STATS_KEY).unwrap().unwrap();
+            assert!(!String::from_utf8_lossy(&raw).contains("total_properties"));
+            assert_eq!(pipeline.load_encrypted_stats().unwrap().as_deref(), Some(stats));
+
+            // Qualquer byte alterado deve ser rejeitado
+            let mut tampered = raw.clone();
+            *tampered.last_mut().unwrap() ^= 1;
+            pipeline.db.set(STATS_KEY, &tampered).unwrap();
+            assert!(pipeline.load_encrypted_stats().is_err());
+        }
+
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
+    }
+
+    #[test]
+    fn test_stats_keyring_protected() {
+        let path = temp_db("test_stats_keyring");
+        let keys_path = format!("{}.keys", path);
+
+        // Sem fonte de chave: nada é gravado e as estatísticas ficam desativadas
+        {
+            let mut pipeline = DubaiDataPipeline::with_stats_source(&path, None).unwrap();
+            assert!(pipeline.save_encrypted_stats("{}").is_err());
+        }
+        assert!(!std::path::Path::new(&keys_path).exists());
+
+        // Keyring em texto puro da versão anterior é migrado para o vault
+        let legacy = Keyring::generate("k1").unwrap();
+        legacy.save(&keys_path).unwrap();
+        {
+            let pipeline = DubaiDataPipeline::with_stats_source(&path, Some(test_key())).unwrap();
+            assert_eq!(pipeline.stats_keys().unwrap().to_spec(), legacy.to_spec());
+        }
+        let stored = fs::read_to_string(&keys_path).unwrap();
+        assert!(stored.starts_with("-----BEGIN AVILA VAULT-----"));
+        assert!(!stored.contains(&legacy.to_spec()));
+
+        fs::remove_file(&path).ok();
+        fs::remove_file(&keys_path).ok();
+    }
+
+    #[test]
+    fn test_stats_key_rotation() {
+        let path = temp_db("test_stats_rotation");
+        let stats = r#"{"average_price": 2500000}"#;
+
+        {
+            let mut pipeline = DubaiDataPipeline::with_stats_source(&path, Some(test_key())).unwrap();
+            pipeline.mongo = None;
+            pipeline.save_encrypted_stats(stats).unwrap();
+            // Registro em texto puro da versão anterior
+            pipeline.db.set("stats:legacy", b"plain").unwrap();
+
+            let new_id = pipeline.rotate_stats_key().unwrap();
+            assert_eq!(new_id, "k2");
+            assert_eq!(pipeline.stats_keys().unwrap().key_ids(), vec!["k2"]);
+
+            let raw = pipeline.db.get(STATS_KEY).unwrap().unwrap();
+            assert_eq!(Envelope::from_bytes(&raw).unwrap().key_id, "k2");
+            assert_eq!(pipeline.load_encrypted_stats().unwrap().as_deref(), Some(stats));
+
+            let legacy = pipeline.db.get("stats:legacy").unwrap().unwrap();
+            let envelope = Envelope::from_bytes(&legacy).unwrap();
+            assert_eq!(pipeline.stats_keys().unwrap().open(&envelope, b"stats:legacy").unwrap(), b"plain".to_vec());
+        }
+
+        // O keyring persistido continua abrindo os dados, e só com a chave do vault
+        {
+            let mut pipeline = DubaiDataPipeline::with_stats_source(&path, Some(test_key())).unwrap();
+            assert_eq!(pipeline.load_encrypted_stats().unwrap().as_deref(), Some(stats));
         }
+        let wrong_key = KeySource::KeyFile(vec![0x5b; 32]);
+        assert!(DubaiDataPipeline::with_stats_source(&path, Some(wrong_key)).is_err());
+        assert!(DubaiDataPipeline::with_stats_source(&path, None).is_err());
 
-        fs::remove_file(path).ok();
+        fs::remove_file(&path).ok();
+        fs::remove_file(format!("{}.keys", path)).ok();
     }
 }
@@ -4,27 +4,38 @@
 
 pub mod integration;
 pub mod property_search {
-    #[derive(Debug, Clone)]
+    use avila_json::{FromJson, ToJson};
+
+    #[derive(Debug, Clone, ToJson, FromJson)]
     pub struct PropertyListing {
         pub title: String,
         pub price: f64,
         pub currency: String,
         pub location: String,
+        #[json(with = "json_format::lat_lon")]
         pub coordinates: Option<(f64, f64)>, // (lat, lon)
         pub bedrooms: u32,
         pub bathrooms: u32,
         pub area_sqm: f64,
         pub property_type: PropertyType,
         pub url: String,
+        #[json(rename = "distance_to_burj_khalifa_km")]
         pub distance_to_burj_khalifa: Option<f64>, // in km
     }
 
-    #[derive(Debug, Clone)]
+    /// Written with the variant's own casing, as Atlas documents always had it;
+    /// lowercase names are accepted too
+    #[derive(Debug, Clone, ToJson, FromJson)]
     pub enum PropertyType {
+        #[json(alias = "apartment")]
         Apartment,
+        #[json(alias = "villa")]
         Villa,
+        #[json(alias = "townhouse")]
         Townhouse,
+        #[json(alias = "penthouse")]
         Penthouse,
+        #[json(alias = "office")]
         Office,
     }
 
@@ -105,7 +116,7 @@ pub mod property_search {
                         "100% repatriation of capital and profits".to_string(),
                         "No currency restrictions".to_string(),
                     ],
-                    business_types: vec!["Trading", "Services", "Consulting"],
+                    business_types: vec!["Trading".to_string(), "Services".to_string(), "Consulting".to_string()],
                 },
                 FreeZoneInfo {
                     name: "Dubai Silicon Oasis (DSO)".to_string(),
@@ -118,7 +129,7 @@ pub mod property_search {
                         "100% foreign ownership".to_string(),
                         "State-of-art infrastructure".to_string(),
                     ],
-                    business_types: vec!["IT", "Software", "E-commerce"],
+                    business_types: vec!["IT".to_string(), "Software".to_string(), "E-commerce".to_string()],
                 },
                 FreeZoneInfo {
                     name: "Dubai Internet City (DIC)".to_string(),
@@ -131,7 +142,7 @@ pub mod property_search {
                         "Tax exemptions".to_string(),
                         "Access to talent pool".to_string(),
                     ],
-                    business_types: vec!["IT", "Media", "E-commerce", "Software"],
+                    business_types: vec!["IT".to_string(), "Media".to_string(), "E-commerce".to_string(), "Software".to_string()],
                 },
             ]
         }
@@ -207,14 +218,17 @@ pub mod property_search {
         pub official_website: String,
     }
 
-    #[derive(Debug, Clone)]
+    #[derive(Debug, Clone, ToJson, FromJson)]
     pub struct FreeZoneInfo {
         pub name: String,
         pub location: String,
         pub website: String,
+        #[json(with = "json_format::min_max")]
         pub cost_range_aed: (f64, f64),
+        #[json(default)]
         pub benefits: Vec<String>,
-        pub business_types: Vec<&'static str>,
+        #[json(default)]
+        pub business_types: Vec<String>,
     }
 
     #[derive(Debug, Clone)]
@@ -223,6 +237,58 @@ pub mod property_search {
         pub note: String,
         pub average_prices_aed_per_sqm: Vec<(&'static str, f64)>,
     }
+
+    /// JSON layouts of fields whose Rust type differs from the documents in `docs/data`
+    pub mod json_format {
+        use avila_json::{FromJson, FromJsonError, JsonObject, JsonValue};
+
+        fn number_field(object: &JsonObject, key: &str) -> Result<f64, FromJsonError> {
+            match object.get(key) {
+                Some(value) => f64::from_json(value).map_err(|e| e.in_field(key)),
+                None => Err(FromJsonError::missing_field(key)),
+            }
+        }
+
+        /// `(lat, lon)` as `{"lat": .., "lon": ..}`
+        pub mod lat_lon {
+            use super::*;
+
+            pub fn to_json(coordinates: &Option<(f64, f64)>) -> JsonValue {
+                let Some((lat, lon)) = *coordinates else {
+                    return JsonValue::Null;
+                };
+                let mut object = JsonObject::new();
+                object.insert("lat", JsonValue::from(lat));
+                object.insert("lon", JsonValue::from(lon));
+                JsonValue::Object(object)
+            }
+
+            pub fn from_json(value: &JsonValue) -> Result<Option<(f64, f64)>, FromJsonError> {
+                match value {
+                    JsonValue::Null => Ok(None),
+                    JsonValue::Object(object) => Ok(Some((number_field(object, "lat")?, number_field(object, "lon")?))),
+                    other => Err(FromJsonError::expected("object", other)),
+                }
+            }
+        }
+
+        /// `(min, max)` as `{"min": .., "max": ..}`
+        pub mod min_max {
+            use super::*;
+
+            pub fn to_json(range: &(f64, f64)) -> JsonValue {
+                let mut object = JsonObject::new();
+                object.insert("min", JsonValue::from(range.0));
+                object.insert("max", JsonValue::from(range.1));
+                JsonValue::Object(object)
+            }
+
+            pub fn from_json(value: &JsonValue) -> Result<(f64, f64), FromJsonError> {
+                let object = value.as_object().ok_or_else(|| FromJsonError::expected("object", value))?;
+                Ok((number_field(object, "min")?, number_field(object, "max")?))
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -248,4 +314,33 @@ mod tests {
         let zones = search.get_free_zones();
         assert!(zones.len() >= 3);
     }
+
+    #[test]
+    fn test_listing_json_mapping() {
+        use avila_json::{parse, FromJson, ToJson};
+
+        let value = parse(
+            r#"{"title": "Marina 2BR", "price": 1850000, "currency": "AED", "location": "Dubai Marina",
+                "coordinates": {"lat": 25.0805, "lon": 55.1399}, "bedrooms": 2, "bathrooms": 2,
+                "area_sqm": 120, "property_type": "Apartment", "url": "https://www.bayut.com/p/1",
+                "distance_to_burj_khalifa_km": 14.2}"#,
+        )
+        .unwrap();
+        let listing = PropertyListing::from_json(&value).unwrap();
+        assert_eq!(listing.coordinates, Some((25.0805, 55.1399)));
+        assert_eq!(listing.distance_to_burj_khalifa, Some(14.2));
+
+        let json = listing.to_json();
+        let object = json.as_object().unwrap();
+        assert_eq!(object.get("property_type").and_then(|v| v.as_str()), Some("Apartment"));
+        assert_eq!(object.get("coordinates"), value.as_object().unwrap().get("coordinates"));
+
+        let lowercase = parse(r#""villa""#).unwrap();
+        assert!(matches!(PropertyType::from_json(&lowercase), Ok(PropertyType::Villa)));
+
+        let mut broken = value.clone();
+        broken.as_object_mut().unwrap().insert("coordinates", parse(r#"{"lat": "north"}"#).unwrap());
+        let err = PropertyListing::from_json(&broken).unwrap_err();
+        assert_eq!(err.to_string(), "$.coordinates.lat: expected number, found string");
+    }
 }
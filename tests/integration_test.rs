@@ -1,6 +1,7 @@
 // TESTES DE INTEGRAÇÃO END-TO-END
 // Verifica que todas as ferramentas Avila* funcionam juntas
 
+use avila_crypto::envelope::Keyring;
 use dubai_project::integration::DubaiDataPipeline;
 use dubai_project::property_search::*;
 
@@ -73,6 +74,8 @@ fn test_job_queue_integration() {
 fn test_crypto_integration() {
     println!("\n🧪 TESTE: Criptografia");
 
+    // As chaves das estatísticas precisam de uma fonte explícita
+    std::env::set_var("AVILA_STATS_KEYS", Keyring::generate("k1").unwrap().to_spec());
     let mut pipeline = DubaiDataPipeline::new("test_crypto.db").unwrap();
 
     let stats = r#"{"total_properties": 1500, "average_price": 2500000}"#;